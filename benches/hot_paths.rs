@@ -0,0 +1,188 @@
+//! Benchmarks for the chunking and encoding hot paths.
+//!
+//! ```sh
+//! cargo bench --features testing
+//! # also fail if any mean exceeds its budget
+//! BENCH_BUDGETS=1 cargo bench --features testing
+//! ```
+//!
+//! Budgets are per call on release builds, about ten times what a current
+//! laptop measures, so only real regressions trip them.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use criterion::{criterion_group, BenchmarkId, Criterion, Throughput};
+use ledger_iota::testing::{block_chain, SimulatedDevice};
+use ledger_iota::{
+    build_batch_transfer_tx, build_transfer_tx, encode_objects, Address, Amount, Bip32Path,
+    GasCoinRef, LedgerIota, ObjectData, ObjectDigest, Owner, Payment, TransactionDigest,
+};
+
+/// `(name, budget)`; names match the benchmark IDs below.
+const BUDGETS: &[(&str, Duration)] = &[
+    ("block_chain/32768", Duration::from_micros(600)),
+    ("encode_objects/16", Duration::from_micros(5)),
+    ("build_transfer_tx", Duration::from_micros(5)),
+    ("build_batch_transfer_tx/100", Duration::from_micros(250)),
+    ("sign_tx/transfer", Duration::from_micros(500)),
+    ("sign_tx/batch_100", Duration::from_millis(1)),
+];
+
+fn gas() -> GasCoinRef {
+    GasCoinRef {
+        object_id: [0xCC; 32],
+        version: 42,
+        digest: ObjectDigest([0xDD; 32]),
+    }
+}
+
+fn coins(n: usize) -> Vec<ObjectData> {
+    (0..n)
+        .map(|i| {
+            let mut contents = vec![i as u8; 32];
+            contents.extend_from_slice(&1_000_000_000u64.to_le_bytes());
+            ObjectData::gas_coin(
+                i as u64,
+                contents,
+                Owner::AddressOwner([0xAA; 32]),
+                TransactionDigest::ZERO,
+                0,
+            )
+        })
+        .collect()
+}
+
+fn transfer() -> Vec<u8> {
+    build_transfer_tx(
+        &Address([0xAA; 32]),
+        &Address([0xBB; 32]),
+        Amount::from_nanos(500),
+        &gas(),
+        Amount::from_nanos(10_000_000),
+        1_000,
+    )
+    .unwrap()
+}
+
+fn batch(n: usize) -> Vec<u8> {
+    let payments: Vec<_> = (0..n)
+        .map(|i| Payment {
+            recipient: Address([(i % 255) as u8 + 1; 32]),
+            amount: Amount::from_nanos(1_000),
+        })
+        .collect();
+    build_batch_transfer_tx(
+        &Address([0xAA; 32]),
+        &payments,
+        &[gas()],
+        Amount::from_nanos(50_000_000),
+        1_000,
+    )
+    .unwrap()
+}
+
+/// The operation measured under each budget name.
+fn operation(name: &str) -> Box<dyn FnMut()> {
+    match name {
+        "block_chain/32768" => {
+            let data = vec![0x5A; 32 * 1024];
+            Box::new(move || {
+                black_box(block_chain(black_box(&data)));
+            })
+        }
+        "encode_objects/16" => {
+            let objects = coins(16);
+            Box::new(move || {
+                black_box(encode_objects(black_box(&objects)).unwrap());
+            })
+        }
+        "build_transfer_tx" => Box::new(|| {
+            black_box(transfer());
+        }),
+        "build_batch_transfer_tx/100" => Box::new(|| {
+            black_box(batch(100));
+        }),
+        "sign_tx/transfer" | "sign_tx/batch_100" => {
+            let ledger = LedgerIota::with_transport(Box::new(SimulatedDevice::speculos()));
+            let tx = if name == "sign_tx/transfer" {
+                transfer()
+            } else {
+                batch(100)
+            };
+            let objects = coins(1);
+            let path = Bip32Path::iota(0, 0, 0);
+            Box::new(move || {
+                black_box(ledger.sign_tx(&tx, &path, Some(&objects)).unwrap());
+            })
+        }
+        other => unreachable!("no benchmark named {other}"),
+    }
+}
+
+fn block_chains(c: &mut Criterion) {
+    let mut group = c.benchmark_group("block_chain");
+    for size in [1024, 32 * 1024] {
+        let data = vec![0x5A; size];
+        group.throughput(Throughput::Bytes(size as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(size), &data, |b, data| {
+            b.iter(|| block_chain(black_box(data)));
+        });
+    }
+    group.finish();
+}
+
+fn object_encoding(c: &mut Criterion) {
+    let mut group = c.benchmark_group("encode_objects");
+    for n in [1, 16] {
+        let objects = coins(n);
+        group.bench_with_input(BenchmarkId::from_parameter(n), &objects, |b, objects| {
+            b.iter(|| encode_objects(black_box(objects)).unwrap());
+        });
+    }
+    group.finish();
+}
+
+fn tx_building(c: &mut Criterion) {
+    c.bench_function("build_transfer_tx", |b| b.iter(transfer));
+    c.bench_function("build_batch_transfer_tx/100", |b| b.iter(|| batch(100)));
+}
+
+fn signing(c: &mut Criterion) {
+    let mut group = c.benchmark_group("sign_tx");
+    for name in ["transfer", "batch_100"] {
+        let mut op = operation(&format!("sign_tx/{name}"));
+        group.bench_function(name, |b| b.iter(&mut op));
+    }
+    group.finish();
+}
+
+/// Time each budgeted operation and report those over budget.
+fn check_budgets() -> bool {
+    let mut ok = true;
+    for &(name, budget) in BUDGETS {
+        let mut op = operation(name);
+        op();
+        let mut runs = 0u32;
+        let start = Instant::now();
+        while start.elapsed() < Duration::from_millis(200) {
+            op();
+            runs += 1;
+        }
+        let mean = start.elapsed() / runs;
+        let verdict = if mean <= budget { "ok" } else { "OVER" };
+        println!("budget {name}: {mean:?} (budget {budget:?}) {verdict}");
+        ok &= mean <= budget;
+    }
+    ok
+}
+
+criterion_group!(benches, block_chains, object_encoding, tx_building, signing);
+
+fn main() {
+    benches();
+    Criterion::default().configure_from_args().final_summary();
+    if std::env::var_os("BENCH_BUDGETS").is_some() && !check_budgets() {
+        std::process::exit(1);
+    }
+}
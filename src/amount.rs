@@ -0,0 +1,269 @@
+//! Conversion between nanos and human-readable IOTA amounts.
+//!
+//! 1 IOTA = 10^9 nanos. All on-chain values are in nanos; [`Amount`]
+//! carries that unit in the type so builders can't be handed whole IOTA by
+//! mistake.
+
+use core::fmt;
+use core::str::FromStr;
+
+use crate::error::LedgerError;
+use crate::prelude::*;
+
+/// Number of decimal places in one IOTA.
+pub const IOTA_DECIMALS: u32 = 9;
+
+/// Nanos per IOTA.
+pub const NANOS_PER_IOTA: u64 = 10u64.pow(IOTA_DECIMALS);
+
+/// Format nanos as a decimal IOTA string, trimming trailing zeros
+/// (`1_500_000_000` -> `"1.5"`, `1` -> `"0.000000001"`).
+#[must_use]
+pub fn to_iota_string(nanos: u64) -> String {
+    format_units(nanos, IOTA_DECIMALS)
+}
+
+/// Format a raw on-chain amount of a coin with `decimals` decimal places,
+/// trimming trailing zeros (`format_units(1_500_000, 6)` -> `"1.5"`).
+#[must_use]
+pub fn format_units(raw: u64, decimals: u32) -> String {
+    let Some(unit) = 10u64.checked_pow(decimals) else {
+        // More decimals than u64 has digits: always below one unit.
+        let digits = raw.to_string();
+        let frac = format!("{digits:0>width$}", width = decimals as usize);
+        let frac = frac.trim_end_matches('0');
+        return if frac.is_empty() {
+            "0".into()
+        } else {
+            format!("0.{frac}")
+        };
+    };
+    let whole = raw / unit;
+    let frac = raw % unit;
+    if frac == 0 {
+        return whole.to_string();
+    }
+    let frac = format!("{frac:0width$}", width = decimals as usize);
+    format!("{whole}.{}", frac.trim_end_matches('0'))
+}
+
+/// Parse a decimal IOTA string (`"1.5"`, `"0.000000001"`, `"42"`) into nanos.
+///
+/// Rejects signs, exponents, more than 9 decimal places, and values that
+/// don't fit in a `u64`.
+pub fn from_iota_string(s: &str) -> Result<u64, LedgerError> {
+    let s = s.trim();
+    let (whole, frac) = match s.split_once('.') {
+        Some((w, f)) => (w, f),
+        None => (s, ""),
+    };
+
+    if whole.is_empty() && frac.is_empty() {
+        return Err(LedgerError::InvalidAmount(format!("{s:?} is not a number")));
+    }
+    if !whole
+        .bytes()
+        .chain(frac.bytes())
+        .all(|b| b.is_ascii_digit())
+    {
+        return Err(LedgerError::InvalidAmount(format!(
+            "{s:?} is not a plain decimal number"
+        )));
+    }
+    if frac.len() > IOTA_DECIMALS as usize {
+        return Err(LedgerError::InvalidAmount(format!(
+            "{s:?} has more than {IOTA_DECIMALS} decimal places"
+        )));
+    }
+
+    let overflow = || LedgerError::InvalidAmount(format!("{s:?} IOTA overflows u64 nanos"));
+
+    let whole: u64 = if whole.is_empty() {
+        0
+    } else {
+        whole.parse().map_err(|_| overflow())?
+    };
+    let frac: u64 = if frac.is_empty() {
+        0
+    } else {
+        let padded = format!("{frac:0<width$}", width = IOTA_DECIMALS as usize);
+        padded.parse().map_err(|_| overflow())?
+    };
+
+    whole
+        .checked_mul(NANOS_PER_IOTA)
+        .and_then(|n| n.checked_add(frac))
+        .ok_or_else(overflow)
+}
+
+/// An IOTA amount, stored in nanos.
+///
+/// Arithmetic is checked; construct from nanos or whole IOTA explicitly.
+/// `Display` and `FromStr` use decimal IOTA (`"1.5 IOTA"` / `"1.5"`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Amount(u64);
+
+impl Amount {
+    pub const ZERO: Self = Self(0);
+    pub const MAX: Self = Self(u64::MAX);
+
+    #[must_use]
+    pub const fn from_nanos(nanos: u64) -> Self {
+        Self(nanos)
+    }
+
+    /// `None` if `iota` whole IOTA overflows `u64` nanos.
+    #[must_use]
+    pub const fn from_iota(iota: u64) -> Option<Self> {
+        match iota.checked_mul(NANOS_PER_IOTA) {
+            Some(nanos) => Some(Self(nanos)),
+            None => None,
+        }
+    }
+
+    #[must_use]
+    pub const fn nanos(self) -> u64 {
+        self.0
+    }
+
+    #[must_use]
+    pub const fn checked_add(self, rhs: Self) -> Option<Self> {
+        match self.0.checked_add(rhs.0) {
+            Some(n) => Some(Self(n)),
+            None => None,
+        }
+    }
+
+    #[must_use]
+    pub const fn checked_sub(self, rhs: Self) -> Option<Self> {
+        match self.0.checked_sub(rhs.0) {
+            Some(n) => Some(Self(n)),
+            None => None,
+        }
+    }
+
+    #[must_use]
+    pub const fn checked_mul(self, factor: u64) -> Option<Self> {
+        match self.0.checked_mul(factor) {
+            Some(n) => Some(Self(n)),
+            None => None,
+        }
+    }
+}
+
+impl fmt::Display for Amount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} IOTA", to_iota_string(self.0))
+    }
+}
+
+/// Parses decimal IOTA (`"1.5"`), with or without a trailing `IOTA` unit.
+impl FromStr for Amount {
+    type Err = LedgerError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let s = s.strip_suffix("IOTA").unwrap_or(s);
+        from_iota_string(s).map(Self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_whole_and_fractional() {
+        assert_eq!(to_iota_string(0), "0");
+        assert_eq!(to_iota_string(1), "0.000000001");
+        assert_eq!(to_iota_string(NANOS_PER_IOTA), "1");
+        assert_eq!(to_iota_string(1_500_000_000), "1.5");
+        assert_eq!(to_iota_string(u64::MAX), "18446744073.709551615");
+    }
+
+    #[test]
+    fn format_units_other_decimals() {
+        assert_eq!(format_units(1_500_000, 6), "1.5");
+        assert_eq!(format_units(42, 0), "42");
+        assert_eq!(format_units(1, 2), "0.01");
+        assert_eq!(format_units(0, 25), "0");
+        assert_eq!(format_units(5, 25), "0.0000000000000000000000005");
+    }
+
+    #[test]
+    fn parse_valid_amounts() {
+        assert_eq!(from_iota_string("0").unwrap(), 0);
+        assert_eq!(from_iota_string("1").unwrap(), NANOS_PER_IOTA);
+        assert_eq!(from_iota_string("1.5").unwrap(), 1_500_000_000);
+        assert_eq!(from_iota_string(".5").unwrap(), 500_000_000);
+        assert_eq!(from_iota_string("2.").unwrap(), 2 * NANOS_PER_IOTA);
+        assert_eq!(from_iota_string("0.000000001").unwrap(), 1);
+        assert_eq!(from_iota_string(" 3 ").unwrap(), 3 * NANOS_PER_IOTA);
+    }
+
+    #[test]
+    fn parse_round_trips_format() {
+        for nanos in [0, 1, 999_999_999, NANOS_PER_IOTA, 123_456_789_012, u64::MAX] {
+            assert_eq!(from_iota_string(&to_iota_string(nanos)).unwrap(), nanos);
+        }
+    }
+
+    #[test]
+    fn parse_rejects_too_many_decimals() {
+        assert!(matches!(
+            from_iota_string("0.0000000001"),
+            Err(LedgerError::InvalidAmount(_))
+        ));
+    }
+
+    #[test]
+    fn parse_rejects_overflow() {
+        assert!(from_iota_string("18446744073.709551615").is_ok());
+        assert!(matches!(
+            from_iota_string("18446744073.709551616"),
+            Err(LedgerError::InvalidAmount(_))
+        ));
+        assert!(matches!(
+            from_iota_string("99999999999999999999"),
+            Err(LedgerError::InvalidAmount(_))
+        ));
+    }
+
+    #[test]
+    fn parse_rejects_garbage() {
+        for s in ["", ".", "-1", "+1", "1e9", "1.2.3", "abc", "1,5"] {
+            assert!(
+                matches!(from_iota_string(s), Err(LedgerError::InvalidAmount(_))),
+                "{s:?} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn amount_constructors() {
+        assert_eq!(Amount::from_iota(2).unwrap().nanos(), 2 * NANOS_PER_IOTA);
+        assert_eq!(Amount::from_nanos(5).nanos(), 5);
+        assert!(Amount::from_iota(u64::MAX).is_none());
+    }
+
+    #[test]
+    fn amount_checked_arithmetic() {
+        let a = Amount::from_nanos(10);
+        let b = Amount::from_nanos(3);
+        assert_eq!(a.checked_add(b), Some(Amount::from_nanos(13)));
+        assert_eq!(a.checked_sub(b), Some(Amount::from_nanos(7)));
+        assert_eq!(b.checked_sub(a), None);
+        assert_eq!(a.checked_mul(4), Some(Amount::from_nanos(40)));
+        assert_eq!(Amount::MAX.checked_add(b), None);
+        assert_eq!(Amount::MAX.checked_mul(2), None);
+    }
+
+    #[test]
+    fn amount_display_and_parse() {
+        let a = Amount::from_nanos(1_500_000_000);
+        assert_eq!(a.to_string(), "1.5 IOTA");
+        assert_eq!(a.to_string().parse::<Amount>().unwrap(), a);
+        assert_eq!("1.5".parse::<Amount>().unwrap(), a);
+        assert!("1.5 SMR".parse::<Amount>().is_err());
+    }
+}
@@ -1,6 +1,12 @@
 //! APDU command and response types.
 //!
 //! The IOTA app uses CLA `0x00` for all commands with P1/P2 always `0x00`.
+//! Dashboard commands follow ISO 7816 more closely and may need an `Le`
+//! byte ([`ApduCommand::with_le`]); send them with
+//! [`LedgerIota::exchange_apdu`](crate::LedgerIota::exchange_apdu).
+
+use crate::error::TransportError;
+use crate::wipe::Wipe;
 
 #[derive(Debug, Clone)]
 pub struct ApduCommand {
@@ -9,6 +15,12 @@ pub struct ApduCommand {
     pub p1: u8,
     pub p2: u8,
     pub data: Vec<u8>,
+    /// Expected response length byte, appended after the data; `0` means
+    /// 256. `None` omits it, as the IOTA app expects.
+    pub le: Option<u8>,
+    /// Most payload bytes (status word excluded) the response may carry;
+    /// transports reject longer responses.
+    pub response_len: Option<usize>,
 }
 
 impl ApduCommand {
@@ -19,6 +31,8 @@ impl ApduCommand {
             p1: 0x00,
             p2: 0x00,
             data: Vec::new(),
+            le: None,
+            response_len: None,
         }
     }
 
@@ -29,28 +43,72 @@ impl ApduCommand {
             p1: 0x00,
             p2: 0x00,
             data,
+            le: None,
+            response_len: None,
         }
     }
 
-    /// Wire format: `[CLA][INS][P1][P2][LC][DATA]`
-    ///
-    /// # Panics
+    /// Append `le` when serializing (ISO 7816 case 2 or 4) and expect at
+    /// most that many response bytes.
+    #[must_use]
+    pub fn with_le(mut self, le: u8) -> Self {
+        self.le = Some(le);
+        self
+    }
+
+    /// Expect at most `len` payload bytes in the response, without
+    /// sending an `Le` byte.
+    #[must_use]
+    pub fn with_response_len(mut self, len: usize) -> Self {
+        self.response_len = Some(len);
+        self
+    }
+
+    /// [`response_len`](Self::response_len), or what [`le`](Self::le)
+    /// allows; `None` if neither is set.
+    pub fn max_response_len(&self) -> Option<usize> {
+        self.response_len.or(self.le.map(|le| match le {
+            0 => 256,
+            le => usize::from(le),
+        }))
+    }
+
+    /// [`TransportError::Comm`] if `answer` carries more payload than
+    /// [`max_response_len`](Self::max_response_len).
+    pub fn check_response(&self, answer: &ApduAnswer) -> Result<(), TransportError> {
+        match self.max_response_len() {
+            Some(max) if answer.data().len() > max => Err(TransportError::Comm(format!(
+                "response to INS 0x{:02X} has {} bytes, expected at most {max}",
+                self.ins,
+                answer.data().len()
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    /// Wire format: `[CLA][INS][P1][P2][LC][DATA]`, then `[LE]` if set.
+    /// Without data, an `Le` takes the place of `LC` (`[CLA][INS][P1][P2][LE]`).
     ///
-    /// Panics if `data` exceeds 255 bytes (short APDU LC limit).
-    pub fn serialize(&self) -> Vec<u8> {
-        assert!(
-            self.data.len() <= 255,
-            "APDU data too long: {} bytes (max 255)",
-            self.data.len()
-        );
-        let mut buf = Vec::with_capacity(5 + self.data.len());
+    /// Fails with [`TransportError::ApduTooLong`] if `data` exceeds 255
+    /// bytes (short APDU LC limit).
+    pub fn serialize(&self) -> Result<Vec<u8>, TransportError> {
+        if self.data.len() > 255 {
+            return Err(TransportError::ApduTooLong(self.data.len()));
+        }
+        let mut buf = Vec::with_capacity(6 + self.data.len());
         buf.push(self.cla);
         buf.push(self.ins);
         buf.push(self.p1);
         buf.push(self.p2);
-        buf.push(self.data.len() as u8);
-        buf.extend_from_slice(&self.data);
-        buf
+        match (self.data.is_empty(), self.le) {
+            (true, Some(le)) => buf.push(le),
+            (_, le) => {
+                buf.push(self.data.len() as u8);
+                buf.extend_from_slice(&self.data);
+                buf.extend(le);
+            }
+        }
+        Ok(buf)
     }
 }
 
@@ -74,6 +132,12 @@ impl ApduAnswer {
         ((self.raw[len - 2] as u16) << 8) | (self.raw[len - 1] as u16)
     }
 
+    /// Payload followed by the status word, as received.
+    #[cfg(feature = "testing")]
+    pub(crate) fn raw(&self) -> &[u8] {
+        &self.raw
+    }
+
     /// Payload only - strips the trailing 2-byte status word.
     pub fn data(&self) -> &[u8] {
         if self.raw.len() < 2 {
@@ -81,6 +145,26 @@ impl ApduAnswer {
         }
         &self.raw[..self.raw.len() - 2]
     }
+
+    /// Payload and status word, taking the buffer without copying.
+    pub fn into_parts(self) -> (Vec<u8>, u16) {
+        let code = self.retcode();
+        let mut payload = self.raw;
+        payload.truncate(payload.len().saturating_sub(2));
+        (payload, code)
+    }
+}
+
+impl Wipe for ApduCommand {
+    fn wipe(&mut self) {
+        self.data.wipe();
+    }
+}
+
+impl Wipe for ApduAnswer {
+    fn wipe(&mut self) {
+        self.raw.wipe();
+    }
 }
 
 #[cfg(test)]
@@ -92,30 +176,81 @@ mod tests {
     #[test]
     fn serialize_empty_data() {
         let cmd = ApduCommand::new(0x42);
-        let buf = cmd.serialize();
+        let buf = cmd.serialize().unwrap();
         assert_eq!(buf, vec![0x00, 0x42, 0x00, 0x00, 0x00]);
     }
 
     #[test]
     fn serialize_with_data() {
         let cmd = ApduCommand::with_data(0x01, vec![0xAA, 0xBB]);
-        let buf = cmd.serialize();
+        let buf = cmd.serialize().unwrap();
         assert_eq!(buf, vec![0x00, 0x01, 0x00, 0x00, 0x02, 0xAA, 0xBB]);
     }
 
     #[test]
     fn serialize_max_255_bytes() {
         let cmd = ApduCommand::with_data(0x01, vec![0xFF; 255]);
-        let buf = cmd.serialize();
+        let buf = cmd.serialize().unwrap();
         assert_eq!(buf.len(), 5 + 255);
         assert_eq!(buf[4], 255); // LC byte
     }
 
     #[test]
-    #[should_panic(expected = "APDU data too long")]
-    fn serialize_panics_at_256_bytes() {
+    fn serialize_rejects_256_bytes() {
         let cmd = ApduCommand::with_data(0x01, vec![0x00; 256]);
-        cmd.serialize();
+        let err = cmd.serialize().unwrap_err();
+        assert!(matches!(err, TransportError::ApduTooLong(256)));
+        assert!(!err.is_retryable());
+    }
+
+    #[test]
+    fn serialize_with_le() {
+        let cmd = ApduCommand::new(0x01).with_le(0);
+        assert_eq!(cmd.serialize().unwrap(), vec![0x00, 0x01, 0x00, 0x00, 0x00]);
+        assert_eq!(cmd.max_response_len(), Some(256));
+
+        let mut cmd = ApduCommand::with_data(0xD8, b"IOTA".to_vec()).with_le(0x10);
+        cmd.cla = 0xE0;
+        assert_eq!(
+            cmd.serialize().unwrap(),
+            vec![0xE0, 0xD8, 0x00, 0x00, 0x04, b'I', b'O', b'T', b'A', 0x10]
+        );
+    }
+
+    #[test]
+    fn response_length_hints() {
+        let answer = ApduAnswer::from_raw(vec![0xAA, 0xBB, 0xCC, 0x90, 0x00]);
+        ApduCommand::new(0x01).check_response(&answer).unwrap();
+        ApduCommand::new(0x01)
+            .with_le(3)
+            .check_response(&answer)
+            .unwrap();
+        assert!(matches!(
+            ApduCommand::new(0x01).with_le(2).check_response(&answer),
+            Err(TransportError::Comm(_))
+        ));
+        // An explicit hint overrides Le.
+        let cmd = ApduCommand::new(0x01).with_le(2).with_response_len(8);
+        assert_eq!(cmd.max_response_len(), Some(8));
+        cmd.check_response(&answer).unwrap();
+    }
+
+    // -- Instruction --
+
+    #[test]
+    fn only_reads_are_idempotent() {
+        let of = |ins| Instruction::of(&ApduCommand::new(ins));
+        assert_eq!(of(0x02), Some(Instruction::GetPubkey));
+        assert!(of(0x00).unwrap().is_idempotent());
+        assert!(of(0x02).unwrap().is_idempotent());
+        assert!(!of(0x01).unwrap().is_idempotent());
+        assert!(!of(0x03).unwrap().is_idempotent());
+        assert!(!of(0xFF).unwrap().is_idempotent());
+        assert_eq!(of(0x42), None);
+
+        let mut dashboard = ApduCommand::new(0x02);
+        dashboard.cla = 0xE0;
+        assert_eq!(Instruction::of(&dashboard), None);
     }
 
     // -- ApduAnswer --
@@ -167,14 +302,57 @@ mod tests {
         let ans = ApduAnswer::from_raw(vec![0xAA, 0xBB, 0xCC, 0x90, 0x00]);
         assert_eq!(ans.data(), &[0xAA, 0xBB, 0xCC]);
     }
+
+    #[test]
+    fn into_parts_splits_without_copying() {
+        let raw = vec![0xAA, 0xBB, 0x69, 0x85];
+        let ptr = raw.as_ptr();
+        let (payload, code) = ApduAnswer::from_raw(raw).into_parts();
+        assert_eq!((payload.as_slice(), code), (&[0xAA, 0xBB][..], 0x6985));
+        assert_eq!(payload.as_ptr(), ptr);
+
+        let (payload, code) = ApduAnswer::from_raw(vec![0x90]).into_parts();
+        assert_eq!((payload.len(), code), (0, 0));
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[repr(u8)]
-pub enum Instruction {
+pub(crate) enum Instruction {
     GetVersion = 0x00,
     VerifyAddress = 0x01,
     GetPubkey = 0x02,
     SignTx = 0x03,
     Quit = 0xFF,
 }
+
+impl Instruction {
+    /// Whether the device asks the user to confirm before answering.
+    pub fn needs_confirmation(self) -> bool {
+        matches!(self, Self::VerifyAddress | Self::SignTx)
+    }
+
+    /// Whether an APDU with this instruction can be sent twice without
+    /// effect on the device: it only reads state and never prompts.
+    #[cfg(any(feature = "hid", test))]
+    pub fn is_idempotent(self) -> bool {
+        matches!(self, Self::GetVersion | Self::GetPubkey)
+    }
+
+    /// The instruction `command` carries, if it is one of the app's.
+    #[cfg(any(feature = "hid", test))]
+    pub fn of(command: &ApduCommand) -> Option<Self> {
+        if command.cla != 0x00 {
+            return None;
+        }
+        [
+            Self::GetVersion,
+            Self::VerifyAddress,
+            Self::GetPubkey,
+            Self::SignTx,
+            Self::Quit,
+        ]
+        .into_iter()
+        .find(|ins| *ins as u8 == command.ins)
+    }
+}
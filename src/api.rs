@@ -1,23 +1,47 @@
 //! High-level API - [`LedgerIota`] wraps a transport connection and
 //! exposes all supported operations.
 
+use std::slice;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::apdu::{ApduAnswer, ApduCommand};
+use crate::capabilities::{self, SigningMode};
 use crate::commands;
 use crate::error::LedgerError;
+use crate::identity::DeviceIdentity;
+use crate::journal::SignJournal;
+use crate::limits::{PayloadKind, PayloadLimits};
+use crate::log_policy::{LogPolicy, Sensitive};
+use crate::network::NetworkProfile;
 use crate::objects::{self, ObjectData};
+use crate::session::AccountSession;
 use crate::transport::{self, Transport, TransportType};
-use crate::types::{AppVersion, Bip32Path};
+use crate::tx;
+use crate::types::{AppVersion, Bip32Path, BlindSigningHash, CoinType, TransactionDigest};
+use crate::wipe::Transient;
 
 #[cfg(not(feature = "iota-sdk-types"))]
-use crate::types::{Address, PublicKey, Signature};
+pub(crate) use crate::types::{Address, PublicKey, Signature};
 
 #[cfg(feature = "iota-sdk-types")]
-type PublicKey = iota_sdk_types::Ed25519PublicKey;
+pub(crate) type PublicKey = iota_sdk_types::Ed25519PublicKey;
+#[cfg(feature = "iota-sdk-types")]
+pub(crate) type Address = iota_sdk_types::Address;
 #[cfg(feature = "iota-sdk-types")]
-type Address = iota_sdk_types::Address;
+pub(crate) type Signature = iota_sdk_types::Ed25519Signature;
+
+/// A crate type as the API type above: converted with `iota-sdk-types`,
+/// passed through as is without it.
 #[cfg(feature = "iota-sdk-types")]
-type Signature = iota_sdk_types::Ed25519Signature;
+pub(crate) fn api_type<T: Into<U>, U>(value: T) -> U {
+    value.into()
+}
 
-const MIN_VERSION: (u8, u8, u8) = (0, 9, 0);
+#[cfg(not(feature = "iota-sdk-types"))]
+pub(crate) fn api_type<T>(value: T) -> T {
+    value
+}
 
 /// Current state of the Ledger device from the wallet's perspective.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -34,8 +58,107 @@ pub enum DeviceStatus {
     Disconnected,
 }
 
-fn is_iota_app(name: &str) -> bool {
-    name.to_ascii_lowercase().contains("iota")
+/// A transaction signature and the predicted signing mode.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignedTransaction {
+    pub signature: Signature,
+    /// Predicted host-side from the transaction and the supplied objects;
+    /// the device does not report how it presented the transaction.
+    pub mode: SigningMode,
+}
+
+/// What a signing request was for; see [`SignEvent`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SignRequest {
+    /// `digest` is `None` for bytes that aren't an intent-prefixed
+    /// transaction.
+    Transaction {
+        digest: Option<TransactionDigest>,
+    },
+    Message,
+}
+
+impl SignRequest {
+    pub(crate) fn transaction(tx: &[u8]) -> Self {
+        Self::Transaction {
+            digest: TransactionDigest::from_intent_bytes(tx).ok(),
+        }
+    }
+}
+
+/// How a signing request ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignStatus {
+    Signed,
+    /// [`LedgerError::UserRejected`].
+    Rejected,
+    /// [`LedgerError::UserTimeout`]: the prompt was left unattended.
+    TimedOut,
+    /// Any other error, including refusals before reaching the device.
+    Failed,
+}
+
+/// Passed to the [`with_sign_listener`](LedgerIota::with_sign_listener)
+/// callback when a signing request ends.
+#[derive(Debug, Clone, Copy)]
+pub struct SignEvent<'a> {
+    pub request: SignRequest,
+    pub path: &'a Bip32Path,
+    pub status: SignStatus,
+    /// Set unless the request was [signed](SignStatus::Signed).
+    pub error: Option<&'a LedgerError>,
+}
+
+impl<'a> SignEvent<'a> {
+    fn new<T>(
+        request: SignRequest,
+        path: &'a Bip32Path,
+        result: &'a Result<T, LedgerError>,
+    ) -> Self {
+        let error = result.as_ref().err();
+        let status = match error {
+            None => SignStatus::Signed,
+            Some(LedgerError::UserRejected { .. }) => SignStatus::Rejected,
+            Some(LedgerError::UserTimeout(_)) => SignStatus::TimedOut,
+            Some(_) => SignStatus::Failed,
+        };
+        Self {
+            request,
+            path,
+            status,
+            error,
+        }
+    }
+}
+
+type SignListener = Box<dyn Fn(&SignEvent<'_>) + Send + Sync>;
+
+/// A path and the keys the device derived for it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DerivedAddress {
+    pub path: Bip32Path,
+    pub public_key: PublicKey,
+    pub address: Address,
+}
+
+/// One account, change and index derived under the mainnet and the
+/// testnet coin type; see [`LedgerIota::network_addresses`].
+///
+/// Displays one line per network: name, path and address.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NetworkAddresses {
+    pub mainnet: DerivedAddress,
+    pub testnet: DerivedAddress,
+}
+
+impl std::fmt::Display for NetworkAddresses {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (network, derived) in [("mainnet", &self.mainnet), ("testnet", &self.testnet)] {
+            writeln!(f, "{network} {} {}", derived.path, derived.address)?;
+        }
+        Ok(())
+    }
 }
 
 /// High-level interface to the IOTA Ledger app.
@@ -45,22 +168,47 @@ fn is_iota_app(name: &str) -> bool {
 /// transaction signing.
 pub struct LedgerIota {
     transport: Box<dyn Transport>,
+    journal: Option<Mutex<SignJournal>>,
+    profile: NetworkProfile,
+    log_policy: LogPolicy,
+    sign_listener: Option<SignListener>,
+    /// Last [`check_status`](Self::check_status) result and when it was probed.
+    last_probe: Mutex<Option<(Instant, DeviceStatus)>>,
+    probe_interval: Duration,
+    limits: Option<PayloadLimits>,
+    #[cfg(feature = "object-cache")]
+    object_cache: Option<Mutex<Box<dyn crate::object_cache::ObjectCache>>>,
 }
 
 impl LedgerIota {
+    /// How long a [`check_status`](Self::check_status) result is reused.
+    pub const DEFAULT_PROBE_INTERVAL: Duration = Duration::from_millis(500);
+
     /// Connect to a Ledger device and verify the IOTA app is open.
     pub fn new(transport_type: &TransportType) -> Result<Self, LedgerError> {
+        Self::new_for_network(transport_type, NetworkProfile::IOTA)
+    }
+
+    /// [`new`](Self::new) for the app of another network, e.g. a fork of
+    /// IOTA Rebased with a rebranded app.
+    pub fn new_for_network(
+        transport_type: &TransportType,
+        profile: NetworkProfile,
+    ) -> Result<Self, LedgerError> {
         let transport = transport::open(transport_type)?;
-        let ledger = Self { transport };
+        let ledger = Self::with_transport(transport).with_network_profile(profile);
 
         let version = ledger.get_version()?;
-        if !is_iota_app(&version.name) {
-            return Err(LedgerError::WrongApp(version.name));
+        if !ledger.profile.matches_app(&version.name) {
+            return Err(LedgerError::WrongApp {
+                name: version.name,
+                status: None,
+            });
         }
-        if !version_ok(&version) {
+        if !ledger.profile.supports(&version) {
+            let (major, minor, patch) = ledger.profile.min_version;
             return Err(LedgerError::InvalidResponse(format!(
-                "app {version} is too old - update to at least {}.{}.{}",
-                MIN_VERSION.0, MIN_VERSION.1, MIN_VERSION.2,
+                "app {version} is too old - update to at least {major}.{minor}.{patch}",
             )));
         }
 
@@ -69,89 +217,640 @@ impl LedgerIota {
 
     /// Useful for testing or injecting a custom transport.
     pub fn with_transport(transport: Box<dyn Transport>) -> Self {
-        Self { transport }
+        Self {
+            transport,
+            journal: None,
+            profile: NetworkProfile::IOTA,
+            log_policy: LogPolicy::DEFAULT,
+            sign_listener: None,
+            last_probe: Mutex::new(None),
+            probe_interval: Self::DEFAULT_PROBE_INTERVAL,
+            limits: None,
+            #[cfg(feature = "object-cache")]
+            object_cache: None,
+        }
+    }
+
+    /// Expect the app and intent of `profile` instead of IOTA's (see
+    /// [`new_for_network`](Self::new_for_network)).
+    pub fn with_network_profile(mut self, profile: NetworkProfile) -> Self {
+        self.profile = profile;
+        self
+    }
+
+    /// The profile app names and intents are checked against.
+    pub fn network_profile(&self) -> &NetworkProfile {
+        &self.profile
+    }
+
+    /// Which addresses, paths and payloads this instance writes to the
+    /// log; [`LogPolicy::DEFAULT`] unless set. Passed on to the transport
+    /// (see [`Transport::set_log_policy`]).
+    #[must_use]
+    pub fn with_log_policy(mut self, policy: LogPolicy) -> Self {
+        self.transport.set_log_policy(&policy);
+        self.log_policy = policy;
+        self
+    }
+
+    pub fn log_policy(&self) -> &LogPolicy {
+        &self.log_policy
+    }
+
+    /// Reuse a [`check_status`](Self::check_status) result for `interval`
+    /// instead of probing the device again ([`DEFAULT_PROBE_INTERVAL`](Self::DEFAULT_PROBE_INTERVAL)
+    /// unless set); `Duration::ZERO` probes on every call.
+    #[must_use]
+    pub fn with_probe_interval(mut self, interval: Duration) -> Self {
+        self.probe_interval = interval;
+        self
+    }
+
+    /// Check payloads against `limits` instead of the connected model's,
+    /// e.g. to enforce transaction or object limits the app doesn't
+    /// publish.
+    #[must_use]
+    pub fn with_payload_limits(mut self, limits: PayloadLimits) -> Self {
+        self.limits = Some(limits);
+        self
+    }
+
+    /// Call `listener` whenever a transaction or message signing request
+    /// ends, signed or not, so a service can notify whoever asked (e.g.
+    /// with a webhook) instead of being polled.
+    ///
+    /// The listener runs on the signing thread before the result is
+    /// returned; hand slow work such as HTTP requests off to another
+    /// thread.
+    #[must_use]
+    pub fn with_sign_listener(
+        mut self,
+        listener: impl Fn(&SignEvent<'_>) + Send + Sync + 'static,
+    ) -> Self {
+        self.sign_listener = Some(Box::new(listener));
+        self
+    }
+
+    /// Run a public signing method and report its result to the sign
+    /// listener, once for each of `paths`. Every signing method goes
+    /// through here, so refusals before reaching the device are reported
+    /// as well.
+    pub(crate) fn signing<T>(
+        &self,
+        request: SignRequest,
+        paths: &[Bip32Path],
+        sign: impl FnOnce() -> Result<T, LedgerError>,
+    ) -> Result<T, LedgerError> {
+        let result = sign();
+        if let Some(listener) = &self.sign_listener {
+            for path in paths {
+                listener(&SignEvent::new(request, path, &result));
+            }
+        }
+        result
+    }
+
+    /// Record every transaction signed through this instance and refuse
+    /// (or warn about) repeats; see [`SignJournal`]. Messages signed with
+    /// [`sign_message`](Self::sign_message) are not journaled.
+    #[must_use]
+    pub fn with_sign_journal(mut self, journal: SignJournal) -> Self {
+        self.journal = Some(Mutex::new(journal));
+        self
+    }
+
+    /// Look objects up in `cache` before fetching them in
+    /// [`sign_tx_resolving`](Self::sign_tx_resolving), and store what was
+    /// fetched.
+    #[cfg(feature = "object-cache")]
+    #[must_use]
+    pub fn with_object_cache(
+        mut self,
+        cache: impl crate::object_cache::ObjectCache + 'static,
+    ) -> Self {
+        self.object_cache = Some(Mutex::new(Box::new(cache)));
+        self
     }
 
     /// Query the app version and name from the device.
     pub fn get_version(&self) -> Result<AppVersion, LedgerError> {
-        commands::get_version::exec(self.transport.as_ref())
+        self.track(commands::get_version::exec(self.transport.as_ref()))
+    }
+
+    /// Forget the [probed status](Self::check_status) if `result` shows
+    /// the app closed or the connection lost, so the next check asks the
+    /// device instead of reporting it connected.
+    pub(crate) fn track<T>(&self, result: Result<T, LedgerError>) -> Result<T, LedgerError> {
+        if matches!(
+            result,
+            Err(LedgerError::AppNotOpen | LedgerError::Transport(_))
+        ) {
+            self.forget_probe();
+        }
+        result
+    }
+
+    fn forget_probe(&self) {
+        *self.last_probe.lock().unwrap_or_else(|e| e.into_inner()) = None;
+    }
+
+    pub(crate) fn transport(&self) -> &dyn Transport {
+        self.transport.as_ref()
+    }
+
+    /// Payload size limits of the connected device model, unless set with
+    /// [`with_payload_limits`](Self::with_payload_limits).
+    pub fn limits(&self) -> PayloadLimits {
+        self.limits.unwrap_or_else(|| self.transport.limits())
+    }
+
+    /// Check `tx` and the encoded `objects` against [`limits`](Self::limits)
+    /// without talking to the device. The signing methods do this before
+    /// sending anything.
+    pub fn check_tx_size(
+        &self,
+        tx: &[u8],
+        objects: Option<&[ObjectData]>,
+    ) -> Result<(), LedgerError> {
+        let encoded = objects
+            .map(objects::encode_objects)
+            .transpose()?
+            .map(Transient);
+        self.limits()
+            .check_tx(tx.len(), encoded.as_ref().map(|e| e.len()))
     }
 
     /// Derive the public key and address for the given BIP32 path.
     pub fn get_pubkey(&self, path: &Bip32Path) -> Result<(PublicKey, Address), LedgerError> {
-        let (pk, addr) = commands::get_pubkey::exec(self.transport.as_ref(), path)?;
+        let (pk, addr) = self.track(commands::get_pubkey::exec(self.transport.as_ref(), path))?;
+        let policy = &self.log_policy;
+        log::debug!(
+            "derived {} at {}",
+            policy.show(Sensitive::Address, log::Level::Debug, &addr),
+            policy.show(Sensitive::Path, log::Level::Debug, path)
+        );
         Ok((pk.into(), addr.into()))
     }
 
+    /// [`get_pubkey`](Self::get_pubkey) for each path, in order; stops at
+    /// the first error.
+    ///
+    /// ```no_run
+    /// # use ledger_iota::{Bip32Path, LedgerIota, TransportType};
+    /// # let ledger = LedgerIota::new(&TransportType::NativeHID)?;
+    /// let first_20 = ledger.get_pubkeys(Bip32Path::iter_indices(0, 0, 0..20))?;
+    /// # Ok::<(), ledger_iota::LedgerError>(())
+    /// ```
+    pub fn get_pubkeys(
+        &self,
+        paths: impl IntoIterator<Item = Bip32Path>,
+    ) -> Result<Vec<(PublicKey, Address)>, LedgerError> {
+        paths
+            .into_iter()
+            .map(|path| self.get_pubkey(&path))
+            .collect()
+    }
+
+    /// Derive the key at `path` once and sign, verify and check senders
+    /// against it without passing the path again; see [`AccountSession`].
+    pub fn open_account(&self, path: Bip32Path) -> Result<AccountSession<'_>, LedgerError> {
+        AccountSession::open(self, path)
+    }
+
+    /// Derive `account`/`change`/`index` under both the profile's coin
+    /// type (`4218'` for IOTA) and the testnet one (`1'`), for checking a
+    /// flow on both networks with the same device.
+    pub fn network_addresses(
+        &self,
+        account: u32,
+        change: u32,
+        index: u32,
+    ) -> Result<NetworkAddresses, LedgerError> {
+        let derive = |path: Bip32Path| {
+            let (public_key, address) = self.get_pubkey(&path)?;
+            Ok::<_, LedgerError>(DerivedAddress {
+                path,
+                public_key,
+                address,
+            })
+        };
+        Ok(NetworkAddresses {
+            mainnet: derive(self.profile.path(account, change, index)?)?,
+            testnet: derive(Bip32Path::for_coin(
+                CoinType::Testnet,
+                account,
+                change,
+                index,
+            )?)?,
+        })
+    }
+
+    /// Fingerprint of the seed on this device, for telling devices apart
+    /// (see [`DeviceNicknames`](crate::DeviceNicknames)).
+    pub fn device_identity(&self) -> Result<DeviceIdentity, LedgerError> {
+        let (pk, _) = self.track(commands::get_pubkey::exec(
+            self.transport.as_ref(),
+            &DeviceIdentity::path(),
+        ))?;
+        Ok(DeviceIdentity::from_pubkey(&pk))
+    }
+
     /// Shows the address on device and waits for user confirmation.
     pub fn verify_address(&self, path: &Bip32Path) -> Result<(PublicKey, Address), LedgerError> {
-        let (pk, addr) = commands::verify_address::exec(self.transport.as_ref(), path)?;
+        let (pk, addr) = self.track(commands::verify_address::exec(
+            self.transport.as_ref(),
+            path,
+        ))?;
+        log::info!(
+            "verified {} at {} on the device",
+            self.log_policy
+                .show(Sensitive::Address, log::Level::Info, &addr),
+            self.log_policy
+                .show(Sensitive::Path, log::Level::Info, path)
+        );
         Ok((pk.into(), addr.into()))
     }
 
     /// Sign an arbitrary message.
     ///
     /// The device displays the message and asks for confirmation before signing.
-    /// The signature covers `Blake2b-256([3, 0, 0] || message)`.
+    /// The signature covers `Blake2b-256([3, 0, 0] || message)`, with the
+    /// profile's intent app id in place of the last `0`.
     ///
-    /// Max message size: 2 KB on Nano X, 4 KB on other devices.
+    /// Max message size: 2 KB on Nano X, 4 KB on other devices (see
+    /// [`limits`](Self::limits)); larger messages fail with
+    /// [`LedgerError::MessageTooLarge`] before reaching the device.
     pub fn sign_message(&self, message: &[u8], path: &Bip32Path) -> Result<Signature, LedgerError> {
-        let mut intent_message = Vec::with_capacity(3 + message.len());
-        intent_message.extend_from_slice(&[3, 0, 0]);
-        intent_message.extend_from_slice(message);
-        self.sign_tx(&intent_message, path, None)
+        self.signing(SignRequest::Message, slice::from_ref(path), || {
+            self.limits().check(PayloadKind::Message, message.len())?;
+            let mut intent_message = Transient(Vec::with_capacity(3 + message.len()));
+            intent_message.extend_from_slice(&self.profile.message_intent());
+            intent_message.extend_from_slice(message);
+            self.sign_raw(&Prepared::message(&intent_message), path)
+        })
     }
 
     /// Pass `objects` to enable clear signing for non-standard tokens.
+    /// Each object is [validated](ObjectData::validate) first. `tx` must
+    /// carry the [network profile](Self::network_profile)'s transaction
+    /// intent.
+    ///
+    /// If the device refuses to blind-sign and some object inputs or gas
+    /// coins had no data in `objects`, the error is
+    /// [`LedgerError::ClearSigningDataMissing`] listing them.
+    ///
+    /// With a [`SignJournal`] attached, a transaction already signed within
+    /// the journal's window is refused before reaching the device.
     pub fn sign_tx(
         &self,
         tx: &[u8],
         path: &Bip32Path,
         objects: Option<&[ObjectData]>,
     ) -> Result<Signature, LedgerError> {
-        let encoded_objects = objects.map(objects::encode_objects);
-        let sig = commands::sign_tx::exec(
-            self.transport.as_ref(),
+        self.signing(SignRequest::transaction(tx), slice::from_ref(path), || {
+            self.sign_tx_inner(tx, path, objects)
+        })
+    }
+
+    /// [`sign_tx`](Self::sign_tx) without reporting to the sign listener,
+    /// for signing methods that report themselves.
+    pub(crate) fn sign_tx_inner(
+        &self,
+        tx: &[u8],
+        path: &Bip32Path,
+        objects: Option<&[ObjectData]>,
+    ) -> Result<Signature, LedgerError> {
+        let prepared = self.prepare(tx, objects)?;
+        self.journaled(tx, || self.sign_raw(&prepared, path))
+    }
+
+    /// Run `sign` for `tx` between the journal's check and its record,
+    /// once however many keys `sign` uses.
+    fn journaled<T>(
+        &self,
+        tx: &[u8],
+        sign: impl FnOnce() -> Result<T, LedgerError>,
+    ) -> Result<T, LedgerError> {
+        let Some(journal) = &self.journal else {
+            return sign();
+        };
+
+        let digest = BlindSigningHash::of(tx).0;
+        // Held across the device round-trip so two threads can't both pass
+        // the check for the same digest.
+        let mut journal = journal.lock().unwrap_or_else(|e| e.into_inner());
+        journal.check(&digest, SystemTime::now())?;
+        let signed = sign()?;
+        journal.record(digest, SystemTime::now())?;
+        Ok(signed)
+    }
+
+    /// Sign `tx` with the key at each of `paths`, in order, for a
+    /// multisig participant holding several keys on this device. The
+    /// device prompts once per key; the signatures come back in the order
+    /// of `paths`.
+    ///
+    /// The first failure (e.g. a rejected prompt) ends the session and is
+    /// returned. With a [`SignJournal`] attached, the transaction is
+    /// checked once before the first prompt and recorded once after the
+    /// last, so co-signing isn't mistaken for signing twice.
+    ///
+    /// The sign listener hears about each of `paths` once the session
+    /// ends, all with its outcome.
+    pub fn sign_tx_multi(
+        &self,
+        tx: &[u8],
+        paths: &[Bip32Path],
+        objects: Option<&[ObjectData]>,
+    ) -> Result<Vec<Signature>, LedgerError> {
+        self.signing(SignRequest::transaction(tx), paths, || {
+            self.sign_tx_multi_inner(tx, paths, objects)
+        })
+    }
+
+    fn sign_tx_multi_inner(
+        &self,
+        tx: &[u8],
+        paths: &[Bip32Path],
+        objects: Option<&[ObjectData]>,
+    ) -> Result<Vec<Signature>, LedgerError> {
+        if paths.is_empty() {
+            return Err(LedgerError::InvalidPath("no paths to sign with".into()));
+        }
+        if let Some(path) = paths
+            .iter()
+            .enumerate()
+            .find_map(|(i, path)| paths[..i].contains(path).then_some(path))
+        {
+            return Err(LedgerError::InvalidPath(format!("{path} is listed twice")));
+        }
+
+        // Validated, encoded and size-checked once for every key.
+        let prepared = self.prepare(tx, objects)?;
+        self.journaled(tx, || {
+            paths
+                .iter()
+                .enumerate()
+                .map(|(i, path)| {
+                    log::info!("co-signing with key {} of {}", i + 1, paths.len());
+                    self.sign_raw(&prepared, path)
+                })
+                .collect()
+        })
+    }
+
+    /// [`sign_tx`](Self::sign_tx), also predicting whether the device
+    /// clear- or blind-signed, for audit logs.
+    ///
+    /// The mode comes from [`capabilities::signing_mode`].
+    pub fn sign_tx_with_mode(
+        &self,
+        tx: &[u8],
+        path: &Bip32Path,
+        objects: Option<&[ObjectData]>,
+    ) -> Result<SignedTransaction, LedgerError> {
+        self.signing(SignRequest::transaction(tx), slice::from_ref(path), || {
+            self.sign_tx_with_mode_inner(tx, path, objects)
+        })
+    }
+
+    fn sign_tx_with_mode_inner(
+        &self,
+        tx: &[u8],
+        path: &Bip32Path,
+        objects: Option<&[ObjectData]>,
+    ) -> Result<SignedTransaction, LedgerError> {
+        let prepared = self.prepare(tx, objects)?;
+        let mode = match tx::TransactionData::from_intent_bytes(tx) {
+            Ok(data) => capabilities::signing_mode(&data, objects.unwrap_or_default()),
+            Err(_) => SigningMode::Blind,
+        };
+        let signature = self.journaled(tx, || self.sign_raw(&prepared, path))?;
+        Ok(SignedTransaction { signature, mode })
+    }
+
+    /// Validate and encode `objects` and check the sizes, once per
+    /// signing call.
+    fn prepare<'a>(
+        &self,
+        tx: &'a [u8],
+        objects: Option<&'a [ObjectData]>,
+    ) -> Result<Prepared<'a>, LedgerError> {
+        let intent = self.profile.transaction_intent();
+        if !tx.starts_with(&intent) {
+            return Err(LedgerError::InvalidTransaction(format!(
+                "transaction intent {:?} isn't the network's {intent:?}",
+                tx.get(..3).unwrap_or(tx)
+            )));
+        }
+        let encoded = match objects {
+            Some(objects) => {
+                for obj in objects {
+                    obj.validate()?;
+                }
+                Some(Transient(objects::encode_objects(objects)?))
+            }
+            None => None,
+        };
+        self.limits()
+            .check_tx(tx.len(), encoded.as_ref().map(|e| e.len()))?;
+        Ok(Prepared {
             tx,
-            path,
-            encoded_objects.as_deref(),
-        )?;
+            objects,
+            encoded,
+        })
+    }
+
+    fn sign_raw(
+        &self,
+        prepared: &Prepared<'_>,
+        path: &Bip32Path,
+    ) -> Result<Signature, LedgerError> {
+        let tx = prepared.tx;
+        let policy = &self.log_policy;
+        log::info!(
+            "signing {} bytes at {}",
+            tx.len(),
+            policy.show(Sensitive::Path, log::Level::Info, path)
+        );
+        if log::log_enabled!(log::Level::Debug)
+            && policy.reveals(Sensitive::Payload, log::Level::Debug)
+        {
+            log::debug!("signing payload {}", hex::encode(tx));
+        }
+        let sig = self
+            .track(commands::sign_tx::exec(
+                self.transport.as_ref(),
+                tx,
+                path,
+                prepared.encoded.as_ref().map(|e| e.as_slice()),
+            ))
+            .map_err(|e| match e {
+                LedgerError::BlindSigningDisabled => blind_signing_hint(tx, prepared.objects),
+                e => e,
+            })?;
         Ok(sig.into())
     }
 
+    /// Same as [`sign_tx`](Self::sign_tx), with the transaction given as
+    /// base64 (the format returned by the JSON-RPC API and wallet dapps).
+    pub fn sign_tx_base64(
+        &self,
+        tx_base64: &str,
+        path: &Bip32Path,
+        objects: Option<&[ObjectData]>,
+    ) -> Result<Signature, LedgerError> {
+        let tx = tx::from_base64(tx_base64).map(Transient);
+        let request = match &tx {
+            Ok(tx) => SignRequest::transaction(tx),
+            Err(_) => SignRequest::Transaction { digest: None },
+        };
+        self.signing(request, slice::from_ref(path), || {
+            self.sign_tx_inner(&tx?, path, objects)
+        })
+    }
+
+    /// Sign with clear-signing data fetched from a node: decodes `tx`,
+    /// fetches its owned object inputs and gas coins through `rpc`, and
+    /// passes the usable ones to [`sign_tx`](Self::sign_tx).
+    ///
+    /// Objects that can't be resolved are left out with a warning rather
+    /// than failing the call; the device falls back to blind signing for
+    /// those.
+    #[cfg(feature = "rpc")]
+    pub fn sign_tx_resolving(
+        &self,
+        tx: &[u8],
+        path: &Bip32Path,
+        rpc: &dyn crate::rpc::RpcClient,
+    ) -> Result<Signature, LedgerError> {
+        self.signing(SignRequest::transaction(tx), slice::from_ref(path), || {
+            self.sign_tx_resolving_inner(tx, path, rpc)
+        })
+    }
+
+    #[cfg(feature = "rpc")]
+    fn sign_tx_resolving_inner(
+        &self,
+        tx: &[u8],
+        path: &Bip32Path,
+        rpc: &dyn crate::rpc::RpcClient,
+    ) -> Result<Signature, LedgerError> {
+        let data = tx::TransactionData::from_intent_bytes(tx)?;
+        #[cfg(feature = "object-cache")]
+        let objects = match &self.object_cache {
+            Some(cache) => {
+                let mut cache = cache.lock().unwrap_or_else(|e| e.into_inner());
+                crate::rpc::resolve_objects_cached(rpc, cache.as_mut(), &data)?
+            }
+            None => crate::rpc::resolve_objects(rpc, &data)?,
+        };
+        #[cfg(not(feature = "object-cache"))]
+        let objects = crate::rpc::resolve_objects(rpc, &data)?;
+        let wanted = data.object_refs().len();
+        if objects.len() < wanted {
+            log::warn!(
+                "resolved {} of {wanted} objects; the device will blind-sign",
+                objects.len()
+            );
+        }
+        let objects = (!objects.is_empty()).then_some(objects.as_slice());
+        self.sign_tx_inner(tx, path, objects)
+    }
+
+    /// Restore a wallet: derive addresses across accounts and chains (on
+    /// the [network profile](Self::network_profile)'s coin type) and
+    /// collect the balances and objects each used address holds; see
+    /// [`portfolio`](crate::portfolio) for when the scan stops.
+    #[cfg(feature = "rpc")]
+    pub fn scan_portfolio(
+        &self,
+        config: &crate::portfolio::PortfolioConfig,
+        rpc: &dyn crate::rpc::RpcClient,
+    ) -> Result<crate::portfolio::Portfolio, LedgerError> {
+        let derive = |account, chain: crate::change::Chain, index| {
+            let path = self.profile.path(account, chain.change(), index)?;
+            let (_, address) =
+                self.track(commands::get_pubkey::exec(self.transport.as_ref(), &path))?;
+            Ok((path, address.0, api_type(address)))
+        };
+        crate::portfolio::scan(derive, rpc, config, &self.log_policy)
+    }
+
+    /// Send `command` as is and return the answer, status word included.
+    ///
+    /// For APDUs this crate has no command for, such as the dashboard's
+    /// (which need [`ApduCommand::with_le`]). A status word other than
+    /// `0x9000` is not an error here; transport failures and responses
+    /// longer than the command allows are.
+    pub fn exchange_apdu(&self, command: &ApduCommand) -> Result<ApduAnswer, LedgerError> {
+        let answer = self.track(self.transport.exchange(command).map_err(LedgerError::from))?;
+        command.check_response(&answer)?;
+        Ok(answer)
+    }
+
     /// Tell the IOTA app to quit (the device goes back to the dashboard).
     pub fn quit(&self) -> Result<(), LedgerError> {
+        self.forget_probe();
         commands::quit::exec(self.transport.as_ref())
     }
 
-    /// Check whether the IOTA app is currently open on the device.
+    /// Check whether the IOTA app is currently open on the device; see
+    /// [`check_status`](Self::check_status).
+    ///
+    /// Like `check_status`, this may answer from a recent probe instead of
+    /// asking the device, and on a stale USB handle it reconnects the HID
+    /// transport before answering.
     pub fn is_app_open(&self) -> bool {
-        match self.get_version() {
-            Ok(v) => is_iota_app(&v.name),
-            Err(_) => false,
-        }
+        self.check_status() == DeviceStatus::Connected
     }
 
     /// Probe the device and return its current status.
+    ///
+    /// A result less than the [probe interval](Self::with_probe_interval)
+    /// old is returned without touching the device, and callers on other
+    /// threads wait for a probe in flight and share its result, so UIs can
+    /// poll this freely.
     pub fn check_status(&self) -> DeviceStatus {
-        match self.get_version() {
-            Ok(v) if is_iota_app(&v.name) => DeviceStatus::Connected,
+        // Held across the probe so concurrent callers share it.
+        let mut last = self.last_probe.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some((at, status)) = &*last {
+            if at.elapsed() < self.probe_interval {
+                return status.clone();
+            }
+        }
+        let status = self.probe_status();
+        *last = Some((Instant::now(), status.clone()));
+        status
+    }
+
+    /// Asks the device directly: [`get_version`](Self::get_version) would
+    /// [forget](Self::track) the probe whose lock the caller holds.
+    fn probe_status(&self) -> DeviceStatus {
+        match commands::get_version::exec(self.transport.as_ref()) {
+            Ok(v) if self.profile.matches_app(&v.name) => DeviceStatus::Connected,
             Ok(v) => DeviceStatus::WrongApp(v.name),
             Err(LedgerError::DeviceLocked) => DeviceStatus::Locked,
             Err(LedgerError::AppNotOpen) => DeviceStatus::AppClosed,
-            Err(LedgerError::WrongApp(name)) => DeviceStatus::WrongApp(name),
+            Err(LedgerError::WrongApp { name, .. }) => DeviceStatus::WrongApp(name),
+            Err(LedgerError::LegacyAppDetected { version }) => {
+                DeviceStatus::WrongApp(format!("IOTA (legacy) {version}"))
+            }
             Err(LedgerError::Transport(_)) => {
                 #[cfg(feature = "hid")]
                 if transport::hid::HidTransport::is_device_present() {
                     // Device is on USB but the stale handle can't talk to it.
                     // Try to reconnect and re-probe before assuming "locked".
                     if self.transport.reconnect().is_ok() {
-                        return match self.get_version() {
-                            Ok(v) if is_iota_app(&v.name) => DeviceStatus::Connected,
+                        return match commands::get_version::exec(self.transport.as_ref()) {
+                            Ok(v) if self.profile.matches_app(&v.name) => DeviceStatus::Connected,
                             Ok(v) => DeviceStatus::WrongApp(v.name),
                             Err(LedgerError::DeviceLocked) => DeviceStatus::Locked,
                             Err(LedgerError::AppNotOpen) => DeviceStatus::AppClosed,
-                            Err(LedgerError::WrongApp(name)) => DeviceStatus::WrongApp(name),
+                            Err(LedgerError::WrongApp { name, .. }) => DeviceStatus::WrongApp(name),
+                            Err(LedgerError::LegacyAppDetected { version }) => {
+                                DeviceStatus::WrongApp(format!("IOTA (legacy) {version}"))
+                            }
                             Err(_) => DeviceStatus::Locked,
                         };
                     }
@@ -165,32 +864,344 @@ impl LedgerIota {
 
     /// Reconnect the underlying transport and verify the IOTA app is still open.
     pub fn reconnect(&self) -> Result<(), LedgerError> {
+        self.forget_probe();
         self.transport.reconnect()?;
         let version = self.get_version()?;
-        if !is_iota_app(&version.name) {
-            return Err(LedgerError::WrongApp(version.name));
+        if !self.profile.matches_app(&version.name) {
+            return Err(LedgerError::WrongApp {
+                name: version.name,
+                status: None,
+            });
         }
         Ok(())
     }
 }
 
-fn version_ok(v: &AppVersion) -> bool {
-    (v.major, v.minor, v.patch) >= MIN_VERSION
+/// Bytes to sign with their objects, validated, encoded and
+/// size-checked by [`LedgerIota::prepare`].
+struct Prepared<'a> {
+    tx: &'a [u8],
+    objects: Option<&'a [ObjectData]>,
+    encoded: Option<Transient<Vec<u8>>>,
+}
+
+impl<'a> Prepared<'a> {
+    /// An intent message, size-checked by the caller.
+    fn message(intent_message: &'a [u8]) -> Self {
+        Self {
+            tx: intent_message,
+            objects: None,
+            encoded: None,
+        }
+    }
+}
+
+/// Refine a blind-signing refusal: if the app decodes every command of
+/// `tx` and some of its object inputs had no data supplied, clear signing
+/// would have worked with it.
+fn blind_signing_hint(tx: &[u8], objects: Option<&[ObjectData]>) -> LedgerError {
+    let Ok(data) = tx::TransactionData::from_intent_bytes(tx) else {
+        return LedgerError::BlindSigningDisabled;
+    };
+    if !capabilities::clear_signable_shape(&data) {
+        return LedgerError::BlindSigningDisabled;
+    }
+    let supplied: Vec<_> = objects
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|obj| obj.digest().ok())
+        .collect();
+    let object_ids: Vec<[u8; 32]> = data
+        .object_refs()
+        .into_iter()
+        .filter(|r| !supplied.contains(&r.digest))
+        .map(|r| r.object_id)
+        .collect();
+    if object_ids.is_empty() {
+        LedgerError::BlindSigningDisabled
+    } else {
+        LedgerError::ClearSigningDataMissing { object_ids }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::error::TransportError;
+    use crate::objects::{Owner, StructTag};
+    use crate::types::TransactionDigest;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
 
-    fn version(major: u8, minor: u8, patch: u8) -> AppVersion {
-        AppVersion {
-            major,
-            minor,
-            patch,
-            name: "iota".into(),
+    /// Answers every exchange with a GetVersion result for app 0.9.0.
+    struct VersionOnly(Arc<AtomicUsize>);
+
+    impl Transport for VersionOnly {
+        fn exchange(&self, _: &ApduCommand) -> Result<ApduAnswer, TransportError> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            Ok(ApduAnswer::from_raw(vec![
+                0x01, 0, 9, 0, b'I', b'O', b'T', b'A', 0x90, 0x00,
+            ]))
         }
     }
 
+    #[test]
+    fn oversized_payloads_fail_before_reaching_the_device() {
+        let exchanges = Arc::new(AtomicUsize::new(0));
+        let ledger = LedgerIota::with_transport(Box::new(VersionOnly(exchanges.clone())));
+        let path = Bip32Path::iota(0, 0, 0);
+
+        let err = ledger.sign_message(&[0; 4097], &path).unwrap_err();
+        assert!(matches!(
+            err,
+            LedgerError::MessageTooLarge {
+                size: 4097,
+                limit: 4096
+            }
+        ));
+        // Transactions only against limits set by the caller.
+        let ledger =
+            ledger.with_payload_limits(PayloadLimits::DEFAULT.with_transaction_limit(32 * 1024));
+        let err = ledger.sign_tx(&vec![0; 40_000], &path, None).unwrap_err();
+        assert!(matches!(
+            err,
+            LedgerError::PayloadTooLarge {
+                kind: PayloadKind::Transaction,
+                ..
+            }
+        ));
+        assert_eq!(exchanges.load(Ordering::SeqCst), 0);
+    }
+
+    #[test]
+    fn transaction_and_objects_are_checked_together() {
+        let exchanges = Arc::new(AtomicUsize::new(0));
+        let ledger = LedgerIota::with_transport(Box::new(VersionOnly(exchanges.clone())))
+            .with_payload_limits(PayloadLimits::DEFAULT.with_total_limit(8192));
+        let object = ObjectData::other(
+            StructTag::new([2; 32], "token", "TOKEN"),
+            true,
+            1,
+            vec![0; 3000],
+            Owner::Immutable,
+            TransactionDigest::ZERO,
+            0,
+        );
+        let objects = [object];
+
+        for sign in [
+            |l: &LedgerIota, tx: &[u8], o: &[ObjectData]| {
+                l.sign_tx(tx, &Bip32Path::iota(0, 0, 0), Some(o))
+                    .map(|_| ())
+            },
+            |l: &LedgerIota, tx: &[u8], o: &[ObjectData]| {
+                l.sign_tx_with_mode(tx, &Bip32Path::iota(0, 0, 0), Some(o))
+                    .map(|_| ())
+            },
+        ] {
+            let err = sign(&ledger, &[0; 6000], &objects).unwrap_err();
+            assert!(matches!(
+                err,
+                LedgerError::PayloadTooLarge {
+                    kind: PayloadKind::Total,
+                    limit: 8192,
+                    ..
+                }
+            ));
+        }
+        assert!(ledger.check_tx_size(&[0; 6000], None).is_ok());
+        assert_eq!(exchanges.load(Ordering::SeqCst), 0);
+    }
+
+    #[test]
+    fn status_probes_are_reused_within_the_interval() {
+        let exchanges = Arc::new(AtomicUsize::new(0));
+        let ledger = Arc::new(LedgerIota::with_transport(Box::new(VersionOnly(
+            exchanges.clone(),
+        ))));
+        let threads: Vec<_> = (0..4)
+            .map(|_| {
+                let ledger = ledger.clone();
+                std::thread::spawn(move || {
+                    (0..10).all(|_| {
+                        ledger.is_app_open() && ledger.check_status() == DeviceStatus::Connected
+                    })
+                })
+            })
+            .collect();
+        for thread in threads {
+            assert!(thread.join().unwrap());
+        }
+        assert_eq!(exchanges.load(Ordering::SeqCst), 1);
+
+        let ledger = LedgerIota::with_transport(Box::new(VersionOnly(exchanges.clone())))
+            .with_probe_interval(Duration::ZERO);
+        ledger.check_status();
+        ledger.check_status();
+        assert_eq!(exchanges.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn lost_app_forgets_the_probed_status() {
+        /// Answers GetVersion; everything else finds the app closed.
+        struct ClosesOnUse(Arc<AtomicUsize>);
+        impl Transport for ClosesOnUse {
+            fn exchange(&self, command: &ApduCommand) -> Result<ApduAnswer, TransportError> {
+                self.0.fetch_add(1, Ordering::SeqCst);
+                Ok(ApduAnswer::from_raw(match command.ins {
+                    0x00 => vec![0x01, 0, 9, 0, b'I', b'O', b'T', b'A', 0x90, 0x00],
+                    _ => vec![0x6E, 0x01],
+                }))
+            }
+        }
+
+        let exchanges = Arc::new(AtomicUsize::new(0));
+        let ledger = LedgerIota::with_transport(Box::new(ClosesOnUse(exchanges.clone())));
+        assert!(ledger.is_app_open());
+        assert!(matches!(
+            ledger.get_pubkey(&Bip32Path::iota(0, 0, 0)),
+            Err(LedgerError::AppNotOpen)
+        ));
+        assert!(ledger.is_app_open());
+        assert_eq!(exchanges.load(Ordering::SeqCst), 3);
+
+        ledger.quit().unwrap();
+        assert!(ledger.is_app_open());
+        assert!(ledger.is_app_open());
+        assert_eq!(exchanges.load(Ordering::SeqCst), 5);
+    }
+
+    #[test]
+    fn blind_signing_refusal_names_objects_without_data() {
+        /// Refuses everything with 0x6808.
+        struct NoBlindSigning;
+        impl Transport for NoBlindSigning {
+            fn exchange(&self, _: &ApduCommand) -> Result<ApduAnswer, TransportError> {
+                Ok(ApduAnswer::from_raw(vec![0x68, 0x08]))
+            }
+        }
+
+        let ledger = LedgerIota::with_transport(Box::new(NoBlindSigning));
+        let path = Bip32Path::iota(0, 0, 0);
+        let gas = tx::ObjectRef {
+            object_id: [0x30; 32],
+            version: 1,
+            digest: crate::types::ObjectDigest([1; 32]),
+        };
+        let data = tx::TransactionData::new(
+            tx::ProgrammableTransaction::default(),
+            [0xAB; 32],
+            vec![gas],
+            crate::Amount::from_nanos(1_000),
+            1,
+        );
+
+        let err = ledger
+            .sign_tx(&data.to_intent_bytes().unwrap(), &path, None)
+            .unwrap_err();
+        assert!(matches!(
+            &err,
+            LedgerError::ClearSigningDataMissing { object_ids } if object_ids == &[[0x30; 32]]
+        ));
+        assert_eq!(err.status_word(), Some(0x6808));
+
+        // A Move call the app can't decode is blind-signed with or without
+        // object data.
+        let mut ptb = tx::ProgrammableTransactionBuilder::new();
+        ptb.move_call([0xCC; 32], "nft", "mint", vec![], vec![]);
+        let mut nft = data.clone();
+        nft.kind = ptb.finish().unwrap();
+        let err = ledger
+            .sign_tx(&nft.to_intent_bytes().unwrap(), &path, None)
+            .unwrap_err();
+        assert!(matches!(err, LedgerError::BlindSigningDisabled));
+
+        // Nothing to supply (or not a transaction): plain refusal.
+        let mut no_objects = data;
+        no_objects.gas_payment.clear();
+        let err = ledger
+            .sign_tx(&no_objects.to_intent_bytes().unwrap(), &path, None)
+            .unwrap_err();
+        assert!(matches!(err, LedgerError::BlindSigningDisabled));
+        let err = ledger.sign_message(b"hi", &path).unwrap_err();
+        assert!(matches!(err, LedgerError::BlindSigningDisabled));
+    }
+
+    #[test]
+    fn app_name_comes_from_the_profile() {
+        let exchanges = Arc::new(AtomicUsize::new(0));
+        let iota = LedgerIota::with_transport(Box::new(VersionOnly(exchanges.clone())));
+        assert!(iota.is_app_open());
+
+        let fork = LedgerIota::with_transport(Box::new(VersionOnly(exchanges)))
+            .with_network_profile(NetworkProfile::new("Acme", crate::CoinType::Custom(9999)));
+        assert!(!fork.is_app_open());
+        assert_eq!(fork.check_status(), DeviceStatus::WrongApp("IOTA".into()));
+    }
+
+    #[test]
+    fn raw_apdus_pass_through() {
+        /// Echoes the serialized command, then an unknown-INS status word.
+        struct Echo;
+        impl Transport for Echo {
+            fn exchange(&self, cmd: &ApduCommand) -> Result<ApduAnswer, TransportError> {
+                let mut raw = cmd.serialize()?;
+                raw.extend([0x6E, 0x00]);
+                Ok(ApduAnswer::from_raw(raw))
+            }
+        }
+
+        let ledger = LedgerIota::with_transport(Box::new(Echo));
+        let mut device_info = ApduCommand::new(0x01).with_le(0);
+        device_info.cla = 0xE0;
+        let answer = ledger.exchange_apdu(&device_info).unwrap();
+        assert_eq!(answer.data(), [0xE0, 0x01, 0x00, 0x00, 0x00]);
+        assert_eq!(answer.retcode(), 0x6E00);
+
+        let err = ledger
+            .exchange_apdu(&device_info.with_response_len(4))
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            LedgerError::Transport(TransportError::Comm(_))
+        ));
+    }
+
+    #[test]
+    fn transaction_intent_comes_from_the_profile() {
+        let profile =
+            NetworkProfile::new("iota", crate::CoinType::Custom(9999)).with_intent_app_id(7);
+        let fork = LedgerIota::with_transport(Box::new(VersionOnly(Arc::default())))
+            .with_network_profile(profile.clone());
+        let data = tx::TransactionData::new(
+            tx::ProgrammableTransaction::default(),
+            [0xAB; 32],
+            vec![],
+            crate::Amount::from_nanos(1_000),
+            1,
+        );
+        let iota_tx = data.to_intent_bytes().unwrap();
+        let err = fork
+            .sign_tx(&iota_tx, &Bip32Path::iota(0, 0, 0), None)
+            .unwrap_err();
+        assert!(matches!(err, LedgerError::InvalidTransaction(_)));
+
+        let fork_tx = data.to_intent_bytes_for(&profile).unwrap();
+        assert_eq!(fork_tx[..3], [0, 0, 7]);
+        assert_eq!(
+            tx::TransactionData::from_intent_bytes(&fork_tx).unwrap(),
+            data
+        );
+    }
+
+    fn version_ok(v: &AppVersion) -> bool {
+        NetworkProfile::IOTA.supports(v)
+    }
+
+    fn version(major: u8, minor: u8, patch: u8) -> AppVersion {
+        AppVersion::new(major, minor, patch, "iota")
+    }
+
     #[test]
     fn version_ok_exact_minimum() {
         assert!(version_ok(&version(0, 9, 0)));
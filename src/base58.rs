@@ -0,0 +1,97 @@
+//! Base58 (Bitcoin alphabet), the text encoding IOTA uses for digests.
+
+use crate::prelude::*;
+
+const ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+pub(crate) fn encode(bytes: &[u8]) -> String {
+    let zeros = bytes.iter().take_while(|&&b| b == 0).count();
+
+    // Base-58 digits, little-endian.
+    let mut digits: Vec<u8> = Vec::with_capacity(bytes.len() * 138 / 100 + 1);
+    for &byte in bytes {
+        let mut carry = u32::from(byte);
+        for digit in &mut digits {
+            carry += u32::from(*digit) << 8;
+            *digit = (carry % 58) as u8;
+            carry /= 58;
+        }
+        while carry > 0 {
+            digits.push((carry % 58) as u8);
+            carry /= 58;
+        }
+    }
+
+    let mut out = String::with_capacity(zeros + digits.len());
+    out.extend(core::iter::repeat('1').take(zeros));
+    out.extend(digits.iter().rev().map(|&d| ALPHABET[d as usize] as char));
+    out
+}
+
+pub(crate) fn decode(s: &str) -> Option<Vec<u8>> {
+    let zeros = s.bytes().take_while(|&b| b == b'1').count();
+
+    // Base-256 digits, little-endian.
+    let mut out: Vec<u8> = Vec::with_capacity(s.len());
+    for c in s.bytes() {
+        let mut carry = ALPHABET.iter().position(|&a| a == c)? as u32;
+        for byte in &mut out {
+            carry += u32::from(*byte) * 58;
+            *byte = carry as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
+            out.push(carry as u8);
+            carry >>= 8;
+        }
+    }
+
+    out.extend(core::iter::repeat(0).take(zeros));
+    out.reverse();
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_known_vectors() {
+        assert_eq!(decode("").unwrap(), b"");
+        assert_eq!(decode("2g").unwrap(), b"a");
+        assert_eq!(decode("11").unwrap(), [0, 0]);
+        assert_eq!(decode("1112").unwrap(), [0, 0, 0, 1]);
+        assert_eq!(decode("StV1DL6CwTryKyV").unwrap(), b"hello world");
+    }
+
+    #[test]
+    fn decode_32_byte_digest() {
+        // 32 zero bytes are 32 '1's; all 0xFF is the largest 44-char digest.
+        assert_eq!(decode(&"1".repeat(32)).unwrap(), [0u8; 32]);
+        assert_eq!(
+            decode("JEKNVnkbo3jma5nREBBJCDoXFVeKkD56V3xKrvRmWxFG").unwrap(),
+            [0xFF; 32]
+        );
+    }
+
+    #[test]
+    fn encode_round_trips() {
+        assert_eq!(encode(b""), "");
+        assert_eq!(encode(b"hello world"), "StV1DL6CwTryKyV");
+        assert_eq!(encode(&[0, 0, 0, 1]), "1112");
+        assert_eq!(
+            encode(&[0xFF; 32]),
+            "JEKNVnkbo3jma5nREBBJCDoXFVeKkD56V3xKrvRmWxFG"
+        );
+        for bytes in [vec![0u8; 32], (0..32).collect(), vec![0, 0, 7, 255, 3]] {
+            assert_eq!(decode(&encode(&bytes)).unwrap(), bytes);
+        }
+    }
+
+    #[test]
+    fn decode_rejects_invalid_chars() {
+        for s in ["0", "O", "I", "l", "abc+"] {
+            assert!(decode(s).is_none(), "{s:?}");
+        }
+    }
+}
@@ -0,0 +1,375 @@
+//! Minimal BCS (Binary Canonical Serialization) primitives.
+//!
+//! Writers append to a `Vec<u8>`; [`Reader`] walks a byte slice. Integers
+//! are little-endian, sequence lengths are ULEB128-prefixed. Enough to
+//! encode transactions and object data by hand, including pure `MoveCall`
+//! arguments, without pulling in serde.
+
+use crate::error::LedgerError;
+use crate::prelude::*;
+
+/// BCS caps sequence lengths at `u32::MAX`.
+pub const MAX_SEQUENCE_LENGTH: u64 = u32::MAX as u64;
+
+pub fn write_uleb128(buf: &mut Vec<u8>, mut val: u64) {
+    loop {
+        let mut byte = (val & 0x7F) as u8;
+        val >>= 7;
+        if val != 0 {
+            byte |= 0x80;
+        }
+        buf.push(byte);
+        if val == 0 {
+            break;
+        }
+    }
+}
+
+pub fn write_bool(buf: &mut Vec<u8>, val: bool) {
+    buf.push(val as u8);
+}
+
+pub fn write_u8(buf: &mut Vec<u8>, val: u8) {
+    buf.push(val);
+}
+
+pub fn write_u16(buf: &mut Vec<u8>, val: u16) {
+    buf.extend_from_slice(&val.to_le_bytes());
+}
+
+pub fn write_u32(buf: &mut Vec<u8>, val: u32) {
+    buf.extend_from_slice(&val.to_le_bytes());
+}
+
+pub fn write_u64(buf: &mut Vec<u8>, val: u64) {
+    buf.extend_from_slice(&val.to_le_bytes());
+}
+
+pub fn write_u128(buf: &mut Vec<u8>, val: u128) {
+    buf.extend_from_slice(&val.to_le_bytes());
+}
+
+/// ULEB128 length prefix for a sequence of `len` elements.
+pub fn write_len(buf: &mut Vec<u8>, len: usize) {
+    write_uleb128(buf, len as u64);
+}
+
+/// `Vec<u8>`: length prefix followed by the raw bytes.
+pub fn write_bytes(buf: &mut Vec<u8>, bytes: &[u8]) {
+    write_len(buf, bytes.len());
+    buf.extend_from_slice(bytes);
+}
+
+/// `String`: same layout as `Vec<u8>` over the UTF-8 bytes.
+pub fn write_string(buf: &mut Vec<u8>, s: &str) {
+    write_bytes(buf, s.as_bytes());
+}
+
+/// `Vec<T>`: length prefix, then each element written by `write_elem`.
+pub fn write_vec<T>(buf: &mut Vec<u8>, items: &[T], mut write_elem: impl FnMut(&mut Vec<u8>, &T)) {
+    write_len(buf, items.len());
+    for item in items {
+        write_elem(buf, item);
+    }
+}
+
+/// `Option<T>`: `0x00` for `None`, `0x01` followed by the value for `Some`.
+pub fn write_option<T>(
+    buf: &mut Vec<u8>,
+    val: Option<&T>,
+    write_val: impl FnOnce(&mut Vec<u8>, &T),
+) {
+    match val {
+        None => buf.push(0),
+        Some(v) => {
+            buf.push(1);
+            write_val(buf, v);
+        }
+    }
+}
+
+/// Cursor over BCS-encoded bytes.
+#[derive(Debug, Clone)]
+pub struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    /// Offset of the next unread byte.
+    pub fn position(&self) -> usize {
+        self.pos
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.data.len() - self.pos
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.remaining() == 0
+    }
+
+    /// Error if any input is left over.
+    pub fn finish(&self) -> Result<(), LedgerError> {
+        if self.is_empty() {
+            Ok(())
+        } else {
+            Err(LedgerError::InvalidEncoding(format!(
+                "{} trailing bytes after offset {}",
+                self.remaining(),
+                self.pos
+            )))
+        }
+    }
+
+    /// Next `n` bytes without any length prefix.
+    pub fn read_raw(&mut self, n: usize) -> Result<&'a [u8], LedgerError> {
+        if n > self.remaining() {
+            return Err(LedgerError::InvalidEncoding(format!(
+                "unexpected end of input: need {n} bytes at offset {}, have {}",
+                self.pos,
+                self.remaining()
+            )));
+        }
+        let out = &self.data[self.pos..self.pos + n];
+        self.pos += n;
+        Ok(out)
+    }
+
+    pub fn read_array<const N: usize>(&mut self) -> Result<[u8; N], LedgerError> {
+        let mut out = [0u8; N];
+        out.copy_from_slice(self.read_raw(N)?);
+        Ok(out)
+    }
+
+    pub fn read_u8(&mut self) -> Result<u8, LedgerError> {
+        Ok(self.read_raw(1)?[0])
+    }
+
+    pub fn read_bool(&mut self) -> Result<bool, LedgerError> {
+        match self.read_u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            b => Err(LedgerError::InvalidEncoding(format!(
+                "invalid bool byte 0x{b:02X} at offset {}",
+                self.pos - 1
+            ))),
+        }
+    }
+
+    pub fn read_u16(&mut self) -> Result<u16, LedgerError> {
+        Ok(u16::from_le_bytes(self.read_array()?))
+    }
+
+    pub fn read_u32(&mut self) -> Result<u32, LedgerError> {
+        Ok(u32::from_le_bytes(self.read_array()?))
+    }
+
+    pub fn read_u64(&mut self) -> Result<u64, LedgerError> {
+        Ok(u64::from_le_bytes(self.read_array()?))
+    }
+
+    pub fn read_u128(&mut self) -> Result<u128, LedgerError> {
+        Ok(u128::from_le_bytes(self.read_array()?))
+    }
+
+    /// ULEB128 value. Rejects non-canonical encodings (trailing zero
+    /// groups) and values that don't fit in a `u64`.
+    pub fn read_uleb128(&mut self) -> Result<u64, LedgerError> {
+        let start = self.pos;
+        let mut val: u64 = 0;
+        let mut shift = 0;
+        loop {
+            let byte = self.read_u8()?;
+            let bits = (byte & 0x7F) as u64;
+            if shift == 63 && bits > 1 || shift > 63 {
+                return Err(LedgerError::InvalidEncoding(format!(
+                    "ULEB128 overflow at offset {start}"
+                )));
+            }
+            val |= bits << shift;
+            if byte & 0x80 == 0 {
+                if byte == 0 && shift > 0 {
+                    return Err(LedgerError::InvalidEncoding(format!(
+                        "non-canonical ULEB128 at offset {start}"
+                    )));
+                }
+                return Ok(val);
+            }
+            shift += 7;
+        }
+    }
+
+    /// Sequence length prefix, capped at [`MAX_SEQUENCE_LENGTH`].
+    pub fn read_len(&mut self) -> Result<usize, LedgerError> {
+        let start = self.pos;
+        let len = self.read_uleb128()?;
+        if len > MAX_SEQUENCE_LENGTH {
+            return Err(LedgerError::InvalidEncoding(format!(
+                "sequence length {len} at offset {start} exceeds the BCS limit"
+            )));
+        }
+        Ok(len as usize)
+    }
+
+    /// Length-prefixed `Vec<u8>`, borrowed from the input.
+    pub fn read_bytes(&mut self) -> Result<&'a [u8], LedgerError> {
+        let len = self.read_len()?;
+        self.read_raw(len)
+    }
+
+    pub fn read_string(&mut self) -> Result<&'a str, LedgerError> {
+        let start = self.pos;
+        let bytes = self.read_bytes()?;
+        core::str::from_utf8(bytes).map_err(|e| {
+            LedgerError::InvalidEncoding(format!("invalid UTF-8 string at offset {start}: {e}"))
+        })
+    }
+
+    /// `Vec<T>` with each element decoded by `read_elem`.
+    pub fn read_vec<T>(
+        &mut self,
+        mut read_elem: impl FnMut(&mut Self) -> Result<T, LedgerError>,
+    ) -> Result<Vec<T>, LedgerError> {
+        let len = self.read_len()?;
+        // Every element takes at least one byte, so a larger count is
+        // malformed -- don't let it drive a huge allocation.
+        let mut out = Vec::with_capacity(len.min(self.remaining()));
+        for _ in 0..len {
+            out.push(read_elem(self)?);
+        }
+        Ok(out)
+    }
+
+    pub fn read_option<T>(
+        &mut self,
+        read_val: impl FnOnce(&mut Self) -> Result<T, LedgerError>,
+    ) -> Result<Option<T>, LedgerError> {
+        match self.read_u8()? {
+            0 => Ok(None),
+            1 => Ok(Some(read_val(self)?)),
+            b => Err(LedgerError::InvalidEncoding(format!(
+                "invalid option tag 0x{b:02X} at offset {}",
+                self.pos - 1
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn uleb128_encoding() {
+        let mut buf = Vec::new();
+        write_uleb128(&mut buf, 0);
+        assert_eq!(buf, vec![0]);
+
+        buf.clear();
+        write_uleb128(&mut buf, 127);
+        assert_eq!(buf, vec![127]);
+
+        buf.clear();
+        write_uleb128(&mut buf, 128);
+        assert_eq!(buf, vec![0x80, 0x01]);
+
+        buf.clear();
+        write_uleb128(&mut buf, 300);
+        assert_eq!(buf, vec![0xAC, 0x02]);
+    }
+
+    #[test]
+    fn uleb128_round_trip() {
+        for val in [0, 1, 127, 128, 300, 16_384, u32::MAX as u64, u64::MAX] {
+            let mut buf = Vec::new();
+            write_uleb128(&mut buf, val);
+            let mut r = Reader::new(&buf);
+            assert_eq!(r.read_uleb128().unwrap(), val);
+            r.finish().unwrap();
+        }
+    }
+
+    #[test]
+    fn uleb128_rejects_non_canonical() {
+        let mut r = Reader::new(&[0x80, 0x00]);
+        assert!(matches!(
+            r.read_uleb128(),
+            Err(LedgerError::InvalidEncoding(_))
+        ));
+    }
+
+    #[test]
+    fn uleb128_rejects_overflow() {
+        let mut r = Reader::new(&[0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x02]);
+        assert!(matches!(
+            r.read_uleb128(),
+            Err(LedgerError::InvalidEncoding(_))
+        ));
+    }
+
+    #[test]
+    fn primitives_round_trip() {
+        let mut buf = Vec::new();
+        write_bool(&mut buf, true);
+        write_u8(&mut buf, 0xAB);
+        write_u16(&mut buf, 0x1234);
+        write_u32(&mut buf, 0xDEAD_BEEF);
+        write_u64(&mut buf, u64::MAX - 1);
+        write_u128(&mut buf, 1 << 100);
+        write_string(&mut buf, "coin");
+        write_vec(&mut buf, &[1u16, 2, 3], |b, v| write_u16(b, *v));
+        write_option(&mut buf, Some(&7u64), |b, v| write_u64(b, *v));
+        write_option::<u64>(&mut buf, None, |b, v| write_u64(b, *v));
+
+        let mut r = Reader::new(&buf);
+        assert!(r.read_bool().unwrap());
+        assert_eq!(r.read_u8().unwrap(), 0xAB);
+        assert_eq!(r.read_u16().unwrap(), 0x1234);
+        assert_eq!(r.read_u32().unwrap(), 0xDEAD_BEEF);
+        assert_eq!(r.read_u64().unwrap(), u64::MAX - 1);
+        assert_eq!(r.read_u128().unwrap(), 1 << 100);
+        assert_eq!(r.read_string().unwrap(), "coin");
+        assert_eq!(r.read_vec(|r| r.read_u16()).unwrap(), vec![1, 2, 3]);
+        assert_eq!(r.read_option(|r| r.read_u64()).unwrap(), Some(7));
+        assert_eq!(r.read_option(|r| r.read_u64()).unwrap(), None);
+        r.finish().unwrap();
+    }
+
+    #[test]
+    fn string_layout() {
+        let mut buf = Vec::new();
+        write_string(&mut buf, "iota");
+        assert_eq!(buf, vec![4, b'i', b'o', b't', b'a']);
+    }
+
+    #[test]
+    fn truncated_input_errors() {
+        let mut r = Reader::new(&[0x01, 0x02]);
+        assert!(matches!(r.read_u32(), Err(LedgerError::InvalidEncoding(_))));
+
+        // Length prefix claims more bytes than are present
+        let mut r = Reader::new(&[0x05, 0xAA]);
+        assert!(matches!(
+            r.read_bytes(),
+            Err(LedgerError::InvalidEncoding(_))
+        ));
+    }
+
+    #[test]
+    fn invalid_bool_and_option_tags() {
+        assert!(Reader::new(&[2]).read_bool().is_err());
+        assert!(Reader::new(&[2]).read_option(|r| r.read_u8()).is_err());
+    }
+
+    #[test]
+    fn finish_reports_trailing_bytes() {
+        let mut r = Reader::new(&[0x01, 0x02]);
+        r.read_u8().unwrap();
+        assert!(matches!(r.finish(), Err(LedgerError::InvalidEncoding(_))));
+    }
+}
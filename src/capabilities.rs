@@ -0,0 +1,150 @@
+//! What the app decodes of a transaction.
+//!
+//! [`signing_mode`] predicts whether a transaction will be clear- or
+//! blind-signed, for audit logs.
+
+use core::fmt;
+
+use crate::objects::ObjectData;
+use crate::tx::{Command, TransactionData};
+
+/// Move calls the app decodes: `(module, function)` in the system
+/// package `0x3`.
+const CLEAR_SIGNED_CALLS: &[(&str, &str)] = &[
+    ("iota_system", "request_add_stake"),
+    ("iota_system", "request_add_stake_mul_coin"),
+    ("iota_system", "request_withdraw_stake"),
+];
+
+const SYSTEM_PACKAGE: [u8; 32] = {
+    let mut id = [0; 32];
+    id[31] = 3;
+    id
+};
+
+/// How the device is expected to present a transaction for approval.
+///
+/// A host-side prediction from [`signing_mode`], not something the device
+/// reports back.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SigningMode {
+    /// Expected to be decoded and shown field by field.
+    Clear,
+    /// Expected to show only the transaction hash.
+    Blind,
+}
+
+impl SigningMode {
+    /// `"clear"` or `"blind"`, for logs.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Clear => "clear",
+            Self::Blind => "blind",
+        }
+    }
+}
+
+impl fmt::Display for SigningMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Host-side prediction of how the app signs `tx` given `objects`:
+/// [`Clear`](SigningMode::Clear) if every command is a coin split, merge or
+/// transfer or a staking call, and every owned input and gas coin has data
+/// in `objects`. Anything else is [`Blind`](SigningMode::Blind).
+#[must_use]
+pub fn signing_mode(tx: &TransactionData, objects: &[ObjectData]) -> SigningMode {
+    let objects_supplied = tx.object_refs().iter().all(|r| {
+        objects
+            .iter()
+            .any(|obj| obj.digest().is_ok_and(|d| d == r.digest))
+    });
+    if clear_signable_shape(tx) && objects_supplied {
+        SigningMode::Clear
+    } else {
+        SigningMode::Blind
+    }
+}
+
+/// Whether the app decodes every command of `tx`, so that it is
+/// clear-signed once the data of its objects is supplied.
+pub(crate) fn clear_signable_shape(tx: &TransactionData) -> bool {
+    tx.kind.commands.iter().all(|command| match command {
+        Command::SplitCoins(..) | Command::MergeCoins(..) | Command::TransferObjects(..) => true,
+        Command::MoveCall(call) => {
+            call.package == SYSTEM_PACKAGE
+                && CLEAR_SIGNED_CALLS.contains(&(call.module.as_str(), call.function.as_str()))
+        }
+        _ => false,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::objects::Owner;
+    use crate::types::TransactionDigest;
+
+    fn with_ref(obj: &ObjectData) -> crate::tx::ObjectRef {
+        crate::tx::ObjectRef {
+            object_id: [0x30; 32],
+            version: 1,
+            digest: obj.digest().unwrap(),
+        }
+    }
+
+    #[test]
+    fn signing_mode_from_shape_and_objects() {
+        use crate::amount::Amount;
+        use crate::tx::{Argument, ProgrammableTransactionBuilder};
+
+        let gas =
+            ObjectData::gas_coin(1, vec![0; 40], Owner::Immutable, TransactionDigest::ZERO, 0);
+        let staked =
+            ObjectData::staked_iota(1, vec![0; 80], Owner::Immutable, TransactionDigest::ZERO, 0);
+        let tx = |ptb: ProgrammableTransactionBuilder| {
+            TransactionData::new(
+                ptb.finish().unwrap(),
+                [0xAA; 32],
+                vec![with_ref(&gas)],
+                Amount::from_nanos(1),
+                1,
+            )
+        };
+
+        let mut transfer = ProgrammableTransactionBuilder::new();
+        let amount = transfer.pure_u64(5);
+        let coin = transfer.split_coins(Argument::GasCoin, vec![amount]);
+        let to = transfer.pure_address(&[0xBB; 32]);
+        transfer.transfer_objects(vec![coin], to);
+        let transfer = tx(transfer);
+        assert_eq!(
+            signing_mode(&transfer, core::slice::from_ref(&gas)),
+            SigningMode::Clear
+        );
+        // Gas coin data missing.
+        assert_eq!(signing_mode(&transfer, &[]), SigningMode::Blind);
+
+        let mut unstake = ProgrammableTransactionBuilder::new();
+        let system = unstake.object(crate::tx::ObjectArg::ImmOrOwnedObject(with_ref(&staked)));
+        unstake.move_call(
+            SYSTEM_PACKAGE,
+            "iota_system",
+            "request_withdraw_stake",
+            vec![],
+            vec![system],
+        );
+        let unstake = tx(unstake);
+        let objects = [gas.clone(), staked];
+        assert_eq!(signing_mode(&unstake, &objects), SigningMode::Clear);
+        assert_eq!(signing_mode(&unstake, &objects[..1]), SigningMode::Blind);
+
+        let mut other = ProgrammableTransactionBuilder::new();
+        other.move_call([0xCC; 32], "nft", "mint", vec![], vec![]);
+        assert_eq!(signing_mode(&tx(other), &[gas]), SigningMode::Blind);
+        assert_eq!(SigningMode::Blind.to_string(), "blind");
+    }
+}
@@ -0,0 +1,272 @@
+//! External (receive) and internal (change) address chains.
+//!
+//! By convention the fourth path component picks the chain: `0'` for
+//! addresses handed out to payers, `1'` for change a wallet sends back
+//! to itself. [`verify_change`] checks that an output meant as change
+//! really pays one of the wallet's own addresses.
+
+use core::fmt;
+
+use crate::error::{LedgerError, RecipientError};
+use crate::prelude::*;
+use crate::tx::TransactionData;
+use crate::types::{Bip32Path, CoinType, HARDENED};
+
+/// Which chain below an account a path is on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Chain {
+    /// `change = 0'`: receive addresses.
+    External,
+    /// `change = 1'`: change addresses.
+    Internal,
+}
+
+impl Chain {
+    /// The unhardened change component.
+    #[must_use]
+    pub const fn change(self) -> u32 {
+        match self {
+            Self::External => 0,
+            Self::Internal => 1,
+        }
+    }
+
+    /// Chain of a `44'/coin'/account'/change'/index'` path, `None` for
+    /// other shapes or change components.
+    #[must_use]
+    pub fn of(path: &Bip32Path) -> Option<Self> {
+        match path.address_components() {
+            [change, _index] if *change == HARDENED => Some(Self::External),
+            [change, _index] if *change == 1 | HARDENED => Some(Self::Internal),
+            _ => None,
+        }
+    }
+
+    /// `"receive"` or `"change"`, for wallet UIs.
+    #[must_use]
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::External => "receive",
+            Self::Internal => "change",
+        }
+    }
+
+    /// Mainnet path of address `index` on this chain of `account`.
+    #[must_use]
+    pub fn iota(self, account: u32, index: u32) -> Bip32Path {
+        Bip32Path::iota(account, self.change(), index)
+    }
+
+    /// Path of address `index` on this chain below an account path
+    /// `44'/coin'/account'` (see [`Bip32Path::account_path`]).
+    pub fn path(self, account: &Bip32Path, index: u32) -> Result<Bip32Path, LedgerError> {
+        match account.components() {
+            [_, coin, account] => Bip32Path::for_coin(
+                CoinType::from_value(coin & !HARDENED),
+                account & !HARDENED,
+                self.change(),
+                index,
+            ),
+            c => Err(LedgerError::InvalidPath(format!(
+                "account path has {} components, expected 3",
+                c.len()
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for Chain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::External => "external",
+            Self::Internal => "internal",
+        })
+    }
+}
+
+/// Check that the `TransferObjects` at `command` pays one of `own`
+/// addresses; returns that address.
+///
+/// Fails with [`LedgerError::InvalidTransaction`] if the command isn't a
+/// transfer to an address input, and with
+/// [`RecipientError::NotOwnAddress`] if the recipient isn't in `own`.
+pub fn verify_change(
+    tx: &TransactionData,
+    command: usize,
+    own: &[[u8; 32]],
+) -> Result<[u8; 32], LedgerError> {
+    let recipient = tx
+        .kind
+        .recipients()
+        .into_iter()
+        .find_map(|(i, recipient)| (i == command).then_some(recipient))
+        .ok_or_else(|| {
+            LedgerError::InvalidTransaction(format!(
+                "command {command} is not a transfer to an address input"
+            ))
+        })?;
+    if own.contains(&recipient) {
+        Ok(recipient)
+    } else {
+        Err(RecipientError::NotOwnAddress(recipient).into())
+    }
+}
+
+#[cfg(feature = "std")]
+pub use self::device::{next_change_address, next_unused_address};
+
+#[cfg(feature = "std")]
+mod device {
+    use super::Chain;
+    use crate::api::{Address, PublicKey};
+    use crate::error::LedgerError;
+    use crate::signer::IotaSigner;
+    use crate::types::{Bip32Path, HARDENED};
+
+    /// First address on `chain` of `account` (an account path) for which
+    /// `is_used` is false, deriving from index 0 upwards.
+    ///
+    /// Derives at most `max_scan` addresses, each a round trip to the
+    /// device, and fails with [`LedgerError::InvalidPath`] if they are all
+    /// used.
+    pub fn next_unused_address(
+        signer: &impl IotaSigner,
+        account: &Bip32Path,
+        chain: Chain,
+        max_scan: u32,
+        mut is_used: impl FnMut(&Address) -> bool,
+    ) -> Result<(Bip32Path, PublicKey, Address), LedgerError> {
+        for index in 0..max_scan.min(HARDENED) {
+            let path = chain.path(account, index)?;
+            let (pk, address) = signer.get_pubkey(&path)?;
+            if !is_used(&address) {
+                return Ok((path, pk, address));
+            }
+        }
+        Err(LedgerError::InvalidPath(format!(
+            "the first {max_scan} addresses on the {chain} chain of {account} are used"
+        )))
+    }
+
+    /// [`next_unused_address`] on the [internal](Chain::Internal) chain.
+    pub fn next_change_address(
+        signer: &impl IotaSigner,
+        account: &Bip32Path,
+        max_scan: u32,
+        is_used: impl FnMut(&Address) -> bool,
+    ) -> Result<(Bip32Path, PublicKey, Address), LedgerError> {
+        next_unused_address(signer, account, Chain::Internal, max_scan, is_used)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::amount::Amount;
+    use crate::tx::{Argument, ProgrammableTransactionBuilder};
+
+    #[test]
+    fn chains_of_paths() {
+        assert_eq!(Chain::of(&Bip32Path::iota(0, 0, 3)), Some(Chain::External));
+        assert_eq!(Chain::of(&Bip32Path::iota(2, 1, 0)), Some(Chain::Internal));
+        assert_eq!(Chain::of(&Bip32Path::iota(0, 2, 0)), None);
+        let account = Bip32Path::iota(0, 1, 0).account_path().unwrap();
+        assert_eq!(Chain::of(&account), None);
+
+        assert_eq!(Chain::Internal.iota(4, 7), Bip32Path::iota(4, 1, 7));
+        assert_eq!(
+            Chain::Internal.path(&account, 7).unwrap(),
+            Bip32Path::iota(0, 1, 7)
+        );
+        assert!(Chain::External.path(&Bip32Path::iota(0, 0, 0), 1).is_err());
+        assert_eq!(Chain::Internal.label(), "change");
+        assert_eq!(Chain::External.to_string(), "external");
+    }
+
+    fn payment_with_change(change_to: [u8; 32]) -> TransactionData {
+        let mut ptb = ProgrammableTransactionBuilder::new();
+        let amounts = vec![ptb.pure_u64(100), ptb.pure_u64(50)];
+        ptb.split_coins(Argument::GasCoin, amounts);
+        let payee = ptb.pure_address(&[0xBB; 32]);
+        ptb.transfer_objects(vec![Argument::NestedResult(0, 0)], payee);
+        let change = ptb.pure_address(&change_to);
+        ptb.transfer_objects(vec![Argument::NestedResult(0, 1)], change);
+        TransactionData::new(
+            ptb.finish().unwrap(),
+            [0xAA; 32],
+            vec![],
+            Amount::from_nanos(1),
+            1,
+        )
+    }
+
+    #[test]
+    fn change_must_pay_an_own_address() {
+        let own = [[0xAA; 32], [0xCC; 32]];
+        let tx = payment_with_change([0xCC; 32]);
+        assert_eq!(verify_change(&tx, 2, &own).unwrap(), [0xCC; 32]);
+
+        let err = verify_change(&tx, 1, &own).unwrap_err();
+        assert!(matches!(
+            err,
+            LedgerError::RecipientRejected(RecipientError::NotOwnAddress(a)) if a == [0xBB; 32]
+        ));
+        assert!(matches!(
+            verify_change(&tx, 0, &own),
+            Err(LedgerError::InvalidTransaction(_))
+        ));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn next_change_address_skips_used() {
+        use crate::api::{Address, PublicKey, Signature};
+        use crate::objects::ObjectData;
+        use crate::signer::IotaSigner;
+
+        /// Address `[index; 32]` for `44'/4218'/0'/1'/index'`.
+        struct Indexed;
+
+        impl IotaSigner for Indexed {
+            fn get_pubkey(&self, path: &Bip32Path) -> Result<(PublicKey, Address), LedgerError> {
+                assert_eq!(Chain::of(path), Some(Chain::Internal));
+                let index = (path.components()[4] & !HARDENED) as u8;
+                Ok((
+                    crate::types::PublicKey([index; 32]).into(),
+                    crate::types::Address([index; 32]).into(),
+                ))
+            }
+
+            fn sign_tx(
+                &self,
+                _: &[u8],
+                _: &Bip32Path,
+                _: Option<&[ObjectData]>,
+            ) -> Result<Signature, LedgerError> {
+                unreachable!()
+            }
+
+            fn sign_message(&self, _: &[u8], _: &Bip32Path) -> Result<Signature, LedgerError> {
+                unreachable!()
+            }
+        }
+
+        let account = Bip32Path::iota(0, 0, 0).account_path().unwrap();
+        let used: Vec<Address> = (0..3u8)
+            .map(|i| crate::types::Address([i; 32]).into())
+            .collect();
+        let (path, _, address) =
+            next_change_address(&Indexed, &account, 20, |a| used.contains(a)).unwrap();
+        assert_eq!(path, Bip32Path::iota(0, 1, 3));
+        assert_eq!(address, crate::types::Address([3; 32]).into());
+
+        let derived = core::cell::Cell::new(0);
+        let err = next_change_address(&Indexed, &account, 3, |a| {
+            derived.set(derived.get() + 1);
+            used.contains(a)
+        })
+        .unwrap_err();
+        assert!(matches!(err, LedgerError::InvalidPath(_)));
+        assert_eq!(derived.get(), 3);
+    }
+}
@@ -0,0 +1,245 @@
+//! Coin metadata for display and clear signing.
+//!
+//! [`CoinRegistry`] maps coin types to symbol and decimals. It is not a
+//! token list: only the native IOTA coin is bundled, since other tokens'
+//! type tags and decimals are deployment-specific and this crate has no
+//! source it can vouch for. Wallets register the tokens they support at
+//! runtime (typically from `iotax_getCoinMetadata`).
+
+use alloc::collections::BTreeMap;
+
+use crate::amount::{self, IOTA_DECIMALS};
+use crate::error::LedgerError;
+use crate::objects::{ObjectData, Owner, StructTag};
+use crate::prelude::*;
+use crate::types::TransactionDigest;
+
+/// Display metadata for one coin type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CoinMetadata {
+    pub symbol: String,
+    pub decimals: u8,
+}
+
+/// Coin type -> [`CoinMetadata`] lookup, filled by the caller apart from
+/// the native IOTA coin.
+#[derive(Debug, Clone)]
+pub struct CoinRegistry {
+    coins: BTreeMap<StructTag, CoinMetadata>,
+}
+
+impl Default for CoinRegistry {
+    /// Only the native IOTA coin (`0x2::iota::IOTA`, 9 decimals).
+    fn default() -> Self {
+        let mut registry = Self::empty();
+        registry.register(
+            iota_coin_type(),
+            CoinMetadata {
+                symbol: "IOTA".into(),
+                decimals: IOTA_DECIMALS as u8,
+            },
+        );
+        registry
+    }
+}
+
+/// `0x2::iota::IOTA`.
+#[must_use]
+pub fn iota_coin_type() -> StructTag {
+    let mut framework = [0u8; 32];
+    framework[31] = 0x2;
+    StructTag::new(framework, "iota", "IOTA")
+}
+
+impl CoinRegistry {
+    /// Registry without any bundled coins.
+    #[must_use]
+    pub fn empty() -> Self {
+        Self {
+            coins: BTreeMap::new(),
+        }
+    }
+
+    /// Add or replace the metadata for `coin_type`.
+    pub fn register(&mut self, coin_type: StructTag, metadata: CoinMetadata) {
+        self.coins.insert(coin_type, metadata);
+    }
+
+    /// [`register`](Self::register) with the coin type as a string
+    /// (`"0xabc::usdc::USDC"`).
+    pub fn register_str(
+        &mut self,
+        coin_type: &str,
+        symbol: &str,
+        decimals: u8,
+    ) -> Result<(), LedgerError> {
+        self.register(
+            coin_type.parse()?,
+            CoinMetadata {
+                symbol: symbol.into(),
+                decimals,
+            },
+        );
+        Ok(())
+    }
+
+    #[must_use]
+    pub fn get(&self, coin_type: &StructTag) -> Option<&CoinMetadata> {
+        self.coins.get(coin_type)
+    }
+
+    /// Look up by type string; short and full-length addresses both match.
+    #[must_use]
+    pub fn get_str(&self, coin_type: &str) -> Option<&CoinMetadata> {
+        self.get(&coin_type.parse().ok()?)
+    }
+
+    /// Coin type registered under `symbol`. `None` if unknown or if the
+    /// symbol is ambiguous.
+    #[must_use]
+    pub fn find_by_symbol(&self, symbol: &str) -> Option<&StructTag> {
+        let mut matches = self
+            .coins
+            .iter()
+            .filter(|(_, m)| m.symbol == symbol)
+            .map(|(t, _)| t);
+        let found = matches.next()?;
+        matches.next().is_none().then_some(found)
+    }
+
+    /// `"1.5 USDC"` for a known coin type, `None` otherwise.
+    #[must_use]
+    pub fn format_amount(&self, coin_type: &StructTag, raw: u64) -> Option<String> {
+        let meta = self.get(coin_type)?;
+        Some(format!(
+            "{} {}",
+            amount::format_units(raw, u32::from(meta.decimals)),
+            meta.symbol
+        ))
+    }
+
+    /// [`ObjectData`] for a coin of the token registered as `symbol`;
+    /// the native IOTA coin becomes [`ObjectData::gas_coin`].
+    pub fn coin_object(
+        &self,
+        symbol: &str,
+        version: u64,
+        contents: Vec<u8>,
+        owner: Owner,
+        previous_transaction: TransactionDigest,
+        storage_rebate: u64,
+    ) -> Result<ObjectData, LedgerError> {
+        let coin_type = self
+            .find_by_symbol(symbol)
+            .ok_or_else(|| LedgerError::UnknownCoin(symbol.into()))?;
+        Ok(if *coin_type == iota_coin_type() {
+            ObjectData::gas_coin(
+                version,
+                contents,
+                owner,
+                previous_transaction,
+                storage_rebate,
+            )
+        } else {
+            ObjectData::coin(
+                coin_type.clone(),
+                version,
+                contents,
+                owner,
+                previous_transaction,
+                storage_rebate,
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::objects::MoveObjectType;
+
+    const USDC: &str = "0xabc::usdc::USDC";
+
+    fn registry() -> CoinRegistry {
+        let mut r = CoinRegistry::default();
+        r.register_str(USDC, "USDC", 6).unwrap();
+        r
+    }
+
+    #[test]
+    fn bundled_registry_knows_iota() {
+        let r = CoinRegistry::default();
+        let meta = r.get_str("0x2::iota::IOTA").unwrap();
+        assert_eq!(meta.symbol, "IOTA");
+        assert_eq!(meta.decimals, 9);
+        assert!(CoinRegistry::empty().get_str("0x2::iota::IOTA").is_none());
+    }
+
+    #[test]
+    fn lookup_normalizes_addresses() {
+        let r = registry();
+        let full = format!("0x{}abc::usdc::USDC", "0".repeat(61));
+        assert_eq!(r.get_str(&full).unwrap().symbol, "USDC");
+        assert!(r.get_str("not a type").is_none());
+    }
+
+    #[test]
+    fn format_amount_uses_decimals() {
+        let r = registry();
+        let usdc: StructTag = USDC.parse().unwrap();
+        assert_eq!(r.format_amount(&usdc, 1_500_000).unwrap(), "1.5 USDC");
+        assert_eq!(
+            r.format_amount(&iota_coin_type(), 2_000_000_000).unwrap(),
+            "2 IOTA"
+        );
+        assert!(CoinRegistry::empty().format_amount(&usdc, 1).is_none());
+    }
+
+    #[test]
+    fn ambiguous_symbol_is_not_found() {
+        let mut r = registry();
+        r.register_str("0xdef::usdc::USDC", "USDC", 6).unwrap();
+        assert!(r.find_by_symbol("USDC").is_none());
+        assert!(r.find_by_symbol("IOTA").is_some());
+    }
+
+    #[test]
+    fn coin_object_by_symbol() {
+        let r = registry();
+        let obj = r
+            .coin_object(
+                "USDC",
+                1,
+                vec![0; 40],
+                Owner::Immutable,
+                TransactionDigest::ZERO,
+                0,
+            )
+            .unwrap();
+        assert!(matches!(&obj.data.type_, MoveObjectType::Coin(t) if t.name == "USDC"));
+
+        let obj = r
+            .coin_object(
+                "IOTA",
+                1,
+                vec![0; 40],
+                Owner::Immutable,
+                TransactionDigest::ZERO,
+                0,
+            )
+            .unwrap();
+        assert!(matches!(obj.data.type_, MoveObjectType::GasCoin));
+
+        assert!(matches!(
+            r.coin_object(
+                "NOPE",
+                1,
+                vec![],
+                Owner::Immutable,
+                TransactionDigest::ZERO,
+                0
+            ),
+            Err(LedgerError::UnknownCoin(_))
+        ));
+    }
+}
@@ -5,12 +5,15 @@ use crate::transport::Transport;
 use crate::types::{Address, Bip32Path, PublicKey};
 
 /// Response: `[pubkey_len][pubkey (32)][address_len][address (32)]`
+///
+/// The address is checked against one derived from the public key, and
+/// with the `verify` feature the key must be a valid Ed25519 point.
 pub fn exec(
     transport: &dyn Transport,
     path: &Bip32Path,
 ) -> Result<(PublicKey, Address), LedgerError> {
     let param = path.serialize();
-    let result = protocol::execute(transport, Instruction::GetPubkey, &[param])?;
+    let result = protocol::execute(transport, Instruction::GetPubkey, &[param.into()])?;
     parse_pubkey_response(&result)
 }
 
@@ -39,7 +42,21 @@ pub(crate) fn parse_pubkey_response(data: &[u8]) -> Result<(PublicKey, Address),
     let mut address = [0u8; 32];
     address.copy_from_slice(&data[34..66]);
 
-    Ok((PublicKey(pubkey), Address(address)))
+    let pubkey = PublicKey(pubkey);
+    let address = Address(address);
+    #[cfg(feature = "verify")]
+    if !pubkey.is_valid_point() {
+        return Err(LedgerError::InvalidResponse(format!(
+            "device returned {pubkey}, which is not a valid Ed25519 public key"
+        )));
+    }
+    if pubkey.to_address() != address {
+        return Err(LedgerError::InvalidResponse(format!(
+            "device address {address} does not match its public key {pubkey}"
+        )));
+    }
+
+    Ok((pubkey, address))
 }
 
 #[cfg(test)]
@@ -51,7 +68,7 @@ mod tests {
         data.push(32); // pk_len
         data.extend_from_slice(&[0xAA; 32]); // pubkey
         data.push(32); // addr_len
-        data.extend_from_slice(&[0xBB; 32]); // address
+        data.extend_from_slice(&PublicKey([0xAA; 32]).to_address().0); // address
         data
     }
 
@@ -59,7 +76,27 @@ mod tests {
     fn parse_valid_response() {
         let (pk, addr) = parse_pubkey_response(&valid_response()).unwrap();
         assert_eq!(pk.0, [0xAA; 32]);
-        assert_eq!(addr.0, [0xBB; 32]);
+        assert_eq!(addr, pk.to_address());
+    }
+
+    #[cfg(feature = "verify")]
+    #[test]
+    fn parse_off_curve_pubkey() {
+        let pk = PublicKey([0x02; 32]);
+        let mut data = vec![32];
+        data.extend_from_slice(&pk.0);
+        data.push(32);
+        data.extend_from_slice(&pk.to_address().0);
+        let err = parse_pubkey_response(&data).unwrap_err();
+        assert!(matches!(err, LedgerError::InvalidResponse(_)));
+    }
+
+    #[test]
+    fn parse_mismatched_address() {
+        let mut data = valid_response();
+        data[34] ^= 1;
+        let err = parse_pubkey_response(&data).unwrap_err();
+        assert!(matches!(err, LedgerError::InvalidResponse(_)));
     }
 
     #[test]
@@ -1,13 +1,51 @@
-use crate::apdu::Instruction;
-use crate::error::LedgerError;
+use crate::apdu::{ApduCommand, Instruction};
+use crate::error::{LedgerError, StatusWord};
 use crate::protocol;
 use crate::transport::Transport;
-use crate::types::AppVersion;
+use crate::types::{AppFlags, AppVersion};
 
-/// Response: `[major][minor][patch][app_name...]`
+/// Response: `[major][minor][patch][app_name...]`, or on newer builds
+/// `[major][minor][patch][name_len][app_name][flags_len][flags]`.
+///
+/// The length-prefixed layout is recognised when its lengths account for
+/// every byte of the response (`[flags_len][flags]` may be absent);
+/// anything else is all name.
+///
+/// An app refusing the command class is asked whether it is the legacy
+/// (Stardust) IOTA app, which fails with
+/// [`LedgerError::LegacyAppDetected`].
 pub fn exec(transport: &dyn Transport) -> Result<AppVersion, LedgerError> {
-    let result = protocol::execute(transport, Instruction::GetVersion, &[])?;
-    parse_version_response(&result)
+    match protocol::execute(transport, Instruction::GetVersion, &[]) {
+        Ok(result) => parse_version_response(&result),
+        Err(
+            e @ LedgerError::WrongApp {
+                status: Some(_), ..
+            },
+        ) => Err(match legacy_app_version(transport) {
+            Some(version) => LedgerError::LegacyAppDetected { version },
+            None => e,
+        }),
+        Err(e) => Err(e),
+    }
+}
+
+/// CLA of the legacy IOTA app.
+pub(crate) const LEGACY_CLA: u8 = 0x7B;
+/// Legacy `GET_APP_CONFIG`: `[major][minor][patch][flags][device][debug]`.
+pub(crate) const LEGACY_GET_APP_CONFIG: u8 = 0x10;
+
+/// Version of the legacy app, if that is what answers.
+fn legacy_app_version(transport: &dyn Transport) -> Option<String> {
+    let mut cmd = ApduCommand::new(LEGACY_GET_APP_CONFIG);
+    cmd.cla = LEGACY_CLA;
+    let answer = transport.exchange(&cmd).ok()?;
+    if !StatusWord::is_success(answer.retcode()) {
+        return None;
+    }
+    match answer.data() {
+        [major, minor, patch, _flags, ..] => Some(format!("{major}.{minor}.{patch}")),
+        _ => None,
+    }
 }
 
 pub(crate) fn parse_version_response(data: &[u8]) -> Result<AppVersion, LedgerError> {
@@ -17,19 +55,79 @@ pub(crate) fn parse_version_response(data: &[u8]) -> Result<AppVersion, LedgerEr
         ));
     }
 
-    let name = String::from_utf8_lossy(&data[3..]).to_string();
+    let (name, flags) = split_prefixed(&data[3..]).unwrap_or((&data[3..], None));
 
     Ok(AppVersion {
         major: data[0],
         minor: data[1],
         patch: data[2],
-        name,
+        name: String::from_utf8_lossy(name).to_string(),
+        flags,
     })
 }
 
+/// Name and flags of a `[name_len][name][flags_len][flags]` tail, if
+/// the lengths frame it exactly. Only one flags byte is defined.
+fn split_prefixed(rest: &[u8]) -> Option<(&[u8], Option<AppFlags>)> {
+    let (&name_len, rest) = rest.split_first()?;
+    let name = rest
+        .get(..usize::from(name_len))
+        .filter(|n| !n.is_empty())?;
+    match &rest[name.len()..] {
+        [] => Some((name, None)),
+        [1, flags] => Some((name, Some(AppFlags(*flags)))),
+        _ => None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::apdu::ApduAnswer;
+    use crate::error::TransportError;
+
+    /// Refuses CLA `0x00`; answers the legacy config query with `config`.
+    struct NotRebased {
+        config: Option<Vec<u8>>,
+    }
+
+    impl Transport for NotRebased {
+        fn exchange(&self, cmd: &ApduCommand) -> Result<ApduAnswer, TransportError> {
+            Ok(ApduAnswer::from_raw(match &self.config {
+                Some(config) if cmd.cla == LEGACY_CLA && cmd.ins == LEGACY_GET_APP_CONFIG => {
+                    [&config[..], &[0x90, 0x00]].concat()
+                }
+                _ => vec![0x6E, 0x00],
+            }))
+        }
+    }
+
+    #[test]
+    fn detects_legacy_app() {
+        let legacy = NotRebased {
+            config: Some(vec![1, 0, 6, 0x02, 0x05, 0]),
+        };
+        let err = exec(&legacy).unwrap_err();
+        assert!(matches!(&err, LedgerError::LegacyAppDetected { version } if version == "1.0.6"));
+        assert!(err.to_string().contains("IOTA Rebased"), "{err}");
+    }
+
+    #[test]
+    fn other_apps_stay_wrong_app() {
+        let other = NotRebased { config: None };
+        assert!(matches!(
+            exec(&other),
+            Err(LedgerError::WrongApp {
+                status: Some(0x6E00),
+                ..
+            })
+        ));
+        // Answers the legacy CLA, but not with an app config.
+        let short = NotRebased {
+            config: Some(vec![1]),
+        };
+        assert!(matches!(exec(&short), Err(LedgerError::WrongApp { .. })));
+    }
 
     #[test]
     fn parse_valid_version() {
@@ -39,6 +137,52 @@ mod tests {
         assert_eq!(v.minor, 2);
         assert_eq!(v.patch, 3);
         assert_eq!(v.name, "iota");
+        assert_eq!(v.flags, None);
+    }
+
+    #[test]
+    fn parse_length_prefixed_flags() {
+        let data = [0x01, 0x00, 0x00, 4, b'I', b'O', b'T', b'A', 1, 0x03];
+        let v = parse_version_response(&data).unwrap();
+        assert_eq!(v.name, "IOTA");
+        let flags = v.flags.unwrap();
+        assert!(flags.is_debug_build());
+        assert!(flags.blind_signing_enabled());
+
+        // Flags in the printable range are still flags, not name.
+        let v = parse_version_response(&[1, 0, 0, 1, b'I', 1, b'A']).unwrap();
+        assert_eq!(v.name, "I");
+        assert_eq!(v.flags, Some(AppFlags(b'A')));
+        assert!(v.flags.unwrap().is_debug_build());
+        assert!(!v.flags.unwrap().blind_signing_enabled());
+
+        // Length-prefixed name without a flags field.
+        let v = parse_version_response(&[1, 0, 0, 1, b'I']).unwrap();
+        assert_eq!(v.name, "I");
+        assert_eq!(v.flags, None);
+    }
+
+    #[test]
+    fn unframed_tail_is_all_name() {
+        // Lengths that overrun, fall short of the response, or claim more
+        // than one flags byte.
+        for data in [
+            &[1, 0, 0, 4, b'I', b'O'][..],
+            &[1, 0, 0, 1, b'I', 0x03],
+            &[1, 0, 0, 1, b'I', 2, 0x03, 0x00],
+            &[1, 0, 0, b'I', b'O', b'T', b'A', 0x03],
+        ] {
+            let v = parse_version_response(data).unwrap();
+            assert_eq!(v.flags, None, "{data:?}");
+            assert_eq!(v.name.len(), data.len() - 3, "{data:?}");
+        }
+    }
+
+    #[test]
+    fn lone_control_byte_is_the_name() {
+        let v = parse_version_response(&[1, 0, 0, 0x02]).unwrap();
+        assert_eq!(v.name, "\u{2}");
+        assert_eq!(v.flags, None);
     }
 
     #[test]
@@ -1,30 +1,31 @@
 use crate::apdu::Instruction;
 use crate::error::LedgerError;
-use crate::protocol;
+use std::borrow::Cow;
+
+use crate::protocol::{self, Param};
 use crate::transport::Transport;
 use crate::types::{Bip32Path, Signature};
+use crate::wipe::Transient;
 
 /// Params: (1) `[tx_size: u32 LE][tx_bytes]`, (2) BIP32 path,
 /// (3, optional) encoded objects for clear signing.
 /// Returns a 64-byte Ed25519 signature.
+///
+/// `tx` and `objects` are borrowed into the params, not copied.
 pub fn exec(
     transport: &dyn Transport,
     tx: &[u8],
     path: &Bip32Path,
     objects: Option<&[u8]>,
 ) -> Result<Signature, LedgerError> {
-    let mut param1 = Vec::with_capacity(4 + tx.len());
-    param1.extend_from_slice(&(tx.len() as u32).to_le_bytes());
-    param1.extend_from_slice(tx);
-
-    let param2 = path.serialize();
-    let mut params = vec![param1, param2];
-
-    if let Some(obj_data) = objects {
-        params.push(obj_data.to_vec());
-    }
+    let tx_size = (tx.len() as u32).to_le_bytes();
+    let mut params = vec![
+        Param::from_parts(vec![Cow::Borrowed(&tx_size[..]), Cow::Borrowed(tx)]),
+        path.serialize().into(),
+    ];
+    params.extend(objects.map(Param::from));
 
-    let result = protocol::execute(transport, Instruction::SignTx, &params)?;
+    let result = Transient(protocol::execute(transport, Instruction::SignTx, &params)?);
     parse_signature(&result)
 }
 
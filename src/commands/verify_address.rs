@@ -11,6 +11,6 @@ pub fn exec(
     path: &Bip32Path,
 ) -> Result<(PublicKey, Address), LedgerError> {
     let param = path.serialize();
-    let result = protocol::execute(transport, Instruction::VerifyAddress, &[param])?;
+    let result = protocol::execute(transport, Instruction::VerifyAddress, &[param.into()])?;
     super::get_pubkey::parse_pubkey_response(&result)
 }
@@ -0,0 +1,171 @@
+//! A software [`IotaSigner`] for CI (feature `dev-signer`).
+//!
+//! [`DevSigner`] derives keys from a mnemonic the way the app does
+//! (BIP39, SLIP-0010 Ed25519) and signs what the app signs, so with the
+//! default Speculos mnemonic it returns the emulator's keys and
+//! signatures. It shows nothing and approves everything: never use it
+//! with a seed that holds funds.
+//!
+//! ```
+//! use ledger_iota::dev_signer::DevSigner;
+//! use ledger_iota::{Bip32Path, IotaSigner};
+//!
+//! let signer = DevSigner::speculos();
+//! let path = Bip32Path::iota(0, 0, 0);
+//! let (pubkey, address) = signer.get_pubkey(&path)?;
+//! let signature = signer.sign_message(b"hello", &path)?;
+//! # Ok::<(), ledger_iota::LedgerError>(())
+//! ```
+
+use crate::api::{api_type, Address, PublicKey, Signature};
+use crate::error::LedgerError;
+use crate::limits::{PayloadKind, PayloadLimits};
+use crate::objects::{self, ObjectData};
+use crate::seed::Seed;
+use crate::signer::IotaSigner;
+use crate::types::Bip32Path;
+use crate::wipe::Transient;
+
+/// Signs in software with keys derived from a [`Seed`].
+#[derive(Debug)]
+pub struct DevSigner {
+    seed: Seed,
+    limits: PayloadLimits,
+}
+
+impl DevSigner {
+    #[must_use]
+    pub fn new(seed: Seed) -> Self {
+        Self {
+            seed,
+            limits: PayloadLimits::DEFAULT,
+        }
+    }
+
+    #[must_use]
+    pub fn from_mnemonic(mnemonic: &str, passphrase: &str) -> Self {
+        Self::new(Seed::from_mnemonic(mnemonic, passphrase))
+    }
+
+    /// The keys of a Speculos instance started without `--seed`.
+    #[must_use]
+    pub fn speculos() -> Self {
+        Self::new(Seed::speculos())
+    }
+
+    /// Refuse payloads over `limits`, as the device model would
+    /// (default [`PayloadLimits::DEFAULT`]).
+    #[must_use]
+    pub fn with_limits(mut self, limits: PayloadLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+}
+
+impl Default for DevSigner {
+    fn default() -> Self {
+        Self::speculos()
+    }
+}
+
+impl IotaSigner for DevSigner {
+    fn get_pubkey(&self, path: &Bip32Path) -> Result<(PublicKey, Address), LedgerError> {
+        let (pk, addr) = self.seed.keys(path);
+        Ok((api_type(pk), api_type(addr)))
+    }
+
+    fn sign_tx(
+        &self,
+        tx: &[u8],
+        path: &Bip32Path,
+        objects: Option<&[ObjectData]>,
+    ) -> Result<Signature, LedgerError> {
+        let encoded = match objects {
+            Some(objects) => {
+                for obj in objects {
+                    obj.validate()?;
+                }
+                Some(Transient(objects::encode_objects(objects)?))
+            }
+            None => None,
+        };
+        self.limits
+            .check_tx(tx.len(), encoded.as_ref().map(|e| e.len()))?;
+        Ok(api_type(self.seed.sign(path, tx)))
+    }
+
+    fn sign_message(&self, message: &[u8], path: &Bip32Path) -> Result<Signature, LedgerError> {
+        self.limits.check(PayloadKind::Message, message.len())?;
+        let mut intent_message = Transient(Vec::with_capacity(3 + message.len()));
+        intent_message.extend_from_slice(&[3, 0, 0]);
+        intent_message.extend_from_slice(message);
+        Ok(api_type(self.seed.sign(path, &intent_message)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::seed::SPECULOS_MNEMONIC;
+
+    fn path() -> Bip32Path {
+        Bip32Path::iota(0, 0, 1)
+    }
+
+    #[test]
+    fn matches_the_seed() {
+        let signer = DevSigner::default();
+        let seed = Seed::speculos();
+        let (pk, addr) = seed.keys(&path());
+        assert_eq!(
+            signer.get_pubkey(&path()).unwrap(),
+            (pk.into(), addr.into())
+        );
+
+        let tx = crate::tx::build_transfer_tx(
+            &crate::types::Address([0xAA; 32]),
+            &crate::types::Address([0xBB; 32]),
+            crate::Amount::from_nanos(500),
+            &crate::GasCoinRef {
+                object_id: [0xCC; 32],
+                version: 1,
+                digest: crate::ObjectDigest([0xDD; 32]),
+            },
+            crate::Amount::from_nanos(10_000_000),
+            1_000,
+        )
+        .unwrap();
+        assert_eq!(
+            signer.sign_tx(&tx, &path(), None).unwrap(),
+            seed.sign(&path(), &tx).into()
+        );
+        assert_eq!(
+            signer.sign_message(b"hi", &path()).unwrap(),
+            seed.sign(&path(), b"\x03\x00\x00hi").into()
+        );
+    }
+
+    #[test]
+    fn mnemonic_selects_keys() {
+        let a = DevSigner::from_mnemonic(SPECULOS_MNEMONIC, "").get_pubkey(&path());
+        let b = DevSigner::from_mnemonic(SPECULOS_MNEMONIC, "x").get_pubkey(&path());
+        assert_eq!(
+            a.unwrap(),
+            DevSigner::speculos().get_pubkey(&path()).unwrap()
+        );
+        assert_ne!(
+            b.unwrap(),
+            DevSigner::speculos().get_pubkey(&path()).unwrap()
+        );
+    }
+
+    #[test]
+    fn enforces_limits() {
+        let signer = DevSigner::speculos().with_limits(PayloadLimits::NANO_X);
+        assert!(matches!(
+            signer.sign_message(&[0; 4096], &path()),
+            Err(LedgerError::MessageTooLarge { .. })
+        ));
+        assert!(signer.sign_message(&[0; 2048], &path()).is_ok());
+    }
+}
@@ -2,23 +2,262 @@
 
 use thiserror::Error;
 
-/// Raw status words returned by the Ledger device.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-#[repr(u16)]
+use crate::prelude::*;
+
+/// Status words returned by the Ledger device.
+///
+/// [`from_u16`](Self::from_u16) and [`as_u16`](Self::as_u16) round-trip
+/// every code; ones without a named variant are [`Unknown`](Self::Unknown).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
 pub enum StatusWord {
-    Ok = 0x9000,
-    DeviceLocked = 0x5515,
-    BlindSigningDisabled = 0x6808,
-    NothingReceived = 0x6982,
-    UserRejected = 0x6985,
-    GeneralError = 0x6D00,
-    WrongApp = 0x6E00,
-    AppNotOpen = 0x6E01,
+    /// `0x9000`
+    Ok,
+    /// `0x5501`
+    RefusedOnDashboard,
+    /// `0x5515`
+    DeviceLocked,
+    /// `0x6808`
+    BlindSigningDisabled,
+    /// `0x6982`
+    NothingReceived,
+    /// `0x6985`
+    UserRejected,
+    /// `0x6A80`
+    InvalidData,
+    /// `0x6B00`
+    WrongP1P2,
+    /// `0x6D00`
+    GeneralError,
+    /// `0x6D02`
+    InsNotSupported,
+    /// `0x6E00`
+    WrongApp,
+    /// `0x6E01`
+    AppNotOpen,
+    /// `0x6Fxx`, with the low byte.
+    Exception(u8),
+    Unknown(u16),
 }
 
 impl StatusWord {
+    const NAMED: [(Self, u16); 12] = [
+        (Self::Ok, 0x9000),
+        (Self::RefusedOnDashboard, 0x5501),
+        (Self::DeviceLocked, 0x5515),
+        (Self::BlindSigningDisabled, 0x6808),
+        (Self::NothingReceived, 0x6982),
+        (Self::UserRejected, 0x6985),
+        (Self::InvalidData, 0x6A80),
+        (Self::WrongP1P2, 0x6B00),
+        (Self::GeneralError, 0x6D00),
+        (Self::InsNotSupported, 0x6D02),
+        (Self::WrongApp, 0x6E00),
+        (Self::AppNotOpen, 0x6E01),
+    ];
+
+    #[must_use]
+    pub fn from_u16(code: u16) -> Self {
+        if let Some((sw, _)) = Self::NAMED.iter().find(|(_, c)| *c == code) {
+            return *sw;
+        }
+        match code {
+            0x6F00..=0x6FFF => Self::Exception(code as u8),
+            _ => Self::Unknown(code),
+        }
+    }
+
+    #[must_use]
+    pub fn as_u16(self) -> u16 {
+        match self {
+            Self::Exception(low) => 0x6F00 | u16::from(low),
+            Self::Unknown(code) => code,
+            named => Self::NAMED
+                .iter()
+                .find(|(sw, _)| *sw == named)
+                .map_or(0, |(_, code)| *code),
+        }
+    }
+
+    #[cfg(any(feature = "std", test))]
     pub(crate) fn is_success(code: u16) -> bool {
-        code == Self::Ok as u16
+        Self::from_u16(code) == Self::Ok
+    }
+}
+
+impl From<u16> for StatusWord {
+    fn from(code: u16) -> Self {
+        Self::from_u16(code)
+    }
+}
+
+impl From<StatusWord> for u16 {
+    fn from(sw: StatusWord) -> Self {
+        sw.as_u16()
+    }
+}
+
+/// Stable numeric identifiers for [`LedgerError`] and [`TransportError`]
+/// variants, for consumers that can't carry Rust enums (FFI, IPC).
+///
+/// Codes are grouped by hundreds and never reused or renumbered; new
+/// variants get new codes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+#[repr(u32)]
+pub enum ErrorCode {
+    // Transport: 1xx
+    DeviceNotFound = 100,
+    Comm = 101,
+    ConnectionFailed = 102,
+    Timeout = 103,
+    Io = 104,
+    ApduTooLong = 105,
+
+    // Device and app: 2xx
+    DeviceStatus = 200,
+    DeviceLocked = 201,
+    AppNotOpen = 202,
+    WrongApp = 203,
+    BlindSigningDisabled = 204,
+    UserRejected = 205,
+    RefusedOnDashboard = 206,
+    InvalidData = 207,
+    WrongParameters = 208,
+    UnsupportedInstruction = 209,
+    AppException = 210,
+    UserTimeout = 211,
+    ClearSigningDataMissing = 212,
+    LegacyAppDetected = 213,
+
+    // Protocol: 3xx
+    InvalidResponse = 300,
+    BlockProtocol = 301,
+
+    // Invalid input: 4xx
+    InvalidPath = 400,
+    InvalidVersionReq = 401,
+    InvalidEncoding = 402,
+    InvalidTransaction = 403,
+    InvalidAmount = 404,
+    InvalidTypeTag = 405,
+    UnknownCoin = 406,
+    InvalidObject = 407,
+    RecipientRejected = 408,
+    PayloadTooLarge = 409,
+    MessageTooLarge = 410,
+    AccountMismatch = 411,
+
+    // Host-side services: 5xx
+    Rpc = 500,
+    DuplicateSignRequest = 501,
+    JournalStore = 502,
+    FinalityTimeout = 503,
+    AlreadyExecuted = 504,
+    BroadcastStore = 505,
+}
+
+impl ErrorCode {
+    const ALL: [Self; 40] = [
+        Self::DeviceNotFound,
+        Self::Comm,
+        Self::ConnectionFailed,
+        Self::Timeout,
+        Self::Io,
+        Self::ApduTooLong,
+        Self::DeviceStatus,
+        Self::DeviceLocked,
+        Self::AppNotOpen,
+        Self::WrongApp,
+        Self::BlindSigningDisabled,
+        Self::UserRejected,
+        Self::RefusedOnDashboard,
+        Self::InvalidData,
+        Self::WrongParameters,
+        Self::UnsupportedInstruction,
+        Self::AppException,
+        Self::UserTimeout,
+        Self::ClearSigningDataMissing,
+        Self::LegacyAppDetected,
+        Self::InvalidResponse,
+        Self::BlockProtocol,
+        Self::InvalidPath,
+        Self::InvalidVersionReq,
+        Self::InvalidEncoding,
+        Self::InvalidTransaction,
+        Self::InvalidAmount,
+        Self::InvalidTypeTag,
+        Self::UnknownCoin,
+        Self::InvalidObject,
+        Self::RecipientRejected,
+        Self::PayloadTooLarge,
+        Self::MessageTooLarge,
+        Self::AccountMismatch,
+        Self::Rpc,
+        Self::DuplicateSignRequest,
+        Self::JournalStore,
+        Self::FinalityTimeout,
+        Self::AlreadyExecuted,
+        Self::BroadcastStore,
+    ];
+
+    #[must_use]
+    pub fn from_code(code: u32) -> Option<Self> {
+        Self::ALL.into_iter().find(|c| c.code() == code)
+    }
+
+    #[must_use]
+    pub fn code(self) -> u32 {
+        self as u32
+    }
+}
+
+impl From<ErrorCode> for u32 {
+    fn from(code: ErrorCode) -> Self {
+        code.code()
+    }
+}
+
+/// Plain-data form of a [`LedgerError`] for sending to remote clients
+/// (daemon, HTTP, IPC).
+///
+/// `variant` is the [`ErrorCode`] name, which matches the `LedgerError`
+/// variant (or the `TransportError` variant for transport failures).
+#[cfg(feature = "serde")]
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ErrorReport {
+    pub variant: String,
+    pub code: u32,
+    pub message: String,
+    pub status_word: Option<u16>,
+}
+
+#[cfg(feature = "serde")]
+impl ErrorReport {
+    /// `None` for codes this version of the library doesn't know.
+    #[must_use]
+    pub fn error_code(&self) -> Option<ErrorCode> {
+        ErrorCode::from_code(self.code)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<&LedgerError> for ErrorReport {
+    fn from(e: &LedgerError) -> Self {
+        let code = e.code();
+        Self {
+            variant: format!("{code:?}"),
+            code: code.code(),
+            message: e.to_string(),
+            status_word: e.status_word(),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl core::fmt::Display for ErrorReport {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(&self.message)
     }
 }
 
@@ -37,14 +276,56 @@ pub enum LedgerError {
     #[error("IOTA app is not open — open it and try again")]
     AppNotOpen,
 
-    #[error("wrong app open on device (found {0}) — close it and open the IOTA app")]
-    WrongApp(String),
+    #[error("wrong app open on device (found {name}) — close it and open the IOTA app")]
+    WrongApp {
+        name: String,
+        /// `None` when detected from the app name rather than a status word.
+        status: Option<u16>,
+    },
+
+    #[error(
+        "the legacy (Stardust) IOTA app v{version} is open — this library needs the IOTA Rebased app: \
+         install the current IOTA app in Ledger Live, open it and try again"
+    )]
+    LegacyAppDetected { version: String },
 
     #[error("blind signing is disabled — enable it in the IOTA app settings")]
     BlindSigningDisabled,
 
+    #[error("no answer from the device after {0}ms — the confirmation prompt may have been left unattended")]
+    UserTimeout(u32),
+
+    #[error(
+        "blind signing is disabled, but this transaction could be clear-signed — supply object data for {}",
+        object_list(.object_ids)
+    )]
+    ClearSigningDataMissing {
+        /// Object inputs and gas coins without supplied [`ObjectData`](crate::ObjectData).
+        object_ids: Vec<[u8; 32]>,
+    },
+
     #[error("user rejected the request on device")]
-    UserRejected,
+    UserRejected {
+        /// `0x6985`, or `0x6D00` from app versions that report rejection that way.
+        status: u16,
+    },
+
+    #[error("request refused on the device dashboard — open the IOTA app and try again")]
+    RefusedOnDashboard,
+
+    #[error("device rejected the request data as invalid — the transaction or path may not be supported by this app version")]
+    InvalidData,
+
+    #[error(
+        "device rejected the command parameters — the IOTA app version may not match this library"
+    )]
+    WrongParameters,
+
+    #[error("the IOTA app doesn't support this command — update the app")]
+    UnsupportedInstruction,
+
+    #[error("the IOTA app hit an internal error (0x{0:04X}) — restart the app and try again")]
+    AppException(u16),
 
     #[error("invalid response: {0}")]
     InvalidResponse(String),
@@ -52,26 +333,237 @@ pub enum LedgerError {
     #[error("invalid BIP32 path: {0}")]
     InvalidPath(String),
 
+    #[error("invalid version requirement {0:?}")]
+    InvalidVersionReq(String),
+
     #[error("block protocol error: {0}")]
     BlockProtocol(String),
+
+    #[error("invalid encoding: {0}")]
+    InvalidEncoding(String),
+
+    #[error("invalid transaction: {0}")]
+    InvalidTransaction(String),
+
+    #[error("invalid amount: {0}")]
+    InvalidAmount(String),
+
+    #[error("invalid type tag: {0}")]
+    InvalidTypeTag(String),
+
+    #[error("RPC error: {0}")]
+    Rpc(String),
+
+    #[error("no coin registered with symbol {0:?}")]
+    UnknownCoin(String),
+
+    #[error("invalid object data: {0}")]
+    InvalidObject(#[from] ValidationError),
+
+    #[error("recipient rejected: {0}")]
+    RecipientRejected(#[from] RecipientError),
+
+    #[error("{kind} is {size} bytes, the device accepts at most {limit}")]
+    PayloadTooLarge {
+        kind: crate::limits::PayloadKind,
+        size: usize,
+        limit: usize,
+    },
+
+    #[error("message is {size} bytes, the device accepts at most {limit}")]
+    MessageTooLarge { size: usize, limit: usize },
+
+    #[error(
+        "account 0x{} is not the session account 0x{}",
+        hex::encode(.found),
+        hex::encode(.expected)
+    )]
+    AccountMismatch {
+        expected: [u8; 32],
+        /// Transaction sender, or the address the device derived.
+        found: [u8; 32],
+    },
+
+    #[error(
+        "transaction 0x{} was already signed {}s ago",
+        hex::encode(.digest),
+        .age.as_secs()
+    )]
+    DuplicateSignRequest {
+        digest: [u8; 32],
+        age: core::time::Duration,
+    },
+
+    #[cfg(feature = "std")]
+    #[error("sign journal storage error: {0}")]
+    JournalStore(#[source] std::io::Error),
+
+    #[error(
+        "transaction {digest} was not finalized within {}ms",
+        .waited.as_millis()
+    )]
+    FinalityTimeout {
+        digest: crate::types::TransactionDigest,
+        waited: core::time::Duration,
+    },
+
+    /// The node already knows the transaction; carries its effects.
+    #[cfg(feature = "rpc")]
+    #[error("transaction {} was already executed", .0.digest)]
+    AlreadyExecuted(Box<crate::rpc::ExecutedTransaction>),
+
+    #[cfg(feature = "rpc")]
+    #[error("broadcast queue storage error: {0}")]
+    BroadcastStore(#[source] std::io::Error),
+}
+
+fn object_list(ids: &[[u8; 32]]) -> String {
+    let ids: Vec<String> = ids
+        .iter()
+        .map(|id| format!("0x{}", hex::encode(id)))
+        .collect();
+    ids.join(", ")
 }
 
 impl LedgerError {
     pub fn from_status(code: u16) -> Self {
-        match code {
-            c if c == StatusWord::DeviceLocked as u16 => Self::DeviceLocked,
-            c if c == StatusWord::BlindSigningDisabled as u16 => Self::BlindSigningDisabled,
-            c if c == StatusWord::NothingReceived as u16 => {
-                Self::DeviceStatus(code, "nothing received")
+        match StatusWord::from_u16(code) {
+            StatusWord::DeviceLocked => Self::DeviceLocked,
+            StatusWord::BlindSigningDisabled => Self::BlindSigningDisabled,
+            StatusWord::NothingReceived => Self::DeviceStatus(code, "nothing received"),
+            StatusWord::UserRejected | StatusWord::GeneralError => {
+                Self::UserRejected { status: code }
             }
-            c if c == StatusWord::UserRejected as u16 || c == StatusWord::GeneralError as u16 => {
-                Self::UserRejected
+            StatusWord::WrongApp => Self::WrongApp {
+                name: "unknown".into(),
+                status: Some(code),
+            },
+            StatusWord::AppNotOpen => Self::AppNotOpen,
+            StatusWord::RefusedOnDashboard => Self::RefusedOnDashboard,
+            StatusWord::InvalidData => Self::InvalidData,
+            StatusWord::WrongP1P2 => Self::WrongParameters,
+            StatusWord::InsNotSupported => Self::UnsupportedInstruction,
+            StatusWord::Exception(_) => Self::AppException(code),
+            StatusWord::Ok | StatusWord::Unknown(_) => Self::DeviceStatus(code, "unknown"),
+        }
+    }
+
+    /// Stable numeric code for this error; see [`ErrorCode`].
+    #[must_use]
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Self::Transport(e) => e.code(),
+            Self::DeviceStatus(..) => ErrorCode::DeviceStatus,
+            Self::DeviceLocked => ErrorCode::DeviceLocked,
+            Self::AppNotOpen => ErrorCode::AppNotOpen,
+            Self::WrongApp { .. } => ErrorCode::WrongApp,
+            Self::LegacyAppDetected { .. } => ErrorCode::LegacyAppDetected,
+            Self::BlindSigningDisabled => ErrorCode::BlindSigningDisabled,
+            Self::UserRejected { .. } => ErrorCode::UserRejected,
+            Self::RefusedOnDashboard => ErrorCode::RefusedOnDashboard,
+            Self::InvalidData => ErrorCode::InvalidData,
+            Self::WrongParameters => ErrorCode::WrongParameters,
+            Self::UnsupportedInstruction => ErrorCode::UnsupportedInstruction,
+            Self::AppException(_) => ErrorCode::AppException,
+            Self::UserTimeout(_) => ErrorCode::UserTimeout,
+            Self::ClearSigningDataMissing { .. } => ErrorCode::ClearSigningDataMissing,
+            Self::InvalidResponse(_) => ErrorCode::InvalidResponse,
+            Self::BlockProtocol(_) => ErrorCode::BlockProtocol,
+            Self::InvalidPath(_) => ErrorCode::InvalidPath,
+            Self::InvalidVersionReq(_) => ErrorCode::InvalidVersionReq,
+            Self::InvalidEncoding(_) => ErrorCode::InvalidEncoding,
+            Self::InvalidTransaction(_) => ErrorCode::InvalidTransaction,
+            Self::InvalidAmount(_) => ErrorCode::InvalidAmount,
+            Self::InvalidTypeTag(_) => ErrorCode::InvalidTypeTag,
+            Self::UnknownCoin(_) => ErrorCode::UnknownCoin,
+            Self::InvalidObject(_) => ErrorCode::InvalidObject,
+            Self::RecipientRejected(_) => ErrorCode::RecipientRejected,
+            Self::PayloadTooLarge { .. } => ErrorCode::PayloadTooLarge,
+            Self::MessageTooLarge { .. } => ErrorCode::MessageTooLarge,
+            Self::AccountMismatch { .. } => ErrorCode::AccountMismatch,
+            Self::Rpc(_) => ErrorCode::Rpc,
+            Self::DuplicateSignRequest { .. } => ErrorCode::DuplicateSignRequest,
+            #[cfg(feature = "std")]
+            Self::JournalStore(_) => ErrorCode::JournalStore,
+            Self::FinalityTimeout { .. } => ErrorCode::FinalityTimeout,
+            #[cfg(feature = "rpc")]
+            Self::AlreadyExecuted(_) => ErrorCode::AlreadyExecuted,
+            #[cfg(feature = "rpc")]
+            Self::BroadcastStore(_) => ErrorCode::BroadcastStore,
+        }
+    }
+
+    /// Serializable summary for remote clients.
+    #[cfg(feature = "serde")]
+    #[must_use]
+    pub fn report(&self) -> ErrorReport {
+        self.into()
+    }
+
+    /// The status word the device returned, for errors that came from one.
+    ///
+    /// Variants with a single source code report that code; `None` for
+    /// host-side errors and for [`WrongApp`](Self::WrongApp) detected from
+    /// the app name.
+    #[must_use]
+    pub fn status_word(&self) -> Option<u16> {
+        let sw = match self {
+            Self::DeviceStatus(code, _) | Self::AppException(code) => return Some(*code),
+            Self::UserRejected { status } => return Some(*status),
+            Self::WrongApp { status, .. } => return *status,
+            Self::DeviceLocked => StatusWord::DeviceLocked,
+            Self::AppNotOpen => StatusWord::AppNotOpen,
+            Self::BlindSigningDisabled | Self::ClearSigningDataMissing { .. } => {
+                StatusWord::BlindSigningDisabled
             }
-            c if c == StatusWord::WrongApp as u16 => Self::WrongApp("unknown".into()),
-            c if c == StatusWord::AppNotOpen as u16 => Self::AppNotOpen,
-            _ => Self::DeviceStatus(code, "unknown"),
+            Self::RefusedOnDashboard => StatusWord::RefusedOnDashboard,
+            Self::InvalidData => StatusWord::InvalidData,
+            Self::WrongParameters => StatusWord::WrongP1P2,
+            Self::UnsupportedInstruction => StatusWord::InsNotSupported,
+            _ => return None,
+        };
+        Some(sw.as_u16())
+    }
+
+    /// A transient transport failure, or a transaction not finalized
+    /// yet; repeating the same request may succeed.
+    #[must_use]
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::Transport(e) => e.is_retryable(),
+            Self::FinalityTimeout { .. } => true,
+            _ => false,
         }
     }
+
+    /// A device or app state the user can fix (unlock, open the app,
+    /// change a setting, update, confirm) before trying again. Includes
+    /// [`UserRejected`](Self::UserRejected).
+    #[must_use]
+    pub fn is_user_actionable(&self) -> bool {
+        matches!(
+            self,
+            Self::Transport(TransportError::DeviceNotFound)
+                | Self::DeviceLocked
+                | Self::AppNotOpen
+                | Self::WrongApp { .. }
+                | Self::LegacyAppDetected { .. }
+                | Self::BlindSigningDisabled
+                | Self::ClearSigningDataMissing { .. }
+                | Self::UserRejected { .. }
+                | Self::UserTimeout(_)
+                | Self::RefusedOnDashboard
+                | Self::UnsupportedInstruction
+                | Self::AppException(_)
+        )
+    }
+
+    /// Neither retryable nor user-actionable: bad input, a protocol
+    /// mismatch or a policy refusal that won't change on retry.
+    #[must_use]
+    pub fn is_fatal(&self) -> bool {
+        !self.is_retryable() && !self.is_user_actionable()
+    }
 }
 
 #[cfg(test)]
@@ -90,6 +582,126 @@ mod tests {
         assert!(!StatusWord::is_success(0x0000));
     }
 
+    #[test]
+    fn classification() {
+        let retryable = [
+            LedgerError::Transport(TransportError::Timeout(1000)),
+            LedgerError::Transport(TransportError::Comm("HID channel mismatch".into())),
+            LedgerError::FinalityTimeout {
+                digest: crate::types::TransactionDigest::ZERO,
+                waited: core::time::Duration::from_secs(60),
+            },
+        ];
+        for e in &retryable {
+            assert!(
+                e.is_retryable() && !e.is_user_actionable() && !e.is_fatal(),
+                "{e}"
+            );
+        }
+
+        let actionable = [
+            LedgerError::Transport(TransportError::DeviceNotFound),
+            LedgerError::DeviceLocked,
+            LedgerError::WrongApp {
+                name: "Bitcoin".into(),
+                status: None,
+            },
+            LedgerError::BlindSigningDisabled,
+            LedgerError::UserRejected { status: 0x6985 },
+        ];
+        for e in &actionable {
+            assert!(
+                !e.is_retryable() && e.is_user_actionable() && !e.is_fatal(),
+                "{e}"
+            );
+        }
+
+        let fatal = [
+            LedgerError::InvalidResponse("short".into()),
+            LedgerError::BlockProtocol("empty response".into()),
+            LedgerError::RecipientRejected(RecipientError::ZeroAddress),
+            LedgerError::from_status(0x1234),
+            LedgerError::Transport(TransportError::ApduTooLong(300)),
+        ];
+        for e in &fatal {
+            assert!(e.is_fatal(), "{e}");
+        }
+    }
+
+    #[test]
+    fn error_codes_are_stable() {
+        // Pinned: these values are part of the FFI/IPC contract.
+        assert_eq!(ErrorCode::DeviceNotFound.code(), 100);
+        assert_eq!(ErrorCode::UserRejected.code(), 205);
+        assert_eq!(ErrorCode::JournalStore.code(), 502);
+
+        for code in ErrorCode::ALL {
+            assert_eq!(ErrorCode::from_code(code.code()), Some(code));
+            assert_eq!(u32::from(code), code.code());
+        }
+        let mut codes: Vec<u32> = ErrorCode::ALL.iter().map(|c| c.code()).collect();
+        codes.sort_unstable();
+        codes.dedup();
+        assert_eq!(codes.len(), ErrorCode::ALL.len());
+        assert_eq!(ErrorCode::from_code(0), None);
+        assert_eq!(ErrorCode::from_code(199), None);
+
+        assert_eq!(
+            LedgerError::from_status(0x6D00).code(),
+            ErrorCode::UserRejected
+        );
+        assert_eq!(
+            LedgerError::Transport(TransportError::Timeout(1000)).code(),
+            ErrorCode::Timeout
+        );
+        assert_eq!(
+            LedgerError::RecipientRejected(RecipientError::SelfTransfer).code(),
+            ErrorCode::RecipientRejected
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn error_report_json() {
+        let report = LedgerError::from_status(0x6D00).report();
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "variant": "UserRejected",
+                "code": 205,
+                "message": "user rejected the request on device",
+                "status_word": 0x6D00,
+            })
+        );
+        let back: ErrorReport = serde_json::from_value(json).unwrap();
+        assert_eq!(back, report);
+        assert_eq!(back.error_code(), Some(ErrorCode::UserRejected));
+
+        let report = LedgerError::Transport(TransportError::Timeout(500)).report();
+        assert_eq!(report.variant, "Timeout");
+        assert_eq!(report.status_word, None);
+        assert_eq!(
+            report.to_string(),
+            "transport error: device timed out after 500ms"
+        );
+    }
+
+    #[test]
+    fn status_word_round_trips() {
+        for code in [
+            0x9000, 0x5515, 0x6985, 0x6D00, 0x6E01, 0x6F00, 0x6F42, 0x1234, 0,
+        ] {
+            assert_eq!(StatusWord::from_u16(code).as_u16(), code);
+        }
+        for (sw, code) in StatusWord::NAMED {
+            assert_eq!(StatusWord::from(code), sw);
+            assert_eq!(u16::from(sw), code);
+        }
+        assert_eq!(StatusWord::from_u16(0x6F42), StatusWord::Exception(0x42));
+        assert_eq!(StatusWord::from_u16(0x6A82), StatusWord::Unknown(0x6A82));
+    }
+
     #[test]
     fn from_status_device_locked() {
         assert!(matches!(
@@ -118,7 +730,7 @@ mod tests {
     fn from_status_user_rejected() {
         assert!(matches!(
             LedgerError::from_status(0x6985),
-            LedgerError::UserRejected
+            LedgerError::UserRejected { status: 0x6985 }
         ));
     }
 
@@ -126,7 +738,7 @@ mod tests {
     fn from_status_general_error_maps_to_user_rejected() {
         assert!(matches!(
             LedgerError::from_status(0x6D00),
-            LedgerError::UserRejected
+            LedgerError::UserRejected { status: 0x6D00 }
         ));
     }
 
@@ -134,7 +746,10 @@ mod tests {
     fn from_status_wrong_app() {
         assert!(matches!(
             LedgerError::from_status(0x6E00),
-            LedgerError::WrongApp(_)
+            LedgerError::WrongApp {
+                status: Some(0x6E00),
+                ..
+            }
         ));
     }
 
@@ -146,6 +761,68 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn from_status_refused_on_dashboard() {
+        assert!(matches!(
+            LedgerError::from_status(0x5501),
+            LedgerError::RefusedOnDashboard
+        ));
+    }
+
+    #[test]
+    fn from_status_invalid_data() {
+        assert!(matches!(
+            LedgerError::from_status(0x6A80),
+            LedgerError::InvalidData
+        ));
+    }
+
+    #[test]
+    fn from_status_wrong_p1_p2() {
+        assert!(matches!(
+            LedgerError::from_status(0x6B00),
+            LedgerError::WrongParameters
+        ));
+    }
+
+    #[test]
+    fn from_status_unsupported_instruction() {
+        assert!(matches!(
+            LedgerError::from_status(0x6D02),
+            LedgerError::UnsupportedInstruction
+        ));
+    }
+
+    #[test]
+    fn from_status_exception_range() {
+        for code in [0x6F00, 0x6F42, 0x6FFF] {
+            assert!(matches!(
+                LedgerError::from_status(code),
+                LedgerError::AppException(c) if c == code
+            ));
+        }
+        assert_eq!(
+            LedgerError::from_status(0x6F42).to_string(),
+            "the IOTA app hit an internal error (0x6F42) — restart the app and try again"
+        );
+    }
+
+    #[test]
+    fn status_word_is_retained() {
+        for code in [
+            0x5501, 0x5515, 0x6808, 0x6982, 0x6985, 0x6A80, 0x6B00, 0x6D00, 0x6D02, 0x6E00, 0x6E01,
+            0x6F42, 0x1234,
+        ] {
+            assert_eq!(LedgerError::from_status(code).status_word(), Some(code));
+        }
+        let by_name = LedgerError::WrongApp {
+            name: "Bitcoin".into(),
+            status: None,
+        };
+        assert_eq!(by_name.status_word(), None);
+        assert_eq!(LedgerError::InvalidPath("x".into()).status_word(), None);
+    }
+
     #[test]
     fn from_status_unknown_code() {
         assert!(matches!(
@@ -170,6 +847,84 @@ pub enum TransportError {
     #[error("device timed out after {0}ms")]
     Timeout(u32),
 
+    #[cfg(feature = "std")]
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
+
+    #[error("APDU data too long: {0} bytes (max 255)")]
+    ApduTooLong(usize),
+}
+
+impl TransportError {
+    /// Stable numeric code for this error; see [`ErrorCode`].
+    #[must_use]
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Self::DeviceNotFound => ErrorCode::DeviceNotFound,
+            Self::Comm(_) => ErrorCode::Comm,
+            Self::ConnectionFailed(_) => ErrorCode::ConnectionFailed,
+            Self::Timeout(_) => ErrorCode::Timeout,
+            #[cfg(feature = "std")]
+            Self::Io(_) => ErrorCode::Io,
+            Self::ApduTooLong(_) => ErrorCode::ApduTooLong,
+        }
+    }
+
+    /// Timeouts, dropped connections and I/O errors; not a missing device
+    /// or a malformed command.
+    #[must_use]
+    pub fn is_retryable(&self) -> bool {
+        !matches!(self, Self::DeviceNotFound | Self::ApduTooLong(_))
+    }
+}
+
+/// Reasons a [`RecipientPolicy`](crate::tx::RecipientPolicy) refuses a recipient.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum RecipientError {
+    #[error("recipient is the all-zero address")]
+    ZeroAddress,
+
+    #[error("recipient is the sender")]
+    SelfTransfer,
+
+    #[error("recipient 0x{} is blocklisted", hex::encode(.0))]
+    Blocked([u8; 32]),
+
+    #[error("recipient 0x{} is not on the allowlist", hex::encode(.0))]
+    NotAllowed([u8; 32]),
+
+    #[error("change goes to 0x{}, which is not one of the wallet's addresses", hex::encode(.0))]
+    NotOwnAddress([u8; 32]),
+
+    #[error("{0}")]
+    Custom(String),
+}
+
+/// Problems found by [`ObjectData::validate`](crate::ObjectData::validate).
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ValidationError {
+    #[error("{0:?} is not a valid Move identifier")]
+    InvalidIdentifier(String),
+
+    #[error("contents are {len} bytes, more than the {max}-byte object limit")]
+    ContentsTooLarge { len: usize, max: usize },
+
+    #[error("{type_} contents must be {expected} bytes, got {actual}")]
+    ContentsLayout {
+        type_: &'static str,
+        expected: usize,
+        actual: usize,
+    },
+
+    #[error("{type_} must have has_public_transfer = {expected}")]
+    PublicTransferMismatch { type_: &'static str, expected: bool },
+
+    #[error("type parameters nested deeper than {0} levels")]
+    TypeTooDeep(usize),
+
+    #[error("`{0}` was not set")]
+    MissingField(&'static str),
+
+    #[error("owner variant {0} has a named Owner variant and can't be Unknown")]
+    ReservedOwnerTag(u32),
 }
@@ -0,0 +1,128 @@
+//! Entry points for fuzz targets (feature `fuzzing`; targets live in
+//! `fuzz/`).
+//!
+//! Each function runs one parser of untrusted device input. Malformed
+//! input must come back as an error; a panic is a bug.
+
+use crate::error::{LedgerError, TransportError};
+use crate::protocol::chunks::Block;
+use crate::protocol::{chunks, DeviceMessage, HostAction, ProtocolSession};
+use crate::transport::framing::{Packet, Reassembler};
+use crate::types::{Address, AppVersion, PublicKey, Signature};
+
+/// `GetVersion` result payload.
+pub fn version_response(data: &[u8]) -> Result<AppVersion, LedgerError> {
+    crate::commands::get_version::parse_version_response(data)
+}
+
+/// `GetPubkey` / `VerifyAddress` result payload.
+pub fn pubkey_response(data: &[u8]) -> Result<(PublicKey, Address), LedgerError> {
+    crate::commands::get_pubkey::parse_pubkey_response(data)
+}
+
+/// `SignTx` result payload.
+pub fn signature_response(data: &[u8]) -> Result<Signature, LedgerError> {
+    crate::commands::sign_tx::parse_signature(data)
+}
+
+/// A block protocol message from the device (response payload without
+/// the status word).
+pub fn device_message(data: &[u8]) -> Result<(), LedgerError> {
+    DeviceMessage::parse(data).map(|_| ())
+}
+
+/// A block protocol session fed a sequence of device messages.
+///
+/// `data` is `[param_len: u8][param][len: u8][message]...`. Replies to
+/// GET_CHUNK must carry a block with the requested hash, or fail.
+pub fn protocol_session(data: &[u8]) -> Result<Option<Vec<u8>>, LedgerError> {
+    let Some((&param_len, rest)) = data.split_first() else {
+        return Ok(None);
+    };
+    let (param, mut rest) = rest.split_at(usize::from(param_len).min(rest.len()));
+    let params = [param.into()];
+    let mut session = ProtocolSession::new(&params);
+    let _ = session.start();
+    while let Some((&len, tail)) = rest.split_first() {
+        let (message, tail) = tail.split_at(usize::from(len).min(tail.len()));
+        rest = tail;
+        let message = DeviceMessage::parse(message)?;
+        let requested = match message {
+            DeviceMessage::GetChunk(hash) => Some(hash),
+            _ => None,
+        };
+        match session.handle(message)? {
+            HostAction::Done(result) => return Ok(Some(result)),
+            HostAction::Reply(reply) => {
+                if let (Some(hash), [0x01, block @ ..]) = (requested, reply.as_slice()) {
+                    assert_eq!(chunks::sha256(block), hash, "served the wrong chunk");
+                }
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// A serialized block (`next_hash ++ data`). Accepted blocks must
+/// serialize back to the input.
+pub fn block(data: &[u8]) -> Result<(), LedgerError> {
+    let block = Block::deserialize(data)?;
+    assert_eq!(block.serialize(), data, "block did not round-trip");
+    Ok(())
+}
+
+/// HID response reassembly, with `data` split into 64-byte packets.
+/// `Ok(None)` if the packets don't complete a response.
+pub fn hid_response(data: &[u8]) -> Result<Option<Vec<u8>>, TransportError> {
+    let mut response = Reassembler::new();
+    for packet in data.chunks(64) {
+        match response.push(packet)? {
+            Packet::Done => return Ok(Some(response.finish())),
+            Packet::Gap { .. } => return Ok(None),
+            Packet::More | Packet::Skipped => {}
+        }
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn malformed_input_is_an_error() {
+        let short: [&[u8]; 3] = [&[], &[0x01], &[0xFF; 3]];
+        for data in short {
+            assert!(version_response(data).is_err(), "{data:?}");
+            assert!(signature_response(data).is_err(), "{data:?}");
+            assert!(block(data).is_err(), "{data:?}");
+        }
+        let inputs: [&[u8]; 5] = [&[], &[0x01], &[0xFF; 3], &[0x02; 20], &[0x20; 300]];
+        for data in inputs {
+            assert!(pubkey_response(data).is_err(), "{data:?}");
+            // The others may accept some of these, but must not panic.
+            let _ = version_response(data);
+            let _ = signature_response(data);
+            let _ = block(data);
+        }
+        for data in [&[][..], &[0x02; 20], &[0x02; 5], &[0xFF, 0x00]] {
+            assert!(device_message(data).is_err(), "{data:?}");
+        }
+        for data in [&[0x01][..], &[0xFF; 3], &[0x01, 0x01, 0x05, 0, 0, 0]] {
+            assert!(hid_response(data).is_err(), "{data:?}");
+        }
+        // A message with an unknown type, and one that is empty.
+        for data in [&[0, 1, 0xFF][..], &[0, 0]] {
+            assert!(protocol_session(data).is_err(), "{data:?}");
+        }
+        // ResultAccumulating "a", then ResultFinal "b".
+        assert_eq!(
+            protocol_session(&[0, 2, 0x00, b'a', 2, 0x01, b'b']).unwrap(),
+            Some(b"ab".to_vec())
+        );
+        assert!(device_message(&[0x02; 20]).is_err());
+        assert!(hid_response(&[0x01, 0x01, 0x05, 0, 0, 0, 2, 0xAB, 0xCD])
+            .unwrap()
+            .is_some_and(|r| r == [0xAB, 0xCD]));
+    }
+}
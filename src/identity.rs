@@ -0,0 +1,182 @@
+//! Telling devices apart across sessions.
+//!
+//! A [`DeviceIdentity`] is a fingerprint of the public key at a fixed path
+//! ([`DeviceIdentity::path`]). It depends only on the seed, so it survives
+//! reconnects, firmware updates and USB port changes; two devices loaded
+//! with the same seed share it. [`DeviceNicknames`] maps identities to
+//! user-chosen labels such as "cold treasury" and persists them to a file.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use blake2::digest::consts::U32;
+use blake2::{Blake2b, Digest};
+
+use crate::error::LedgerError;
+use crate::types::{Bip32Path, PublicKey};
+
+/// Mixed into the fingerprint so it can't be matched against other
+/// hashes of the same key, such as its address.
+const FINGERPRINT_DOMAIN: &[u8] = b"ledger-iota device identity";
+
+/// 8-byte fingerprint of a device's seed, shown as 16 hex digits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct DeviceIdentity(pub [u8; 8]);
+
+impl DeviceIdentity {
+    /// Path whose public key is fingerprinted: account 0, change 0,
+    /// index 0 on mainnet.
+    #[must_use]
+    pub fn path() -> Bip32Path {
+        Bip32Path::iota(0, 0, 0)
+    }
+
+    /// Fingerprint of the key at [`path`](Self::path).
+    #[must_use]
+    pub fn from_pubkey(pubkey: &PublicKey) -> Self {
+        let hash = Blake2b::<U32>::new()
+            .chain_update(FINGERPRINT_DOMAIN)
+            .chain_update(pubkey.0)
+            .finalize();
+        let mut id = [0u8; 8];
+        id.copy_from_slice(&hash[..8]);
+        Self(id)
+    }
+}
+
+impl std::fmt::Display for DeviceIdentity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&hex::encode(self.0))
+    }
+}
+
+impl std::str::FromStr for DeviceIdentity {
+    type Err = LedgerError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut id = [0u8; 8];
+        hex::decode_to_slice(s, &mut id).map_err(|_| {
+            LedgerError::InvalidEncoding(format!("{s:?} is not a 16-digit device identity"))
+        })?;
+        Ok(Self(id))
+    }
+}
+
+/// User-assigned device labels.
+///
+/// Stored as one `<identity> <nickname>` line per device. Line breaks and
+/// other control characters in nicknames are replaced with spaces.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DeviceNicknames {
+    names: BTreeMap<DeviceIdentity, String>,
+}
+
+impl DeviceNicknames {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Read nicknames from `path`; a missing file gives an empty set.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Self::new()),
+            Err(e) => return Err(e),
+        };
+        let mut names = BTreeMap::new();
+        for (n, line) in text.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let (id, name) = line.split_once(' ').unwrap_or((line, ""));
+            let id = id.parse().map_err(|e| {
+                io::Error::new(io::ErrorKind::InvalidData, format!("line {}: {e}", n + 1))
+            })?;
+            names.insert(id, name.to_string());
+        }
+        Ok(Self { names })
+    }
+
+    /// Write all nicknames to `path`, replacing it atomically.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let mut text = String::new();
+        for (id, name) in &self.names {
+            text += &format!("{id} {name}\n");
+        }
+        crate::fs_util::write_atomic(path.as_ref(), text.as_bytes())
+    }
+
+    #[must_use]
+    pub fn get(&self, id: &DeviceIdentity) -> Option<&str> {
+        self.names.get(id).map(String::as_str)
+    }
+
+    /// Label `id`, returning its previous nickname.
+    pub fn set(&mut self, id: DeviceIdentity, nickname: &str) -> Option<String> {
+        let nickname = nickname
+            .trim()
+            .chars()
+            .map(|c| if c.is_control() { ' ' } else { c })
+            .collect();
+        self.names.insert(id, nickname)
+    }
+
+    pub fn remove(&mut self, id: &DeviceIdentity) -> Option<String> {
+        self.names.remove(id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&DeviceIdentity, &str)> {
+        self.names.iter().map(|(id, name)| (id, name.as_str()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fingerprint_is_stable_and_distinct() {
+        let a = DeviceIdentity::from_pubkey(&PublicKey([1; 32]));
+        assert_eq!(a, DeviceIdentity::from_pubkey(&PublicKey([1; 32])));
+        assert_ne!(a, DeviceIdentity::from_pubkey(&PublicKey([2; 32])));
+        assert_eq!(a.to_string().len(), 16);
+        assert_eq!(a.to_string().parse::<DeviceIdentity>().unwrap(), a);
+        assert!("abcd".parse::<DeviceIdentity>().is_err());
+    }
+
+    #[test]
+    fn nicknames_round_trip_through_file() {
+        let dir = crate::fs_util::TempDir::new("nicknames");
+        let path = dir.join("devices.txt");
+
+        assert!(DeviceNicknames::load(&path).unwrap().is_empty());
+
+        let cold = DeviceIdentity([1; 8]);
+        let daily = DeviceIdentity([2; 8]);
+        let mut names = DeviceNicknames::new();
+        assert_eq!(names.set(cold, "cold treasury"), None);
+        names.set(daily, " daily\nwallet ");
+        assert_eq!(names.get(&daily), Some("daily wallet"));
+        names.save(&path).unwrap();
+
+        let loaded = DeviceNicknames::load(&path).unwrap();
+        assert_eq!(loaded, names);
+        assert_eq!(loaded.get(&cold), Some("cold treasury"));
+        assert_eq!(loaded.iter().count(), 2);
+
+        fs::write(&path, "not-hex name\n").unwrap();
+        let err = DeviceNicknames::load(&path).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+}
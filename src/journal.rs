@@ -0,0 +1,205 @@
+//! Double-sign protection.
+//!
+//! A [`SignJournal`] attached to a [`LedgerIota`](crate::LedgerIota) records
+//! the [`BlindSigningHash`](crate::BlindSigningHash) of every transaction
+//! it signs. Asking to sign the same hash again within the configured
+//! window either logs a warning or fails with
+//! [`LedgerError::DuplicateSignRequest`], depending on [`JournalMode`].
+//!
+//! Storage is pluggable through [`JournalStore`]; [`MemoryJournalStore`]
+//! covers a single process.
+
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime};
+
+use crate::error::LedgerError;
+
+/// Where signed digests are kept.
+///
+/// Persistent stores (file, database) let protection survive restarts;
+/// errors are surfaced as [`LedgerError::JournalStore`].
+pub trait JournalStore: Send {
+    /// When `digest` was last recorded, if ever.
+    fn last_signed(&self, digest: &[u8; 32]) -> std::io::Result<Option<SystemTime>>;
+
+    fn record(&mut self, digest: [u8; 32], at: SystemTime) -> std::io::Result<()>;
+}
+
+/// In-process store. Entries older than `retain` are dropped on insert.
+#[derive(Debug, Clone)]
+pub struct MemoryJournalStore {
+    entries: HashMap<[u8; 32], SystemTime>,
+    retain: Duration,
+}
+
+impl MemoryJournalStore {
+    #[must_use]
+    pub fn new(retain: Duration) -> Self {
+        Self {
+            entries: HashMap::new(),
+            retain,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl JournalStore for MemoryJournalStore {
+    fn last_signed(&self, digest: &[u8; 32]) -> std::io::Result<Option<SystemTime>> {
+        Ok(self.entries.get(digest).copied())
+    }
+
+    fn record(&mut self, digest: [u8; 32], at: SystemTime) -> std::io::Result<()> {
+        let retain = self.retain;
+        self.entries
+            .retain(|_, t| at.duration_since(*t).map_or(true, |age| age <= retain));
+        self.entries.insert(digest, at);
+        Ok(())
+    }
+}
+
+/// What to do when a digest is signed twice within the window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JournalMode {
+    /// Log a warning and sign anyway.
+    Warn,
+    /// Refuse with [`LedgerError::DuplicateSignRequest`].
+    Reject,
+}
+
+/// Records signed transaction digests and flags repeats.
+pub struct SignJournal {
+    store: Box<dyn JournalStore>,
+    window: Duration,
+    mode: JournalMode,
+}
+
+impl SignJournal {
+    pub fn new(store: impl JournalStore + 'static, window: Duration, mode: JournalMode) -> Self {
+        Self {
+            store: Box::new(store),
+            window,
+            mode,
+        }
+    }
+
+    /// Rejecting journal backed by a [`MemoryJournalStore`].
+    #[must_use]
+    pub fn in_memory(window: Duration) -> Self {
+        Self::new(MemoryJournalStore::new(window), window, JournalMode::Reject)
+    }
+
+    /// Check `digest` against the journal as of `now`. Called before the
+    /// transaction is sent to the device.
+    pub fn check(&self, digest: &[u8; 32], now: SystemTime) -> Result<(), LedgerError> {
+        let Some(previous) = self
+            .store
+            .last_signed(digest)
+            .map_err(LedgerError::JournalStore)?
+        else {
+            return Ok(());
+        };
+        // A clock that went backwards still counts as "within the window".
+        let age = now.duration_since(previous).unwrap_or_default();
+        if age > self.window {
+            return Ok(());
+        }
+        match self.mode {
+            JournalMode::Warn => {
+                log::warn!(
+                    "signing transaction 0x{} again ({}s after the previous signature)",
+                    hex::encode(digest),
+                    age.as_secs()
+                );
+                Ok(())
+            }
+            JournalMode::Reject => Err(LedgerError::DuplicateSignRequest {
+                digest: *digest,
+                age,
+            }),
+        }
+    }
+
+    /// Record a successful signature of `digest`.
+    pub fn record(&mut self, digest: [u8; 32], now: SystemTime) -> Result<(), LedgerError> {
+        self.store
+            .record(digest, now)
+            .map_err(LedgerError::JournalStore)
+    }
+}
+
+impl std::fmt::Debug for SignJournal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SignJournal")
+            .field("window", &self.window)
+            .field("mode", &self.mode)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::BlindSigningHash;
+
+    const WINDOW: Duration = Duration::from_secs(60);
+
+    fn at(secs: u64) -> SystemTime {
+        SystemTime::UNIX_EPOCH + Duration::from_secs(secs)
+    }
+
+    #[test]
+    fn digest_is_blake2b_256() {
+        // Blake2b-256 of the empty string.
+        assert_eq!(
+            hex::encode(BlindSigningHash::of(b"").0),
+            "0e5751c026e543b2e8ab2eb06099daa1d1e5df47778f7787faab45cdf12fe3a8"
+        );
+    }
+
+    #[test]
+    fn rejects_repeat_within_window() {
+        let mut journal = SignJournal::in_memory(WINDOW);
+        let d = [1u8; 32];
+        journal.check(&d, at(0)).unwrap();
+        journal.record(d, at(0)).unwrap();
+
+        assert!(matches!(
+            journal.check(&d, at(30)),
+            Err(LedgerError::DuplicateSignRequest { .. })
+        ));
+        assert!(journal.check(&[2u8; 32], at(30)).is_ok());
+    }
+
+    #[test]
+    fn allows_repeat_after_window() {
+        let mut journal = SignJournal::in_memory(WINDOW);
+        let d = [1u8; 32];
+        journal.record(d, at(0)).unwrap();
+        assert!(journal.check(&d, at(61)).is_ok());
+    }
+
+    #[test]
+    fn warn_mode_does_not_fail() {
+        let mut journal =
+            SignJournal::new(MemoryJournalStore::new(WINDOW), WINDOW, JournalMode::Warn);
+        let d = [1u8; 32];
+        journal.record(d, at(0)).unwrap();
+        assert!(journal.check(&d, at(1)).is_ok());
+    }
+
+    #[test]
+    fn memory_store_prunes_old_entries() {
+        let mut store = MemoryJournalStore::new(WINDOW);
+        store.record([1u8; 32], at(0)).unwrap();
+        store.record([2u8; 32], at(100)).unwrap();
+        assert_eq!(store.len(), 1);
+        assert!(store.last_signed(&[1u8; 32]).unwrap().is_none());
+    }
+}
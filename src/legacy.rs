@@ -0,0 +1,694 @@
+//! The legacy (Stardust) IOTA app's APDU protocol (feature `legacy`).
+//!
+//! Funds on Stardust Ed25519 addresses are controlled by the legacy app
+//! (`CLA 0x7B`), which derives `m/44'/coin'/account'/change'/index'`
+//! for the coin type of its [`AppMode`] and signs transaction essences.
+//! [`LegacyIota`] generates those addresses and signs the essences that
+//! move their outputs, so a migration tool can sweep them to a Rebased
+//! address obtained from [`LedgerIota`](crate::LedgerIota) once the user
+//! switches apps.
+//!
+//! ```no_run
+//! use ledger_iota::legacy::{AppMode, LegacyIota};
+//! use ledger_iota::TransportType;
+//!
+//! let legacy = LegacyIota::new(&TransportType::NativeHID)?;
+//! legacy.set_account(AppMode::IotaStardust, 0)?;
+//! for address in legacy.generate_addresses(false, 0, 5)? {
+//!     println!("{}", address.to_bech32("iota"));
+//! }
+//! # Ok::<(), ledger_iota::LedgerError>(())
+//! ```
+
+use crate::apdu::ApduCommand;
+use crate::commands::get_version::{LEGACY_CLA, LEGACY_GET_APP_CONFIG};
+use crate::error::{LedgerError, StatusWord};
+use crate::limits::PayloadKind;
+use crate::transport::{self, Transport, TransportType};
+use crate::types::HARDENED;
+use crate::wipe::Transient;
+
+const SET_ACCOUNT: u8 = 0x11;
+const GET_DATA_BUFFER_STATE: u8 = 0x80;
+const WRITE_DATA_BUFFER: u8 = 0x81;
+const READ_DATA_BUFFER: u8 = 0x82;
+const CLEAR_DATA_BUFFER: u8 = 0x83;
+const PREPARE_SIGNING: u8 = 0xA0;
+const GENERATE_ADDRESS: u8 = 0xA1;
+const SIGN: u8 = 0xA2;
+const USER_CONFIRM: u8 = 0xA3;
+const PREPARE_BLINDSIGNING: u8 = 0xA4;
+
+/// `[type = 0][Blake2b-256(pubkey)]`
+const ADDRESS_LEN: usize = 33;
+/// `SIGN` addresses inputs by a one-byte index.
+const MAX_INPUTS: usize = 256;
+
+/// Network the legacy app derives keys for; selects the coin type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum AppMode {
+    /// Chrysalis IOTA, coin type 4218.
+    IotaChrysalis,
+    /// Stardust IOTA, coin type 4218.
+    IotaStardust,
+    /// Shimmer outputs claimable with IOTA keys (coin type 4218).
+    ShimmerClaiming,
+    /// Shimmer, coin type 4219.
+    Shimmer,
+}
+
+impl AppMode {
+    fn byte(self, testnet: bool) -> u8 {
+        let mode = match self {
+            Self::IotaChrysalis => 0x00,
+            Self::IotaStardust => 0x01,
+            Self::ShimmerClaiming => 0x02,
+            Self::Shimmer => 0x03,
+        };
+        if testnet {
+            mode | 0x80
+        } else {
+            mode
+        }
+    }
+}
+
+/// Reply to the legacy `GET_APP_CONFIG`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AppConfig {
+    pub version: (u8, u8, u8),
+    /// App flags as reported; bit 0 is set while the device is locked.
+    pub flags: u8,
+    /// Device model id (`0` Nano S, `1` Nano X, `2` Nano S Plus, ...).
+    pub device: u8,
+    pub debug: bool,
+}
+
+impl AppConfig {
+    fn parse(data: &[u8]) -> Result<Self, LedgerError> {
+        match data {
+            [major, minor, patch, flags, device, debug, ..] => Ok(Self {
+                version: (*major, *minor, *patch),
+                flags: *flags,
+                device: *device,
+                debug: *debug != 0,
+            }),
+            _ => Err(LedgerError::InvalidResponse(format!(
+                "legacy app config is {} bytes, expected 6",
+                data.len()
+            ))),
+        }
+    }
+}
+
+/// Change and address index below the account set with
+/// [`LegacyIota::set_account`]; both are hardened on the wire.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LegacyBip32 {
+    pub change: bool,
+    pub index: u32,
+}
+
+impl LegacyBip32 {
+    #[must_use]
+    pub fn new(change: bool, index: u32) -> Self {
+        Self { change, index }
+    }
+
+    fn encode_into(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&(self.index | HARDENED).to_le_bytes());
+        out.extend_from_slice(&(u32::from(self.change) | HARDENED).to_le_bytes());
+    }
+}
+
+/// A Stardust Ed25519 address: Blake2b-256 of the public key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct StardustAddress(pub [u8; 32]);
+
+impl StardustAddress {
+    /// Bech32 form for `hrp` (`iota`, `smr`, `atoi`, `rms`).
+    #[must_use]
+    pub fn to_bech32(&self, hrp: &str) -> String {
+        let mut bytes = Vec::with_capacity(ADDRESS_LEN);
+        bytes.push(0x00);
+        bytes.extend_from_slice(&self.0);
+        bech32::encode(hrp, &bech32::to_base32(&bytes))
+    }
+}
+
+/// Unlock block returned for one input of a signed essence.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LegacyUnlock {
+    Signature {
+        public_key: [u8; 32],
+        signature: [u8; 64],
+    },
+    /// Same key as the unlock at this index.
+    Reference(u16),
+}
+
+impl LegacyUnlock {
+    fn parse(data: &[u8]) -> Result<Self, LedgerError> {
+        match data {
+            [0x00, 0x00, rest @ ..] if rest.len() >= 96 => Ok(Self::Signature {
+                public_key: rest[..32].try_into().expect("32 bytes"),
+                signature: rest[32..96].try_into().expect("64 bytes"),
+            }),
+            [0x01, lo, hi, ..] => Ok(Self::Reference(u16::from_le_bytes([*lo, *hi]))),
+            _ => Err(LedgerError::InvalidResponse(format!(
+                "malformed legacy unlock block ({} bytes)",
+                data.len()
+            ))),
+        }
+    }
+}
+
+/// Where the device should send change; the essence's output at
+/// `output_index` must pay to `path`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Remainder {
+    pub output_index: u16,
+    pub path: LegacyBip32,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct BufferState {
+    len: usize,
+    block_size: usize,
+    block_count: usize,
+}
+
+/// A connection to the legacy IOTA app.
+pub struct LegacyIota {
+    transport: Box<dyn Transport>,
+}
+
+impl LegacyIota {
+    /// Connect and check that the legacy app is open.
+    pub fn new(transport_type: &TransportType) -> Result<Self, LedgerError> {
+        let legacy = Self::with_transport(transport::open(transport_type)?);
+        legacy.get_app_config()?;
+        Ok(legacy)
+    }
+
+    pub fn with_transport(transport: Box<dyn Transport>) -> Self {
+        Self { transport }
+    }
+
+    pub fn get_app_config(&self) -> Result<AppConfig, LedgerError> {
+        AppConfig::parse(&self.exchange(LEGACY_GET_APP_CONFIG, 0, &[])?)
+    }
+
+    /// Select the network and account later commands derive keys under.
+    pub fn set_account(&self, mode: AppMode, account: u32) -> Result<(), LedgerError> {
+        self.set_account_on(mode, false, account)
+    }
+
+    /// [`set_account`](Self::set_account) for the network's testnet.
+    pub fn set_account_on(
+        &self,
+        mode: AppMode,
+        testnet: bool,
+        account: u32,
+    ) -> Result<(), LedgerError> {
+        self.exchange(
+            SET_ACCOUNT,
+            mode.byte(testnet),
+            &(account | HARDENED).to_le_bytes(),
+        )?;
+        Ok(())
+    }
+
+    /// `count` consecutive addresses starting at `index`.
+    pub fn generate_addresses(
+        &self,
+        change: bool,
+        index: u32,
+        count: u32,
+    ) -> Result<Vec<StardustAddress>, LedgerError> {
+        self.generate(LegacyBip32::new(change, index), count, false)
+    }
+
+    /// Show the address on the device and wait for the user to confirm it.
+    pub fn show_address(&self, path: LegacyBip32) -> Result<StardustAddress, LedgerError> {
+        let mut addresses = self.generate(path, 1, true)?;
+        addresses
+            .pop()
+            .ok_or_else(|| LedgerError::InvalidResponse("no address generated".into()))
+    }
+
+    fn generate(
+        &self,
+        start: LegacyBip32,
+        count: u32,
+        show: bool,
+    ) -> Result<Vec<StardustAddress>, LedgerError> {
+        let mut data = Vec::with_capacity(12);
+        start.encode_into(&mut data);
+        data.extend_from_slice(&count.to_le_bytes());
+        self.exchange(GENERATE_ADDRESS, u8::from(show), &data)?;
+
+        let buffer = self.read_buffer()?;
+        if buffer.len() < count as usize * ADDRESS_LEN {
+            return Err(LedgerError::InvalidResponse(format!(
+                "expected {count} addresses, data buffer holds {} bytes",
+                buffer.len()
+            )));
+        }
+        Ok(buffer
+            .chunks_exact(ADDRESS_LEN)
+            .take(count as usize)
+            .map(|a| StardustAddress(a[1..].try_into().expect("32 bytes")))
+            .collect())
+    }
+
+    /// Have the user review and approve a serialized Stardust essence,
+    /// then sign it with the key of each input's path (in input order).
+    pub fn sign_essence(
+        &self,
+        essence: &[u8],
+        inputs: &[LegacyBip32],
+        remainder: Option<Remainder>,
+    ) -> Result<Vec<LegacyUnlock>, LedgerError> {
+        self.sign_prepared(PREPARE_SIGNING, essence, inputs, remainder)
+    }
+
+    /// Like [`sign_essence`](Self::sign_essence) with only the essence
+    /// hash, for essences too large for the app to parse; the device
+    /// shows the hash and needs blind signing enabled.
+    pub fn blind_sign_essence(
+        &self,
+        essence_hash: &[u8; 32],
+        inputs: &[LegacyBip32],
+    ) -> Result<Vec<LegacyUnlock>, LedgerError> {
+        self.sign_prepared(PREPARE_BLINDSIGNING, essence_hash, inputs, None)
+    }
+
+    fn sign_prepared(
+        &self,
+        prepare: u8,
+        payload: &[u8],
+        inputs: &[LegacyBip32],
+        remainder: Option<Remainder>,
+    ) -> Result<Vec<LegacyUnlock>, LedgerError> {
+        if inputs.len() > MAX_INPUTS {
+            return Err(LedgerError::InvalidTransaction(format!(
+                "{} inputs, the app signs at most {MAX_INPUTS}",
+                inputs.len()
+            )));
+        }
+        let mut data = Vec::with_capacity(payload.len() + 8 * inputs.len());
+        data.extend_from_slice(payload);
+        for input in inputs {
+            input.encode_into(&mut data);
+        }
+        self.write_buffer(&data)?;
+
+        let mut params = Vec::with_capacity(10);
+        let has_remainder = match remainder {
+            Some(r) => {
+                params.extend_from_slice(&r.output_index.to_le_bytes());
+                r.path.encode_into(&mut params);
+                true
+            }
+            None => {
+                params.resize(10, 0);
+                false
+            }
+        };
+        self.exchange(prepare, u8::from(has_remainder), &params)?;
+        self.exchange(USER_CONFIRM, 0, &[])?;
+
+        (0..=u8::MAX)
+            .zip(inputs)
+            .map(|(index, _)| LegacyUnlock::parse(&self.exchange(SIGN, index, &[])?))
+            .collect()
+    }
+
+    fn buffer_state(&self) -> Result<BufferState, LedgerError> {
+        match self.exchange(GET_DATA_BUFFER_STATE, 0, &[])?[..] {
+            [lo, hi, _data_type, block_size, block_count, ..] if block_size > 0 => {
+                Ok(BufferState {
+                    len: usize::from(u16::from_le_bytes([lo, hi])),
+                    block_size: usize::from(block_size),
+                    block_count: usize::from(block_count),
+                })
+            }
+            _ => Err(LedgerError::InvalidResponse(
+                "malformed legacy data buffer state".into(),
+            )),
+        }
+    }
+
+    fn write_buffer(&self, data: &[u8]) -> Result<(), LedgerError> {
+        self.exchange(CLEAR_DATA_BUFFER, 0, &[])?;
+        let state = self.buffer_state()?;
+        let limit = state.block_size * state.block_count;
+        if data.len() > limit {
+            return Err(LedgerError::PayloadTooLarge {
+                kind: PayloadKind::Transaction,
+                size: data.len(),
+                limit,
+            });
+        }
+        for (block, chunk) in data.chunks(state.block_size).enumerate() {
+            let mut padded = chunk.to_vec();
+            padded.resize(state.block_size, 0);
+            self.exchange(WRITE_DATA_BUFFER, block as u8, &padded)?;
+        }
+        Ok(())
+    }
+
+    fn read_buffer(&self) -> Result<Vec<u8>, LedgerError> {
+        let state = self.buffer_state()?;
+        let mut data = Vec::with_capacity(state.len);
+        let blocks = state.len.div_ceil(state.block_size);
+        for block in 0..blocks.min(state.block_count) {
+            data.extend_from_slice(&self.exchange(READ_DATA_BUFFER, block as u8, &[])?);
+        }
+        data.truncate(state.len);
+        Ok(data)
+    }
+
+    fn exchange(&self, ins: u8, p1: u8, data: &[u8]) -> Result<Vec<u8>, LedgerError> {
+        let mut cmd = Transient(ApduCommand::with_data(ins, data.to_vec()));
+        cmd.cla = LEGACY_CLA;
+        cmd.p1 = p1;
+        let answer = Transient(self.transport.exchange(&cmd)?);
+        if !StatusWord::is_success(answer.retcode()) {
+            return Err(LedgerError::from_status(answer.retcode()));
+        }
+        Ok(answer.data().to_vec())
+    }
+}
+
+/// Just enough BIP-173 bech32 to print Stardust addresses.
+mod bech32 {
+    const CHARSET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+
+    fn polymod(values: impl Iterator<Item = u8>) -> u32 {
+        const GEN: [u32; 5] = [
+            0x3b6a_57b2,
+            0x2650_8e6d,
+            0x1ea1_19fa,
+            0x3d42_33dd,
+            0x2a14_62b3,
+        ];
+        values.fold(1, |chk, v| {
+            let top = chk >> 25;
+            let chk = ((chk & 0x01ff_ffff) << 5) ^ u32::from(v);
+            (0..5)
+                .filter(|i| (top >> i) & 1 == 1)
+                .fold(chk, |chk, i| chk ^ GEN[i])
+        })
+    }
+
+    pub(super) fn to_base32(bytes: &[u8]) -> Vec<u8> {
+        let mut out = Vec::with_capacity(bytes.len() * 8 / 5 + 1);
+        let (mut acc, mut bits) = (0u32, 0u32);
+        for &b in bytes {
+            acc = (acc << 8) | u32::from(b);
+            bits += 8;
+            while bits >= 5 {
+                bits -= 5;
+                out.push(((acc >> bits) & 31) as u8);
+            }
+        }
+        if bits > 0 {
+            out.push(((acc << (5 - bits)) & 31) as u8);
+        }
+        out
+    }
+
+    pub(super) fn encode(hrp: &str, data: &[u8]) -> String {
+        let expanded = hrp
+            .bytes()
+            .map(|b| b >> 5)
+            .chain([0])
+            .chain(hrp.bytes().map(|b| b & 31));
+        let chk = polymod(expanded.chain(data.iter().copied()).chain([0; 6])) ^ 1;
+        let checksum = (0..6).map(|i| ((chk >> (5 * (5 - i))) & 31) as u8);
+
+        let mut out = String::with_capacity(hrp.len() + 1 + data.len() + 6);
+        out.push_str(hrp);
+        out.push('1');
+        out.extend(
+            data.iter()
+                .copied()
+                .chain(checksum)
+                .map(|v| CHARSET[usize::from(v)] as char),
+        );
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::apdu::ApduAnswer;
+    use crate::error::TransportError;
+    use std::sync::{Arc, Mutex};
+
+    const BLOCK_SIZE: usize = 251;
+    const BLOCK_COUNT: usize = 4;
+
+    /// Legacy app keeping a data buffer; "signs" with the input index.
+    #[derive(Default)]
+    struct LegacyDevice {
+        buffer: Mutex<Vec<u8>>,
+        log: Mutex<Vec<(u8, u8, Vec<u8>)>>,
+        reject: bool,
+    }
+
+    impl LegacyDevice {
+        fn reply(&self, cmd: &ApduCommand) -> Vec<u8> {
+            let mut buffer = self.buffer.lock().unwrap();
+            match cmd.ins {
+                LEGACY_GET_APP_CONFIG => vec![1, 0, 6, 0x02, 0x01, 0],
+                SET_ACCOUNT => vec![],
+                CLEAR_DATA_BUFFER => {
+                    buffer.clear();
+                    vec![]
+                }
+                GET_DATA_BUFFER_STATE => {
+                    let len = (buffer.len() as u16).to_le_bytes();
+                    vec![len[0], len[1], 0, BLOCK_SIZE as u8, BLOCK_COUNT as u8]
+                }
+                WRITE_DATA_BUFFER => {
+                    let at = usize::from(cmd.p1) * BLOCK_SIZE;
+                    buffer.resize(at, 0);
+                    buffer.extend_from_slice(&cmd.data);
+                    vec![]
+                }
+                READ_DATA_BUFFER => {
+                    let at = usize::from(cmd.p1) * BLOCK_SIZE;
+                    let mut block = buffer[at..].to_vec();
+                    block.resize(BLOCK_SIZE, 0);
+                    block
+                }
+                GENERATE_ADDRESS => {
+                    let count = u32::from_le_bytes(cmd.data[8..12].try_into().unwrap());
+                    let start = cmd.data[0];
+                    buffer.clear();
+                    for i in 0..count as u8 {
+                        buffer.push(0);
+                        buffer.extend_from_slice(&[start + i; 32]);
+                    }
+                    vec![]
+                }
+                SIGN if cmd.p1 > 0 => vec![0x01, 0x00, 0x00],
+                SIGN => [&[0x00, 0x00][..], &[0xAA; 32], &[0xBB; 64]].concat(),
+                _ => vec![],
+            }
+        }
+    }
+
+    impl Transport for LegacyDevice {
+        fn exchange(&self, cmd: &ApduCommand) -> Result<ApduAnswer, TransportError> {
+            assert_eq!(cmd.cla, LEGACY_CLA);
+            self.log
+                .lock()
+                .unwrap()
+                .push((cmd.ins, cmd.p1, cmd.data.clone()));
+            if self.reject && cmd.ins == USER_CONFIRM {
+                return Ok(ApduAnswer::from_raw(vec![0x69, 0x85]));
+            }
+            let mut raw = self.reply(cmd);
+            raw.extend_from_slice(&[0x90, 0x00]);
+            Ok(ApduAnswer::from_raw(raw))
+        }
+    }
+
+    fn legacy(device: LegacyDevice) -> (LegacyIota, Arc<LegacyDevice>) {
+        let device = Arc::new(device);
+        (LegacyIota::with_transport(Box::new(device.clone())), device)
+    }
+
+    #[test]
+    fn reads_app_config() {
+        let (legacy, _) = legacy(LegacyDevice::default());
+        let config = legacy.get_app_config().unwrap();
+        assert_eq!(config.version, (1, 0, 6));
+        assert_eq!(config.device, 1);
+        assert!(!config.debug);
+        assert!(AppConfig::parse(&[1, 0, 6]).is_err());
+    }
+
+    #[test]
+    fn set_account_hardens_and_flags_testnet() {
+        let (legacy, device) = legacy(LegacyDevice::default());
+        legacy.set_account(AppMode::IotaStardust, 2).unwrap();
+        legacy.set_account_on(AppMode::Shimmer, true, 0).unwrap();
+        let log = device.log.lock().unwrap();
+        assert_eq!(log[0], (SET_ACCOUNT, 0x01, vec![2, 0, 0, 0x80]));
+        assert_eq!(log[1], (SET_ACCOUNT, 0x83, vec![0, 0, 0, 0x80]));
+    }
+
+    #[test]
+    fn generates_addresses() {
+        let (legacy, device) = legacy(LegacyDevice::default());
+        let addresses = legacy.generate_addresses(true, 7, 3).unwrap();
+        assert_eq!(
+            addresses,
+            vec![
+                StardustAddress([7; 32]),
+                StardustAddress([8; 32]),
+                StardustAddress([9; 32])
+            ]
+        );
+        let log = device.log.lock().unwrap();
+        let (ins, show, data) = &log[0];
+        assert_eq!((*ins, *show), (GENERATE_ADDRESS, 0));
+        assert_eq!(data, &[7, 0, 0, 0x80, 1, 0, 0, 0x80, 3, 0, 0, 0]);
+    }
+
+    #[test]
+    fn signs_essence() {
+        let (legacy, device) = legacy(LegacyDevice::default());
+        let essence = vec![0x42; 300];
+        let inputs = [LegacyBip32::new(false, 0), LegacyBip32::new(false, 0)];
+        let remainder = Remainder {
+            output_index: 1,
+            path: LegacyBip32::new(true, 5),
+        };
+        let unlocks = legacy
+            .sign_essence(&essence, &inputs, Some(remainder))
+            .unwrap();
+        assert_eq!(
+            unlocks,
+            vec![
+                LegacyUnlock::Signature {
+                    public_key: [0xAA; 32],
+                    signature: [0xBB; 64]
+                },
+                LegacyUnlock::Reference(0)
+            ]
+        );
+
+        // The buffer holds the essence followed by the input paths.
+        let buffer = device.buffer.lock().unwrap();
+        assert_eq!(&buffer[..300], &essence[..]);
+        assert_eq!(&buffer[300..308], &[0, 0, 0, 0x80, 0, 0, 0, 0x80]);
+
+        let log = device.log.lock().unwrap();
+        let prepare = log
+            .iter()
+            .find(|(ins, ..)| *ins == PREPARE_SIGNING)
+            .unwrap();
+        assert_eq!(prepare.1, 1);
+        assert_eq!(prepare.2, vec![1, 0, 5, 0, 0, 0x80, 1, 0, 0, 0x80]);
+        let signs: Vec<u8> = log
+            .iter()
+            .filter(|(ins, ..)| *ins == SIGN)
+            .map(|(_, p1, _)| *p1)
+            .collect();
+        assert_eq!(signs, vec![0, 1]);
+    }
+
+    #[test]
+    fn blind_signs_hash() {
+        let (legacy, device) = legacy(LegacyDevice::default());
+        let unlocks = legacy
+            .blind_sign_essence(&[0x11; 32], &[LegacyBip32::new(false, 3)])
+            .unwrap();
+        assert_eq!(unlocks.len(), 1);
+        let log = device.log.lock().unwrap();
+        let prepare = log
+            .iter()
+            .find(|(ins, ..)| *ins == PREPARE_BLINDSIGNING)
+            .unwrap();
+        assert_eq!((prepare.1, prepare.2.len()), (0, 10));
+    }
+
+    #[test]
+    fn rejection_stops_before_signing() {
+        let (legacy, device) = legacy(LegacyDevice {
+            reject: true,
+            ..Default::default()
+        });
+        let err = legacy
+            .sign_essence(&[0; 10], &[LegacyBip32::new(false, 0)], None)
+            .unwrap_err();
+        assert!(matches!(err, LedgerError::UserRejected { .. }), "{err}");
+        assert!(!device
+            .log
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|(ins, ..)| *ins == SIGN));
+    }
+
+    #[test]
+    fn refuses_oversized_essence() {
+        let (legacy, _) = legacy(LegacyDevice::default());
+        let err = legacy
+            .sign_essence(
+                &[0; BLOCK_SIZE * BLOCK_COUNT],
+                &[LegacyBip32::new(false, 0)],
+                None,
+            )
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            LedgerError::PayloadTooLarge {
+                limit: 1004,
+                size: 1012,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn refuses_too_many_inputs() {
+        let (legacy, device) = legacy(LegacyDevice::default());
+        let inputs = vec![LegacyBip32::new(false, 0); MAX_INPUTS + 1];
+        let err = legacy.sign_essence(&[0; 10], &inputs, None).unwrap_err();
+        assert!(matches!(err, LedgerError::InvalidTransaction(_)), "{err}");
+        assert!(device.log.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn parses_unlocks() {
+        assert!(LegacyUnlock::parse(&[0x00, 0x00, 1, 2]).is_err());
+        assert!(LegacyUnlock::parse(&[0x02]).is_err());
+        assert_eq!(
+            LegacyUnlock::parse(&[0x01, 0x03, 0x01]).unwrap(),
+            LegacyUnlock::Reference(259)
+        );
+    }
+
+    #[test]
+    fn bech32_matches_bip173() {
+        assert_eq!(bech32::encode("a", &[]), "a12uel5l");
+        let data: Vec<u8> = (0..32).collect();
+        assert_eq!(
+            bech32::encode("abcdef", &data),
+            "abcdef1qpzry9x8gf2tvdw0s3jn54khce6mua7lmqqqxw"
+        );
+        // Type byte plus hash: 33 bytes in 53 characters after the separator.
+        let address = StardustAddress([0; 32]).to_bech32("iota");
+        assert!(address.starts_with("iota1qqqqqqqq"), "{address}");
+        assert_eq!(address.len(), "iota1".len() + 53 + 6);
+    }
+}
@@ -20,38 +20,162 @@
 //!
 //! # Modules
 //!
+//! - [`amount`] -- nanos <-> decimal IOTA string conversion
+//! - [`apdu`] -- raw APDU commands and answers ([`LedgerIota::exchange_apdu`])
 //! - [`api`] -- high-level [`LedgerIota`] facade
+//! - [`bcs_util`] -- BCS encoding/decoding primitives
+//! - [`capabilities`] -- clear vs blind signing prediction ([`SigningMode`])
+//! - [`change`] -- receive and change chains, next unused change address, change output checks
+//! - [`coins`] -- coin metadata registry ([`CoinRegistry`]); bundles IOTA only
+//! - [`identity`] -- device fingerprints and nicknames ([`DeviceIdentity`])
+//! - [`journal`] -- double-sign protection ([`SignJournal`])
+//! - `legacy` -- the legacy (Stardust) app protocol (feature `legacy`)
+//! - [`log_policy`] -- redaction of addresses, paths, payloads and screens in logs ([`LogPolicy`])
+//! - [`network`] -- app name, minimum version, coin type and intent of the target network ([`NetworkProfile`])
+//! - `portfolio` -- wallet restore scan with balances and owned objects (feature `rpc`)
+//! - `object_cache` -- cache of fetched clear-signing object data (feature `object-cache`)
+//! - [`session`] -- signing for one pinned derivation path with sender checks ([`AccountSession`])
+//! - `testing` -- mock transport and simulated device for integration tests (feature `testing`)
 //! - [`transport`] -- device communication (USB HID, TCP)
 //! - [`objects`] -- object data encoding for clear signing
 //! - [`tx`] -- transaction building helpers ([`build_transfer_tx`])
-//! - [`types`] -- [`Bip32Path`], [`AppVersion`], [`PublicKey`], [`Address`], [`Signature`]
+//! - [`types`] -- [`Bip32Path`], [`AppVersion`], [`PublicKey`], [`Address`], [`Signature`], [`TransactionDigest`], [`ObjectDigest`], [`BlindSigningHash`]
 //!
 //! # Feature flags
 //!
+//! - `std` (default) -- everything that talks to a device: [`LedgerIota`],
+//!   transports, the block protocol and journals. Without it the
+//!   crate is `no_std` (with `alloc`) and keeps [`types`], [`tx`],
+//!   [`objects`], [`bcs_util`], [`amount`], [`coins`], [`capabilities`],
+//!   [`limits`], [`log_policy`], [`network`] and the error types, for building transactions and
+//!   decoding data on hosts without an OS; needs Rust 1.81
 //! - `hid` (default) -- USB HID transport for real Ledger devices
 //! - `tcp` -- TCP transport for the Speculos simulator
 //! - `iota-sdk-types` -- return [`iota_sdk_types`] types from `get_pubkey`/`sign_tx`
 //!   instead of the built-in [`PublicKey`], [`Address`], [`Signature`] wrappers
+//! - `rpc` -- [`ObjectData::from_rpc_json`] for `iota_getObject` responses and
+//!   [`LedgerIota::sign_tx_resolving`] with a user-supplied [`rpc::RpcClient`];
+//!   `LedgerIota::scan_portfolio` for restoring wallets
+//! - `object-cache` -- `object_cache` module with in-memory and on-disk
+//!   caches consulted by `LedgerIota::sign_tx_resolving`; enables `rpc`
+//! - `serde` -- `Serialize`/`Deserialize` for [`ObjectData`], [`Owner`],
+//!   [`MoveObjectType`], [`TypeTag`] and [`StructTag`]
+//! - `bcs` -- encode [`ObjectData`] for the device with the `bcs` crate
+//!   instead of the built-in writer
+//! - `zeroize` -- wipe intermediate buffers holding transaction bytes,
+//!   messages and signatures once they've been sent or parsed
+//! - `verify` -- reject public keys from the device that aren't canonical,
+//!   on-curve Ed25519 points ([`PublicKey::is_valid_point`])
+//! - `testing` -- `testing` module with a mock transport, a simulated
+//!   device and Speculos helpers for downstream integration tests
+//! - `dev-signer` -- [`DevSigner`], an [`IotaSigner`] computing the keys
+//!   and signatures of a mnemonic in software (by default the Speculos one)
+//! - `legacy` -- [`legacy::LegacyIota`] for the legacy (Stardust) IOTA
+//!   app: address generation and essence signing, for migration tools
+//! - `fuzzing` -- `fuzzing` module exposing the device-response parsers
+//!   to the fuzz targets in `fuzz/`
 
-pub(crate) mod apdu;
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
+
+pub mod amount;
+#[cfg(feature = "std")]
+pub mod apdu;
+#[cfg(feature = "std")]
 pub mod api;
+pub(crate) mod base58;
+pub mod bcs_util;
+pub mod capabilities;
+pub mod change;
+pub mod coins;
+#[cfg(feature = "std")]
 pub(crate) mod commands;
+#[cfg(feature = "dev-signer")]
+pub mod dev_signer;
 pub mod error;
+#[cfg(feature = "std")]
+pub(crate) mod fs_util;
+#[cfg(feature = "fuzzing")]
+pub mod fuzzing;
+#[cfg(feature = "std")]
+pub mod identity;
+#[cfg(feature = "std")]
+pub mod journal;
+#[cfg(feature = "legacy")]
+pub mod legacy;
+pub mod limits;
+pub mod log_policy;
+pub mod network;
+#[cfg(feature = "object-cache")]
+pub mod object_cache;
 pub mod objects;
+#[cfg(feature = "rpc")]
+pub mod portfolio;
+pub(crate) mod prelude;
+#[cfg(feature = "std")]
 pub(crate) mod protocol;
+#[cfg(feature = "rpc")]
+pub mod rpc;
+#[cfg(any(feature = "testing", feature = "dev-signer"))]
+pub mod seed;
+#[cfg(feature = "serde")]
+pub(crate) mod serde_hex;
+#[cfg(feature = "std")]
+pub mod session;
+#[cfg(feature = "std")]
+pub mod signer;
+#[cfg(feature = "testing")]
+pub mod testing;
+#[cfg(feature = "std")]
 pub mod transport;
 pub mod tx;
 pub mod types;
+#[cfg(feature = "std")]
+pub(crate) mod wipe;
 
-pub use api::{DeviceStatus, LedgerIota};
-pub use error::LedgerError;
+pub use amount::Amount;
+#[cfg(feature = "std")]
+pub use api::{
+    DerivedAddress, DeviceStatus, LedgerIota, NetworkAddresses, SignEvent, SignRequest, SignStatus,
+    SignedTransaction,
+};
+pub use capabilities::SigningMode;
+pub use change::Chain;
+pub use coins::{CoinMetadata, CoinRegistry};
+#[cfg(feature = "dev-signer")]
+pub use dev_signer::DevSigner;
+#[cfg(feature = "serde")]
+pub use error::ErrorReport;
+pub use error::{ErrorCode, LedgerError, RecipientError, ValidationError};
+#[cfg(feature = "std")]
+pub use identity::{DeviceIdentity, DeviceNicknames};
 #[cfg(feature = "iota-sdk-types")]
 pub use iota_sdk_types::{Address, Ed25519PublicKey, Ed25519Signature};
-pub use objects::{encode_objects, MoveObjectType, ObjectData, Owner, TypeTag};
+#[cfg(feature = "std")]
+pub use journal::{JournalMode, SignJournal};
+pub use limits::{PayloadKind, PayloadLimits};
+pub use log_policy::LogPolicy;
+pub use network::NetworkProfile;
+pub use objects::{
+    decode_objects, encode_objects, encode_objects_into, MoveObjectType, ObjectData, Owner,
+    StructTag, TypeTag,
+};
+#[cfg(feature = "std")]
+pub use session::AccountSession;
+#[cfg(feature = "std")]
+pub use signer::IotaSigner;
 #[cfg(feature = "hid")]
 pub use transport::hid::DeviceType;
+#[cfg(feature = "std")]
 pub use transport::TransportType;
-pub use tx::{build_transfer_tx, GasCoinRef};
+pub use tx::{
+    build_batch_transfer_tx, build_transfer_tx, GasCoinRef, Payment, RecipientPolicy,
+    RecipientRules,
+};
 #[cfg(not(feature = "iota-sdk-types"))]
 pub use types::{Address, PublicKey, Signature};
-pub use types::{AppVersion, Bip32Path};
+pub use types::{
+    AddressFormat, AppFlags, AppVersion, Bip32Path, BlindSigningHash, CoinType, ObjectDigest,
+    TransactionDigest,
+};
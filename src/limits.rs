@@ -0,0 +1,220 @@
+//! Payload sizes the IOTA app accepts, per device model.
+//!
+//! Oversized payloads are rejected by the device with an opaque status
+//! word, or exhaust its memory mid-exchange. [`LedgerIota`](crate::LedgerIota)
+//! checks against the connected model's [`PayloadLimits`] (or those set
+//! with [`with_payload_limits`](crate::LedgerIota::with_payload_limits))
+//! first and fails with [`LedgerError::MessageTooLarge`] or
+//! [`LedgerError::PayloadTooLarge`].
+
+use core::fmt;
+
+use crate::error::LedgerError;
+
+/// What a size limit applies to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum PayloadKind {
+    /// Personal message, without the intent prefix.
+    Message,
+    /// Intent-prefixed transaction bytes.
+    Transaction,
+    /// Encoded clear-signing objects.
+    Objects,
+    /// Transaction and object data together.
+    Total,
+}
+
+impl fmt::Display for PayloadKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Message => "message",
+            Self::Transaction => "transaction",
+            Self::Objects => "object data",
+            Self::Total => "transaction with object data",
+        })
+    }
+}
+
+/// Maximum payload sizes in bytes.
+///
+/// Message limits are the app's message buffer sizes. The app publishes
+/// no transaction or object limits, so those are `None` (left to the
+/// device) unless set with the `with_*_limit` methods.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct PayloadLimits {
+    pub message: usize,
+    pub transaction: Option<usize>,
+    pub objects: Option<usize>,
+    /// Transaction and object data together.
+    pub total: Option<usize>,
+}
+
+impl PayloadLimits {
+    /// Nano S, Nano S+, Stax, Flex, and devices of unknown model
+    /// (including Speculos).
+    pub const DEFAULT: Self = Self::messages(4 * 1024);
+
+    /// The Nano X has less RAM available to apps.
+    pub const NANO_X: Self = Self::messages(2 * 1024);
+
+    const fn messages(message: usize) -> Self {
+        Self {
+            message,
+            transaction: None,
+            objects: None,
+            total: None,
+        }
+    }
+
+    #[must_use]
+    pub const fn with_message_limit(mut self, limit: usize) -> Self {
+        self.message = limit;
+        self
+    }
+
+    #[must_use]
+    pub const fn with_transaction_limit(mut self, limit: usize) -> Self {
+        self.transaction = Some(limit);
+        self
+    }
+
+    #[must_use]
+    pub const fn with_objects_limit(mut self, limit: usize) -> Self {
+        self.objects = Some(limit);
+        self
+    }
+
+    #[must_use]
+    pub const fn with_total_limit(mut self, limit: usize) -> Self {
+        self.total = Some(limit);
+        self
+    }
+
+    /// `None` if `kind` isn't checked on the host.
+    #[must_use]
+    pub fn get(&self, kind: PayloadKind) -> Option<usize> {
+        match kind {
+            PayloadKind::Message => Some(self.message),
+            PayloadKind::Transaction => self.transaction,
+            PayloadKind::Objects => self.objects,
+            PayloadKind::Total => self.total,
+        }
+    }
+
+    /// Fail if `size` bytes of `kind` exceed the limit.
+    pub fn check(&self, kind: PayloadKind, size: usize) -> Result<(), LedgerError> {
+        let Some(limit) = self.get(kind).filter(|&limit| size > limit) else {
+            return Ok(());
+        };
+        Err(match kind {
+            PayloadKind::Message => LedgerError::MessageTooLarge { size, limit },
+            _ => LedgerError::PayloadTooLarge { kind, size, limit },
+        })
+    }
+
+    /// Fail if a `tx_len`-byte transaction with `objects_len` bytes of
+    /// encoded objects exceeds any of the transaction, object or total limits.
+    pub fn check_tx(&self, tx_len: usize, objects_len: Option<usize>) -> Result<(), LedgerError> {
+        self.check(PayloadKind::Transaction, tx_len)?;
+        if let Some(objects_len) = objects_len {
+            self.check(PayloadKind::Objects, objects_len)?;
+            self.check(PayloadKind::Total, tx_len + objects_len)?;
+        }
+        Ok(())
+    }
+}
+
+impl Default for PayloadLimits {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+#[cfg(feature = "hid")]
+impl From<crate::DeviceType> for PayloadLimits {
+    fn from(device: crate::DeviceType) -> Self {
+        match device {
+            crate::DeviceType::NanoX => Self::NANO_X,
+            _ => Self::DEFAULT,
+        }
+    }
+}
+
+#[cfg(all(feature = "testing", feature = "tcp"))]
+impl From<crate::testing::speculos::Model> for PayloadLimits {
+    fn from(model: crate::testing::speculos::Model) -> Self {
+        use crate::testing::speculos::Model;
+        match model {
+            Model::NanoX => Self::NANO_X,
+            Model::NanoS | Model::NanoSPlus | Model::Stax | Model::Flex => Self::DEFAULT,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_at_and_over_limit() {
+        let limits = PayloadLimits::NANO_X;
+        assert!(limits.check(PayloadKind::Message, 2048).is_ok());
+        assert!(matches!(
+            limits.check(PayloadKind::Message, 2049),
+            Err(LedgerError::MessageTooLarge {
+                size: 2049,
+                limit: 2048
+            })
+        ));
+
+        // Not checked unless set.
+        assert!(limits.check(PayloadKind::Transaction, 1 << 20).is_ok());
+        let err = limits
+            .with_transaction_limit(16_384)
+            .check(PayloadKind::Transaction, 20_000)
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            LedgerError::PayloadTooLarge {
+                kind: PayloadKind::Transaction,
+                size: 20_000,
+                limit: 16_384
+            }
+        ));
+        assert_eq!(
+            err.to_string(),
+            "transaction is 20000 bytes, the device accepts at most 16384"
+        );
+    }
+
+    #[test]
+    fn check_tx_limits_the_total() {
+        let limits = PayloadLimits::DEFAULT
+            .with_objects_limit(8192)
+            .with_total_limit(8192);
+        assert!(limits.check_tx(8192, None).is_ok());
+        assert!(limits.check_tx(4096, Some(4096)).is_ok());
+        let err = limits.check_tx(6000, Some(3000)).unwrap_err();
+        assert!(matches!(
+            err,
+            LedgerError::PayloadTooLarge {
+                kind: PayloadKind::Total,
+                size: 9000,
+                limit: 8192
+            }
+        ));
+        assert_eq!(
+            err.to_string(),
+            "transaction with object data is 9000 bytes, the device accepts at most 8192"
+        );
+        assert!(matches!(
+            limits.check_tx(100, Some(9000)),
+            Err(LedgerError::PayloadTooLarge {
+                kind: PayloadKind::Objects,
+                ..
+            })
+        ));
+    }
+}
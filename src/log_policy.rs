@@ -0,0 +1,176 @@
+//! Which sensitive values appear in logs and transcripts.
+//!
+//! Addresses, derivation paths, transaction bytes and device screens
+//! identify accounts and reveal what is being signed. A [`LogPolicy`]
+//! says, per kind of value, from which log level on it is written out;
+//! below that level it shows as `<redacted ...>`. The
+//! [default](LogPolicy::DEFAULT) keeps payloads and screens out of
+//! `info` and less verbose records.
+//!
+//! ```
+//! use ledger_iota::log_policy::{LogPolicy, Sensitive};
+//!
+//! let policy = LogPolicy::DEFAULT;
+//! let tx = "000102";
+//! assert_eq!(
+//!     policy.show(Sensitive::Payload, log::Level::Info, tx).to_string(),
+//!     "<redacted payload>"
+//! );
+//! assert_eq!(policy.show(Sensitive::Payload, log::Level::Debug, tx).to_string(), "000102");
+//! ```
+
+use core::fmt;
+
+use log::Level;
+
+/// A kind of value a [`LogPolicy`] controls.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Sensitive {
+    /// Account addresses and public keys.
+    Address,
+    /// BIP32 derivation paths.
+    Path,
+    /// Transaction, message and APDU bytes.
+    Payload,
+    /// Text shown on the device.
+    Screen,
+}
+
+impl fmt::Display for Sensitive {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Address => "address",
+            Self::Path => "path",
+            Self::Payload => "payload",
+            Self::Screen => "screen",
+        })
+    }
+}
+
+/// The least verbose log level at which each kind of value is written
+/// out; `None` redacts it at every level.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LogPolicy {
+    pub addresses: Option<Level>,
+    pub paths: Option<Level>,
+    pub payloads: Option<Level>,
+    pub screens: Option<Level>,
+}
+
+impl LogPolicy {
+    /// Addresses and paths from `info`, payloads and screens only in
+    /// `debug` and `trace` records.
+    pub const DEFAULT: Self = Self {
+        addresses: Some(Level::Info),
+        paths: Some(Level::Info),
+        payloads: Some(Level::Debug),
+        screens: Some(Level::Debug),
+    };
+
+    /// Nothing sensitive at any level, for custody environments.
+    pub const REDACT_ALL: Self = Self {
+        addresses: None,
+        paths: None,
+        payloads: None,
+        screens: None,
+    };
+
+    /// Everything at every level, for development.
+    pub const REVEAL_ALL: Self = Self {
+        addresses: Some(Level::Error),
+        paths: Some(Level::Error),
+        payloads: Some(Level::Error),
+        screens: Some(Level::Error),
+    };
+
+    #[must_use]
+    pub fn get(&self, kind: Sensitive) -> Option<Level> {
+        match kind {
+            Sensitive::Address => self.addresses,
+            Sensitive::Path => self.paths,
+            Sensitive::Payload => self.payloads,
+            Sensitive::Screen => self.screens,
+        }
+    }
+
+    /// Whether values of `kind` are written out in records at `level`.
+    #[must_use]
+    pub fn reveals(&self, kind: Sensitive, level: Level) -> bool {
+        self.get(kind).is_some_and(|from| level >= from)
+    }
+
+    /// `value` for a record at `level`: itself if the policy
+    /// [reveals](Self::reveals) it, `<redacted {kind}>` otherwise.
+    #[must_use]
+    pub fn show<T: fmt::Display>(&self, kind: Sensitive, level: Level, value: T) -> Shown<T> {
+        Shown {
+            kind,
+            value: self.reveals(kind, level).then_some(value),
+        }
+    }
+}
+
+impl Default for LogPolicy {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// A value as a [`LogPolicy`] lets it be shown; see [`LogPolicy::show`].
+#[derive(Debug, Clone)]
+pub struct Shown<T> {
+    kind: Sensitive,
+    value: Option<T>,
+}
+
+impl<T: fmt::Display> fmt::Display for Shown<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.value {
+            Some(value) => value.fmt(f),
+            None => write!(f, "<redacted {}>", self.kind),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn levels() {
+        let policy = LogPolicy::DEFAULT;
+        assert!(policy.reveals(Sensitive::Address, Level::Info));
+        assert!(!policy.reveals(Sensitive::Address, Level::Warn));
+        assert!(!policy.reveals(Sensitive::Payload, Level::Info));
+        assert!(policy.reveals(Sensitive::Payload, Level::Trace));
+        assert!(!policy.reveals(Sensitive::Screen, Level::Info));
+
+        for kind in [
+            Sensitive::Address,
+            Sensitive::Path,
+            Sensitive::Payload,
+            Sensitive::Screen,
+        ] {
+            assert!(!LogPolicy::REDACT_ALL.reveals(kind, Level::Trace));
+            assert!(LogPolicy::REVEAL_ALL.reveals(kind, Level::Error));
+        }
+    }
+
+    #[test]
+    fn show() {
+        let policy = LogPolicy::REDACT_ALL;
+        assert_eq!(
+            policy
+                .show(Sensitive::Path, Level::Trace, "m/44'/4218'/0'/0'/0'")
+                .to_string(),
+            "<redacted path>"
+        );
+        assert_eq!(
+            LogPolicy::DEFAULT
+                .show(Sensitive::Path, Level::Info, "m/44'/4218'/0'/0'/0'")
+                .to_string(),
+            "m/44'/4218'/0'/0'/0'"
+        );
+    }
+}
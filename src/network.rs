@@ -0,0 +1,136 @@
+//! What identifies the app and chain a [`LedgerIota`](crate::LedgerIota)
+//! talks to.
+//!
+//! Forks of IOTA Rebased ship rebranded builds of the Ledger app with
+//! their own name, versioning, coin type and intent app id. A
+//! [`NetworkProfile`] collects those so the rest of the crate works
+//! with them unchanged; [`NetworkProfile::IOTA`] is the default.
+
+use alloc::borrow::Cow;
+
+use crate::error::LedgerError;
+use crate::types::{AppVersion, Bip32Path, CoinType};
+
+/// Expected app and chain parameters.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct NetworkProfile {
+    /// Matched case-insensitively against the name the app reports; the
+    /// reported name only needs to contain it.
+    pub app_name: Cow<'static, str>,
+    /// Oldest app release accepted when connecting.
+    pub min_version: (u8, u8, u8),
+    /// Coin type of [`path`](Self::path).
+    pub coin_type: CoinType,
+    /// Third byte of the intent prefix (`0` for IOTA).
+    pub intent_app_id: u8,
+}
+
+impl NetworkProfile {
+    /// IOTA Rebased mainnet and the `IOTA` app from 0.9.0.
+    pub const IOTA: Self = Self {
+        app_name: Cow::Borrowed("iota"),
+        min_version: (0, 9, 0),
+        coin_type: CoinType::IotaMainnet,
+        intent_app_id: 0,
+    };
+
+    /// A fork's profile; starts from [`IOTA`](Self::IOTA)'s minimum
+    /// version and intent app id.
+    #[must_use]
+    pub fn new(app_name: impl Into<Cow<'static, str>>, coin_type: CoinType) -> Self {
+        Self {
+            app_name: app_name.into(),
+            coin_type,
+            ..Self::IOTA
+        }
+    }
+
+    #[must_use]
+    pub fn with_min_version(mut self, major: u8, minor: u8, patch: u8) -> Self {
+        self.min_version = (major, minor, patch);
+        self
+    }
+
+    #[must_use]
+    pub fn with_intent_app_id(mut self, app_id: u8) -> Self {
+        self.intent_app_id = app_id;
+        self
+    }
+
+    /// Whether an app reporting `name` is this network's app.
+    #[must_use]
+    pub fn matches_app(&self, name: &str) -> bool {
+        name.to_ascii_lowercase()
+            .contains(&self.app_name.to_ascii_lowercase())
+    }
+
+    /// Whether `version` is at least [`min_version`](Self::min_version).
+    #[must_use]
+    pub fn supports(&self, version: &AppVersion) -> bool {
+        let (major, minor, patch) = self.min_version;
+        version.at_least(major, minor, patch)
+    }
+
+    /// `44'/coin_type'/account'/change'/index'`.
+    pub fn path(&self, account: u32, change: u32, index: u32) -> Result<Bip32Path, LedgerError> {
+        Bip32Path::for_coin(self.coin_type, account, change, index)
+    }
+
+    /// Intent prefix of `TransactionData`: `[0, 0, app_id]`.
+    #[must_use]
+    pub fn transaction_intent(&self) -> [u8; 3] {
+        [0, 0, self.intent_app_id]
+    }
+
+    /// Intent prefix of personal messages: `[3, 0, app_id]`.
+    #[must_use]
+    pub fn message_intent(&self) -> [u8; 3] {
+        [3, 0, self.intent_app_id]
+    }
+}
+
+impl Default for NetworkProfile {
+    fn default() -> Self {
+        Self::IOTA
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version(major: u8, minor: u8, patch: u8, name: &str) -> AppVersion {
+        AppVersion::new(major, minor, patch, name)
+    }
+
+    #[test]
+    fn iota_defaults() {
+        let iota = NetworkProfile::default();
+        assert!(iota.matches_app("IOTA"));
+        assert!(!iota.matches_app("Shimmer"));
+        assert!(iota.supports(&version(0, 9, 0, "IOTA")));
+        assert!(!iota.supports(&version(0, 8, 9, "IOTA")));
+        assert_eq!(iota.path(0, 0, 1).unwrap(), Bip32Path::iota(0, 0, 1));
+        assert_eq!(iota.message_intent(), [3, 0, 0]);
+        assert_eq!(iota.transaction_intent(), [0, 0, 0]);
+    }
+
+    #[test]
+    fn fork_profile() {
+        let fork = NetworkProfile::new("Acme", CoinType::Custom(9999))
+            .with_min_version(2, 1, 0)
+            .with_intent_app_id(7);
+        assert!(fork.matches_app("ACME Rebased"));
+        assert!(!fork.matches_app("IOTA"));
+        assert!(!fork.supports(&version(2, 0, 9, "Acme")));
+        assert!(fork.supports(&version(2, 1, 0, "Acme")));
+        let path = fork.path(0, 0, 0).unwrap();
+        assert_eq!(path.to_string(), "m/44'/9999'/0'/0'/0'");
+        assert_eq!(
+            Bip32Path::parse_for_coin(&path.to_string(), fork.coin_type).unwrap(),
+            path
+        );
+        assert_eq!(fork.message_intent(), [3, 0, 7]);
+        assert_eq!(fork.transaction_intent(), [0, 0, 7]);
+    }
+}
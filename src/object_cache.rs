@@ -0,0 +1,280 @@
+//! Cache of clear-signing object data.
+//!
+//! An object at a given version never changes, so entries keyed by object
+//! id and version never go stale; they only stop being useful once the
+//! object moves on. [`rpc::resolve_objects_cached`](crate::rpc::resolve_objects_cached)
+//! and [`LedgerIota::with_object_cache`](crate::LedgerIota::with_object_cache)
+//! consult the cache before asking the node.
+//!
+//! [`MemoryObjectCache`] lives for one process; [`FileObjectCache`] keeps
+//! one file per object in a directory. Both keep only the newest version
+//! of each object and hold at most [`DEFAULT_MAX_ENTRIES`] objects unless
+//! set otherwise, evicting the least recently stored first.
+
+use std::collections::{HashMap, VecDeque};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use crate::objects::ObjectData;
+
+/// Objects a cache holds unless created `with_max_entries`.
+pub const DEFAULT_MAX_ENTRIES: usize = 10_000;
+
+/// Storage for fetched [`ObjectData`], keyed by object id and version.
+///
+/// Errors are logged by the resolution path and treated as misses; the
+/// cache is never required for signing.
+pub trait ObjectCache: Send {
+    fn get(&self, id: &[u8; 32], version: u64) -> io::Result<Option<ObjectData>>;
+
+    /// Store `obj` (at `obj.data.version`) for object `id`.
+    fn put(&mut self, id: [u8; 32], obj: &ObjectData) -> io::Result<()>;
+}
+
+/// In-process cache.
+#[derive(Debug)]
+pub struct MemoryObjectCache {
+    /// Object id -> newest version stored and its data.
+    objects: HashMap<[u8; 32], (u64, ObjectData)>,
+    /// Ids in the order they were stored, oldest first.
+    order: VecDeque<[u8; 32]>,
+    max_entries: usize,
+}
+
+impl MemoryObjectCache {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::with_max_entries(DEFAULT_MAX_ENTRIES)
+    }
+
+    #[must_use]
+    pub fn with_max_entries(max_entries: usize) -> Self {
+        Self {
+            objects: HashMap::new(),
+            order: VecDeque::new(),
+            max_entries,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.objects.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.objects.is_empty()
+    }
+}
+
+impl Default for MemoryObjectCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ObjectCache for MemoryObjectCache {
+    fn get(&self, id: &[u8; 32], version: u64) -> io::Result<Option<ObjectData>> {
+        Ok(self
+            .objects
+            .get(id)
+            .filter(|(stored, _)| *stored == version)
+            .map(|(_, obj)| obj.clone()))
+    }
+
+    fn put(&mut self, id: [u8; 32], obj: &ObjectData) -> io::Result<()> {
+        let version = obj.data.version;
+        if let Some((stored, _)) = self.objects.get(&id) {
+            if *stored >= version {
+                return Ok(());
+            }
+            self.order.retain(|other| *other != id);
+        }
+        self.objects.insert(id, (version, obj.clone()));
+        self.order.push_back(id);
+        while self.objects.len() > self.max_entries {
+            let Some(oldest) = self.order.pop_front() else {
+                break;
+            };
+            self.objects.remove(&oldest);
+        }
+        Ok(())
+    }
+}
+
+/// One file per object (`<id hex>-<version>`, holding
+/// [`ObjectData::to_bytes`]) in a directory.
+#[derive(Debug, Clone)]
+pub struct FileObjectCache {
+    dir: PathBuf,
+    max_entries: usize,
+}
+
+impl FileObjectCache {
+    /// Use `dir`, creating it if needed.
+    pub fn new(dir: impl Into<PathBuf>) -> io::Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        Ok(Self {
+            dir,
+            max_entries: DEFAULT_MAX_ENTRIES,
+        })
+    }
+
+    #[must_use]
+    pub fn with_max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries = max_entries;
+        self
+    }
+
+    fn path(&self, id: &[u8; 32], version: u64) -> PathBuf {
+        self.dir.join(format!("{}-{version}", hex::encode(id)))
+    }
+
+    /// Entries of the directory: object id (hex), version, path and
+    /// modification time.
+    fn entries(&self) -> io::Result<Vec<(String, u64, PathBuf, SystemTime)>> {
+        let mut entries = Vec::new();
+        for entry in fs::read_dir(&self.dir)? {
+            let entry = entry?;
+            let name = entry.file_name();
+            let Some((id, version)) = name.to_str().and_then(|name| name.split_once('-')) else {
+                continue;
+            };
+            let Ok(version) = version.parse() else {
+                continue;
+            };
+            let modified = entry
+                .metadata()
+                .and_then(|m| m.modified())
+                .unwrap_or(SystemTime::UNIX_EPOCH);
+            entries.push((id.to_owned(), version, entry.path(), modified));
+        }
+        Ok(entries)
+    }
+}
+
+impl ObjectCache for FileObjectCache {
+    fn get(&self, id: &[u8; 32], version: u64) -> io::Result<Option<ObjectData>> {
+        let bytes = match fs::read(self.path(id, version)) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        ObjectData::from_bytes(&bytes)
+            .map(Some)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    fn put(&mut self, id: [u8; 32], obj: &ObjectData) -> io::Result<()> {
+        let version = obj.data.version;
+        let id_hex = hex::encode(id);
+        let mut entries = self.entries()?;
+        if entries
+            .iter()
+            .any(|(i, v, ..)| *i == id_hex && *v >= version)
+        {
+            return Ok(());
+        }
+        for (_, _, path, _) in entries.iter().filter(|(i, ..)| *i == id_hex) {
+            fs::remove_file(path)?;
+        }
+        entries.retain(|(i, ..)| *i != id_hex);
+
+        let bytes = obj
+            .to_bytes()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        crate::fs_util::write_atomic(&self.path(&id, version), &bytes)?;
+        // Make room for the new entry, least recently written first.
+        let excess = (entries.len() + 1).saturating_sub(self.max_entries);
+        entries.sort_by_key(|(.., modified)| *modified);
+        for (_, _, path, _) in entries.iter().take(excess) {
+            fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fs_util::TempDir;
+    use crate::objects::Owner;
+    use crate::types::TransactionDigest;
+
+    fn coin(version: u64) -> ObjectData {
+        ObjectData::gas_coin(
+            version,
+            vec![9; 40],
+            Owner::Immutable,
+            TransactionDigest::ZERO,
+            1,
+        )
+    }
+
+    fn exercise(cache: &mut dyn ObjectCache) {
+        assert!(cache.get(&[1; 32], 5).unwrap().is_none());
+        cache.put([1; 32], &coin(5)).unwrap();
+        let hit = cache.get(&[1; 32], 5).unwrap().unwrap();
+        assert_eq!(hit.to_bytes().unwrap(), coin(5).to_bytes().unwrap());
+        assert!(cache.get(&[1; 32], 6).unwrap().is_none());
+        assert!(cache.get(&[2; 32], 5).unwrap().is_none());
+    }
+
+    #[test]
+    fn memory_cache() {
+        let mut cache = MemoryObjectCache::new();
+        exercise(&mut cache);
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    fn file_cache() {
+        let dir = TempDir::new("cache");
+        let mut cache = FileObjectCache::new(&*dir).unwrap();
+        exercise(&mut cache);
+
+        // Survives reopening; corrupt entries are errors, not hits.
+        let reopened = FileObjectCache::new(&*dir).unwrap();
+        assert!(reopened.get(&[1; 32], 5).unwrap().is_some());
+        fs::write(reopened.path(&[3; 32], 1), b"junk").unwrap();
+        assert!(reopened.get(&[3; 32], 1).is_err());
+    }
+
+    #[test]
+    fn newer_versions_replace_older_ones() {
+        let mut memory = MemoryObjectCache::new();
+        let dir = TempDir::new("evict");
+        let mut file = FileObjectCache::new(&*dir).unwrap();
+        for cache in [&mut memory as &mut dyn ObjectCache, &mut file] {
+            cache.put([1; 32], &coin(5)).unwrap();
+            cache.put([1; 32], &coin(7)).unwrap();
+            // An older version arriving late doesn't replace the newer one.
+            cache.put([1; 32], &coin(6)).unwrap();
+            assert!(cache.get(&[1; 32], 5).unwrap().is_none());
+            assert!(cache.get(&[1; 32], 6).unwrap().is_none());
+            assert!(cache.get(&[1; 32], 7).unwrap().is_some());
+        }
+        assert_eq!(memory.len(), 1);
+        assert_eq!(fs::read_dir(&*dir).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn caches_are_capped() {
+        let mut memory = MemoryObjectCache::with_max_entries(2);
+        let dir = TempDir::new("cap");
+        let mut file = FileObjectCache::new(&*dir).unwrap().with_max_entries(2);
+        for cache in [&mut memory as &mut dyn ObjectCache, &mut file] {
+            for id in 1..=3 {
+                cache.put([id; 32], &coin(1)).unwrap();
+                // Distinct modification times for the file cache.
+                std::thread::sleep(std::time::Duration::from_millis(10));
+            }
+            assert!(cache.get(&[1; 32], 1).unwrap().is_none());
+            assert!(cache.get(&[2; 32], 1).unwrap().is_some());
+            assert!(cache.get(&[3; 32], 1).unwrap().is_some());
+        }
+        assert_eq!(memory.len(), 2);
+        assert_eq!(fs::read_dir(&*dir).unwrap().count(), 2);
+    }
+}
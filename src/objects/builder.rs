@@ -0,0 +1,244 @@
+//! Step-by-step construction of [`ObjectData`].
+//!
+//! The object kind has to be chosen before [`ObjectDataBuilder::build`]
+//! becomes available; the remaining fields are checked when building,
+//! followed by [`ObjectData::validate`].
+
+use super::{
+    timelocked_iota_type, timelocked_staked_iota_type, MoveObject, MoveObjectType, ObjectData,
+    Owner, StructTag,
+};
+use crate::error::ValidationError;
+use crate::prelude::*;
+use crate::types::TransactionDigest;
+
+/// Builder state before the object kind is set.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoKind;
+
+/// Builder for [`ObjectData`], see [`ObjectData::builder`].
+#[derive(Debug, Clone)]
+#[must_use]
+pub struct ObjectDataBuilder<K> {
+    kind: K,
+    has_public_transfer: Option<bool>,
+    version: Option<u64>,
+    contents: Option<Vec<u8>>,
+    owner: Option<Owner>,
+    previous_transaction: Option<TransactionDigest>,
+    storage_rebate: u64,
+}
+
+impl ObjectData {
+    /// Start building an object; pick the kind first.
+    ///
+    /// ```
+    /// # use ledger_iota::{ObjectData, Owner};
+    /// let obj = ObjectData::builder()
+    ///     .gas_coin()
+    ///     .version(42)
+    ///     .contents(vec![0; 40])
+    ///     .owner(Owner::Immutable)
+    ///     .previous_transaction([0; 32])
+    ///     .build()?;
+    /// # Ok::<(), ledger_iota::ValidationError>(())
+    /// ```
+    pub fn builder() -> ObjectDataBuilder<NoKind> {
+        ObjectDataBuilder {
+            kind: NoKind,
+            has_public_transfer: None,
+            version: None,
+            contents: None,
+            owner: None,
+            previous_transaction: None,
+            storage_rebate: 0,
+        }
+    }
+}
+
+impl ObjectDataBuilder<NoKind> {
+    pub fn kind(self, kind: MoveObjectType) -> ObjectDataBuilder<MoveObjectType> {
+        ObjectDataBuilder {
+            kind,
+            has_public_transfer: self.has_public_transfer,
+            version: self.version,
+            contents: self.contents,
+            owner: self.owner,
+            previous_transaction: self.previous_transaction,
+            storage_rebate: self.storage_rebate,
+        }
+    }
+
+    /// `Coin<0x2::iota::IOTA>`.
+    pub fn gas_coin(self) -> ObjectDataBuilder<MoveObjectType> {
+        self.kind(MoveObjectType::GasCoin)
+    }
+
+    /// `Coin<coin_type>`.
+    pub fn coin(self, coin_type: StructTag) -> ObjectDataBuilder<MoveObjectType> {
+        self.kind(MoveObjectType::Coin(coin_type))
+    }
+
+    pub fn staked_iota(self) -> ObjectDataBuilder<MoveObjectType> {
+        self.kind(MoveObjectType::StakedIota)
+    }
+
+    pub fn timelocked_staked_iota(self) -> ObjectDataBuilder<MoveObjectType> {
+        self.kind(MoveObjectType::Other(timelocked_staked_iota_type()))
+    }
+
+    pub fn timelocked_iota(self) -> ObjectDataBuilder<MoveObjectType> {
+        self.kind(MoveObjectType::Other(timelocked_iota_type()))
+    }
+
+    /// Any other object type; also requires
+    /// [`has_public_transfer`](ObjectDataBuilder::has_public_transfer)
+    /// unless the type is one this crate knows.
+    pub fn other(self, type_: StructTag) -> ObjectDataBuilder<MoveObjectType> {
+        self.kind(MoveObjectType::Other(type_))
+    }
+}
+
+impl<K> ObjectDataBuilder<K> {
+    /// Only needed for unknown object types; known kinds imply the value
+    /// and reject a different one.
+    pub fn has_public_transfer(mut self, value: bool) -> Self {
+        self.has_public_transfer = Some(value);
+        self
+    }
+
+    pub fn version(mut self, version: u64) -> Self {
+        self.version = Some(version);
+        self
+    }
+
+    /// The object's BCS-encoded Move struct.
+    pub fn contents(mut self, contents: Vec<u8>) -> Self {
+        self.contents = Some(contents);
+        self
+    }
+
+    pub fn owner(mut self, owner: Owner) -> Self {
+        self.owner = Some(owner);
+        self
+    }
+
+    /// Digest of the transaction that last mutated the object.
+    pub fn previous_transaction(mut self, digest: impl Into<TransactionDigest>) -> Self {
+        self.previous_transaction = Some(digest.into());
+        self
+    }
+
+    /// Defaults to 0.
+    pub fn storage_rebate(mut self, storage_rebate: u64) -> Self {
+        self.storage_rebate = storage_rebate;
+        self
+    }
+}
+
+impl ObjectDataBuilder<MoveObjectType> {
+    /// Fails with [`ValidationError::MissingField`] if a required field is
+    /// unset, otherwise with whatever [`ObjectData::validate`] reports.
+    pub fn build(self) -> Result<ObjectData, ValidationError> {
+        let has_public_transfer = self
+            .has_public_transfer
+            .or_else(|| self.kind.expected_public_transfer())
+            .ok_or(ValidationError::MissingField("has_public_transfer"))?;
+        let previous_transaction = self
+            .previous_transaction
+            .ok_or(ValidationError::MissingField("previous_transaction"))?;
+
+        let obj = ObjectData {
+            data: MoveObject {
+                type_: self.kind,
+                has_public_transfer,
+                version: self
+                    .version
+                    .ok_or(ValidationError::MissingField("version"))?,
+                contents: self
+                    .contents
+                    .ok_or(ValidationError::MissingField("contents"))?,
+            },
+            owner: self.owner.ok_or(ValidationError::MissingField("owner"))?,
+            previous_transaction,
+            storage_rebate: self.storage_rebate,
+        };
+        obj.validate()?;
+        Ok(obj)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn coin() -> ObjectDataBuilder<MoveObjectType> {
+        ObjectData::builder()
+            .gas_coin()
+            .version(3)
+            .contents(vec![0; 40])
+            .owner(Owner::AddressOwner([0xAA; 32]))
+            .previous_transaction([0xBB; 32])
+    }
+
+    #[test]
+    fn matches_positional_constructor() {
+        let prev = TransactionDigest::from_bytes([0xBB; 32]);
+        let expected =
+            ObjectData::gas_coin(3, vec![0; 40], Owner::AddressOwner([0xAA; 32]), prev, 9);
+        let built = coin().storage_rebate(9).build().unwrap();
+        assert_eq!(built.to_bytes().unwrap(), expected.to_bytes().unwrap());
+    }
+
+    #[test]
+    fn implied_public_transfer() {
+        let staked = ObjectData::builder()
+            .version(1)
+            .contents(vec![0; 80])
+            .owner(Owner::Immutable)
+            .previous_transaction([0; 32])
+            .staked_iota()
+            .build()
+            .unwrap();
+        assert!(!staked.data.has_public_transfer);
+
+        assert!(matches!(
+            coin().has_public_transfer(false).build(),
+            Err(ValidationError::PublicTransferMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn missing_fields() {
+        let no_owner = ObjectData::builder()
+            .gas_coin()
+            .version(1)
+            .contents(vec![0; 40])
+            .previous_transaction([0; 32]);
+        assert!(matches!(
+            no_owner.build(),
+            Err(ValidationError::MissingField("owner"))
+        ));
+
+        let nft = StructTag::new([1; 32], "nft", "Nft");
+        let other = ObjectData::builder()
+            .other(nft)
+            .version(1)
+            .contents(vec![1])
+            .owner(Owner::Immutable)
+            .previous_transaction([0; 32]);
+        assert!(matches!(
+            other.clone().build(),
+            Err(ValidationError::MissingField("has_public_transfer"))
+        ));
+        assert!(other.has_public_transfer(true).build().is_ok());
+    }
+
+    #[test]
+    fn validates_layout() {
+        assert!(matches!(
+            coin().contents(vec![0; 39]).build(),
+            Err(ValidationError::ContentsLayout { .. })
+        ));
+    }
+}
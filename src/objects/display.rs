@@ -0,0 +1,229 @@
+//! Human-readable summaries of [`ObjectData`] for confirmation prompts and
+//! debugging.
+
+use core::fmt;
+
+use super::{
+    timelocked_iota_type, timelocked_staked_iota_type, MoveObjectType, ObjectData, Owner,
+    StructTag, COIN_CONTENTS_LEN, STAKED_IOTA_CONTENTS_LEN,
+};
+use crate::coins::{iota_coin_type, CoinRegistry};
+use crate::prelude::*;
+use crate::types::AddressFormat;
+
+/// Byte range of the balance in the contents of each known object kind.
+fn balance_range(type_: &MoveObjectType) -> Option<core::ops::Range<usize>> {
+    match type_ {
+        // UID, Balance
+        MoveObjectType::GasCoin | MoveObjectType::Coin(_) => Some(32..COIN_CONTENTS_LEN),
+        // UID, pool id, activation epoch, principal
+        MoveObjectType::StakedIota => Some(72..STAKED_IOTA_CONTENTS_LEN),
+        // UID, Balance, expiration, label
+        MoveObjectType::Other(tag) if *tag == timelocked_iota_type() => Some(32..40),
+        // UID, StakedIota, expiration, label
+        MoveObjectType::Other(tag) if *tag == timelocked_staked_iota_type() => Some(104..112),
+        MoveObjectType::Other(_) => None,
+    }
+}
+
+impl MoveObjectType {
+    /// Coin type whose balance the object holds; `None` for objects
+    /// without a balance.
+    #[must_use]
+    pub fn balance_coin_type(&self) -> Option<StructTag> {
+        match self {
+            Self::Coin(tag) => Some(tag.clone()),
+            _ if balance_range(self).is_some() => Some(iota_coin_type()),
+            _ => None,
+        }
+    }
+}
+
+impl ObjectData {
+    /// Balance read from the contents of coins, staked and timelocked
+    /// IOTA. `None` for other objects or contents too short to hold one.
+    #[must_use]
+    pub fn balance(&self) -> Option<u64> {
+        let bytes = self.data.contents.get(balance_range(&self.data.type_)?)?;
+        Some(u64::from_le_bytes(bytes.try_into().ok()?))
+    }
+
+    /// One-line description: kind, balance (formatted with `registry`
+    /// when the coin is known, raw otherwise), owner and version. Owner
+    /// addresses are shortened with [`AddressFormat::SHORT`].
+    #[must_use]
+    pub fn summary(&self, registry: &CoinRegistry) -> String {
+        self.summary_with(registry, AddressFormat::SHORT)
+    }
+
+    /// [`summary`](Self::summary) with owner addresses rendered by `format`.
+    #[must_use]
+    pub fn summary_with(&self, registry: &CoinRegistry, format: AddressFormat) -> String {
+        let mut out = match &self.data.type_ {
+            MoveObjectType::GasCoin => "IOTA coin".to_string(),
+            MoveObjectType::StakedIota => "StakedIota".to_string(),
+            MoveObjectType::Coin(tag) => format!("Coin<{tag}>"),
+            MoveObjectType::Other(tag) => tag.to_string(),
+        };
+        if let (Some(raw), Some(coin)) = (self.balance(), self.data.type_.balance_coin_type()) {
+            match registry.format_amount(&coin, raw) {
+                Some(amount) => out += &format!(", balance {amount}"),
+                None => out += &format!(", balance {raw} (raw)"),
+            }
+        }
+        out += &format!(
+            ", owner {}, version {}",
+            self.owner.label(format),
+            self.data.version
+        );
+        out
+    }
+}
+
+/// [`ObjectData::summary`] with the bundled [`CoinRegistry`].
+impl fmt::Display for ObjectData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.summary(&CoinRegistry::default()))
+    }
+}
+
+impl Owner {
+    fn label(&self, format: AddressFormat) -> String {
+        match self {
+            Self::AddressOwner(addr) => format.apply(addr),
+            Self::ObjectOwner(id) => format!("object {}", format.apply(id)),
+            Self::Shared {
+                initial_shared_version,
+            } => format!("shared (since version {initial_shared_version})"),
+            Self::Immutable => "immutable".to_string(),
+            Self::Unknown { tag, .. } => format!("unknown owner kind {tag}"),
+        }
+    }
+}
+
+/// Addresses in full; see [`ObjectData::summary`] for the short form.
+impl fmt::Display for Owner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.label(AddressFormat::FULL))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::TransactionDigest;
+
+    fn coin_contents(balance: u64) -> Vec<u8> {
+        let mut c = vec![0xEE; 32];
+        c.extend_from_slice(&balance.to_le_bytes());
+        c
+    }
+
+    #[test]
+    fn gas_coin_display() {
+        let obj = ObjectData::gas_coin(
+            42,
+            coin_contents(1_500_000_000),
+            Owner::AddressOwner([0xAA; 32]),
+            TransactionDigest::ZERO,
+            0,
+        );
+        assert_eq!(obj.balance(), Some(1_500_000_000));
+        assert_eq!(
+            obj.to_string(),
+            "IOTA coin, balance 1.5 IOTA, owner 0xaaaa\u{2026}aaaa, version 42"
+        );
+        assert_eq!(
+            obj.summary_with(&CoinRegistry::default(), AddressFormat::FULL),
+            format!(
+                "IOTA coin, balance 1.5 IOTA, owner 0x{}, version 42",
+                "aa".repeat(32)
+            )
+        );
+        assert_eq!(obj.owner.to_string(), format!("0x{}", "aa".repeat(32)));
+    }
+
+    #[test]
+    fn custom_coin_uses_registry() {
+        let usdc: StructTag = "0xabc::usdc::USDC".parse().unwrap();
+        let obj = ObjectData::coin(
+            usdc.clone(),
+            3,
+            coin_contents(2_500_000),
+            Owner::Immutable,
+            TransactionDigest::ZERO,
+            0,
+        );
+        assert!(obj
+            .to_string()
+            .ends_with("::usdc::USDC>, balance 2500000 (raw), owner immutable, version 3"));
+
+        let mut registry = CoinRegistry::default();
+        registry
+            .register_str("0xabc::usdc::USDC", "USDC", 6)
+            .unwrap();
+        assert!(obj.summary(&registry).contains("balance 2.5 USDC"));
+        assert_eq!(obj.data.type_.balance_coin_type(), Some(usdc));
+    }
+
+    #[test]
+    fn stake_and_timelock_balances() {
+        let mut staked = vec![0; 72];
+        staked.extend_from_slice(&7u64.to_le_bytes());
+        let obj = ObjectData::staked_iota(
+            1,
+            staked.clone(),
+            Owner::Immutable,
+            TransactionDigest::ZERO,
+            0,
+        );
+        assert_eq!(obj.balance(), Some(7));
+
+        let mut locked = vec![0; 32];
+        locked.extend_from_slice(&staked);
+        locked.extend_from_slice(&[0; 9]);
+        let obj = ObjectData::timelocked_staked_iota(
+            1,
+            locked,
+            Owner::Immutable,
+            TransactionDigest::ZERO,
+            0,
+        );
+        assert_eq!(obj.balance(), Some(7));
+
+        let obj = ObjectData::timelocked_iota(
+            1,
+            coin_contents(9),
+            Owner::Immutable,
+            TransactionDigest::ZERO,
+            0,
+        );
+        assert_eq!(obj.balance(), Some(9));
+    }
+
+    #[test]
+    fn objects_without_balance() {
+        let nft = StructTag::new([1; 32], "nft", "Nft");
+        let obj = ObjectData::other(
+            nft,
+            true,
+            2,
+            vec![0; 64],
+            Owner::Shared {
+                initial_shared_version: 5,
+            },
+            TransactionDigest::ZERO,
+            0,
+        );
+        assert_eq!(obj.balance(), None);
+        assert!(obj
+            .to_string()
+            .ends_with("::nft::Nft, owner shared (since version 5), version 2"));
+
+        // Truncated contents: kind and owner are still shown.
+        let obj =
+            ObjectData::gas_coin(1, vec![0; 10], Owner::Immutable, TransactionDigest::ZERO, 0);
+        assert_eq!(obj.balance(), None);
+        assert_eq!(obj.to_string(), "IOTA coin, owner immutable, version 1");
+    }
+}
@@ -0,0 +1,1637 @@
+//! Object data encoding for clear signing.
+//!
+//! For non-standard token transfers, the host provides object data so the
+//! device can show coin details instead of falling back to blind signing.
+
+use core::fmt;
+use core::str::FromStr;
+
+use crate::bcs_util;
+use crate::error::{LedgerError, ValidationError};
+use crate::prelude::*;
+use crate::tx::ObjectRef;
+use crate::types::{self, ObjectDigest, TransactionDigest};
+
+#[cfg(feature = "bcs")]
+mod bcs_wire;
+mod builder;
+mod display;
+#[cfg(feature = "rpc")]
+pub(crate) mod rpc;
+#[cfg(feature = "serde")]
+mod serde_impl;
+
+pub use builder::{NoKind, ObjectDataBuilder};
+
+/// Largest Move object the protocol accepts (`max_move_object_size`).
+pub const MAX_OBJECT_CONTENTS: usize = 250 * 1024;
+
+/// Longest Move identifier (`max_move_identifier_len`).
+pub const MAX_IDENTIFIER_LEN: usize = 128;
+
+/// Deepest type-parameter nesting accepted by [`ObjectData::validate`].
+pub const MAX_TYPE_DEPTH: usize = 16;
+
+/// `Coin<T>`: `UID` (32) + `Balance<T>` (u64).
+const COIN_CONTENTS_LEN: usize = 40;
+
+/// `StakedIota`: `UID` (32) + pool id (32) + activation epoch (u64) + `Balance<IOTA>` (u64).
+const STAKED_IOTA_CONTENTS_LEN: usize = 80;
+
+/// Provides coin details so the device can clear-sign non-standard tokens.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ObjectData {
+    pub data: MoveObject,
+    pub owner: Owner,
+    pub previous_transaction: TransactionDigest,
+    pub storage_rebate: u64,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MoveObject {
+    pub type_: MoveObjectType,
+    pub has_public_transfer: bool,
+    pub version: u64,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex::vec"))]
+    pub contents: Vec<u8>,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MoveObjectType {
+    /// Any other object type, e.g. `TimelockedStakedIota` or `TimeLock<Balance<IOTA>>`.
+    Other(StructTag),
+    GasCoin,
+    StakedIota,
+    /// `Coin<T>`; encoded as the bare struct tag of `T`.
+    Coin(StructTag),
+}
+
+fn system_address(last: u8) -> [u8; 32] {
+    let mut a = [0u8; 32];
+    a[31] = last;
+    a
+}
+
+fn framework_struct(module: &str, name: &str) -> StructTag {
+    StructTag::new(system_address(0x2), module, name)
+}
+
+/// `0x3::timelocked_staking::TimelockedStakedIota`.
+#[must_use]
+pub fn timelocked_staked_iota_type() -> StructTag {
+    StructTag::new(
+        system_address(0x3),
+        "timelocked_staking",
+        "TimelockedStakedIota",
+    )
+}
+
+/// `0x2::timelock::TimeLock<0x2::balance::Balance<0x2::iota::IOTA>>`.
+#[must_use]
+pub fn timelocked_iota_type() -> StructTag {
+    let balance = framework_struct("balance", "Balance")
+        .with_type_params(vec![framework_struct("iota", "IOTA").into()]);
+    framework_struct("timelock", "TimeLock").with_type_params(vec![balance.into()])
+}
+
+impl MoveObjectType {
+    /// Classify a full object type (`0x2::coin::Coin<0x2::iota::IOTA>` becomes
+    /// [`GasCoin`](Self::GasCoin)). `None` for types the app can't render.
+    #[must_use]
+    pub fn from_struct_tag(tag: StructTag) -> Option<Self> {
+        if tag.address == system_address(0x2) && tag.module == "coin" && tag.name == "Coin" {
+            return match <[TypeTag; 1]>::try_from(tag.type_params) {
+                Ok([TypeTag::Struct(inner)]) if *inner == framework_struct("iota", "IOTA") => {
+                    Some(Self::GasCoin)
+                }
+                Ok([TypeTag::Struct(inner)]) => Some(Self::Coin(*inner)),
+                _ => None,
+            };
+        }
+        if tag == StructTag::new(system_address(0x3), "staking_pool", "StakedIota") {
+            return Some(Self::StakedIota);
+        }
+        if tag == timelocked_staked_iota_type() || tag == timelocked_iota_type() {
+            return Some(Self::Other(tag));
+        }
+        None
+    }
+
+    /// `has_public_transfer` for kinds where it is fixed by the type's
+    /// abilities. Coins have `store`; staked and timelocked objects don't.
+    #[must_use]
+    pub fn expected_public_transfer(&self) -> Option<bool> {
+        match self {
+            Self::GasCoin | Self::Coin(_) => Some(true),
+            Self::StakedIota => Some(false),
+            Self::Other(tag) if *tag == timelocked_staked_iota_type() => Some(false),
+            Self::Other(tag) if *tag == timelocked_iota_type() => Some(false),
+            Self::Other(_) => None,
+        }
+    }
+}
+
+/// A Move type, in BCS variant order.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TypeTag {
+    Bool,
+    U8,
+    U64,
+    U128,
+    Address,
+    Signer,
+    Vector(Box<TypeTag>),
+    Struct(Box<StructTag>),
+    U16,
+    U32,
+    U256,
+}
+
+impl TypeTag {
+    #[must_use]
+    pub fn vector(elem: TypeTag) -> Self {
+        Self::Vector(Box::new(elem))
+    }
+}
+
+impl From<StructTag> for TypeTag {
+    fn from(tag: StructTag) -> Self {
+        Self::Struct(Box::new(tag))
+    }
+}
+
+/// A Move struct type, e.g. `0x2::wrapper::Wrapped<0xabc::usdc::USDC>`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct StructTag {
+    pub address: [u8; 32],
+    pub module: String,
+    pub name: String,
+    /// Generic arguments, encoded recursively.
+    pub type_params: Vec<TypeTag>,
+}
+
+impl StructTag {
+    /// Non-generic struct type.
+    pub fn new(address: [u8; 32], module: impl Into<String>, name: impl Into<String>) -> Self {
+        Self {
+            address,
+            module: module.into(),
+            name: name.into(),
+            type_params: Vec::new(),
+        }
+    }
+
+    #[must_use]
+    pub fn with_type_params(mut self, type_params: Vec<TypeTag>) -> Self {
+        self.type_params = type_params;
+        self
+    }
+}
+
+/// Canonical form: full-length `0x`-prefixed addresses and `, `-separated
+/// generics (`0x00..02::coin::Coin<0x00..02::iota::IOTA>`).
+impl fmt::Display for TypeTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Bool => f.write_str("bool"),
+            Self::U8 => f.write_str("u8"),
+            Self::U16 => f.write_str("u16"),
+            Self::U32 => f.write_str("u32"),
+            Self::U64 => f.write_str("u64"),
+            Self::U128 => f.write_str("u128"),
+            Self::U256 => f.write_str("u256"),
+            Self::Address => f.write_str("address"),
+            Self::Signer => f.write_str("signer"),
+            Self::Vector(elem) => write!(f, "vector<{elem}>"),
+            Self::Struct(s) => s.fmt(f),
+        }
+    }
+}
+
+impl fmt::Display for StructTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "0x{}::{}::{}",
+            hex::encode(self.address),
+            self.module,
+            self.name
+        )?;
+        if let Some((first, rest)) = self.type_params.split_first() {
+            write!(f, "<{first}")?;
+            for param in rest {
+                write!(f, ", {param}")?;
+            }
+            f.write_str(">")?;
+        }
+        Ok(())
+    }
+}
+
+/// Parses `u64`, `vector<u8>`, `0x2::iota::IOTA`, `0x2::coin::Coin<0x2::iota::IOTA>`.
+/// Addresses may be short (`0x2`) and are left-padded to 32 bytes.
+impl FromStr for TypeTag {
+    type Err = LedgerError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = TypeTagParser { input: s, rest: s };
+        let tag = parser.type_tag()?;
+        parser.skip_ws();
+        if !parser.rest.is_empty() {
+            return Err(parser.error("trailing input"));
+        }
+        Ok(tag)
+    }
+}
+
+impl FromStr for StructTag {
+    type Err = LedgerError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.parse()? {
+            TypeTag::Struct(tag) => Ok(*tag),
+            other => Err(LedgerError::InvalidTypeTag(format!(
+                "{s:?}: expected a struct type, got {other}"
+            ))),
+        }
+    }
+}
+
+struct TypeTagParser<'a> {
+    input: &'a str,
+    rest: &'a str,
+}
+
+impl<'a> TypeTagParser<'a> {
+    fn error(&self, msg: &str) -> LedgerError {
+        let offset = self.input.len() - self.rest.len();
+        LedgerError::InvalidTypeTag(format!("{:?}: {msg} at offset {offset}", self.input))
+    }
+
+    fn skip_ws(&mut self) {
+        self.rest = self.rest.trim_start();
+    }
+
+    fn eat(&mut self, token: &str) -> bool {
+        self.skip_ws();
+        match self.rest.strip_prefix(token) {
+            Some(rest) => {
+                self.rest = rest;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn expect(&mut self, token: &str) -> Result<(), LedgerError> {
+        if self.eat(token) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected `{token}`")))
+        }
+    }
+
+    /// `[A-Za-z0-9_]+`; identifiers and hex addresses both fit.
+    fn word(&mut self) -> Result<&'a str, LedgerError> {
+        self.skip_ws();
+        let end = self
+            .rest
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .unwrap_or(self.rest.len());
+        if end == 0 {
+            return Err(self.error("expected identifier"));
+        }
+        let (word, rest) = self.rest.split_at(end);
+        self.rest = rest;
+        Ok(word)
+    }
+
+    fn identifier(&mut self) -> Result<String, LedgerError> {
+        let word = self.word()?;
+        if word.starts_with(|c: char| c.is_ascii_digit()) {
+            return Err(self.error(&format!("invalid identifier `{word}`")));
+        }
+        Ok(word.to_string())
+    }
+
+    fn type_tag(&mut self) -> Result<TypeTag, LedgerError> {
+        self.skip_ws();
+        if self.rest.starts_with("0x") || self.rest.starts_with("0X") {
+            return Ok(self.struct_tag()?.into());
+        }
+        let tag = match self.word()? {
+            "bool" => TypeTag::Bool,
+            "u8" => TypeTag::U8,
+            "u16" => TypeTag::U16,
+            "u32" => TypeTag::U32,
+            "u64" => TypeTag::U64,
+            "u128" => TypeTag::U128,
+            "u256" => TypeTag::U256,
+            "address" => TypeTag::Address,
+            "signer" => TypeTag::Signer,
+            "vector" => {
+                self.expect("<")?;
+                let elem = self.type_tag()?;
+                self.expect(">")?;
+                TypeTag::vector(elem)
+            }
+            other => return Err(self.error(&format!("unknown type `{other}`"))),
+        };
+        Ok(tag)
+    }
+
+    fn struct_tag(&mut self) -> Result<StructTag, LedgerError> {
+        let word = self.word()?;
+        let digits = &word[2..];
+        if digits.is_empty() || digits.len() > 64 {
+            return Err(self.error(&format!("invalid address `{word}`")));
+        }
+        let padded = format!("{digits:0>64}");
+        let mut address = [0u8; 32];
+        hex::decode_to_slice(&padded, &mut address)
+            .map_err(|_| self.error(&format!("invalid address `{word}`")))?;
+
+        self.expect("::")?;
+        let module = self.identifier()?;
+        self.expect("::")?;
+        let name = self.identifier()?;
+
+        let mut type_params = Vec::new();
+        if self.eat("<") {
+            loop {
+                type_params.push(self.type_tag()?);
+                if self.eat(">") {
+                    break;
+                }
+                self.expect(",")?;
+            }
+        }
+
+        Ok(StructTag {
+            address,
+            module,
+            name,
+            type_params,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum Owner {
+    AddressOwner(#[cfg_attr(feature = "serde", serde(with = "crate::serde_hex::array"))] [u8; 32]),
+    ObjectOwner(#[cfg_attr(feature = "serde", serde(with = "crate::serde_hex::array"))] [u8; 32]),
+    Shared {
+        initial_shared_version: u64,
+    },
+    Immutable,
+    /// Ownership kind this crate doesn't know yet: the BCS variant index
+    /// and the variant's encoded fields, passed through verbatim.
+    Unknown {
+        tag: u32,
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex::vec"))]
+        payload: Vec<u8>,
+    },
+}
+
+/// Highest [`Owner`] variant index with a named variant.
+const LAST_KNOWN_OWNER_TAG: u32 = 3;
+
+impl Owner {
+    fn encode_into(&self, buf: &mut Vec<u8>) {
+        match self {
+            Owner::AddressOwner(addr) => {
+                buf.push(0);
+                buf.extend_from_slice(addr);
+            }
+            Owner::ObjectOwner(addr) => {
+                buf.push(1);
+                buf.extend_from_slice(addr);
+            }
+            Owner::Shared {
+                initial_shared_version,
+            } => {
+                buf.push(2);
+                bcs_util::write_u64(buf, *initial_shared_version);
+            }
+            Owner::Immutable => {
+                buf.push(3);
+            }
+            Owner::Unknown { tag, payload } => {
+                bcs_util::write_uleb128(buf, u64::from(*tag));
+                buf.extend_from_slice(payload);
+            }
+        }
+    }
+
+    /// BCS encoding.
+    #[must_use]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        self.encode_into(&mut buf);
+        buf
+    }
+
+    /// Decode a BCS-encoded owner. Unrecognised variants become
+    /// [`Owner::Unknown`] with the rest of `bytes` as payload, since their
+    /// length can't be known.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, LedgerError> {
+        let mut r = bcs_util::Reader::new(bytes);
+        let tag = u32::try_from(r.read_uleb128()?)
+            .map_err(|_| LedgerError::InvalidEncoding("owner variant index exceeds u32".into()))?;
+        let owner = match tag {
+            0 => Owner::AddressOwner(r.read_array()?),
+            1 => Owner::ObjectOwner(r.read_array()?),
+            2 => Owner::Shared {
+                initial_shared_version: r.read_u64()?,
+            },
+            3 => Owner::Immutable,
+            _ => Owner::Unknown {
+                tag,
+                payload: r.read_raw(r.remaining())?.to_vec(),
+            },
+        };
+        r.finish()?;
+        Ok(owner)
+    }
+}
+
+/// Positional constructors; [`ObjectData::builder`] names each field and
+/// validates the result.
+impl ObjectData {
+    pub fn gas_coin(
+        version: u64,
+        contents: Vec<u8>,
+        owner: Owner,
+        previous_transaction: TransactionDigest,
+        storage_rebate: u64,
+    ) -> Self {
+        Self {
+            data: MoveObject {
+                type_: MoveObjectType::GasCoin,
+                has_public_transfer: true,
+                version,
+                contents,
+            },
+            owner,
+            previous_transaction,
+            storage_rebate,
+        }
+    }
+
+    pub fn coin(
+        type_tag: StructTag,
+        version: u64,
+        contents: Vec<u8>,
+        owner: Owner,
+        previous_transaction: TransactionDigest,
+        storage_rebate: u64,
+    ) -> Self {
+        Self {
+            data: MoveObject {
+                type_: MoveObjectType::Coin(type_tag),
+                has_public_transfer: true,
+                version,
+                contents,
+            },
+            owner,
+            previous_transaction,
+            storage_rebate,
+        }
+    }
+
+    pub fn staked_iota(
+        version: u64,
+        contents: Vec<u8>,
+        owner: Owner,
+        previous_transaction: TransactionDigest,
+        storage_rebate: u64,
+    ) -> Self {
+        Self {
+            data: MoveObject {
+                type_: MoveObjectType::StakedIota,
+                has_public_transfer: false,
+                version,
+                contents,
+            },
+            owner,
+            previous_transaction,
+            storage_rebate,
+        }
+    }
+
+    /// `TimelockedStakedIota`: a stake made from vested (timelocked) funds.
+    pub fn timelocked_staked_iota(
+        version: u64,
+        contents: Vec<u8>,
+        owner: Owner,
+        previous_transaction: TransactionDigest,
+        storage_rebate: u64,
+    ) -> Self {
+        Self::other(
+            timelocked_staked_iota_type(),
+            false,
+            version,
+            contents,
+            owner,
+            previous_transaction,
+            storage_rebate,
+        )
+    }
+
+    /// `TimeLock<Balance<IOTA>>`: vested IOTA that unlocks at a timestamp.
+    pub fn timelocked_iota(
+        version: u64,
+        contents: Vec<u8>,
+        owner: Owner,
+        previous_transaction: TransactionDigest,
+        storage_rebate: u64,
+    ) -> Self {
+        Self::other(
+            timelocked_iota_type(),
+            false,
+            version,
+            contents,
+            owner,
+            previous_transaction,
+            storage_rebate,
+        )
+    }
+
+    /// Any object type not covered by the other constructors.
+    pub fn other(
+        type_: StructTag,
+        has_public_transfer: bool,
+        version: u64,
+        contents: Vec<u8>,
+        owner: Owner,
+        previous_transaction: TransactionDigest,
+        storage_rebate: u64,
+    ) -> Self {
+        Self {
+            data: MoveObject {
+                type_: MoveObjectType::Other(type_),
+                has_public_transfer,
+                version,
+                contents,
+            },
+            owner,
+            previous_transaction,
+            storage_rebate,
+        }
+    }
+
+    /// Check the fields the device relies on before sending them.
+    ///
+    /// Catches malformed identifiers, oversized or mis-shaped contents and
+    /// wrong `has_public_transfer` flags, which the device would otherwise
+    /// reject without saying why. [`LedgerIota::sign_tx`](crate::LedgerIota::sign_tx)
+    /// runs this on every object.
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        let (type_name, expected_len) = match &self.data.type_ {
+            MoveObjectType::GasCoin => ("GasCoin", Some(COIN_CONTENTS_LEN)),
+            MoveObjectType::StakedIota => ("StakedIota", Some(STAKED_IOTA_CONTENTS_LEN)),
+            MoveObjectType::Coin(tag) => {
+                validate_struct_tag(tag, 0)?;
+                ("Coin", Some(COIN_CONTENTS_LEN))
+            }
+            // Timelocked objects end in an optional label, so no fixed size.
+            MoveObjectType::Other(tag) => {
+                validate_struct_tag(tag, 0)?;
+                ("Other", None)
+            }
+        };
+
+        let len = self.data.contents.len();
+        if len > MAX_OBJECT_CONTENTS {
+            return Err(ValidationError::ContentsTooLarge {
+                len,
+                max: MAX_OBJECT_CONTENTS,
+            });
+        }
+        if let Some(expected) = expected_len.filter(|&e| e != len) {
+            return Err(ValidationError::ContentsLayout {
+                type_: type_name,
+                expected,
+                actual: len,
+            });
+        }
+        if let Some(expected) = self
+            .data
+            .type_
+            .expected_public_transfer()
+            .filter(|&e| e != self.data.has_public_transfer)
+        {
+            return Err(ValidationError::PublicTransferMismatch {
+                type_: type_name,
+                expected,
+            });
+        }
+        if let Owner::Unknown { tag, .. } = self.owner {
+            if tag <= LAST_KNOWN_OWNER_TAG {
+                return Err(ValidationError::ReservedOwnerTag(tag));
+            }
+        }
+        Ok(())
+    }
+
+    /// The BCS encoding sent to the device (without the length prefix
+    /// [`encode_objects`] adds).
+    pub fn to_bytes(&self) -> Result<Vec<u8>, LedgerError> {
+        let mut buf = Vec::with_capacity(self.size_hint());
+        self.encode_into(&mut buf)?;
+        Ok(buf)
+    }
+
+    /// The object's id: the `UID` every Move object's contents start
+    /// with. `None` if the contents are shorter than an id.
+    pub fn object_id(&self) -> Option<[u8; 32]> {
+        self.data.contents.get(..32)?.try_into().ok()
+    }
+
+    /// Reference to the object at its version, for use as a gas coin or an
+    /// owned input.
+    pub fn object_ref(&self) -> Result<ObjectRef, LedgerError> {
+        let object_id = self.object_id().ok_or_else(|| {
+            LedgerError::InvalidEncoding(format!(
+                "object contents are {} bytes, too short for an object id",
+                self.data.contents.len()
+            ))
+        })?;
+        Ok(ObjectRef {
+            object_id,
+            version: self.data.version,
+            digest: self.digest()?,
+        })
+    }
+
+    /// The object's on-chain digest, `Blake2b-256("Object::" || to_bytes())`.
+    pub fn digest(&self) -> Result<ObjectDigest, LedgerError> {
+        Ok(ObjectDigest(types::salted_digest(
+            types::OBJECT_SALT,
+            &self.to_bytes()?,
+        )))
+    }
+
+    /// Inverse of [`to_bytes`](Self::to_bytes).
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, LedgerError> {
+        // previous_transaction and storage_rebate
+        const TRAILER_LEN: usize = 33 + 8;
+
+        let mut r = bcs_util::Reader::new(bytes);
+        match r.read_uleb128()? {
+            0 => {}
+            tag => {
+                return Err(LedgerError::InvalidEncoding(format!(
+                    "object data variant {tag} is not a Move object"
+                )))
+            }
+        }
+        let type_ = match r.read_uleb128()? {
+            0 => MoveObjectType::Other(decode_struct_tag_at(&mut r, 0)?),
+            1 => MoveObjectType::GasCoin,
+            2 => MoveObjectType::StakedIota,
+            3 => MoveObjectType::Coin(decode_struct_tag_at(&mut r, 0)?),
+            tag => {
+                return Err(LedgerError::InvalidEncoding(format!(
+                    "unknown object type variant {tag}"
+                )))
+            }
+        };
+        let has_public_transfer = r.read_bool()?;
+        let version = r.read_u64()?;
+        let contents = r.read_bytes()?.to_vec();
+
+        // An unknown owner's payload has no length of its own; the owner
+        // is whatever precedes the fixed-size trailer.
+        let owner_len = r.remaining().checked_sub(TRAILER_LEN).ok_or_else(|| {
+            LedgerError::InvalidEncoding("object data truncated before owner".into())
+        })?;
+        let owner = Owner::from_bytes(r.read_raw(owner_len)?)?;
+        if r.read_u8()? != 32 {
+            return Err(LedgerError::InvalidEncoding(
+                "previous_transaction must be a 32-byte digest".into(),
+            ));
+        }
+        let previous_transaction = TransactionDigest(r.read_array()?);
+        let storage_rebate = r.read_u64()?;
+        r.finish()?;
+
+        Ok(Self {
+            data: MoveObject {
+                type_,
+                has_public_transfer,
+                version,
+                contents,
+            },
+            owner,
+            previous_transaction,
+            storage_rebate,
+        })
+    }
+
+    /// With the `bcs` feature the encoding comes from the `bcs` crate, and
+    /// its errors (sequences or nesting beyond its limits) are returned as
+    /// [`LedgerError::InvalidEncoding`].
+    fn encode_into(&self, buf: &mut Vec<u8>) -> Result<(), LedgerError> {
+        #[cfg(feature = "bcs")]
+        {
+            bcs_wire::encode_into(self, buf)
+                .map_err(|e| LedgerError::InvalidEncoding(format!("object data: {e}")))
+        }
+        #[cfg(not(feature = "bcs"))]
+        {
+            self.encode_manual(buf);
+            Ok(())
+        }
+    }
+
+    #[cfg(any(not(feature = "bcs"), test))]
+    fn encode_manual(&self, buf: &mut Vec<u8>) {
+        buf.push(0x00); // ObjectData::Move
+        match &self.data.type_ {
+            MoveObjectType::Other(tag) => {
+                buf.push(0);
+                encode_struct_tag(buf, tag);
+            }
+            MoveObjectType::GasCoin => buf.push(1),
+            MoveObjectType::StakedIota => buf.push(2),
+            MoveObjectType::Coin(tag) => {
+                buf.push(3);
+                encode_struct_tag(buf, tag);
+            }
+        }
+
+        bcs_util::write_bool(buf, self.data.has_public_transfer);
+        bcs_util::write_u64(buf, self.data.version);
+        bcs_util::write_bytes(buf, &self.data.contents);
+
+        self.owner.encode_into(buf);
+
+        buf.extend_from_slice(&self.previous_transaction.to_bcs_bytes());
+        bcs_util::write_u64(buf, self.storage_rebate);
+    }
+
+    /// Rough encoded size, for preallocation: the contents plus room for
+    /// the fixed fields and a short type tag.
+    fn size_hint(&self) -> usize {
+        self.data.contents.len() + 192
+    }
+}
+
+pub(crate) fn encode_type_tag(buf: &mut Vec<u8>, tag: &TypeTag) {
+    match tag {
+        TypeTag::Bool => buf.push(0),
+        TypeTag::U8 => buf.push(1),
+        TypeTag::U64 => buf.push(2),
+        TypeTag::U128 => buf.push(3),
+        TypeTag::Address => buf.push(4),
+        TypeTag::Signer => buf.push(5),
+        TypeTag::Vector(elem) => {
+            buf.push(6);
+            encode_type_tag(buf, elem);
+        }
+        TypeTag::Struct(s) => {
+            buf.push(7);
+            encode_struct_tag(buf, s);
+        }
+        TypeTag::U16 => buf.push(8),
+        TypeTag::U32 => buf.push(9),
+        TypeTag::U256 => buf.push(10),
+    }
+}
+
+pub(crate) fn encode_struct_tag(buf: &mut Vec<u8>, tag: &StructTag) {
+    buf.extend_from_slice(&tag.address);
+    bcs_util::write_string(buf, &tag.module);
+    bcs_util::write_string(buf, &tag.name);
+    bcs_util::write_vec(buf, &tag.type_params, encode_type_tag);
+}
+
+pub(crate) fn decode_type_tag(r: &mut bcs_util::Reader<'_>) -> Result<TypeTag, LedgerError> {
+    decode_type_tag_at(r, 0)
+}
+
+fn decode_type_tag_at(r: &mut bcs_util::Reader<'_>, depth: usize) -> Result<TypeTag, LedgerError> {
+    if depth > MAX_TYPE_DEPTH {
+        return Err(LedgerError::InvalidEncoding(format!(
+            "type tag nested deeper than {MAX_TYPE_DEPTH} levels"
+        )));
+    }
+    let offset = r.position();
+    Ok(match r.read_u8()? {
+        0 => TypeTag::Bool,
+        1 => TypeTag::U8,
+        2 => TypeTag::U64,
+        3 => TypeTag::U128,
+        4 => TypeTag::Address,
+        5 => TypeTag::Signer,
+        6 => TypeTag::vector(decode_type_tag_at(r, depth + 1)?),
+        7 => decode_struct_tag_at(r, depth)?.into(),
+        8 => TypeTag::U16,
+        9 => TypeTag::U32,
+        10 => TypeTag::U256,
+        v => {
+            return Err(LedgerError::InvalidEncoding(format!(
+                "invalid TypeTag variant {v} at offset {offset}"
+            )))
+        }
+    })
+}
+
+fn decode_struct_tag_at(
+    r: &mut bcs_util::Reader<'_>,
+    depth: usize,
+) -> Result<StructTag, LedgerError> {
+    Ok(StructTag {
+        address: r.read_array()?,
+        module: r.read_string()?.to_owned(),
+        name: r.read_string()?.to_owned(),
+        type_params: r.read_vec(|r| decode_type_tag_at(r, depth + 1))?,
+    })
+}
+
+/// `[A-Za-z][A-Za-z0-9_]*` or `_[A-Za-z0-9_]+`, at most [`MAX_IDENTIFIER_LEN`] bytes.
+fn is_valid_identifier(s: &str) -> bool {
+    let mut chars = s.chars();
+    let valid_start = match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() => true,
+        Some('_') => s.len() > 1,
+        _ => false,
+    };
+    valid_start
+        && s.len() <= MAX_IDENTIFIER_LEN
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn validate_struct_tag(tag: &StructTag, depth: usize) -> Result<(), ValidationError> {
+    if depth > MAX_TYPE_DEPTH {
+        return Err(ValidationError::TypeTooDeep(MAX_TYPE_DEPTH));
+    }
+    for ident in [&tag.module, &tag.name] {
+        if !is_valid_identifier(ident) {
+            return Err(ValidationError::InvalidIdentifier(ident.clone()));
+        }
+    }
+    tag.type_params
+        .iter()
+        .try_for_each(|param| validate_type_tag(param, depth + 1))
+}
+
+fn validate_type_tag(tag: &TypeTag, depth: usize) -> Result<(), ValidationError> {
+    if depth > MAX_TYPE_DEPTH {
+        return Err(ValidationError::TypeTooDeep(MAX_TYPE_DEPTH));
+    }
+    match tag {
+        TypeTag::Vector(elem) => validate_type_tag(elem, depth + 1),
+        TypeTag::Struct(s) => validate_struct_tag(s, depth),
+        _ => Ok(()),
+    }
+}
+
+/// Wire format for SignTx parameter 3:
+/// `[count: u32 LE][obj_len: u32 LE][obj_data]...`
+pub fn encode_objects(objects: &[ObjectData]) -> Result<Vec<u8>, LedgerError> {
+    let capacity = 4 + objects.iter().map(|o| 4 + o.size_hint()).sum::<usize>();
+    let mut buf = Vec::with_capacity(capacity);
+    encode_objects_into(&mut buf, objects)?;
+    Ok(buf)
+}
+
+/// [`encode_objects`] appending to `buf`. Each object is encoded in place
+/// and its length prefix patched afterwards.
+pub fn encode_objects_into(buf: &mut Vec<u8>, objects: &[ObjectData]) -> Result<(), LedgerError> {
+    bcs_util::write_u32(buf, objects.len() as u32);
+
+    for obj in objects {
+        let len_at = buf.len();
+        buf.extend_from_slice(&[0; 4]);
+        obj.encode_into(buf)?;
+        let len = (buf.len() - len_at - 4) as u32;
+        buf[len_at..len_at + 4].copy_from_slice(&len.to_le_bytes());
+    }
+    Ok(())
+}
+
+/// Inverse of [`encode_objects`].
+pub fn decode_objects(bytes: &[u8]) -> Result<Vec<ObjectData>, LedgerError> {
+    let mut r = bcs_util::Reader::new(bytes);
+    let count = r.read_u32()?;
+    let mut objects = Vec::new();
+    for _ in 0..count {
+        let len = r.read_u32()? as usize;
+        objects.push(ObjectData::from_bytes(r.read_raw(len)?)?);
+    }
+    r.finish()?;
+    Ok(objects)
+}
+
+#[cfg(feature = "iota-sdk-types")]
+impl TryFrom<iota_sdk_types::Object> for ObjectData {
+    type Error = String;
+
+    fn try_from(obj: iota_sdk_types::Object) -> Result<Self, Self::Error> {
+        let move_struct = match obj.data {
+            iota_sdk_types::ObjectData::Struct(s) => s,
+            iota_sdk_types::ObjectData::Package(_) => {
+                return Err("packages cannot be converted to ObjectData for clear signing".into());
+            }
+        };
+
+        let (type_, has_public_transfer) = convert_struct_type(&move_struct.type_)?;
+
+        Ok(ObjectData {
+            data: MoveObject {
+                type_,
+                has_public_transfer,
+                version: move_struct.version,
+                contents: move_struct.contents,
+            },
+            owner: convert_owner(obj.owner),
+            previous_transaction: TransactionDigest(*obj.previous_transaction.inner()),
+            storage_rebate: obj.storage_rebate,
+        })
+    }
+}
+
+/// A coin object (or any other object [`ObjectData`] converts) as a
+/// [`GasCoinRef`](crate::GasCoinRef) or owned input.
+#[cfg(feature = "iota-sdk-types")]
+impl TryFrom<iota_sdk_types::Object> for ObjectRef {
+    type Error = String;
+
+    fn try_from(obj: iota_sdk_types::Object) -> Result<Self, Self::Error> {
+        ObjectData::try_from(obj)?
+            .object_ref()
+            .map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(feature = "iota-sdk-types")]
+fn convert_struct_type(tag: &iota_sdk_types::StructTag) -> Result<(MoveObjectType, bool), String> {
+    let tag = convert_struct_tag(tag)?;
+    let display = tag.to_string();
+    let type_ = MoveObjectType::from_struct_tag(tag)
+        .ok_or_else(|| format!("unsupported object type {display} for clear signing"))?;
+    let has_public_transfer = type_.expected_public_transfer().unwrap_or(false);
+    Ok((type_, has_public_transfer))
+}
+
+#[cfg(feature = "iota-sdk-types")]
+fn convert_struct_tag(tag: &iota_sdk_types::StructTag) -> Result<StructTag, String> {
+    Ok(StructTag {
+        address: tag.address.into_inner(),
+        module: tag.module.as_str().to_string(),
+        name: tag.name.as_str().to_string(),
+        type_params: tag
+            .type_params
+            .iter()
+            .map(convert_type_tag)
+            .collect::<Result<_, _>>()?,
+    })
+}
+
+#[cfg(feature = "iota-sdk-types")]
+fn convert_type_tag(tag: &iota_sdk_types::TypeTag) -> Result<TypeTag, String> {
+    use iota_sdk_types::TypeTag as Sdk;
+
+    Ok(match tag {
+        Sdk::Bool => TypeTag::Bool,
+        Sdk::U8 => TypeTag::U8,
+        Sdk::U16 => TypeTag::U16,
+        Sdk::U32 => TypeTag::U32,
+        Sdk::U64 => TypeTag::U64,
+        Sdk::U128 => TypeTag::U128,
+        Sdk::U256 => TypeTag::U256,
+        Sdk::Address => TypeTag::Address,
+        Sdk::Signer => TypeTag::Signer,
+        Sdk::Vector(elem) => TypeTag::vector(convert_type_tag(elem)?),
+        Sdk::Struct(s) => convert_struct_tag(s)?.into(),
+    })
+}
+
+#[cfg(feature = "iota-sdk-types")]
+fn convert_owner(owner: iota_sdk_types::Owner) -> Owner {
+    match owner {
+        iota_sdk_types::Owner::Address(addr) => Owner::AddressOwner(addr.into_inner()),
+        iota_sdk_types::Owner::Object(id) => Owner::ObjectOwner(id.into_inner()),
+        iota_sdk_types::Owner::Shared(version) => Owner::Shared {
+            initial_shared_version: version,
+        },
+        iota_sdk_types::Owner::Immutable => Owner::Immutable,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_gas_coin_object() {
+        let obj = ObjectData::gas_coin(
+            42,
+            vec![0u8; 40],
+            Owner::AddressOwner([0xAA; 32]),
+            TransactionDigest::ZERO,
+            1000,
+        );
+        let encoded = obj.to_bytes().unwrap();
+        assert_eq!(encoded[0], 0x00);
+        assert_eq!(encoded[1], 1);
+        assert_eq!(encoded[2], 1);
+    }
+
+    #[test]
+    fn encode_coin_type_without_params() {
+        let mut buf = Vec::new();
+        encode_struct_tag(&mut buf, &StructTag::new([0x11; 32], "usdc", "USDC"));
+
+        let mut expected = vec![0x11; 32];
+        expected.extend_from_slice(&[4, b'u', b's', b'd', b'c']);
+        expected.extend_from_slice(&[4, b'U', b'S', b'D', b'C']);
+        expected.push(0); // no type_params
+        assert_eq!(buf, expected);
+    }
+
+    #[test]
+    fn encode_nested_type_params() {
+        // 0x22::wrapper::Wrapped<0x11::usdc::USDC>
+        let inner = StructTag::new([0x11; 32], "usdc", "USDC");
+        let outer = StructTag::new([0x22; 32], "wrapper", "Wrapped")
+            .with_type_params(vec![inner.clone().into()]);
+
+        let mut buf = Vec::new();
+        encode_struct_tag(&mut buf, &outer);
+
+        let mut expected = vec![0x22; 32];
+        expected.push(7);
+        expected.extend_from_slice(b"wrapper");
+        expected.push(7);
+        expected.extend_from_slice(b"Wrapped");
+        expected.push(1); // one type param
+        expected.push(7); // TypeTag::Struct
+        encode_struct_tag(&mut expected, &inner);
+        assert_eq!(buf, expected);
+    }
+
+    #[test]
+    fn type_tag_decode_round_trips() {
+        let tag: TypeTag = "0x2::coin::Coin<vector<0xa::m::S<u8, u256>>>"
+            .parse()
+            .unwrap();
+        let mut buf = Vec::new();
+        encode_type_tag(&mut buf, &tag);
+        let mut r = bcs_util::Reader::new(&buf);
+        assert_eq!(decode_type_tag(&mut r).unwrap(), tag);
+        r.finish().unwrap();
+
+        let mut r = bcs_util::Reader::new(&[11]);
+        assert!(decode_type_tag(&mut r).is_err());
+
+        let deep = vec![6u8; MAX_TYPE_DEPTH + 2];
+        assert!(decode_type_tag(&mut bcs_util::Reader::new(&deep)).is_err());
+    }
+
+    #[test]
+    fn type_tag_variant_order() {
+        let cases = [
+            (TypeTag::Bool, 0),
+            (TypeTag::U8, 1),
+            (TypeTag::U64, 2),
+            (TypeTag::U128, 3),
+            (TypeTag::Address, 4),
+            (TypeTag::Signer, 5),
+            (TypeTag::U16, 8),
+            (TypeTag::U32, 9),
+            (TypeTag::U256, 10),
+        ];
+        for (tag, variant) in cases {
+            let mut buf = Vec::new();
+            encode_type_tag(&mut buf, &tag);
+            assert_eq!(buf, [variant], "{tag:?}");
+        }
+    }
+
+    #[test]
+    fn encode_vector_and_struct_type_tags() {
+        let mut buf = Vec::new();
+        encode_type_tag(&mut buf, &TypeTag::vector(TypeTag::vector(TypeTag::U8)));
+        assert_eq!(buf, [6, 6, 1]);
+
+        let tag: TypeTag = StructTag::new([0x33; 32], "m", "S")
+            .with_type_params(vec![TypeTag::U64])
+            .into();
+        buf.clear();
+        encode_type_tag(&mut buf, &tag);
+        let mut expected = vec![7];
+        expected.extend_from_slice(&[0x33; 32]);
+        expected.extend_from_slice(&[1, b'm', 1, b'S', 1, 2]);
+        assert_eq!(buf, expected);
+    }
+
+    fn addr(last: u8) -> [u8; 32] {
+        let mut a = [0u8; 32];
+        a[31] = last;
+        a
+    }
+
+    #[test]
+    fn parse_short_struct_tag() {
+        let tag: StructTag = "0x2::iota::IOTA".parse().unwrap();
+        assert_eq!(tag, StructTag::new(addr(2), "iota", "IOTA"));
+    }
+
+    #[test]
+    fn parse_generic_and_primitive_tags() {
+        let tag: TypeTag = "0x2::coin::Coin<0x2::iota::IOTA>".parse().unwrap();
+        let expected: TypeTag = StructTag::new(addr(2), "coin", "Coin")
+            .with_type_params(vec![StructTag::new(addr(2), "iota", "IOTA").into()])
+            .into();
+        assert_eq!(tag, expected);
+
+        let tag: TypeTag = "0xab::pool::Pool< u64 ,vector<vector<u8>> >"
+            .parse()
+            .unwrap();
+        let TypeTag::Struct(s) = tag else {
+            panic!("expected struct")
+        };
+        assert_eq!(
+            s.type_params,
+            [TypeTag::U64, TypeTag::vector(TypeTag::vector(TypeTag::U8))]
+        );
+    }
+
+    #[test]
+    fn display_is_canonical_and_round_trips() {
+        let tag: TypeTag = "0x2::coin::Coin<0x2::iota::IOTA>".parse().unwrap();
+        let s = tag.to_string();
+        assert_eq!(
+            s,
+            format!("0x{0}::coin::Coin<0x{0}::iota::IOTA>", "0".repeat(63) + "2")
+        );
+        assert_eq!(s.parse::<TypeTag>().unwrap(), tag);
+
+        let tag: TypeTag = "0x1::m::S<u8, vector<address>>".parse().unwrap();
+        assert!(tag.to_string().ends_with("::m::S<u8, vector<address>>"));
+        assert_eq!(tag.to_string().parse::<TypeTag>().unwrap(), tag);
+    }
+
+    #[test]
+    fn parse_rejects_malformed() {
+        for s in [
+            "",
+            "0x2::iota",
+            "0x2::iota::IOTA<",
+            "0x2::iota::IOTA<>",
+            "0xzz::iota::IOTA",
+            "0x2::9iota::IOTA",
+            "u64 u8",
+            "vector<u8",
+            "float",
+            &format!("0x{}::m::S", "1".repeat(65)),
+        ] {
+            assert!(
+                matches!(s.parse::<TypeTag>(), Err(LedgerError::InvalidTypeTag(_))),
+                "{s:?} should be rejected"
+            );
+        }
+        assert!("u64".parse::<StructTag>().is_err());
+    }
+
+    fn coin_object(tag: StructTag) -> ObjectData {
+        ObjectData::coin(
+            tag,
+            1,
+            vec![0u8; 40],
+            Owner::Immutable,
+            TransactionDigest::ZERO,
+            0,
+        )
+    }
+
+    #[test]
+    fn validate_accepts_well_formed_objects() {
+        let prev = TransactionDigest::ZERO;
+        ObjectData::gas_coin(1, vec![0; 40], Owner::Immutable, prev, 0)
+            .validate()
+            .unwrap();
+        ObjectData::staked_iota(1, vec![0; 80], Owner::Immutable, prev, 0)
+            .validate()
+            .unwrap();
+        coin_object(StructTag::new([1; 32], "usdc", "USDC"))
+            .validate()
+            .unwrap();
+    }
+
+    #[test]
+    fn validate_rejects_bad_identifiers() {
+        for bad in ["", "_", "9lives", "has-dash", "ünï", &"a".repeat(129)] {
+            let obj = coin_object(StructTag::new([1; 32], bad, "USDC"));
+            assert_eq!(
+                obj.validate(),
+                Err(ValidationError::InvalidIdentifier(bad.to_string())),
+                "{bad:?}"
+            );
+        }
+        let nested = StructTag::new([1; 32], "w", "W")
+            .with_type_params(vec![StructTag::new([1; 32], "ok", "bad name").into()]);
+        assert!(matches!(
+            coin_object(nested).validate(),
+            Err(ValidationError::InvalidIdentifier(_))
+        ));
+        assert!(is_valid_identifier("_ok"));
+    }
+
+    #[test]
+    fn validate_rejects_deep_types() {
+        let mut tag = TypeTag::U8;
+        for _ in 0..=MAX_TYPE_DEPTH {
+            tag = TypeTag::vector(tag);
+        }
+        let obj = coin_object(StructTag::new([1; 32], "m", "S").with_type_params(vec![tag]));
+        assert_eq!(
+            obj.validate(),
+            Err(ValidationError::TypeTooDeep(MAX_TYPE_DEPTH))
+        );
+    }
+
+    #[test]
+    fn validate_rejects_bad_contents_and_flags() {
+        let mut obj =
+            ObjectData::gas_coin(1, vec![0; 39], Owner::Immutable, TransactionDigest::ZERO, 0);
+        assert!(matches!(
+            obj.validate(),
+            Err(ValidationError::ContentsLayout {
+                expected: 40,
+                actual: 39,
+                ..
+            })
+        ));
+
+        obj.data.contents = vec![0; MAX_OBJECT_CONTENTS + 1];
+        assert!(matches!(
+            obj.validate(),
+            Err(ValidationError::ContentsTooLarge { .. })
+        ));
+
+        obj.data.contents = vec![0; 40];
+        obj.data.has_public_transfer = false;
+        assert!(matches!(
+            obj.validate(),
+            Err(ValidationError::PublicTransferMismatch { expected: true, .. })
+        ));
+    }
+
+    #[test]
+    fn classify_struct_tags() {
+        let classify = |s: &str| MoveObjectType::from_struct_tag(s.parse().unwrap());
+        assert!(matches!(
+            classify("0x2::coin::Coin<0x2::iota::IOTA>"),
+            Some(MoveObjectType::GasCoin)
+        ));
+        assert!(matches!(
+            classify("0x2::coin::Coin<0xabc::usdc::USDC>"),
+            Some(MoveObjectType::Coin(_))
+        ));
+        assert!(matches!(
+            classify("0x3::staking_pool::StakedIota"),
+            Some(MoveObjectType::StakedIota)
+        ));
+        assert!(matches!(
+            classify("0x3::timelocked_staking::TimelockedStakedIota"),
+            Some(MoveObjectType::Other(_))
+        ));
+        assert!(matches!(
+            classify("0x2::timelock::TimeLock<0x2::balance::Balance<0x2::iota::IOTA>>"),
+            Some(MoveObjectType::Other(_))
+        ));
+        assert!(classify("0x2::kiosk::Kiosk").is_none());
+        assert!(classify("0x2::coin::Coin<u64>").is_none());
+    }
+
+    #[test]
+    fn encode_timelocked_staked_iota() {
+        let obj = ObjectData::timelocked_staked_iota(
+            3,
+            vec![0u8; 130],
+            Owner::AddressOwner([0xAA; 32]),
+            TransactionDigest::ZERO,
+            0,
+        );
+        obj.validate().unwrap();
+        assert!(!obj.data.has_public_transfer);
+
+        let encoded = obj.to_bytes().unwrap();
+        assert_eq!(encoded[0], 0x00); // ObjectData::Move
+        assert_eq!(encoded[1], 0); // MoveObjectType::Other
+        let mut tag = Vec::new();
+        encode_struct_tag(&mut tag, &timelocked_staked_iota_type());
+        assert_eq!(&encoded[2..2 + tag.len()], &tag[..]);
+        assert_eq!(encoded[2 + tag.len()], 0); // has_public_transfer
+    }
+
+    #[test]
+    fn validate_timelocked_public_transfer() {
+        let mut obj = ObjectData::timelocked_iota(
+            1,
+            vec![0u8; 49],
+            Owner::Immutable,
+            TransactionDigest::ZERO,
+            0,
+        );
+        obj.validate().unwrap();
+        obj.data.has_public_transfer = true;
+        assert!(matches!(
+            obj.validate(),
+            Err(ValidationError::PublicTransferMismatch {
+                expected: false,
+                ..
+            })
+        ));
+
+        // Unknown kinds accept either flag.
+        let other = StructTag::new([1; 32], "nft", "Nft");
+        ObjectData::other(
+            other,
+            true,
+            1,
+            vec![1],
+            Owner::Immutable,
+            TransactionDigest::ZERO,
+            0,
+        )
+        .validate()
+        .unwrap();
+    }
+
+    #[test]
+    fn encode_objects_format() {
+        let obj = ObjectData::gas_coin(
+            1,
+            vec![0u8; 40],
+            Owner::Immutable,
+            TransactionDigest::ZERO,
+            0,
+        );
+        let buf = encode_objects(&[obj]).unwrap();
+
+        assert_eq!(&buf[0..4], &[1, 0, 0, 0]); // count=1 LE
+        let obj_len = u32::from_le_bytes([buf[4], buf[5], buf[6], buf[7]]) as usize;
+        assert_eq!(buf.len(), 4 + 4 + obj_len);
+    }
+
+    #[test]
+    fn encode_objects_matches_per_object_encoding() {
+        let usdc: StructTag = "0xabc::usdc::USDC".parse().unwrap();
+        let objects = [
+            ObjectData::gas_coin(1, vec![1; 40], Owner::Immutable, TransactionDigest::ZERO, 5),
+            ObjectData::coin(
+                usdc,
+                2,
+                vec![2; 40],
+                Owner::AddressOwner([3; 32]),
+                TransactionDigest::ZERO,
+                6,
+            ),
+            ObjectData::staked_iota(3, vec![3; 80], Owner::Immutable, TransactionDigest::ZERO, 7),
+        ];
+        let mut expected = vec![3, 0, 0, 0];
+        for obj in &objects {
+            let encoded = obj.to_bytes().unwrap();
+            expected.extend_from_slice(&(encoded.len() as u32).to_le_bytes());
+            expected.extend_from_slice(&encoded);
+        }
+        assert_eq!(encode_objects(&objects).unwrap(), expected);
+
+        let mut buf = vec![0xFF];
+        encode_objects_into(&mut buf, &objects).unwrap();
+        assert_eq!(buf[1..], expected[..]);
+        assert_eq!(encode_objects(&[]).unwrap(), [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn object_data_round_trip() {
+        let usdc: StructTag = "0x2::wrapper::Wrapped<0xabc::usdc::USDC>".parse().unwrap();
+        let objects = [
+            ObjectData::gas_coin(
+                1,
+                vec![1; 40],
+                Owner::AddressOwner([2; 32]),
+                TransactionDigest::ZERO,
+                5,
+            ),
+            ObjectData::coin(
+                usdc,
+                2,
+                vec![2; 40],
+                Owner::Immutable,
+                TransactionDigest([7; 32]),
+                6,
+            ),
+            ObjectData::timelocked_staked_iota(
+                3,
+                vec![3; 130],
+                Owner::Unknown {
+                    tag: 6,
+                    payload: vec![1, 2, 3, 4],
+                },
+                TransactionDigest::ZERO,
+                7,
+            ),
+        ];
+        for obj in &objects {
+            let bytes = obj.to_bytes().unwrap();
+            assert_eq!(
+                ObjectData::from_bytes(&bytes).unwrap().to_bytes().unwrap(),
+                bytes
+            );
+        }
+
+        let bytes = objects[0].to_bytes().unwrap();
+        assert!(ObjectData::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        assert!(ObjectData::from_bytes(&[1]).is_err());
+    }
+
+    #[test]
+    fn object_digest() {
+        let obj = ObjectData::gas_coin(
+            1,
+            vec![1; 40],
+            Owner::AddressOwner([2; 32]),
+            TransactionDigest::ZERO,
+            5,
+        );
+        // hashlib.blake2b(b"Object::" + obj.to_bytes(), digest_size=32)
+        assert_eq!(
+            obj.digest().unwrap().to_string(),
+            "GE9GK9Lo9vXFT2bHBpZDHDXLrRrCwqd1hys8qzA49niP"
+        );
+    }
+
+    #[test]
+    fn object_ref_from_contents() {
+        let mut contents = vec![0x11; 32];
+        contents.extend_from_slice(&5u64.to_le_bytes());
+        let obj = ObjectData::gas_coin(9, contents, Owner::Immutable, TransactionDigest::ZERO, 0);
+        assert_eq!(obj.object_id(), Some([0x11; 32]));
+        assert_eq!(
+            obj.object_ref().unwrap(),
+            ObjectRef {
+                object_id: [0x11; 32],
+                version: 9,
+                digest: obj.digest().unwrap(),
+            }
+        );
+
+        let short =
+            ObjectData::gas_coin(9, vec![1; 8], Owner::Immutable, TransactionDigest::ZERO, 0);
+        assert_eq!(short.object_id(), None);
+        assert!(matches!(
+            short.object_ref(),
+            Err(LedgerError::InvalidEncoding(_))
+        ));
+    }
+
+    #[cfg(feature = "iota-sdk-types")]
+    #[test]
+    fn converts_sdk_objects() {
+        let usdc: StructTag = "0xabc::usdc::USDC".parse().unwrap();
+        let objects = [
+            ObjectData::gas_coin(
+                3,
+                vec![1; 40],
+                Owner::AddressOwner([2; 32]),
+                TransactionDigest([7; 32]),
+                5,
+            ),
+            ObjectData::coin(
+                usdc,
+                4,
+                vec![3; 40],
+                Owner::Shared {
+                    initial_shared_version: 2,
+                },
+                TransactionDigest::ZERO,
+                6,
+            ),
+            ObjectData::staked_iota(5, vec![4; 80], Owner::Immutable, TransactionDigest::ZERO, 7),
+        ];
+        for obj in &objects {
+            let bytes = obj.to_bytes().unwrap();
+            let sdk = || bcs::from_bytes::<iota_sdk_types::Object>(&bytes).unwrap();
+            assert_eq!(
+                ObjectData::try_from(sdk()).unwrap().to_bytes().unwrap(),
+                bytes
+            );
+            assert_eq!(
+                ObjectRef::try_from(sdk()).unwrap(),
+                obj.object_ref().unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn owner_round_trip() {
+        for owner in [
+            Owner::AddressOwner([0xAA; 32]),
+            Owner::ObjectOwner([0xBB; 32]),
+            Owner::Shared {
+                initial_shared_version: 7,
+            },
+            Owner::Immutable,
+            Owner::Unknown {
+                tag: 4,
+                payload: vec![1, 2, 3],
+            },
+            Owner::Unknown {
+                tag: 200,
+                payload: vec![],
+            },
+        ] {
+            let bytes = owner.to_bytes();
+            assert_eq!(Owner::from_bytes(&bytes).unwrap().to_bytes(), bytes);
+        }
+        assert_eq!(
+            Owner::Unknown {
+                tag: 200,
+                payload: vec![9]
+            }
+            .to_bytes(),
+            [0xC8, 0x01, 9]
+        );
+        assert!(Owner::from_bytes(&[2, 1]).is_err());
+        assert!(Owner::from_bytes(&[3, 0]).is_err());
+    }
+
+    #[test]
+    fn unknown_owner_is_encoded_verbatim() {
+        let owner = Owner::Unknown {
+            tag: 5,
+            payload: vec![0xAB; 40],
+        };
+        let obj = ObjectData::gas_coin(1, vec![0u8; 40], owner.clone(), TransactionDigest::ZERO, 0);
+        obj.validate().unwrap();
+        let encoded = obj.to_bytes().unwrap();
+        let owner_bytes = owner.to_bytes();
+        let owner_at = encoded.len() - 33 - 8 - owner_bytes.len();
+        assert_eq!(&encoded[owner_at..encoded.len() - 41], &owner_bytes[..]);
+
+        let shadowing = ObjectData::gas_coin(
+            1,
+            vec![0u8; 40],
+            Owner::Unknown {
+                tag: 3,
+                payload: vec![],
+            },
+            TransactionDigest::ZERO,
+            0,
+        );
+        assert!(matches!(
+            shadowing.validate(),
+            Err(ValidationError::ReservedOwnerTag(3))
+        ));
+    }
+
+    #[test]
+    fn decode_objects_rejects_trailing_bytes() {
+        let mut bytes = encode_objects(&[]).unwrap();
+        assert!(decode_objects(&bytes).unwrap().is_empty());
+        bytes.push(0);
+        assert!(decode_objects(&bytes).is_err());
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn encode_objects_round_trips(
+            objects in proptest::collection::vec(
+                (
+                    proptest::num::u64::ANY,
+                    proptest::collection::vec(proptest::num::u8::ANY, 32..64),
+                    proptest::array::uniform32(proptest::num::u8::ANY),
+                    proptest::num::u64::ANY,
+                ),
+                0..4,
+            )
+        ) {
+            let objects: Vec<_> = objects
+                .into_iter()
+                .map(|(version, contents, owner, rebate)| {
+                    ObjectData::gas_coin(
+                        version,
+                        contents,
+                        Owner::AddressOwner(owner),
+                        TransactionDigest::ZERO,
+                        rebate,
+                    )
+                })
+                .collect();
+            let bytes = encode_objects(&objects).unwrap();
+            let decoded = decode_objects(&bytes).unwrap();
+            proptest::prop_assert_eq!(encode_objects(&decoded).unwrap(), bytes);
+        }
+    }
+}
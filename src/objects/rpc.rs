@@ -0,0 +1,247 @@
+//! [`ObjectData`] from `iota_getObject` JSON.
+//!
+//! The response must have been requested with `showBcs`, `showOwner`,
+//! `showPreviousTransaction` and `showStorageRebate` (or `showType`
+//! alongside `showBcs`); the Move contents come from `bcs.bcsBytes`.
+
+use serde_json::Value;
+
+use super::{MoveObject, MoveObjectType, ObjectData, Owner, StructTag};
+use crate::error::LedgerError;
+use crate::prelude::*;
+use crate::tx;
+use crate::types::{Address, TransactionDigest};
+
+fn err(msg: impl core::fmt::Display) -> LedgerError {
+    LedgerError::InvalidEncoding(format!("object JSON: {msg}"))
+}
+
+pub(crate) fn field<'a>(obj: &'a Value, name: &str) -> Result<&'a Value, LedgerError> {
+    obj.get(name)
+        .ok_or_else(|| err(format!("missing `{name}`")))
+}
+
+pub(crate) fn str_field<'a>(obj: &'a Value, name: &str) -> Result<&'a str, LedgerError> {
+    field(obj, name)?
+        .as_str()
+        .ok_or_else(|| err(format!("`{name}` is not a string")))
+}
+
+/// u64s are strings in most JSON-RPC fields but plain numbers in `bcs`.
+pub(crate) fn u64_field(obj: &Value, name: &str) -> Result<u64, LedgerError> {
+    let v = field(obj, name)?;
+    v.as_u64()
+        .or_else(|| v.as_str().and_then(|s| s.parse().ok()))
+        .ok_or_else(|| err(format!("`{name}` is not a u64")))
+}
+
+fn parse_owner(v: &Value) -> Result<Owner, LedgerError> {
+    if v.as_str() == Some("Immutable") {
+        return Ok(Owner::Immutable);
+    }
+    if let Some(addr) = v.get("AddressOwner").and_then(Value::as_str) {
+        return Ok(Owner::AddressOwner(addr.parse::<Address>()?.0));
+    }
+    if let Some(addr) = v.get("ObjectOwner").and_then(Value::as_str) {
+        return Ok(Owner::ObjectOwner(addr.parse::<Address>()?.0));
+    }
+    if let Some(shared) = v.get("Shared") {
+        return Ok(Owner::Shared {
+            initial_shared_version: u64_field(shared, "initial_shared_version")?,
+        });
+    }
+    Err(err(format!("unsupported owner {v}")))
+}
+
+impl ObjectData {
+    /// Build from an `iota_getObject` response.
+    ///
+    /// Accepts the full JSON-RPC envelope (`{"result": {"data": ...}}`),
+    /// the `result` (`{"data": ...}`) or the object itself.
+    pub fn from_rpc_json(value: &Value) -> Result<Self, LedgerError> {
+        let value = value.get("result").unwrap_or(value);
+        if let Some(error) = value.get("error") {
+            return Err(err(format!("RPC returned error {error}")));
+        }
+        let obj = value.get("data").unwrap_or(value);
+
+        let bcs = field(obj, "bcs")?;
+        if str_field(bcs, "dataType")? != "moveObject" {
+            return Err(err("packages cannot be converted to ObjectData"));
+        }
+
+        let type_str = obj
+            .get("type")
+            .or_else(|| bcs.get("type"))
+            .and_then(Value::as_str)
+            .ok_or_else(|| err("missing `type`"))?;
+        let tag: StructTag = type_str.parse()?;
+        let type_ = MoveObjectType::from_struct_tag(tag).ok_or_else(|| {
+            err(format!(
+                "unsupported object type {type_str} for clear signing"
+            ))
+        })?;
+
+        let has_public_transfer = bcs
+            .get("hasPublicTransfer")
+            .and_then(Value::as_bool)
+            .or_else(|| type_.expected_public_transfer())
+            .ok_or_else(|| err("missing `hasPublicTransfer`"))?;
+
+        let contents = tx::from_base64(str_field(bcs, "bcsBytes")?)?;
+
+        let previous_transaction: TransactionDigest = str_field(obj, "previousTransaction")?
+            .parse()
+            .map_err(|_| err("`previousTransaction` is not a base58 32-byte digest"))?;
+
+        Ok(ObjectData {
+            data: MoveObject {
+                type_,
+                has_public_transfer,
+                version: u64_field(bcs, "version").or_else(|_| u64_field(obj, "version"))?,
+                contents,
+            },
+            owner: parse_owner(field(obj, "owner")?)?,
+            previous_transaction,
+            storage_rebate: u64_field(obj, "storageRebate")?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn gas_coin_json() -> Value {
+        json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "result": {
+                "data": {
+                    "objectId": "0x5ad5c5a5e0fcf9c3f0c0c8cb1c4ea3c3b6a7f6c0a5b4e3d2c1b0a9f8e7d6c5b4",
+                    "version": "42",
+                    "digest": "JEKNVnkbo3jma5nREBBJCDoXFVeKkD56V3xKrvRmWxFG",
+                    "type": "0x2::coin::Coin<0x2::iota::IOTA>",
+                    "owner": { "AddressOwner": "0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa" },
+                    "previousTransaction": "11111111111111111111111111111111",
+                    "storageRebate": "988000",
+                    "bcs": {
+                        "dataType": "moveObject",
+                        "type": "0x2::coin::Coin<0x2::iota::IOTA>",
+                        "hasPublicTransfer": true,
+                        "version": 42,
+                        "bcsBytes": "AAECAwQFBgc="
+                    }
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn parses_gas_coin() {
+        let obj = ObjectData::from_rpc_json(&gas_coin_json()).unwrap();
+        assert!(matches!(obj.data.type_, MoveObjectType::GasCoin));
+        assert!(obj.data.has_public_transfer);
+        assert_eq!(obj.data.version, 42);
+        assert_eq!(obj.data.contents, [0, 1, 2, 3, 4, 5, 6, 7]);
+        assert!(matches!(obj.owner, Owner::AddressOwner(a) if a == [0xAA; 32]));
+        assert_eq!(obj.previous_transaction, TransactionDigest::ZERO);
+        assert_eq!(obj.storage_rebate, 988_000);
+    }
+
+    #[test]
+    fn accepts_unwrapped_shapes() {
+        let full = gas_coin_json();
+        let result = &full["result"];
+        let data = &result["data"];
+        let expected = ObjectData::from_rpc_json(&full)
+            .unwrap()
+            .to_bytes()
+            .unwrap();
+        assert_eq!(
+            ObjectData::from_rpc_json(result)
+                .unwrap()
+                .to_bytes()
+                .unwrap(),
+            expected
+        );
+        assert_eq!(
+            ObjectData::from_rpc_json(data).unwrap().to_bytes().unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn parses_custom_coin_and_owners() {
+        let mut v = gas_coin_json();
+        let data = &mut v["result"]["data"];
+        data["type"] = json!("0x2::coin::Coin<0xabc::usdc::USDC>");
+        data["owner"] = json!({ "Shared": { "initial_shared_version": 7 } });
+        let obj = ObjectData::from_rpc_json(&v).unwrap();
+        let MoveObjectType::Coin(tag) = &obj.data.type_ else {
+            panic!("expected Coin");
+        };
+        assert_eq!(tag.to_string().rsplit("::").next(), Some("USDC"));
+        assert!(matches!(
+            obj.owner,
+            Owner::Shared {
+                initial_shared_version: 7
+            }
+        ));
+
+        v["result"]["data"]["owner"] = json!("Immutable");
+        let obj = ObjectData::from_rpc_json(&v).unwrap();
+        assert!(matches!(obj.owner, Owner::Immutable));
+    }
+
+    #[test]
+    fn staked_iota_has_no_public_transfer_by_default() {
+        let mut v = gas_coin_json();
+        let data = &mut v["result"]["data"];
+        data["type"] = json!("0x3::staking_pool::StakedIota");
+        data["bcs"]
+            .as_object_mut()
+            .unwrap()
+            .remove("hasPublicTransfer");
+        let obj = ObjectData::from_rpc_json(&v).unwrap();
+        assert!(matches!(obj.data.type_, MoveObjectType::StakedIota));
+        assert!(!obj.data.has_public_transfer);
+    }
+
+    #[test]
+    fn parses_timelocked_stake() {
+        let mut v = gas_coin_json();
+        let data = &mut v["result"]["data"];
+        data["type"] = json!("0x3::timelocked_staking::TimelockedStakedIota");
+        data["bcs"]
+            .as_object_mut()
+            .unwrap()
+            .remove("hasPublicTransfer");
+        let obj = ObjectData::from_rpc_json(&v).unwrap();
+        assert!(matches!(obj.data.type_, MoveObjectType::Other(_)));
+        assert!(!obj.data.has_public_transfer);
+    }
+
+    #[test]
+    fn rejects_unusable_objects() {
+        let mut v = gas_coin_json();
+        v["result"]["data"]["type"] = json!("0x2::kiosk::Kiosk");
+        assert!(ObjectData::from_rpc_json(&v).is_err());
+
+        let mut v = gas_coin_json();
+        v["result"]["data"]["bcs"]["dataType"] = json!("package");
+        assert!(ObjectData::from_rpc_json(&v).is_err());
+
+        let mut v = gas_coin_json();
+        v["result"]["data"].as_object_mut().unwrap().remove("bcs");
+        assert!(ObjectData::from_rpc_json(&v).is_err());
+
+        let mut v = gas_coin_json();
+        v["result"]["data"]["owner"] = json!({ "AddressOwner": "0xaa" });
+        assert!(ObjectData::from_rpc_json(&v).is_err());
+
+        let v = json!({ "result": { "error": { "code": "notExists" } } });
+        assert!(ObjectData::from_rpc_json(&v).is_err());
+    }
+}
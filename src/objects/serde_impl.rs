@@ -0,0 +1,156 @@
+//! serde support: type tags as their canonical strings
+//! (`"0x2::coin::Coin<0x2::iota::IOTA>"`), digests in base58, other byte
+//! fields as `0x` hex.
+
+use core::fmt;
+use core::str::FromStr;
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use super::{StructTag, TypeTag};
+use crate::prelude::*;
+use crate::types::{ObjectDigest, TransactionDigest};
+
+fn serialize_display<S: Serializer>(v: &impl fmt::Display, s: S) -> Result<S::Ok, S::Error> {
+    s.collect_str(v)
+}
+
+fn deserialize_from_str<'de, D, T>(d: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    String::deserialize(d)?.parse().map_err(de::Error::custom)
+}
+
+impl Serialize for TypeTag {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        serialize_display(self, s)
+    }
+}
+
+impl<'de> Deserialize<'de> for TypeTag {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        deserialize_from_str(d)
+    }
+}
+
+impl Serialize for StructTag {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        serialize_display(self, s)
+    }
+}
+
+impl<'de> Deserialize<'de> for StructTag {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        deserialize_from_str(d)
+    }
+}
+
+macro_rules! base58_serde {
+    ($($name:ident),*) => {$(
+        impl Serialize for $name {
+            fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+                serialize_display(self, s)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+                deserialize_from_str(d)
+            }
+        }
+    )*};
+}
+
+base58_serde!(TransactionDigest, ObjectDigest);
+
+#[cfg(test)]
+mod tests {
+    use super::super::{MoveObjectType, ObjectData, Owner};
+    use super::*;
+    use serde_json::json;
+
+    fn usdc_coin() -> ObjectData {
+        ObjectData::coin(
+            "0xabc::usdc::USDC".parse().unwrap(),
+            7,
+            vec![0xAB; 40],
+            Owner::AddressOwner([0xAA; 32]),
+            TransactionDigest::ZERO,
+            100,
+        )
+    }
+
+    #[test]
+    fn json_shape() {
+        let v = serde_json::to_value(usdc_coin()).unwrap();
+        assert_eq!(
+            v["data"]["type_"]["Coin"],
+            json!(format!("0x{:0>64}::usdc::USDC", "abc"))
+        );
+        assert_eq!(
+            v["data"]["contents"],
+            json!(format!("0x{}", "ab".repeat(40)))
+        );
+        assert_eq!(
+            v["owner"]["AddressOwner"],
+            json!(format!("0x{}", "aa".repeat(32)))
+        );
+        assert_eq!(v["previous_transaction"], json!("1".repeat(32)));
+
+        let gas = ObjectData::gas_coin(1, vec![], Owner::Immutable, TransactionDigest::ZERO, 0);
+        let v = serde_json::to_value(gas).unwrap();
+        assert_eq!(v["data"]["type_"], json!("GasCoin"));
+        assert_eq!(v["owner"], json!("Immutable"));
+    }
+
+    #[test]
+    fn round_trip_preserves_encoding() {
+        let mut unknown = usdc_coin();
+        unknown.owner = Owner::Unknown {
+            tag: 9,
+            payload: vec![1, 2, 3],
+        };
+        let other = ObjectData::other(
+            "0x3::timelocked_staking::TimelockedStakedIota"
+                .parse()
+                .unwrap(),
+            false,
+            1,
+            vec![0; 88],
+            Owner::Shared {
+                initial_shared_version: 4,
+            },
+            TransactionDigest::ZERO,
+            0,
+        );
+        for obj in [usdc_coin(), unknown, other] {
+            let json = serde_json::to_string(&obj).unwrap();
+            let back: ObjectData = serde_json::from_str(&json).unwrap();
+            assert_eq!(back.to_bytes().unwrap(), obj.to_bytes().unwrap());
+        }
+    }
+
+    #[test]
+    fn type_tags_as_strings() {
+        let tag: TypeTag = "vector<0x2::coin::Coin<0x2::iota::IOTA>>".parse().unwrap();
+        let v = serde_json::to_value(&tag).unwrap();
+        assert_eq!(v, json!(tag.to_string()));
+        assert_eq!(serde_json::from_value::<TypeTag>(v).unwrap(), tag);
+        assert!(serde_json::from_value::<StructTag>(json!("u8")).is_err());
+        assert!(serde_json::from_value::<MoveObjectType>(json!({ "Coin": "nope" })).is_err());
+    }
+
+    #[test]
+    fn rejects_wrong_length_bytes() {
+        let mut v = serde_json::to_value(usdc_coin()).unwrap();
+        v["owner"]["AddressOwner"] = json!("0x20");
+        assert!(serde_json::from_value::<ObjectData>(v.clone()).is_err());
+
+        v["owner"] = json!("Immutable");
+        v["previous_transaction"] = json!("2g");
+        assert!(serde_json::from_value::<ObjectData>(v).is_err());
+    }
+}
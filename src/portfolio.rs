@@ -0,0 +1,376 @@
+//! Wallet discovery over JSON-RPC (feature `rpc`).
+//!
+//! [`LedgerIota::scan_portfolio`](crate::LedgerIota::scan_portfolio)
+//! walks accounts and their receive and change chains the way BIP44
+//! wallets restore: a chain ends after [`gap_limit`](PortfolioConfig::gap_limit)
+//! unused addresses in a row, and the scan ends at the first account
+//! without any used address. An address counts as used if it sent or
+//! received any transaction, so an emptied address still resets the gap.
+//! On nodes that can't query transaction history, it counts as used if it
+//! holds a balance or any object.
+
+use alloc::collections::BTreeMap;
+
+use crate::api::Address;
+use crate::change::Chain;
+use crate::error::LedgerError;
+use crate::log_policy::{LogPolicy, Sensitive};
+use crate::objects::StructTag;
+use crate::rpc::{
+    get_all_balances_request, get_owned_objects_request, parse_balances, parse_owned_objects,
+    parse_transaction_digests, query_transactions_request, AddressFilter, Balance, OwnedObject,
+    RpcClient,
+};
+use crate::types::{Bip32Path, HARDENED};
+
+/// How far [`scan_portfolio`](crate::LedgerIota::scan_portfolio) looks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PortfolioConfig {
+    /// Accounts `0..max_accounts` at most.
+    pub max_accounts: u32,
+    /// Unused addresses in a row that end a chain.
+    pub gap_limit: u32,
+    /// Chains scanned in each account.
+    pub chains: Vec<Chain>,
+    /// Keep going past an account without used addresses.
+    pub scan_unused_accounts: bool,
+    /// Page size for `iotax_getOwnedObjects` (node default if `None`).
+    pub page_limit: Option<usize>,
+}
+
+impl Default for PortfolioConfig {
+    fn default() -> Self {
+        Self {
+            max_accounts: 10,
+            gap_limit: 20,
+            chains: vec![Chain::External, Chain::Internal],
+            scan_unused_accounts: false,
+            page_limit: None,
+        }
+    }
+}
+
+/// A used address and what it holds (nothing, if it was emptied).
+#[derive(Debug, Clone)]
+pub struct AddressReport {
+    pub path: Bip32Path,
+    pub chain: Chain,
+    pub index: u32,
+    pub address: Address,
+    /// Coin types with a non-zero balance.
+    pub balances: Vec<Balance>,
+    /// Every object the address owns, coins included.
+    pub objects: Vec<OwnedObject>,
+}
+
+#[derive(Debug, Clone)]
+pub struct AccountReport {
+    pub account: u32,
+    /// Used addresses, chain by chain in index order.
+    pub addresses: Vec<AddressReport>,
+    /// Addresses derived for this account, used or not.
+    pub scanned: u32,
+}
+
+/// Result of a scan: accounts with at least one used address.
+#[derive(Debug, Clone, Default)]
+pub struct Portfolio {
+    pub accounts: Vec<AccountReport>,
+}
+
+impl Portfolio {
+    pub fn addresses(&self) -> impl Iterator<Item = &AddressReport> {
+        self.accounts.iter().flat_map(|a| &a.addresses)
+    }
+
+    /// Sum of balances per coin type over all addresses.
+    #[must_use]
+    pub fn totals(&self) -> BTreeMap<StructTag, u128> {
+        let mut totals = BTreeMap::new();
+        for balance in self.addresses().flat_map(|a| &a.balances) {
+            *totals.entry(balance.coin_type.clone()).or_insert(0u128) += balance.total_balance;
+        }
+        totals
+    }
+}
+
+/// `derive(account, chain, index)` returns the path, owner bytes and
+/// address to query.
+pub(crate) fn scan(
+    mut derive: impl FnMut(u32, Chain, u32) -> Result<(Bip32Path, [u8; 32], Address), LedgerError>,
+    rpc: &dyn RpcClient,
+    config: &PortfolioConfig,
+    policy: &LogPolicy,
+) -> Result<Portfolio, LedgerError> {
+    let mut portfolio = Portfolio::default();
+    let mut history = true;
+    for account in 0..config.max_accounts {
+        let mut report = AccountReport {
+            account,
+            addresses: Vec::new(),
+            scanned: 0,
+        };
+        for &chain in &config.chains {
+            let mut gap = 0;
+            let mut index = 0;
+            while gap < config.gap_limit && index < HARDENED {
+                let (path, owner, address) = derive(account, chain, index)?;
+                report.scanned += 1;
+                let mut used = None;
+                if history {
+                    used = has_history(rpc, &owner)?;
+                    if used.is_none() {
+                        log::debug!("node has no transaction history, judging use by holdings");
+                        history = false;
+                    }
+                }
+                let found = match used {
+                    Some(false) => None,
+                    _ => {
+                        let (balances, objects) = holdings(rpc, &owner, config.page_limit)?;
+                        let held = !balances.is_empty() || !objects.is_empty();
+                        (used == Some(true) || held).then_some((balances, objects))
+                    }
+                };
+                if let Some((balances, objects)) = found {
+                    log::debug!(
+                        "{}: {} coin types, {} objects",
+                        policy.show(Sensitive::Path, log::Level::Debug, &path),
+                        balances.len(),
+                        objects.len()
+                    );
+                    gap = 0;
+                    report.addresses.push(AddressReport {
+                        path,
+                        chain,
+                        index,
+                        address,
+                        balances,
+                        objects,
+                    });
+                } else {
+                    gap += 1;
+                }
+                index += 1;
+            }
+        }
+        if report.addresses.is_empty() {
+            if config.scan_unused_accounts {
+                continue;
+            }
+            break;
+        }
+        portfolio.accounts.push(report);
+    }
+    Ok(portfolio)
+}
+
+/// Whether `owner` sent or received any transaction; `None` if the node
+/// can't query transaction history.
+fn has_history(rpc: &dyn RpcClient, owner: &[u8; 32]) -> Result<Option<bool>, LedgerError> {
+    for filter in [AddressFilter::From, AddressFilter::To] {
+        let response = rpc.call(&query_transactions_request(filter, owner, None, Some(1)))?;
+        if response.get("error").is_some() {
+            return Ok(None);
+        }
+        if !parse_transaction_digests(&response)?.0.is_empty() {
+            return Ok(Some(true));
+        }
+    }
+    Ok(Some(false))
+}
+
+fn holdings(
+    rpc: &dyn RpcClient,
+    owner: &[u8; 32],
+    page_limit: Option<usize>,
+) -> Result<(Vec<Balance>, Vec<OwnedObject>), LedgerError> {
+    let mut balances = parse_balances(&rpc.call(&get_all_balances_request(owner))?)?;
+    balances.retain(|b| b.total_balance > 0);
+
+    let mut objects = Vec::new();
+    let mut cursor = None;
+    loop {
+        let request = get_owned_objects_request(owner, cursor.as_deref(), page_limit);
+        let (page, next) = parse_owned_objects(&rpc.call(&request)?)?;
+        objects.extend(page);
+        match next {
+            Some(next) if cursor.as_ref() != Some(&next) => cursor = Some(next),
+            _ => break,
+        }
+    }
+    Ok((balances, objects))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+
+    /// Owner bytes `[account, change, index, 0, ...]`.
+    fn derive(
+        account: u32,
+        chain: Chain,
+        index: u32,
+    ) -> Result<(Bip32Path, [u8; 32], Address), LedgerError> {
+        let mut owner = [0; 32];
+        owner[..3].copy_from_slice(&[account as u8, chain.change() as u8, index as u8]);
+        Ok((
+            chain.iota(account, index),
+            owner,
+            crate::types::Address(owner).into(),
+        ))
+    }
+
+    fn owner_of(request: &Value) -> [u8; 3] {
+        let param = &request["params"][0];
+        let owner = param
+            .as_str()
+            .or_else(|| param["filter"].as_object()?.values().next()?.as_str())
+            .unwrap();
+        let owner = hex::decode(&owner[2..]).unwrap();
+        [owner[0], owner[1], owner[2]]
+    }
+
+    fn digests(count: usize) -> Result<Value, LedgerError> {
+        let data = vec![json!({ "digest": "11111111111111111111111111111111" }); count];
+        Ok(json!({ "result": { "data": data, "hasNextPage": false } }))
+    }
+
+    /// Account 0 has IOTA at 0/0/1 and 0/1/0 and an object at 0/0/4, all
+    /// received, and 0/0/6 sent everything it had; account 1 is empty.
+    fn node(request: &Value) -> Result<Value, LedgerError> {
+        let owner = owner_of(request);
+        match request["method"].as_str().unwrap() {
+            "iotax_queryTransactionBlocks" => {
+                let filter = &request["params"][0]["filter"];
+                let sent = filter.get("FromAddress").is_some() && owner == [0, 0, 6];
+                let received = filter.get("ToAddress").is_some()
+                    && matches!(owner, [0, 0, 1] | [0, 1, 0] | [0, 0, 4] | [0, 0, 6]);
+                digests(usize::from(sent || received))
+            }
+            "iotax_getAllBalances" => {
+                let balance = match owner {
+                    [0, 0, 1] => "1000",
+                    [0, 1, 0] => "25",
+                    _ => "0",
+                };
+                Ok(json!({ "result": [{
+                    "coinType": "0x2::iota::IOTA",
+                    "coinObjectCount": 1,
+                    "totalBalance": balance,
+                }] }))
+            }
+            "iotax_getOwnedObjects" => {
+                let data = if owner == [0, 0, 4] {
+                    vec![json!({ "data": {
+                        "objectId": format!("0x{}", "04".repeat(32)),
+                        "version": "1",
+                        "digest": "11111111111111111111111111111111",
+                        "type": "0xabc::nft::Nft",
+                    } })]
+                } else {
+                    vec![]
+                };
+                Ok(json!({ "result": { "data": data, "hasNextPage": false } }))
+            }
+            m => panic!("unexpected {m}"),
+        }
+    }
+
+    #[test]
+    fn scans_until_the_gap_and_first_unused_account() {
+        let config = PortfolioConfig {
+            gap_limit: 3,
+            ..Default::default()
+        };
+        let portfolio = scan(derive, &node, &config, &LogPolicy::DEFAULT).unwrap();
+
+        assert_eq!(portfolio.accounts.len(), 1);
+        let account = &portfolio.accounts[0];
+        let used: Vec<String> = account
+            .addresses
+            .iter()
+            .map(|a| a.path.to_string())
+            .collect();
+        assert_eq!(
+            used,
+            [
+                "m/44'/4218'/0'/0'/1'",
+                "m/44'/4218'/0'/0'/4'",
+                "m/44'/4218'/0'/0'/6'",
+                "m/44'/4218'/0'/1'/0'"
+            ]
+        );
+        // External: 0..=6 then 3 unused; internal: 0 then 3 unused.
+        assert_eq!(account.scanned, 10 + 4);
+        assert_eq!(account.addresses[1].objects.len(), 1);
+        assert!(account.addresses[1].balances.is_empty());
+        assert!(account.addresses[2].balances.is_empty());
+        assert!(account.addresses[2].objects.is_empty());
+        assert_eq!(
+            portfolio.totals().get(&crate::coins::iota_coin_type()),
+            Some(&1025)
+        );
+    }
+
+    #[test]
+    fn falls_back_to_holdings_without_history() {
+        use std::cell::Cell;
+
+        let queries = Cell::new(0);
+        let no_history = |request: &Value| {
+            if request["method"] == "iotax_queryTransactionBlocks" {
+                queries.set(queries.get() + 1);
+                return Ok(json!({ "error": { "code": -32601, "message": "Method not found" } }));
+            }
+            node(request)
+        };
+        let config = PortfolioConfig {
+            gap_limit: 3,
+            ..Default::default()
+        };
+        let portfolio = scan(derive, &no_history, &config, &LogPolicy::DEFAULT).unwrap();
+
+        let account = &portfolio.accounts[0];
+        assert_eq!(account.addresses.len(), 3);
+        // The emptied 0/0/6 is beyond the gap: 0..=4 then 3 unused.
+        assert_eq!(account.scanned, 8 + 4);
+        assert_eq!(queries.get(), 1);
+    }
+
+    #[test]
+    fn follows_object_pages() {
+        use std::cell::Cell;
+
+        let pages = Cell::new(0);
+        let paged = |request: &Value| {
+            if request["method"] == "iotax_queryTransactionBlocks" && owner_of(request) == [0, 0, 0]
+            {
+                return digests(1);
+            }
+            if request["method"] == "iotax_getOwnedObjects" && owner_of(request) == [0, 0, 0] {
+                pages.set(pages.get() + 1);
+                let last = !request["params"][2].is_null();
+                return Ok(json!({ "result": {
+                    "data": [{ "data": {
+                        "objectId": format!("0x{}", hex::encode([pages.get() as u8; 32])),
+                        "version": "1",
+                        "digest": "11111111111111111111111111111111",
+                    } }],
+                    "nextCursor": "0xpage2",
+                    "hasNextPage": !last,
+                } }));
+            }
+            node(request)
+        };
+        let config = PortfolioConfig {
+            gap_limit: 1,
+            chains: vec![Chain::External],
+            ..Default::default()
+        };
+        let portfolio = scan(derive, &paged, &config, &LogPolicy::DEFAULT).unwrap();
+        assert_eq!(pages.get(), 2);
+        assert_eq!(portfolio.accounts[0].addresses[0].objects.len(), 2);
+    }
+}
@@ -0,0 +1,12 @@
+//! The `alloc` items the `std` prelude would provide, for the modules
+//! that build without `std`.
+
+#[allow(unused_imports)]
+pub(crate) use alloc::{
+    borrow::ToOwned,
+    boxed::Box,
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
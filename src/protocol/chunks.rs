@@ -1,15 +1,20 @@
 use sha2::{Digest, Sha256};
 
-const BLOCK_DATA_SIZE: usize = 180;
+#[cfg(any(feature = "testing", feature = "fuzzing", test))]
+use crate::wipe::Wipe;
+
+pub(crate) const BLOCK_DATA_SIZE: usize = 180;
 
 /// Up to 180 bytes of data + a 32-byte SHA256 hash pointing to the
 /// next block (all zeros for the last one).
+#[cfg(any(feature = "testing", feature = "fuzzing", test))]
 #[derive(Debug, Clone)]
 pub struct Block {
     pub next_hash: [u8; 32],
     pub data: Vec<u8>,
 }
 
+#[cfg(any(feature = "testing", feature = "fuzzing", test))]
 impl Block {
     pub fn serialized_len(&self) -> usize {
         32 + self.data.len()
@@ -25,11 +30,36 @@ impl Block {
         self.serialize_into(&mut buf);
         buf
     }
+
+    /// Inverse of [`serialize_into`](Self::serialize_into): `next_hash ++ data`,
+    /// with at most 180 bytes of data.
+    pub fn deserialize(bytes: &[u8]) -> Result<Self, crate::error::LedgerError> {
+        if bytes.len() < 32 || bytes.len() > 32 + BLOCK_DATA_SIZE {
+            return Err(crate::error::LedgerError::BlockProtocol(format!(
+                "block is {} bytes, expected 32 to {}",
+                bytes.len(),
+                32 + BLOCK_DATA_SIZE
+            )));
+        }
+        let (next_hash, data) = bytes.split_at(32);
+        Ok(Self {
+            next_hash: next_hash.try_into().expect("32 bytes"),
+            data: data.to_vec(),
+        })
+    }
+}
+
+#[cfg(any(feature = "testing", feature = "fuzzing", test))]
+impl Drop for Block {
+    fn drop(&mut self) {
+        self.data.wipe();
+    }
 }
 
 /// Split data into SHA256-linked blocks. Built backwards so each
 /// block's `next_hash` points to the serialized hash of the next one
 /// (last block gets all zeros).
+#[cfg(any(feature = "testing", test))]
 pub fn build_block_chain(data: &[u8]) -> Vec<Block> {
     if data.is_empty() {
         return vec![Block {
@@ -57,8 +87,23 @@ pub fn build_block_chain(data: &[u8]) -> Vec<Block> {
 }
 
 /// SHA256 of the serialized block (`next_hash ++ data`).
+#[cfg(any(feature = "testing", test))]
 pub fn hash_block(block: &Block) -> [u8; 32] {
-    sha256(&block.serialize())
+    hash_block_parts(&block.next_hash, [&block.data[..]])
+}
+
+/// SHA256 of a serialized block given as its `next_hash` and its data in
+/// pieces, without building it.
+pub(crate) fn hash_block_parts<'a>(
+    next_hash: &[u8; 32],
+    data: impl IntoIterator<Item = &'a [u8]>,
+) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(next_hash);
+    for piece in data {
+        hasher.update(piece);
+    }
+    hasher.finalize().into()
 }
 
 pub fn sha256(data: &[u8]) -> [u8; 32] {
@@ -110,6 +155,17 @@ mod tests {
         assert_eq!(blocks[0].data.len(), 180);
     }
 
+    #[test]
+    fn deserialize_round_trips() {
+        for block in build_block_chain(&[0x5A; 400]) {
+            let parsed = Block::deserialize(&block.serialize()).unwrap();
+            assert_eq!(parsed.next_hash, block.next_hash);
+            assert_eq!(parsed.data, block.data);
+        }
+        assert!(Block::deserialize(&[0; 31]).is_err());
+        assert!(Block::deserialize(&[0; 32 + 181]).is_err());
+    }
+
     #[test]
     fn hash_chain_integrity() {
         let data = vec![0xFF; 500];
@@ -120,4 +176,21 @@ mod tests {
             assert_eq!(blocks[i].next_hash, expected);
         }
     }
+
+    proptest::proptest! {
+        #[test]
+        fn block_chain_round_trips(data in proptest::collection::vec(proptest::num::u8::ANY, 0..1000)) {
+            let mut rebuilt = Vec::new();
+            let blocks = build_block_chain(&data);
+            for (i, block) in blocks.iter().enumerate() {
+                let bytes = block.serialize();
+                let decoded = Block::deserialize(&bytes).unwrap();
+                proptest::prop_assert_eq!(decoded.serialize(), bytes);
+                let next = blocks.get(i + 1).map_or([0; 32], hash_block);
+                proptest::prop_assert_eq!(decoded.next_hash, next);
+                rebuilt.extend_from_slice(&decoded.data);
+            }
+            proptest::prop_assert_eq!(rebuilt, data);
+        }
+    }
 }
@@ -6,12 +6,14 @@
 
 pub mod chunks;
 
+use std::borrow::Cow;
 use std::collections::HashMap;
 
 use crate::apdu::{ApduAnswer, ApduCommand, Instruction};
-use crate::error::{LedgerError, StatusWord};
+use crate::error::{LedgerError, StatusWord, TransportError};
 use crate::transport::Transport;
-use chunks::{build_block_chain, Block};
+use crate::wipe::{Transient, Wipe};
+use chunks::BLOCK_DATA_SIZE;
 
 #[repr(u8)]
 enum HostMsg {
@@ -30,97 +32,281 @@ enum DeviceMsg {
     PutChunk = 0x03,
 }
 
-/// Run the block protocol for a given instruction.
-///
-/// Each parameter gets chunked into 180-byte SHA256-linked blocks.
-/// We send the first-block hashes, then respond to device requests
-/// until it yields a final result.
-pub fn execute(
-    transport: &dyn Transport,
-    ins: Instruction,
-    params: &[Vec<u8>],
-) -> Result<Vec<u8>, LedgerError> {
-    let mut hash_map: HashMap<[u8; 32], Block> = HashMap::new();
-    let mut first_hashes: Vec<[u8; 32]> = Vec::new();
+/// A device request or result, parsed from a response payload.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum DeviceMessage<'a> {
+    ResultAccumulating(&'a [u8]),
+    ResultFinal(&'a [u8]),
+    GetChunk([u8; 32]),
+    PutChunk(&'a [u8]),
+}
 
-    for param in params {
-        let blocks = build_block_chain(param);
-        if let Some(first) = blocks.first() {
-            first_hashes.push(chunks::hash_block(first));
-        }
-        for block in blocks {
-            let h = chunks::hash_block(&block);
-            hash_map.insert(h, block);
+impl<'a> DeviceMessage<'a> {
+    pub(crate) fn parse(data: &'a [u8]) -> Result<Self, LedgerError> {
+        let Some((&kind, body)) = data.split_first() else {
+            return Err(LedgerError::BlockProtocol("empty response".into()));
+        };
+        match kind {
+            x if x == DeviceMsg::ResultFinal as u8 => Ok(Self::ResultFinal(body)),
+            x if x == DeviceMsg::ResultAccumulating as u8 => Ok(Self::ResultAccumulating(body)),
+            x if x == DeviceMsg::GetChunk as u8 => {
+                let hash = body.get(..32).ok_or_else(|| {
+                    LedgerError::BlockProtocol("GET_CHUNK response too short".into())
+                })?;
+                Ok(Self::GetChunk(hash.try_into().expect("32 bytes")))
+            }
+            x if x == DeviceMsg::PutChunk as u8 => Ok(Self::PutChunk(body)),
+            other => Err(LedgerError::BlockProtocol(format!(
+                "unknown device message type: 0x{other:02X}"
+            ))),
         }
     }
+}
 
-    // Device can also push chunks back to us via PUT_CHUNK
-    let mut put_store: HashMap<[u8; 32], Vec<u8>> = HashMap::new();
+/// One parameter of a command: the concatenation of its parts. Parts the
+/// caller already holds (transaction bytes, encoded objects) are borrowed
+/// instead of copied; owned parts are wiped on drop.
+pub struct Param<'a>(Vec<Cow<'a, [u8]>>);
 
-    let mut start_data = Vec::with_capacity(1 + first_hashes.len() * 32);
-    start_data.push(HostMsg::Start as u8);
-    for h in &first_hashes {
-        start_data.extend_from_slice(h);
+impl<'a> Param<'a> {
+    pub fn from_parts(parts: Vec<Cow<'a, [u8]>>) -> Self {
+        Self(parts)
     }
 
-    let mut result = Vec::new();
-    let mut response = send_apdu(transport, ins, start_data)?;
+    fn len(&self) -> usize {
+        self.0.iter().map(|part| part.len()).sum()
+    }
 
-    loop {
-        let data = response.data();
-        if data.is_empty() {
-            let code = response.retcode();
-            if code != 0 && !StatusWord::is_success(code) {
-                return Err(LedgerError::from_status(code));
+    /// Blocks this param is split into; an empty param is one empty block.
+    fn block_count(&self) -> usize {
+        self.len().div_ceil(BLOCK_DATA_SIZE).max(1)
+    }
+
+    /// Data of block `index`, as pieces of the parts it spans.
+    fn block_data(&self, index: usize) -> impl Iterator<Item = &[u8]> {
+        let start = index * BLOCK_DATA_SIZE;
+        let end = start + BLOCK_DATA_SIZE;
+        let mut offset = 0;
+        self.0.iter().filter_map(move |part| {
+            let part_start = offset;
+            offset += part.len();
+            let (from, to) = (start.max(part_start), end.min(offset));
+            (from < to).then(|| &part[from - part_start..to - part_start])
+        })
+    }
+
+    /// Hash of each block, first to last. Each block commits to the next
+    /// one's hash, so all of them are needed for the first; they're
+    /// computed back to front straight from the parts, without building
+    /// the blocks.
+    fn block_hashes(&self) -> Vec<[u8; 32]> {
+        let mut hashes = vec![[0u8; 32]; self.block_count()];
+        let mut next = [0u8; 32];
+        for (index, hash) in hashes.iter_mut().enumerate().rev() {
+            next = chunks::hash_block_parts(&next, self.block_data(index));
+            *hash = next;
+        }
+        hashes
+    }
+}
+
+impl From<Vec<u8>> for Param<'_> {
+    fn from(bytes: Vec<u8>) -> Self {
+        Self(vec![Cow::Owned(bytes)])
+    }
+}
+
+impl<'a> From<&'a [u8]> for Param<'a> {
+    fn from(bytes: &'a [u8]) -> Self {
+        Self(vec![Cow::Borrowed(bytes)])
+    }
+}
+
+impl Drop for Param<'_> {
+    fn drop(&mut self) {
+        for part in &mut self.0 {
+            if let Cow::Owned(bytes) = part {
+                bytes.wipe();
             }
-            return Err(LedgerError::BlockProtocol("empty response".into()));
         }
+    }
+}
 
-        match data[0] {
-            x if x == DeviceMsg::ResultFinal as u8 => {
-                result.extend_from_slice(&data[1..]);
-                return Ok(result);
+/// What the host sends next, as decided by [`ProtocolSession::handle`].
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum HostAction {
+    /// Send this payload to the device and feed its reply back in.
+    Reply(Vec<u8>),
+    /// The device has produced its final result.
+    Done(Vec<u8>),
+}
+
+/// Host side of the block protocol, without I/O.
+///
+/// Each parameter gets chunked into 180-byte SHA256-linked blocks. Only
+/// the block hashes are kept; a block is assembled from the parameter's
+/// bytes when the device asks for it.
+/// [`start`](Self::start) is the first message; every device message is
+/// then passed to [`handle`](Self::handle) until it yields
+/// [`HostAction::Done`].
+pub(crate) struct ProtocolSession<'p> {
+    params: &'p [Param<'p>],
+    /// Block hashes of each param, first to last.
+    hashes: Vec<Vec<[u8; 32]>>,
+    /// Block hash -> (param, block index).
+    index: HashMap<[u8; 32], (usize, usize)>,
+    /// The last reply served the final block of a param.
+    served_last_block: bool,
+    /// Chunks the device pushed to us via PUT_CHUNK.
+    put_store: HashMap<[u8; 32], Transient<Vec<u8>>>,
+    result: Transient<Vec<u8>>,
+    done: bool,
+}
+
+impl<'p> ProtocolSession<'p> {
+    pub(crate) fn new(params: &'p [Param<'p>]) -> Self {
+        let hashes: Vec<Vec<[u8; 32]>> = params.iter().map(Param::block_hashes).collect();
+        let index = hashes
+            .iter()
+            .enumerate()
+            .flat_map(|(param, hashes)| {
+                hashes
+                    .iter()
+                    .enumerate()
+                    .map(move |(block, hash)| (*hash, (param, block)))
+            })
+            .collect::<HashMap<_, _>>();
+        Self {
+            params,
+            served_last_block: false,
+            hashes,
+            index,
+            put_store: HashMap::new(),
+            result: Transient(Vec::new()),
+            done: false,
+        }
+    }
+
+    /// Whether the device may answer the last reply only after a prompt:
+    /// it asked for the final block of a param, so it may have all it
+    /// needs. Params it never asks for (e.g. object data it doesn't
+    /// decode) don't hold this back.
+    pub(crate) fn may_prompt(&self) -> bool {
+        self.served_last_block
+    }
+
+    /// The START message carrying the first-block hashes.
+    pub(crate) fn start(&self) -> Vec<u8> {
+        let mut start = Vec::with_capacity(1 + self.hashes.len() * 32);
+        start.push(HostMsg::Start as u8);
+        for hashes in &self.hashes {
+            start.extend_from_slice(&hashes[0]);
+        }
+        start
+    }
+
+    pub(crate) fn handle(&mut self, msg: DeviceMessage<'_>) -> Result<HostAction, LedgerError> {
+        if self.done {
+            return Err(LedgerError::BlockProtocol(
+                "device message after final result".into(),
+            ));
+        }
+        self.served_last_block = false;
+        let reply = match msg {
+            DeviceMessage::ResultFinal(body) => {
+                self.result.extend_from_slice(body);
+                self.done = true;
+                let result = std::mem::take(&mut *self.result);
+                return Ok(HostAction::Done(result));
             }
-            x if x == DeviceMsg::ResultAccumulating as u8 => {
-                result.extend_from_slice(&data[1..]);
-                let ack = vec![HostMsg::ResultAccumulatingResponse as u8];
-                response = send_apdu(transport, ins, ack)?;
+            DeviceMessage::ResultAccumulating(body) => {
+                self.result.extend_from_slice(body);
+                vec![HostMsg::ResultAccumulatingResponse as u8]
             }
-            x if x == DeviceMsg::GetChunk as u8 => {
-                if data.len() < 33 {
-                    return Err(LedgerError::BlockProtocol(
-                        "GET_CHUNK response too short".into(),
-                    ));
-                }
-                let mut hash = [0u8; 32];
-                hash.copy_from_slice(&data[1..33]);
-
-                if let Some(block) = hash_map.get(&hash) {
-                    let mut reply = Vec::with_capacity(1 + block.serialized_len());
+            DeviceMessage::GetChunk(hash) => {
+                if let Some(&(param, block)) = self.index.get(&hash) {
+                    let next_hash = self.hashes[param].get(block + 1).copied();
+                    self.served_last_block = next_hash.is_none();
+                    let mut reply = Vec::with_capacity(1 + 32 + BLOCK_DATA_SIZE);
                     reply.push(HostMsg::GetChunkResponseSuccess as u8);
-                    block.serialize_into(&mut reply);
-                    response = send_apdu(transport, ins, reply)?;
-                } else if let Some(stored) = put_store.get(&hash) {
+                    reply.extend_from_slice(&next_hash.unwrap_or([0; 32]));
+                    for piece in self.params[param].block_data(block) {
+                        reply.extend_from_slice(piece);
+                    }
+                    reply
+                } else if let Some(stored) = self.put_store.get(&hash) {
                     let mut reply = Vec::with_capacity(1 + stored.len());
                     reply.push(HostMsg::GetChunkResponseSuccess as u8);
                     reply.extend_from_slice(stored);
-                    response = send_apdu(transport, ins, reply)?;
+                    reply
                 } else {
-                    let reply = vec![HostMsg::GetChunkResponseFailure as u8];
-                    response = send_apdu(transport, ins, reply)?;
+                    vec![HostMsg::GetChunkResponseFailure as u8]
                 }
             }
-            x if x == DeviceMsg::PutChunk as u8 => {
-                let chunk_data = data[1..].to_vec();
+            DeviceMessage::PutChunk(body) => {
+                let chunk_data = body.to_vec();
                 let hash = chunks::sha256(&chunk_data);
-                put_store.insert(hash, chunk_data);
-                let ack = vec![HostMsg::PutChunkResponse as u8];
-                response = send_apdu(transport, ins, ack)?;
+                self.put_store.insert(hash, Transient(chunk_data));
+                vec![HostMsg::PutChunkResponse as u8]
+            }
+        };
+        Ok(HostAction::Reply(reply))
+    }
+
+    /// [`handle`](Self::handle) for an owned response payload: a final
+    /// result that arrives in one piece and pushed chunks are kept in the
+    /// payload's buffer instead of being copied.
+    pub(crate) fn handle_payload(
+        &mut self,
+        mut payload: Transient<Vec<u8>>,
+    ) -> Result<HostAction, LedgerError> {
+        match payload.first() {
+            Some(&kind)
+                if kind == DeviceMsg::ResultFinal as u8 && self.result.is_empty() && !self.done =>
+            {
+                payload.drain(..1);
+                self.done = true;
+                Ok(HostAction::Done(payload.into_inner()))
             }
-            other => {
-                return Err(LedgerError::BlockProtocol(format!(
-                    "unknown device message type: 0x{other:02X}"
-                )));
+            Some(&kind) if kind == DeviceMsg::PutChunk as u8 && !self.done => {
+                self.served_last_block = false;
+                payload.drain(..1);
+                self.put_store.insert(chunks::sha256(&payload), payload);
+                Ok(HostAction::Reply(vec![HostMsg::PutChunkResponse as u8]))
+            }
+            _ => self.handle(DeviceMessage::parse(&payload)?),
+        }
+    }
+}
+
+/// Run the block protocol for a given instruction, driving a
+/// [`ProtocolSession`] over `transport`.
+pub fn execute(
+    transport: &dyn Transport,
+    ins: Instruction,
+    params: &[Param<'_>],
+) -> Result<Vec<u8>, LedgerError> {
+    let mut session = ProtocolSession::new(params);
+    let mut response = send_apdu(transport, ins, session.start(), false)?;
+
+    loop {
+        let (payload, code) = response.into_parts();
+        let payload = Transient(payload);
+        if payload.is_empty() {
+            if code != 0 && !StatusWord::is_success(code) {
+                return Err(LedgerError::from_status(code));
+            }
+            return Err(LedgerError::BlockProtocol("empty response".into()));
+        }
+
+        match session.handle_payload(payload)? {
+            HostAction::Done(result) => return Ok(result),
+            HostAction::Reply(reply) => {
+                // After the last block of a param, the device may be
+                // showing a confirmation prompt while we wait for the
+                // next reply.
+                let awaiting_user = ins.needs_confirmation() && session.may_prompt();
+                response = send_apdu(transport, ins, reply, awaiting_user)?;
             }
         }
     }
@@ -129,19 +315,27 @@ pub fn execute(
 /// The block protocol has its own flow control via message type bytes,
 /// so the SW is irrelevant during exchanges -- matches the reference
 /// Python client.
+///
+/// With `awaiting_user`, a timeout is reported as
+/// [`LedgerError::UserTimeout`] rather than a transport failure.
 fn send_apdu(
     transport: &dyn Transport,
     ins: Instruction,
     data: Vec<u8>,
+    awaiting_user: bool,
 ) -> Result<ApduAnswer, LedgerError> {
-    let cmd = ApduCommand::with_data(ins as u8, data);
-    let answer = transport.exchange(&cmd).map_err(LedgerError::Transport)?;
+    let cmd = Transient(ApduCommand::with_data(ins as u8, data));
+    let answer = transport.exchange(&cmd).map_err(|e| match e {
+        TransportError::Timeout(ms) if awaiting_user => LedgerError::UserTimeout(ms),
+        e => LedgerError::Transport(e),
+    })?;
     Ok(answer)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use chunks::build_block_chain;
     use std::sync::Mutex;
 
     /// Mock transport that returns a sequence of pre-built APDU responses.
@@ -227,7 +421,7 @@ mod tests {
 
         let transport = MockTransport::new(vec![apdu_ok(&get_chunk), apdu_ok(&final_resp)]);
 
-        let result = execute(&transport, Instruction::GetVersion, &[param]).unwrap();
+        let result = execute(&transport, Instruction::GetVersion, &[param.into()]).unwrap();
         assert_eq!(result, b"ok");
     }
 
@@ -301,7 +495,7 @@ mod tests {
         let transport = MockTransport::new(vec![vec![0x69, 0x85]]);
 
         let err = execute(&transport, Instruction::GetVersion, &[]).unwrap_err();
-        assert!(matches!(err, LedgerError::UserRejected));
+        assert!(matches!(err, LedgerError::UserRejected { .. }));
     }
 
     #[test]
@@ -327,5 +521,138 @@ mod tests {
 
         let err = execute(&FailTransport, Instruction::GetVersion, &[]).unwrap_err();
         assert!(matches!(err, LedgerError::Transport(_)));
+        // No answer to the first exchange: the device never got as far as
+        // prompting.
+        let err = execute(&FailTransport, Instruction::SignTx, &[vec![1].into()]).unwrap_err();
+        assert!(matches!(
+            err,
+            LedgerError::Transport(crate::error::TransportError::Timeout(5000))
+        ));
+    }
+
+    #[test]
+    fn timeout_while_prompting_is_user_timeout() {
+        /// Asks for the first block, then never answers again.
+        struct PromptTransport(Mutex<Option<[u8; 32]>>);
+        impl Transport for PromptTransport {
+            fn exchange(
+                &self,
+                _cmd: &ApduCommand,
+            ) -> Result<ApduAnswer, crate::error::TransportError> {
+                match self.0.lock().unwrap().take() {
+                    Some(hash) => {
+                        let mut get_chunk = vec![DeviceMsg::GetChunk as u8];
+                        get_chunk.extend_from_slice(&hash);
+                        Ok(ApduAnswer::from_raw(apdu_ok(&get_chunk)))
+                    }
+                    None => Err(crate::error::TransportError::Timeout(30_000)),
+                }
+            }
+        }
+
+        let param = vec![0xAB; 10];
+        let hash = chunks::hash_block(&build_block_chain(&param)[0]);
+        let transport = PromptTransport(Mutex::new(Some(hash)));
+        let err = execute(&transport, Instruction::SignTx, &[param[..].into()]).unwrap_err();
+        assert!(matches!(err, LedgerError::UserTimeout(30_000)));
+        assert!(err.is_user_actionable());
+
+        // GetPubkey has no prompt, so it stays a transport timeout.
+        let transport = PromptTransport(Mutex::new(Some(hash)));
+        let err = execute(&transport, Instruction::GetPubkey, &[param.into()]).unwrap_err();
+        assert!(matches!(err, LedgerError::Transport(_)));
+
+        // Two blocks: the device still needs the second, so it can't be
+        // prompting yet.
+        let param = vec![0xAB; 200];
+        let hash = chunks::hash_block(&build_block_chain(&param)[0]);
+        let transport = PromptTransport(Mutex::new(Some(hash)));
+        let err = execute(&transport, Instruction::SignTx, &[param.into()]).unwrap_err();
+        assert!(matches!(
+            err,
+            LedgerError::Transport(crate::error::TransportError::Timeout(30_000))
+        ));
+
+        // An optional param the device never asks for (object data it
+        // blind-signs without) doesn't keep the prompt from counting.
+        let tx = vec![0xAB; 10];
+        let objects = vec![0xCD; 10];
+        let hash = chunks::hash_block(&build_block_chain(&tx)[0]);
+        let transport = PromptTransport(Mutex::new(Some(hash)));
+        let err = execute(
+            &transport,
+            Instruction::SignTx,
+            &[tx[..].into(), objects.into()],
+        )
+        .unwrap_err();
+        assert!(matches!(err, LedgerError::UserTimeout(30_000)));
+    }
+
+    #[test]
+    fn session_is_pure() {
+        let param = vec![0x11; 200];
+        let chain = build_block_chain(&param);
+        let first = chunks::hash_block(&chain[0]);
+        let params = [param[..].into()];
+        let mut session = ProtocolSession::new(&params);
+
+        let mut start = vec![HostMsg::Start as u8];
+        start.extend_from_slice(&first);
+        assert_eq!(session.start(), start);
+
+        let HostAction::Reply(reply) = session.handle(DeviceMessage::GetChunk(first)).unwrap()
+        else {
+            panic!("expected a reply");
+        };
+        assert_eq!(reply[0], HostMsg::GetChunkResponseSuccess as u8);
+        assert_eq!(reply[1..], chain[0].serialize());
+
+        assert_eq!(
+            session
+                .handle(DeviceMessage::ResultAccumulating(b"ab"))
+                .unwrap(),
+            HostAction::Reply(vec![HostMsg::ResultAccumulatingResponse as u8])
+        );
+        assert_eq!(
+            session.handle(DeviceMessage::ResultFinal(b"c")).unwrap(),
+            HostAction::Done(b"abc".to_vec())
+        );
+        assert!(matches!(
+            session.handle(DeviceMessage::ResultFinal(b"")),
+            Err(LedgerError::BlockProtocol(_))
+        ));
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn serves_the_blocks_of_the_eager_chain(
+            data in proptest::collection::vec(proptest::num::u8::ANY, 0..1000),
+            cuts in proptest::collection::vec(0usize..1000, 0..4),
+        ) {
+            let mut cuts: Vec<usize> = cuts.into_iter().map(|c| c.min(data.len())).collect();
+            cuts.sort_unstable();
+            let mut parts = Vec::new();
+            let mut start = 0;
+            for cut in cuts {
+                parts.push(Cow::Borrowed(&data[start..cut]));
+                start = cut;
+            }
+            parts.push(Cow::Borrowed(&data[start..]));
+            let params = [Param::from_parts(parts)];
+            let mut session = ProtocolSession::new(&params);
+
+            let chain = build_block_chain(&data);
+            proptest::prop_assert_eq!(&session.start()[1..], &chunks::hash_block(&chain[0])[..]);
+            for block in &chain {
+                let HostAction::Reply(reply) = session
+                    .handle(DeviceMessage::GetChunk(chunks::hash_block(block)))
+                    .unwrap()
+                else {
+                    panic!("expected a reply");
+                };
+                proptest::prop_assert_eq!(reply[0], HostMsg::GetChunkResponseSuccess as u8);
+                proptest::prop_assert_eq!(&reply[1..], &block.serialize()[..]);
+            }
+        }
     }
 }
@@ -0,0 +1,525 @@
+//! Queueing signed transactions until a node accepts them.
+//!
+//! A [`BroadcastQueue`] holds signed transactions in the order they were
+//! pushed and submits them with [`flush`](BroadcastQueue::flush). When
+//! the node can't be reached, the head of the queue is retried under a
+//! [`RetryPolicy`] and, if it still fails, left queued with everything
+//! behind it: a later transaction never overtakes an earlier one, since
+//! it may spend objects the earlier one creates. Signing can go on in
+//! the meantime; the next flush picks up where this one stopped.
+//!
+//! A [`BroadcastStore`] persists the queue after every change, so
+//! pending transactions survive restarts.
+
+use std::collections::VecDeque;
+use std::thread;
+use std::time::Duration;
+
+use serde_json::{json, Value};
+
+use super::execute::{execute_transaction_request, get_transaction, ExecutedTransaction};
+use super::RpcClient;
+use crate::error::LedgerError;
+use crate::objects::rpc::{str_field, u64_field};
+use crate::tx::TransactionData;
+use crate::types::TransactionDigest;
+
+/// A signed transaction waiting to be submitted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PendingTransaction {
+    /// Intent-prefixed transaction bytes.
+    pub tx: Vec<u8>,
+    /// Base64 user signatures (see
+    /// [`Signature::to_user_signature_base64`](crate::Signature::to_user_signature_base64)).
+    pub signatures: Vec<String>,
+    pub digest: TransactionDigest,
+    /// Submissions that failed to reach the node so far.
+    pub attempts: u32,
+}
+
+impl PendingTransaction {
+    /// Fails if `tx` doesn't decode, as it could never be submitted.
+    pub fn new(tx: Vec<u8>, signatures: Vec<String>) -> Result<Self, LedgerError> {
+        TransactionData::from_intent_bytes(&tx)?;
+        Ok(Self {
+            digest: TransactionDigest::from_intent_bytes(&tx)?,
+            tx,
+            signatures,
+            attempts: 0,
+        })
+    }
+
+    /// `{"tx": <base64>, "signatures": [...], "attempts": n}`, for stores.
+    #[must_use]
+    pub fn to_json(&self) -> Value {
+        json!({
+            "tx": crate::tx::to_base64(&self.tx),
+            "signatures": self.signatures,
+            "attempts": self.attempts,
+        })
+    }
+
+    /// Inverse of [`to_json`](Self::to_json).
+    pub fn from_json(value: &Value) -> Result<Self, LedgerError> {
+        let signatures = value
+            .get("signatures")
+            .and_then(Value::as_array)
+            .ok_or_else(|| LedgerError::InvalidEncoding("missing \"signatures\" array".into()))?
+            .iter()
+            .map(|s| {
+                s.as_str().map(str::to_owned).ok_or_else(|| {
+                    LedgerError::InvalidEncoding("signatures must be strings".into())
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        let mut pending = Self::new(crate::tx::from_base64(str_field(value, "tx")?)?, signatures)?;
+        pending.attempts = u64_field(value, "attempts")?.try_into().unwrap_or(u32::MAX);
+        Ok(pending)
+    }
+}
+
+/// Where the queue is persisted; errors are surfaced as
+/// [`LedgerError::BroadcastStore`].
+pub trait BroadcastStore: Send {
+    /// The queue as last saved, oldest first.
+    fn load(&self) -> std::io::Result<Vec<PendingTransaction>>;
+
+    /// Replace the saved queue with `pending`, oldest first.
+    fn save(&mut self, pending: &[PendingTransaction]) -> std::io::Result<()>;
+}
+
+/// Keeps nothing: the queue lives only in the [`BroadcastQueue`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MemoryBroadcastStore;
+
+impl BroadcastStore for MemoryBroadcastStore {
+    fn load(&self) -> std::io::Result<Vec<PendingTransaction>> {
+        Ok(Vec::new())
+    }
+
+    fn save(&mut self, _: &[PendingTransaction]) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// How [`BroadcastQueue::flush`] retries a submission that didn't reach
+/// the node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Submissions per flush before leaving the transaction queued.
+    pub max_attempts: u32,
+    /// Delay before the second attempt; doubled after each one.
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(8),
+        }
+    }
+}
+
+/// What happened to a transaction that left the queue.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BroadcastOutcome {
+    /// The node executed it (successfully or not; see
+    /// [`ExecutedTransaction::is_success`]).
+    Executed(ExecutedTransaction),
+    /// The node refused it, e.g. for an invalid signature or a stale
+    /// object version; resubmitting won't help.
+    Rejected {
+        pending: PendingTransaction,
+        error: String,
+    },
+}
+
+/// Result of one [`BroadcastQueue::flush`].
+#[derive(Debug, Default)]
+pub struct FlushReport {
+    /// Transactions that left the queue, in queue order.
+    pub outcomes: Vec<BroadcastOutcome>,
+    /// Why the flush stopped with transactions still queued: the last
+    /// error reaching the node.
+    pub stalled: Option<LedgerError>,
+}
+
+/// Signed transactions waiting to be submitted, oldest first.
+pub struct BroadcastQueue {
+    store: Box<dyn BroadcastStore>,
+    pending: VecDeque<PendingTransaction>,
+    policy: RetryPolicy,
+}
+
+impl BroadcastQueue {
+    /// Queue backed by `store`, starting with what it saved.
+    pub fn new(store: impl BroadcastStore + 'static) -> Result<Self, LedgerError> {
+        let pending = store.load().map_err(LedgerError::BroadcastStore)?;
+        Ok(Self {
+            store: Box::new(store),
+            pending: pending.into(),
+            policy: RetryPolicy::default(),
+        })
+    }
+
+    /// Queue kept in memory only.
+    #[must_use]
+    pub fn in_memory() -> Self {
+        Self {
+            store: Box::new(MemoryBroadcastStore),
+            pending: VecDeque::new(),
+            policy: RetryPolicy::default(),
+        }
+    }
+
+    #[must_use]
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Add `pending` at the end of the queue and persist it.
+    pub fn push(&mut self, pending: PendingTransaction) -> Result<(), LedgerError> {
+        self.pending.push_back(pending);
+        self.save()
+    }
+
+    /// Queued transactions, oldest first.
+    pub fn pending(&self) -> impl Iterator<Item = &PendingTransaction> {
+        self.pending.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Submit queued transactions in order until the queue is empty or
+    /// the node stays unreachable for the head of the queue.
+    ///
+    /// A failed call to `rpc`, a transient error answer (overload, timing
+    /// out before finality, an internal error) and an unreadable answer
+    /// are retried, the last after checking whether the node executed the
+    /// transaction anyway. Any other error answer rejects the transaction.
+    /// Fails only if the store can't be written.
+    pub fn flush(&mut self, rpc: &(impl RpcClient + ?Sized)) -> Result<FlushReport, LedgerError> {
+        let mut report = FlushReport::default();
+        while let Some(head) = self.pending.front_mut() {
+            match submit(rpc, head, &self.policy) {
+                Ok(outcome) => {
+                    self.pending.pop_front();
+                    report.outcomes.push(outcome);
+                    self.save()?;
+                }
+                Err(e) => {
+                    log::warn!(
+                        "transaction {} not submitted after {} attempts: {e}",
+                        head.digest,
+                        head.attempts
+                    );
+                    report.stalled = Some(e);
+                    self.save()?;
+                    break;
+                }
+            }
+        }
+        Ok(report)
+    }
+
+    fn save(&mut self) -> Result<(), LedgerError> {
+        self.store
+            .save(self.pending.make_contiguous())
+            .map_err(LedgerError::BroadcastStore)
+    }
+}
+
+impl std::fmt::Debug for BroadcastQueue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BroadcastQueue")
+            .field("pending", &self.pending.len())
+            .field("policy", &self.policy)
+            .finish_non_exhaustive()
+    }
+}
+
+/// JSON-RPC error codes the node uses for failures worth retrying:
+/// `-32050` for transient errors (overload, timing out before finality)
+/// and `-32603` for internal errors.
+const RETRYABLE_ERROR_CODES: [i64; 2] = [-32050, -32603];
+
+/// Whether an error answer from the node may go away on resubmission.
+fn is_retryable(error: &Value) -> bool {
+    error["code"]
+        .as_i64()
+        .is_some_and(|code| RETRYABLE_ERROR_CODES.contains(&code))
+        || error["message"]
+            .as_str()
+            .is_some_and(|message| message.contains("timed out before"))
+}
+
+/// Submit `pending` until the node answers or `policy` runs out; `Err`
+/// is the last error reaching the node.
+fn submit(
+    rpc: &(impl RpcClient + ?Sized),
+    pending: &mut PendingTransaction,
+    policy: &RetryPolicy,
+) -> Result<BroadcastOutcome, LedgerError> {
+    let request = execute_transaction_request(&pending.tx, &pending.signatures)?;
+    let max_attempts = policy.max_attempts.max(1);
+    let mut backoff = policy.initial_backoff;
+    let mut attempt = 1;
+    loop {
+        let error = match rpc.call(&request) {
+            Ok(response) => match response.get("error") {
+                Some(error) if !is_retryable(error) => {
+                    return Ok(BroadcastOutcome::Rejected {
+                        pending: pending.clone(),
+                        error: format!("iota_executeTransactionBlock: {error}"),
+                    });
+                }
+                Some(error) => LedgerError::Rpc(format!("iota_executeTransactionBlock: {error}")),
+                None => {
+                    match ExecutedTransaction::parse(response.get("result").unwrap_or(&response)) {
+                        Ok(executed) => return Ok(BroadcastOutcome::Executed(executed)),
+                        // The node took the transaction but the answer is
+                        // unusable; ask whether it was executed.
+                        Err(e) => match get_transaction(rpc, &pending.digest) {
+                            Ok(Some(executed)) => return Ok(BroadcastOutcome::Executed(executed)),
+                            Ok(None) => e,
+                            Err(lookup) => lookup,
+                        },
+                    }
+                }
+            },
+            Err(e) => e,
+        };
+        pending.attempts = pending.attempts.saturating_add(1);
+        if attempt == max_attempts {
+            return Err(error);
+        }
+        log::debug!(
+            "transaction {} not submitted, retrying in {backoff:?}: {error}",
+            pending.digest
+        );
+        thread::sleep(backoff);
+        backoff = (backoff * 2).min(policy.max_backoff);
+        attempt += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::amount::Amount;
+    use std::cell::RefCell;
+    use std::sync::{Arc, Mutex};
+
+    fn tx(version: u64) -> Vec<u8> {
+        crate::tx::build_transfer_tx(
+            &crate::types::Address([0xAA; 32]),
+            &crate::types::Address([0xBB; 32]),
+            Amount::from_nanos(5),
+            &crate::tx::ObjectRef {
+                object_id: [3; 32],
+                version,
+                digest: crate::types::ObjectDigest([0; 32]),
+            },
+            Amount::from_nanos(5_000_000),
+            1000,
+        )
+        .unwrap()
+    }
+
+    fn pending(version: u64) -> PendingTransaction {
+        PendingTransaction::new(tx(version), vec!["c2ln".into()]).unwrap()
+    }
+
+    fn fast() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(2),
+        }
+    }
+
+    fn executed(request: &Value) -> Value {
+        let bytes = crate::tx::from_base64(request["params"][0].as_str().unwrap()).unwrap();
+        json!({ "result": {
+            "digest": TransactionDigest::from_tx_data(&bytes).to_string(),
+            "effects": { "status": { "status": "success" } },
+        } })
+    }
+
+    #[derive(Clone, Default)]
+    struct SharedStore(Arc<Mutex<Vec<PendingTransaction>>>);
+
+    impl BroadcastStore for SharedStore {
+        fn load(&self) -> std::io::Result<Vec<PendingTransaction>> {
+            Ok(self.0.lock().unwrap().clone())
+        }
+
+        fn save(&mut self, pending: &[PendingTransaction]) -> std::io::Result<()> {
+            *self.0.lock().unwrap() = pending.to_vec();
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn submits_in_order_and_waits_out_outages() {
+        let store = SharedStore::default();
+        let mut queue = BroadcastQueue::new(store.clone())
+            .unwrap()
+            .with_retry_policy(fast());
+        for version in 1..=3 {
+            queue.push(pending(version)).unwrap();
+        }
+        assert_eq!(store.0.lock().unwrap().len(), 3);
+
+        // The node goes away after the first submission.
+        let submitted = RefCell::new(Vec::new());
+        let up = RefCell::new(true);
+        let node = |req: &Value| {
+            if !*up.borrow() {
+                return Err(LedgerError::Rpc("connection refused".into()));
+            }
+            *up.borrow_mut() = false;
+            submitted.borrow_mut().push(req["params"][0].clone());
+            Ok(executed(req))
+        };
+        let report = queue.flush(&node).unwrap();
+        assert_eq!(report.outcomes.len(), 1);
+        assert!(matches!(report.stalled, Some(LedgerError::Rpc(_))));
+        assert_eq!(queue.len(), 2);
+        assert_eq!(queue.pending().next().unwrap().attempts, 3);
+
+        // A restarted process picks the queue up from the store.
+        let mut queue = BroadcastQueue::new(store.clone()).unwrap();
+        assert_eq!(queue.len(), 2);
+        *up.borrow_mut() = true;
+        let always_up = |req: &Value| {
+            submitted.borrow_mut().push(req["params"][0].clone());
+            Ok(executed(req))
+        };
+        let report = queue.flush(&always_up).unwrap();
+        assert!(report.stalled.is_none());
+        assert_eq!(report.outcomes.len(), 2);
+        assert!(queue.is_empty());
+        assert!(store.0.lock().unwrap().is_empty());
+
+        let expected: Vec<Value> = (1..=3)
+            .map(|v| json!(crate::tx::to_base64(&tx(v)[3..])))
+            .collect();
+        assert_eq!(*submitted.borrow(), expected);
+    }
+
+    #[test]
+    fn rejected_transactions_leave_the_queue() {
+        let mut queue = BroadcastQueue::in_memory().with_retry_policy(fast());
+        queue.push(pending(1)).unwrap();
+        queue.push(pending(2)).unwrap();
+        let node = |req: &Value| {
+            if req["params"][0] == json!(crate::tx::to_base64(&tx(1)[3..])) {
+                return Ok(json!({ "error": { "code": -32002, "message": "invalid signature" } }));
+            }
+            Ok(executed(req))
+        };
+        let report = queue.flush(&node).unwrap();
+        assert!(queue.is_empty());
+        let [BroadcastOutcome::Rejected {
+            pending: rejected,
+            error,
+        }, BroadcastOutcome::Executed(done)] = &report.outcomes[..]
+        else {
+            panic!("{:?}", report.outcomes);
+        };
+        assert_eq!(rejected.digest, pending(1).digest);
+        assert!(error.contains("invalid signature"));
+        assert_eq!(done.digest, pending(2).digest);
+    }
+
+    #[test]
+    fn transient_node_errors_are_retried() {
+        let mut queue = BroadcastQueue::in_memory().with_retry_policy(fast());
+        queue.push(pending(1)).unwrap();
+        let calls = RefCell::new(0);
+        let node = |req: &Value| {
+            *calls.borrow_mut() += 1;
+            Ok(match *calls.borrow() {
+                1 => json!({ "error": { "code": -32050, "message": "system overloaded" } }),
+                2 => json!({ "error": {
+                    "code": -32000,
+                    "message": "Transaction timed out before reaching finality",
+                } }),
+                _ => executed(req),
+            })
+        };
+        let report = queue.flush(&node).unwrap();
+        assert!(report.stalled.is_none());
+        assert!(matches!(
+            report.outcomes[..],
+            [BroadcastOutcome::Executed(_)]
+        ));
+        assert_eq!(*calls.borrow(), 3);
+
+        // Still failing after the last attempt: left queued.
+        queue.push(pending(2)).unwrap();
+        let overloaded =
+            |_: &Value| Ok(json!({ "error": { "code": -32603, "message": "internal error" } }));
+        let report = queue.flush(&overloaded).unwrap();
+        assert!(matches!(report.stalled, Some(LedgerError::Rpc(_))));
+        assert_eq!(queue.pending().next().unwrap().attempts, 3);
+    }
+
+    #[test]
+    fn unreadable_answers_are_looked_up() {
+        let mut queue = BroadcastQueue::in_memory().with_retry_policy(fast());
+        queue.push(pending(1)).unwrap();
+        let submissions = RefCell::new(0);
+        let node = |req: &Value| {
+            if req["method"] == "iota_getTransactionBlock" {
+                return Ok(json!({ "result": {
+                    "digest": req["params"][0],
+                    "effects": { "status": { "status": "success" } },
+                } }));
+            }
+            *submissions.borrow_mut() += 1;
+            Ok(json!({ "result": { "unexpected": true } }))
+        };
+        let report = queue.flush(&node).unwrap();
+        assert!(queue.is_empty());
+        let [BroadcastOutcome::Executed(done)] = &report.outcomes[..] else {
+            panic!("{:?}", report.outcomes);
+        };
+        assert_eq!(done.digest, pending(1).digest);
+        assert_eq!(*submissions.borrow(), 1);
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let mut original = pending(7);
+        original.attempts = 4;
+        assert_eq!(
+            PendingTransaction::from_json(&original.to_json()).unwrap(),
+            original
+        );
+        assert!(PendingTransaction::from_json(&json!({ "tx": "AAAA" })).is_err());
+        assert!(PendingTransaction::new(vec![0, 0, 0, 1], Vec::new()).is_err());
+    }
+
+    #[test]
+    fn attempts_saturate() {
+        let mut saved = pending(1).to_json();
+        saved["attempts"] = json!(u64::MAX);
+        let mut stuck = PendingTransaction::from_json(&saved).unwrap();
+        assert_eq!(stuck.attempts, u32::MAX);
+        let down = |_: &Value| Err(LedgerError::Rpc("connection refused".into()));
+        assert!(submit(&down, &mut stuck, &fast()).is_err());
+        assert_eq!(stuck.attempts, u32::MAX);
+    }
+}
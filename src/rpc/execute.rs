@@ -0,0 +1,319 @@
+//! Submitting signed transactions and following them to finality.
+//!
+//! A wallet moves a transaction from signed to submitted with
+//! [`execute_transaction`] and from submitted to finalized with
+//! [`wait_for_finality`], which polls until a checkpoint includes it.
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde_json::{json, Value};
+
+use super::RpcClient;
+use crate::error::LedgerError;
+use crate::objects::rpc::{str_field, u64_field};
+use crate::tx::TransactionData;
+use crate::types::TransactionDigest;
+
+fn effects_options() -> Value {
+    json!({ "showEffects": true })
+}
+
+/// `iota_executeTransactionBlock` request for intent-prefixed `tx` and
+/// its base64 user signatures (see
+/// [`Signature::to_user_signature_base64`](crate::Signature::to_user_signature_base64)).
+pub fn execute_transaction_request(tx: &[u8], signatures: &[String]) -> Result<Value, LedgerError> {
+    let mut bytes = Vec::new();
+    TransactionData::from_intent_bytes(tx)?.encode_into(&mut bytes);
+    Ok(json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "iota_executeTransactionBlock",
+        "params": [crate::tx::to_base64(&bytes), signatures, effects_options()],
+    }))
+}
+
+/// `iota_getTransactionBlock` request for `digest`, with effects.
+#[must_use]
+pub fn get_transaction_request(digest: &TransactionDigest) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "iota_getTransactionBlock",
+        "params": [digest.to_string(), effects_options()],
+    })
+}
+
+/// A transaction the node knows, with its effects.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExecutedTransaction {
+    pub digest: TransactionDigest,
+    /// `effects` as returned by the node.
+    pub effects: Value,
+    /// Checkpoint including the transaction; `None` until finalized.
+    pub checkpoint: Option<u64>,
+}
+
+impl ExecutedTransaction {
+    pub(super) fn parse(result: &Value) -> Result<Self, LedgerError> {
+        Ok(Self {
+            digest: str_field(result, "digest")?.parse()?,
+            effects: result.get("effects").cloned().unwrap_or(Value::Null),
+            checkpoint: result
+                .get("checkpoint")
+                .filter(|c| !c.is_null())
+                .map(|_| u64_field(result, "checkpoint"))
+                .transpose()?,
+        })
+    }
+
+    #[must_use]
+    pub fn is_finalized(&self) -> bool {
+        self.checkpoint.is_some()
+    }
+
+    /// Whether execution succeeded; aborted transactions still use gas.
+    #[must_use]
+    pub fn is_success(&self) -> bool {
+        self.effects["status"]["status"] == "success"
+    }
+
+    /// Abort reason of a failed transaction.
+    #[must_use]
+    pub fn error(&self) -> Option<&str> {
+        self.effects["status"]["error"].as_str()
+    }
+}
+
+fn rpc_error(method: &str, response: &Value) -> Option<LedgerError> {
+    response
+        .get("error")
+        .map(|error| LedgerError::Rpc(format!("{method}: {error}")))
+}
+
+/// Submit intent-prefixed `tx` with its user signatures.
+pub fn execute_transaction(
+    rpc: &(impl RpcClient + ?Sized),
+    tx: &[u8],
+    signatures: &[String],
+) -> Result<ExecutedTransaction, LedgerError> {
+    let response = rpc.call(&execute_transaction_request(tx, signatures)?)?;
+    if let Some(e) = rpc_error("iota_executeTransactionBlock", &response) {
+        return Err(e);
+    }
+    ExecutedTransaction::parse(response.get("result").unwrap_or(&response))
+}
+
+/// [`execute_transaction`] unless the node already knows the digest of
+/// `tx`, for jobs that may be retried after a successful submission.
+///
+/// Fails with [`LedgerError::AlreadyExecuted`] carrying the original
+/// effects instead of submitting again.
+pub fn execute_transaction_once(
+    rpc: &(impl RpcClient + ?Sized),
+    tx: &[u8],
+    signatures: &[String],
+) -> Result<ExecutedTransaction, LedgerError> {
+    let digest = TransactionDigest::from_intent_bytes(tx)?;
+    if let Some(executed) = get_transaction(rpc, &digest)? {
+        log::debug!("transaction {digest} already executed, not resubmitting");
+        return Err(LedgerError::AlreadyExecuted(Box::new(executed)));
+    }
+    execute_transaction(rpc, tx, signatures)
+}
+
+/// The transaction with `digest`, or `None` if the node doesn't know it.
+pub fn get_transaction(
+    rpc: &(impl RpcClient + ?Sized),
+    digest: &TransactionDigest,
+) -> Result<Option<ExecutedTransaction>, LedgerError> {
+    let response = rpc.call(&get_transaction_request(digest))?;
+    if let Some(error) = response.get("error") {
+        let message = error["message"].as_str().unwrap_or_default();
+        if message.contains("Could not find") {
+            return Ok(None);
+        }
+        return Err(LedgerError::Rpc(format!(
+            "iota_getTransactionBlock: {error}"
+        )));
+    }
+    ExecutedTransaction::parse(response.get("result").unwrap_or(&response)).map(Some)
+}
+
+/// How [`wait_for_finality`] polls.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FinalityPolicy {
+    /// Give up after this long.
+    pub timeout: Duration,
+    /// Delay before the second poll; doubled after each one.
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl Default for FinalityPolicy {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(60),
+            initial_backoff: Duration::from_millis(250),
+            max_backoff: Duration::from_secs(4),
+        }
+    }
+}
+
+/// Poll until a checkpoint includes the transaction with `digest` and
+/// return its final effects, or fail with
+/// [`LedgerError::FinalityTimeout`] once `policy.timeout` has passed.
+///
+/// A transaction the node doesn't know yet counts as pending, so this
+/// can follow a submission made elsewhere.
+pub fn wait_for_finality(
+    rpc: &(impl RpcClient + ?Sized),
+    digest: &TransactionDigest,
+    policy: &FinalityPolicy,
+) -> Result<ExecutedTransaction, LedgerError> {
+    let start = Instant::now();
+    let mut backoff = policy.initial_backoff;
+    loop {
+        if let Some(tx) = get_transaction(rpc, digest)? {
+            if tx.is_finalized() {
+                return Ok(tx);
+            }
+        }
+        let waited = start.elapsed();
+        if waited >= policy.timeout {
+            return Err(LedgerError::FinalityTimeout {
+                digest: *digest,
+                waited,
+            });
+        }
+        log::debug!("transaction {digest} not final after {waited:?}, retrying in {backoff:?}");
+        thread::sleep(backoff.min(policy.timeout - waited));
+        backoff = (backoff * 2).min(policy.max_backoff);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::amount::Amount;
+    use std::cell::Cell;
+
+    fn tx() -> Vec<u8> {
+        crate::tx::build_transfer_tx(
+            &crate::types::Address([0xAA; 32]),
+            &crate::types::Address([0xBB; 32]),
+            Amount::from_nanos(5),
+            &crate::tx::ObjectRef {
+                object_id: [3; 32],
+                version: 9,
+                digest: crate::types::ObjectDigest([0; 32]),
+            },
+            Amount::from_nanos(5_000_000),
+            1000,
+        )
+        .unwrap()
+    }
+
+    fn fast() -> FinalityPolicy {
+        FinalityPolicy {
+            timeout: Duration::from_millis(50),
+            initial_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(4),
+        }
+    }
+
+    fn known(digest: &TransactionDigest, checkpoint: Option<u64>) -> Value {
+        json!({ "result": {
+            "digest": digest.to_string(),
+            "effects": { "status": { "status": "success" } },
+            "checkpoint": checkpoint.map(|c| c.to_string()),
+        } })
+    }
+
+    #[test]
+    fn executes_without_the_intent_prefix() {
+        let tx = tx();
+        let digest = TransactionDigest::from_intent_bytes(&tx).unwrap();
+        let node = |req: &Value| {
+            assert_eq!(req["method"], "iota_executeTransactionBlock");
+            let sent = crate::tx::from_base64(req["params"][0].as_str().unwrap()).unwrap();
+            assert_eq!(sent, tx[3..]);
+            assert_eq!(req["params"][1][0], "c2ln");
+            Ok(json!({ "result": {
+                "digest": digest.to_string(),
+                "effects": { "status": { "status": "failure", "error": "InsufficientGas" } },
+            } }))
+        };
+        let executed = execute_transaction(&node, &tx, &["c2ln".into()]).unwrap();
+        assert_eq!(executed.digest, digest);
+        assert!(!executed.is_finalized());
+        assert!(!executed.is_success());
+        assert_eq!(executed.error(), Some("InsufficientGas"));
+    }
+
+    #[test]
+    fn refuses_to_replay() {
+        let tx = tx();
+        let digest = TransactionDigest::from_intent_bytes(&tx).unwrap();
+        let submitted = Cell::new(false);
+        let node = |req: &Value| {
+            if req["method"] == "iota_executeTransactionBlock" {
+                submitted.set(true);
+                return Ok(known(&digest, None));
+            }
+            Ok(if submitted.get() {
+                known(&digest, Some(42))
+            } else {
+                json!({ "error": { "code": -32602, "message": "Could not find the referenced transaction" } })
+            })
+        };
+        execute_transaction_once(&node, &tx, &["c2ln".into()]).unwrap();
+        assert!(submitted.get());
+
+        let err = execute_transaction_once(&node, &tx, &["c2ln".into()]).unwrap_err();
+        let LedgerError::AlreadyExecuted(original) = &err else {
+            panic!("{err}");
+        };
+        assert_eq!(original.digest, digest);
+        assert_eq!(original.checkpoint, Some(42));
+        assert!(err.is_fatal());
+        assert_eq!(err.code().code(), 504);
+    }
+
+    #[test]
+    fn waits_for_a_checkpoint() {
+        let digest = TransactionDigest([7; 32]);
+        let polls = Cell::new(0);
+        let node = |req: &Value| {
+            assert_eq!(req["method"], "iota_getTransactionBlock");
+            polls.set(polls.get() + 1);
+            Ok(match polls.get() {
+                1 => json!({ "error": {
+                    "code": -32602,
+                    "message": "Could not find the referenced transaction",
+                } }),
+                2 => known(&digest, None),
+                _ => known(&digest, Some(1234)),
+            })
+        };
+        let executed = wait_for_finality(&node, &digest, &fast()).unwrap();
+        assert_eq!(executed.checkpoint, Some(1234));
+        assert!(executed.is_success());
+        assert_eq!(polls.get(), 3);
+    }
+
+    #[test]
+    fn times_out() {
+        let digest = TransactionDigest([7; 32]);
+        let node = |_: &Value| Ok(known(&digest, None));
+        let err = wait_for_finality(&node, &digest, &fast()).unwrap_err();
+        assert!(matches!(err, LedgerError::FinalityTimeout { .. }));
+        assert!(err.is_retryable());
+
+        let broken = |_: &Value| Ok(json!({ "error": { "code": -32000, "message": "boom" } }));
+        assert!(matches!(
+            wait_for_finality(&broken, &digest, &fast()),
+            Err(LedgerError::Rpc(_))
+        ));
+    }
+}
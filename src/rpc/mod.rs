@@ -0,0 +1,999 @@
+//! Object resolution over JSON-RPC.
+//!
+//! The crate doesn't ship an HTTP client; implement [`RpcClient`] on top of
+//! whichever one the application already uses. [`resolve_objects`] then
+//! fetches the coin objects a transaction touches so
+//! [`LedgerIota::sign_tx_resolving`](crate::LedgerIota::sign_tx_resolving)
+//! can clear-sign without the caller assembling [`ObjectData`] by hand.
+//! [`execute_transaction`] and [`wait_for_finality`] submit the signed
+//! result and follow it to a checkpoint; a [`BroadcastQueue`] holds signed
+//! transactions while the node is unreachable.
+
+pub mod broadcast;
+pub mod execute;
+
+pub use broadcast::{
+    BroadcastOutcome, BroadcastQueue, BroadcastStore, FlushReport, MemoryBroadcastStore,
+    PendingTransaction, RetryPolicy,
+};
+pub use execute::{
+    execute_transaction, execute_transaction_once, get_transaction, wait_for_finality,
+    ExecutedTransaction, FinalityPolicy,
+};
+
+use serde_json::{json, Value};
+
+use crate::amount::Amount;
+use crate::error::LedgerError;
+#[cfg(feature = "object-cache")]
+use crate::object_cache::ObjectCache;
+use crate::objects::rpc::{str_field, u64_field};
+use crate::objects::{ObjectData, StructTag};
+use crate::tx::{ObjectRef, TransactionData, TransactionExpiration};
+use crate::types::{Address, ObjectDigest, TransactionDigest};
+
+/// Sends one JSON-RPC request to a node.
+///
+/// Implementations POST `request` to the node URL and return the parsed
+/// response body (including the `result`/`error` envelope). Transport
+/// failures should be reported as [`LedgerError::Rpc`].
+pub trait RpcClient {
+    fn call(&self, request: &Value) -> Result<Value, LedgerError>;
+}
+
+impl<F> RpcClient for F
+where
+    F: Fn(&Value) -> Result<Value, LedgerError>,
+{
+    fn call(&self, request: &Value) -> Result<Value, LedgerError> {
+        self(request)
+    }
+}
+
+/// Everything [`ObjectData::from_rpc_json`] needs.
+fn object_options() -> Value {
+    json!({
+        "showType": true,
+        "showOwner": true,
+        "showPreviousTransaction": true,
+        "showStorageRebate": true,
+        "showBcs": true,
+    })
+}
+
+/// `iota_multiGetObjects` request for `ids`.
+#[must_use]
+pub fn multi_get_objects_request(ids: &[[u8; 32]]) -> Value {
+    let ids: Vec<String> = ids
+        .iter()
+        .map(|id| format!("0x{}", hex::encode(id)))
+        .collect();
+    json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "iota_multiGetObjects",
+        "params": [ids, object_options()],
+    })
+}
+
+/// `iotax_getCoins` request for one page of `owner`'s coins of
+/// `coin_type` (IOTA if `None`).
+#[must_use]
+pub fn get_coins_request(
+    owner: &[u8; 32],
+    coin_type: Option<&str>,
+    cursor: Option<&str>,
+    limit: Option<usize>,
+) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "iotax_getCoins",
+        "params": [format!("0x{}", hex::encode(owner)), coin_type, cursor, limit],
+    })
+}
+
+/// One entry of an `iotax_getCoins` / `iotax_getAllCoins` page.
+#[derive(Debug, Clone)]
+pub struct CoinInfo {
+    pub coin_type: StructTag,
+    /// Ready to use as a [`GasCoinRef`](crate::GasCoinRef) or an owned
+    /// object input; pass to [`fetch_objects`] for its [`ObjectData`].
+    pub object_ref: ObjectRef,
+    /// In the coin's smallest unit.
+    pub balance: u64,
+    pub previous_transaction: TransactionDigest,
+}
+
+/// A page of coins plus the cursor for the next one (`None` on the last
+/// page).
+pub fn parse_coins(response: &Value) -> Result<(Vec<CoinInfo>, Option<String>), LedgerError> {
+    if let Some(error) = response.get("error") {
+        return Err(LedgerError::Rpc(format!("iotax_getCoins: {error}")));
+    }
+    let page = response.get("result").unwrap_or(response);
+    let data = page
+        .get("data")
+        .and_then(Value::as_array)
+        .ok_or_else(|| LedgerError::Rpc("iotax_getCoins: missing data array".into()))?;
+
+    let coins = data
+        .iter()
+        .map(|coin| {
+            Ok(CoinInfo {
+                coin_type: str_field(coin, "coinType")?.parse()?,
+                object_ref: ObjectRef {
+                    object_id: str_field(coin, "coinObjectId")?.parse::<Address>()?.0,
+                    version: u64_field(coin, "version")?,
+                    digest: str_field(coin, "digest")?.parse::<ObjectDigest>()?,
+                },
+                balance: u64_field(coin, "balance")?,
+                previous_transaction: str_field(coin, "previousTransaction")?.parse()?,
+            })
+        })
+        .collect::<Result<Vec<_>, LedgerError>>()?;
+
+    let has_next = page.get("hasNextPage").and_then(Value::as_bool) == Some(true);
+    let cursor = page
+        .get("nextCursor")
+        .and_then(Value::as_str)
+        .filter(|_| has_next)
+        .map(str::to_owned);
+    Ok((coins, cursor))
+}
+
+/// `iotax_getAllBalances` request for `owner`.
+#[must_use]
+pub fn get_all_balances_request(owner: &[u8; 32]) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "iotax_getAllBalances",
+        "params": [format!("0x{}", hex::encode(owner))],
+    })
+}
+
+/// Total balance of one coin type held by an address.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Balance {
+    pub coin_type: StructTag,
+    pub coin_object_count: u64,
+    /// In the coin's smallest unit.
+    pub total_balance: u128,
+}
+
+pub fn parse_balances(response: &Value) -> Result<Vec<Balance>, LedgerError> {
+    if let Some(error) = response.get("error") {
+        return Err(LedgerError::Rpc(format!("iotax_getAllBalances: {error}")));
+    }
+    response
+        .get("result")
+        .unwrap_or(response)
+        .as_array()
+        .ok_or_else(|| LedgerError::Rpc("iotax_getAllBalances: missing result array".into()))?
+        .iter()
+        .map(|balance| {
+            let total = str_field(balance, "totalBalance")?;
+            Ok(Balance {
+                coin_type: str_field(balance, "coinType")?.parse()?,
+                coin_object_count: u64_field(balance, "coinObjectCount")?,
+                total_balance: total.parse().map_err(|_| {
+                    LedgerError::Rpc(format!("iotax_getAllBalances: bad balance {total:?}"))
+                })?,
+            })
+        })
+        .collect()
+}
+
+/// `iotax_getOwnedObjects` request for one page of `owner`'s objects,
+/// with their types.
+#[must_use]
+pub fn get_owned_objects_request(
+    owner: &[u8; 32],
+    cursor: Option<&str>,
+    limit: Option<usize>,
+) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "iotax_getOwnedObjects",
+        "params": [
+            format!("0x{}", hex::encode(owner)),
+            { "options": { "showType": true } },
+            cursor,
+            limit,
+        ],
+    })
+}
+
+/// One entry of an `iotax_getOwnedObjects` page.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OwnedObject {
+    pub object_ref: ObjectRef,
+    /// Move type as reported, e.g. `0x2::coin::Coin<0x2::iota::IOTA>`.
+    pub type_: Option<String>,
+}
+
+/// A page of owned objects plus the cursor for the next one (`None` on
+/// the last page). Entries the node reports as errors are skipped.
+pub fn parse_owned_objects(
+    response: &Value,
+) -> Result<(Vec<OwnedObject>, Option<String>), LedgerError> {
+    if let Some(error) = response.get("error") {
+        return Err(LedgerError::Rpc(format!("iotax_getOwnedObjects: {error}")));
+    }
+    let page = response.get("result").unwrap_or(response);
+    let data = page
+        .get("data")
+        .and_then(Value::as_array)
+        .ok_or_else(|| LedgerError::Rpc("iotax_getOwnedObjects: missing data array".into()))?;
+
+    let objects = data
+        .iter()
+        .filter_map(|entry| entry.get("data"))
+        .map(|obj| {
+            Ok(OwnedObject {
+                object_ref: ObjectRef {
+                    object_id: str_field(obj, "objectId")?.parse::<Address>()?.0,
+                    version: u64_field(obj, "version")?,
+                    digest: str_field(obj, "digest")?.parse::<ObjectDigest>()?,
+                },
+                type_: obj.get("type").and_then(Value::as_str).map(str::to_owned),
+            })
+        })
+        .collect::<Result<Vec<_>, LedgerError>>()?;
+
+    let has_next = page.get("hasNextPage").and_then(Value::as_bool) == Some(true);
+    let cursor = page
+        .get("nextCursor")
+        .and_then(Value::as_str)
+        .filter(|_| has_next)
+        .map(str::to_owned);
+    Ok((objects, cursor))
+}
+
+/// Which side of a transaction [`query_transactions_request`] matches an
+/// address on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AddressFilter {
+    /// Transactions the address sent.
+    From,
+    /// Transactions that sent objects to the address.
+    To,
+}
+
+/// `iotax_queryTransactionBlocks` request for one page of the digests of
+/// transactions from or to `address`, newest first.
+#[must_use]
+pub fn query_transactions_request(
+    filter: AddressFilter,
+    address: &[u8; 32],
+    cursor: Option<&str>,
+    limit: Option<usize>,
+) -> Value {
+    let address = format!("0x{}", hex::encode(address));
+    let filter = match filter {
+        AddressFilter::From => json!({ "FromAddress": address }),
+        AddressFilter::To => json!({ "ToAddress": address }),
+    };
+    json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "iotax_queryTransactionBlocks",
+        "params": [{ "filter": filter, "options": {} }, cursor, limit, true],
+    })
+}
+
+/// A page of transaction digests plus the cursor for the next one (`None`
+/// on the last page).
+pub fn parse_transaction_digests(
+    response: &Value,
+) -> Result<(Vec<TransactionDigest>, Option<String>), LedgerError> {
+    if let Some(error) = response.get("error") {
+        return Err(LedgerError::Rpc(format!(
+            "iotax_queryTransactionBlocks: {error}"
+        )));
+    }
+    let page = response.get("result").unwrap_or(response);
+    let digests = page
+        .get("data")
+        .and_then(Value::as_array)
+        .ok_or_else(|| LedgerError::Rpc("iotax_queryTransactionBlocks: missing data array".into()))?
+        .iter()
+        .map(|tx| str_field(tx, "digest")?.parse::<TransactionDigest>())
+        .collect::<Result<Vec<_>, LedgerError>>()?;
+
+    let has_next = page.get("hasNextPage").and_then(Value::as_bool) == Some(true);
+    let cursor = page
+        .get("nextCursor")
+        .and_then(Value::as_str)
+        .filter(|_| has_next)
+        .map(str::to_owned);
+    Ok((digests, cursor))
+}
+
+/// `iotax_getReferenceGasPrice` request.
+#[must_use]
+pub fn reference_gas_price_request() -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "iotax_getReferenceGasPrice",
+        "params": [],
+    })
+}
+
+pub fn parse_reference_gas_price(response: &Value) -> Result<u64, LedgerError> {
+    if let Some(error) = response.get("error") {
+        return Err(LedgerError::Rpc(format!(
+            "iotax_getReferenceGasPrice: {error}"
+        )));
+    }
+    let result = response.get("result").unwrap_or(response);
+    result
+        .as_u64()
+        .or_else(|| result.as_str().and_then(|s| s.parse().ok()))
+        .ok_or_else(|| LedgerError::Rpc(format!("iotax_getReferenceGasPrice: bad price {result}")))
+}
+
+/// `iota_dryRunTransactionBlock` request for `tx`.
+#[must_use]
+pub fn dry_run_request(tx: &TransactionData) -> Value {
+    let mut bytes = Vec::new();
+    tx.encode_into(&mut bytes);
+    json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "iota_dryRunTransactionBlock",
+        "params": [crate::tx::to_base64(&bytes)],
+    })
+}
+
+/// Gas a dry run charged, in nanos.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GasUsed {
+    pub computation_cost: u64,
+    pub storage_cost: u64,
+    pub storage_rebate: u64,
+}
+
+/// Gas used by a dry run; fails with [`LedgerError::Rpc`] if the
+/// transaction would abort.
+pub fn parse_dry_run(response: &Value) -> Result<GasUsed, LedgerError> {
+    if let Some(error) = response.get("error") {
+        return Err(LedgerError::Rpc(format!(
+            "iota_dryRunTransactionBlock: {error}"
+        )));
+    }
+    let effects = response
+        .get("result")
+        .unwrap_or(response)
+        .get("effects")
+        .ok_or_else(|| LedgerError::Rpc("iota_dryRunTransactionBlock: missing effects".into()))?;
+    let status = &effects["status"];
+    if status["status"] != "success" {
+        return Err(LedgerError::Rpc(format!(
+            "dry run failed: {}",
+            status
+                .get("error")
+                .and_then(Value::as_str)
+                .unwrap_or("unknown error")
+        )));
+    }
+    let gas = &effects["gasUsed"];
+    Ok(GasUsed {
+        computation_cost: u64_field(gas, "computationCost")?,
+        storage_cost: u64_field(gas, "storageCost")?,
+        storage_rebate: u64_field(gas, "storageRebate")?,
+    })
+}
+
+/// Safety margin [`estimate_gas`] adds to the dry run's gas usage.
+pub const DEFAULT_GAS_MARGIN_PERCENT: u32 = 20;
+
+/// Recommended gas settings for a transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GasEstimate {
+    /// The network's reference gas price.
+    pub gas_price: u64,
+    pub used: GasUsed,
+    /// Computation plus storage cost, plus the margin. Storage rebates
+    /// are only paid out after execution, so they don't lower it.
+    pub budget: Amount,
+}
+
+/// [`estimate_gas_with_margin`] with [`DEFAULT_GAS_MARGIN_PERCENT`].
+pub fn estimate_gas(
+    tx: &TransactionData,
+    rpc: &(impl RpcClient + ?Sized),
+) -> Result<GasEstimate, LedgerError> {
+    estimate_gas_with_margin(tx, rpc, DEFAULT_GAS_MARGIN_PERCENT)
+}
+
+/// Dry-run `tx` at the reference gas price and recommend a budget of its
+/// gas usage plus `margin_percent`. `tx.gas_budget` is the most the dry
+/// run may spend, so it must fit the gas coins.
+pub fn estimate_gas_with_margin(
+    tx: &TransactionData,
+    rpc: &(impl RpcClient + ?Sized),
+    margin_percent: u32,
+) -> Result<GasEstimate, LedgerError> {
+    let gas_price = parse_reference_gas_price(&rpc.call(&reference_gas_price_request())?)?;
+    estimate_at(tx, rpc, gas_price, margin_percent)
+}
+
+fn estimate_at(
+    tx: &TransactionData,
+    rpc: &(impl RpcClient + ?Sized),
+    gas_price: u64,
+    margin_percent: u32,
+) -> Result<GasEstimate, LedgerError> {
+    let mut probe = tx.clone();
+    probe.gas_price = gas_price;
+    let used = parse_dry_run(&rpc.call(&dry_run_request(&probe))?)?;
+
+    let cost = u128::from(used.computation_cost) + u128::from(used.storage_cost);
+    let budget = cost * (100 + u128::from(margin_percent)) / 100;
+    let budget = u64::try_from(budget)
+        .map_err(|_| LedgerError::InvalidAmount(format!("gas budget {budget} overflows u64")))?;
+    Ok(GasEstimate {
+        gas_price,
+        used,
+        budget: Amount::from_nanos(budget),
+    })
+}
+
+/// Build a transaction with an automatic gas budget: `build(budget,
+/// gas_price)` is called once with `max_budget` for a dry run and again
+/// with the [estimated](estimate_gas) budget, at the reference gas price.
+///
+/// Fails with [`LedgerError::InvalidAmount`] if the estimate exceeds
+/// `max_budget`.
+///
+/// ```no_run
+/// # use ledger_iota::{build_transfer_tx, Address, Amount, GasCoinRef};
+/// # fn demo(client: &dyn ledger_iota::rpc::RpcClient, sender: Address, recipient: Address, gas: GasCoinRef) -> Result<(), ledger_iota::LedgerError> {
+/// let tx = ledger_iota::rpc::build_with_auto_gas(client, Amount::from_nanos(50_000_000), |budget, price| {
+///     build_transfer_tx(&sender, &recipient, Amount::from_nanos(1_000), &gas, budget, price)
+/// })?;
+/// # Ok(()) }
+/// ```
+pub fn build_with_auto_gas(
+    rpc: &(impl RpcClient + ?Sized),
+    max_budget: Amount,
+    build: impl Fn(Amount, u64) -> Result<Vec<u8>, LedgerError>,
+) -> Result<Vec<u8>, LedgerError> {
+    let gas_price = parse_reference_gas_price(&rpc.call(&reference_gas_price_request())?)?;
+    let probe = TransactionData::from_intent_bytes(&build(max_budget, gas_price)?)?;
+    let estimate = estimate_at(&probe, rpc, gas_price, DEFAULT_GAS_MARGIN_PERCENT)?;
+    if estimate.budget > max_budget {
+        return Err(LedgerError::InvalidAmount(format!(
+            "estimated gas budget {} exceeds the maximum {max_budget}",
+            estimate.budget
+        )));
+    }
+    build(estimate.budget, gas_price)
+}
+
+/// `iotax_getLatestIotaSystemState` request.
+#[must_use]
+pub fn latest_system_state_request() -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "iotax_getLatestIotaSystemState",
+        "params": [],
+    })
+}
+
+/// The `epoch` of an `iotax_getLatestIotaSystemState` response.
+pub fn parse_epoch(response: &Value) -> Result<u64, LedgerError> {
+    if let Some(error) = response.get("error") {
+        return Err(LedgerError::Rpc(format!(
+            "iotax_getLatestIotaSystemState: {error}"
+        )));
+    }
+    u64_field(response.get("result").unwrap_or(response), "epoch")
+}
+
+/// The network's current epoch.
+pub fn current_epoch(rpc: &(impl RpcClient + ?Sized)) -> Result<u64, LedgerError> {
+    parse_epoch(&rpc.call(&latest_system_state_request())?)
+}
+
+/// Expiration `epochs` after the current one: the transaction can
+/// execute during this epoch and the next `epochs`, but not later.
+pub fn expiration_in(
+    rpc: &(impl RpcClient + ?Sized),
+    epochs: u64,
+) -> Result<TransactionExpiration, LedgerError> {
+    let current = current_epoch(rpc)?;
+    current
+        .checked_add(epochs)
+        .map(TransactionExpiration::Epoch)
+        .ok_or_else(|| {
+            LedgerError::InvalidTransaction(format!("epoch {current} + {epochs} overflows"))
+        })
+}
+
+/// Re-encode intent-prefixed `tx` (e.g. from a builder) with an
+/// [`expiration_in`] `epochs`, keeping its intent prefix.
+///
+/// ```no_run
+/// # use ledger_iota::{build_transfer_tx, Address, Amount, GasCoinRef};
+/// # fn demo(client: &dyn ledger_iota::rpc::RpcClient, sender: Address, recipient: Address, gas: GasCoinRef) -> Result<(), ledger_iota::LedgerError> {
+/// let tx = build_transfer_tx(&sender, &recipient, Amount::from_nanos(1_000), &gas, Amount::from_nanos(5_000_000), 1_000)?;
+/// let tx = ledger_iota::rpc::expire_in(&tx, client, 2)?;
+/// # Ok(()) }
+/// ```
+pub fn expire_in(
+    tx: &[u8],
+    rpc: &(impl RpcClient + ?Sized),
+    epochs: u64,
+) -> Result<Vec<u8>, LedgerError> {
+    let (intent, _) = crate::tx::split_intent(tx)?;
+    let mut data = TransactionData::from_intent_bytes(tx)?;
+    data.expiration = expiration_in(rpc, epochs)?;
+    data.to_intent_bytes_for(&crate::NetworkProfile::IOTA.with_intent_app_id(intent[2]))
+}
+
+/// Fetch the objects `refs` point to and convert those usable for clear
+/// signing (coins, staked IOTA).
+///
+/// Objects of other types, objects failing [`ObjectData::validate`], and
+/// objects whose current version differs from the referenced one (already
+/// spent) are skipped -- the device then
+/// falls back to blind signing for them.
+pub fn fetch_objects(
+    client: &(impl RpcClient + ?Sized),
+    refs: &[ObjectRef],
+) -> Result<Vec<ObjectData>, LedgerError> {
+    Ok(fetch_with_ids(client, refs)?
+        .into_iter()
+        .map(|(_, obj)| obj)
+        .collect())
+}
+
+fn fetch_with_ids(
+    client: &(impl RpcClient + ?Sized),
+    refs: &[ObjectRef],
+) -> Result<Vec<([u8; 32], ObjectData)>, LedgerError> {
+    if refs.is_empty() {
+        return Ok(Vec::new());
+    }
+    let ids: Vec<[u8; 32]> = refs.iter().map(|r| r.object_id).collect();
+    let response = client.call(&multi_get_objects_request(&ids))?;
+    if let Some(error) = response.get("error") {
+        return Err(LedgerError::Rpc(format!("iota_multiGetObjects: {error}")));
+    }
+    let results = response
+        .get("result")
+        .and_then(Value::as_array)
+        .ok_or_else(|| LedgerError::Rpc("iota_multiGetObjects: missing result array".into()))?;
+
+    let mut objects = Vec::new();
+    for (obj_ref, entry) in refs.iter().zip(results) {
+        let id = hex::encode(obj_ref.object_id);
+        let obj = match ObjectData::from_rpc_json(entry) {
+            Ok(obj) => obj,
+            Err(e) => {
+                log::debug!("object 0x{id} not usable for clear signing: {e}");
+                continue;
+            }
+        };
+        if obj.data.version != obj_ref.version {
+            log::warn!(
+                "object 0x{id} is at version {}, transaction references {}; skipping",
+                obj.data.version,
+                obj_ref.version
+            );
+        } else if let Err(e) = obj.validate() {
+            log::warn!("object 0x{id} failed validation, skipping: {e}");
+        } else {
+            objects.push((obj_ref.object_id, obj));
+        }
+    }
+    Ok(objects)
+}
+
+/// Fetch clear-signing data for every owned object input and gas coin of
+/// `tx` (see [`fetch_objects`]).
+pub fn resolve_objects(
+    client: &(impl RpcClient + ?Sized),
+    tx: &TransactionData,
+) -> Result<Vec<ObjectData>, LedgerError> {
+    fetch_objects(client, &tx.object_refs())
+}
+
+/// [`resolve_objects`], answering from `cache` where possible and adding
+/// what had to be fetched. Cache errors are logged and treated as misses.
+#[cfg(feature = "object-cache")]
+pub fn resolve_objects_cached(
+    client: &(impl RpcClient + ?Sized),
+    cache: &mut dyn ObjectCache,
+    tx: &TransactionData,
+) -> Result<Vec<ObjectData>, LedgerError> {
+    let mut objects = Vec::new();
+    let mut missing = Vec::new();
+    for r in tx.object_refs() {
+        match cache.get(&r.object_id, r.version) {
+            Ok(Some(obj)) => objects.push(obj),
+            Ok(None) => missing.push(r),
+            Err(e) => {
+                log::warn!("object cache read failed: {e}");
+                missing.push(r);
+            }
+        }
+    }
+    for (id, obj) in fetch_with_ids(client, &missing)? {
+        if let Err(e) = cache.put(id, &obj) {
+            log::warn!("object cache write failed: {e}");
+        }
+        objects.push(obj);
+    }
+    Ok(objects)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::amount::Amount;
+    use crate::tx::{Argument, ObjectArg, ProgrammableTransactionBuilder};
+
+    fn object_json(id: u8, version: u64, type_: &str) -> Value {
+        json!({
+            "data": {
+                "objectId": format!("0x{}", hex::encode([id; 32])),
+                "version": version.to_string(),
+                "type": type_,
+                "owner": { "AddressOwner": format!("0x{}", hex::encode([0xAA; 32])) },
+                "previousTransaction": "11111111111111111111111111111111",
+                "storageRebate": "100",
+                "bcs": {
+                    "dataType": "moveObject",
+                    "hasPublicTransfer": true,
+                    "version": version,
+                    "bcsBytes": "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=="
+                }
+            }
+        })
+    }
+
+    fn obj_ref(id: u8, version: u64) -> ObjectRef {
+        ObjectRef {
+            object_id: [id; 32],
+            version,
+            digest: ObjectDigest([0; 32]),
+        }
+    }
+
+    fn tx() -> TransactionData {
+        let mut ptb = ProgrammableTransactionBuilder::new();
+        let coin = ptb.object(ObjectArg::ImmOrOwnedObject(obj_ref(1, 5)));
+        let nft = ptb.object(ObjectArg::ImmOrOwnedObject(obj_ref(2, 5)));
+        ptb.object(ObjectArg::clock());
+        let me = ptb.pure_address(&[0xAA; 32]);
+        ptb.merge_coins(Argument::GasCoin, vec![coin]);
+        ptb.transfer_objects(vec![nft], me);
+        TransactionData::new(
+            ptb.finish().unwrap(),
+            [0xAA; 32],
+            vec![obj_ref(3, 9)],
+            Amount::from_nanos(1),
+            1,
+        )
+    }
+
+    #[test]
+    fn request_shape() {
+        let req = multi_get_objects_request(&[[0x01; 32]]);
+        assert_eq!(req["method"], "iota_multiGetObjects");
+        assert_eq!(req["params"][0][0], format!("0x{}", "01".repeat(32)));
+        assert_eq!(req["params"][1]["showBcs"], true);
+    }
+
+    #[test]
+    fn resolves_owned_inputs_and_gas() {
+        let client = |req: &Value| {
+            let ids = req["params"][0].as_array().unwrap();
+            assert_eq!(ids.len(), 3, "clock must not be fetched");
+            Ok(json!({
+                "result": [
+                    object_json(1, 5, "0x2::coin::Coin<0xabc::usdc::USDC>"),
+                    object_json(2, 5, "0xabc::nft::Nft"),
+                    object_json(3, 9, "0x2::coin::Coin<0x2::iota::IOTA>"),
+                ]
+            }))
+        };
+        let objects = resolve_objects(&client, &tx()).unwrap();
+        assert_eq!(objects.len(), 2, "the NFT is skipped");
+    }
+
+    #[test]
+    fn skips_stale_versions() {
+        let client = |_: &Value| {
+            Ok(json!({
+                "result": [
+                    object_json(1, 6, "0x2::coin::Coin<0x2::iota::IOTA>"),
+                    object_json(2, 5, "0x2::coin::Coin<0x2::iota::IOTA>"),
+                    object_json(3, 9, "0x2::coin::Coin<0x2::iota::IOTA>"),
+                ]
+            }))
+        };
+        assert_eq!(resolve_objects(&client, &tx()).unwrap().len(), 2);
+    }
+
+    #[cfg(feature = "object-cache")]
+    #[test]
+    fn cache_is_consulted_first() {
+        use crate::object_cache::MemoryObjectCache;
+        use std::cell::Cell;
+
+        let calls = Cell::new(0);
+        let client = |req: &Value| {
+            calls.set(calls.get() + 1);
+            let ids = req["params"][0].as_array().unwrap();
+            let all = [
+                object_json(1, 5, "0x2::coin::Coin<0x2::iota::IOTA>"),
+                object_json(2, 5, "0xabc::nft::Nft"),
+                object_json(3, 9, "0x2::coin::Coin<0x2::iota::IOTA>"),
+            ];
+            let result: Vec<Value> = all
+                .into_iter()
+                .filter(|o| ids.contains(&o["data"]["objectId"]))
+                .collect();
+            Ok(json!({ "result": result }))
+        };
+        let mut cache = MemoryObjectCache::new();
+        assert_eq!(
+            resolve_objects_cached(&client, &mut cache, &tx())
+                .unwrap()
+                .len(),
+            2
+        );
+        assert_eq!(cache.len(), 2);
+
+        // Only the NFT (never cached) is asked for again.
+        let client = |req: &Value| {
+            assert_eq!(req["params"][0].as_array().unwrap().len(), 1);
+            client(req)
+        };
+        assert_eq!(
+            resolve_objects_cached(&client, &mut cache, &tx())
+                .unwrap()
+                .len(),
+            2
+        );
+        assert_eq!(calls.get(), 2);
+    }
+
+    #[test]
+    fn parses_coin_pages() {
+        let response = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "result": {
+                "data": [{
+                    "coinType": "0x2::iota::IOTA",
+                    "coinObjectId": format!("0x{}", "01".repeat(32)),
+                    "version": "17",
+                    "digest": "JEKNVnkbo3jma5nREBBJCDoXFVeKkD56V3xKrvRmWxFG",
+                    "balance": "1500000000",
+                    "previousTransaction": "11111111111111111111111111111111"
+                }],
+                "nextCursor": "0xabc",
+                "hasNextPage": true
+            }
+        });
+        let (coins, cursor) = parse_coins(&response).unwrap();
+        assert_eq!(cursor.as_deref(), Some("0xabc"));
+        let coin = &coins[0];
+        assert_eq!(coin.coin_type, crate::coins::iota_coin_type());
+        assert_eq!(
+            coin.object_ref,
+            ObjectRef {
+                object_id: [1; 32],
+                version: 17,
+                digest: ObjectDigest([0xFF; 32]),
+            }
+        );
+        assert_eq!(coin.balance, 1_500_000_000);
+        assert_eq!(coin.previous_transaction, TransactionDigest::ZERO);
+
+        let last = json!({ "result": { "data": [], "nextCursor": "0xabc", "hasNextPage": false } });
+        assert_eq!(parse_coins(&last).unwrap().1, None);
+
+        let bad = json!({ "result": { "data": [{ "coinType": "0x2::iota::IOTA" }] } });
+        assert!(parse_coins(&bad).is_err());
+    }
+
+    #[test]
+    fn coins_request_shape() {
+        let req = get_coins_request(&[0xAA; 32], None, Some("0x1"), Some(50));
+        assert_eq!(req["method"], "iotax_getCoins");
+        assert_eq!(req["params"][1], Value::Null);
+        assert_eq!(req["params"][2], "0x1");
+        assert_eq!(req["params"][3], 50);
+    }
+
+    #[test]
+    fn transaction_query_shape_and_digests() {
+        let req = query_transactions_request(AddressFilter::To, &[0xAA; 32], None, Some(1));
+        assert_eq!(req["method"], "iotax_queryTransactionBlocks");
+        assert_eq!(
+            req["params"][0]["filter"]["ToAddress"],
+            format!("0x{}", "aa".repeat(32))
+        );
+        assert_eq!(req["params"][2], 1);
+
+        let response = json!({ "result": {
+            "data": [{ "digest": "11111111111111111111111111111111" }],
+            "nextCursor": "11111111111111111111111111111111",
+            "hasNextPage": false,
+        } });
+        let (digests, cursor) = parse_transaction_digests(&response).unwrap();
+        assert_eq!(digests, [TransactionDigest::ZERO]);
+        assert_eq!(cursor, None);
+        assert!(parse_transaction_digests(&json!({ "error": { "code": -32601 } })).is_err());
+    }
+
+    #[test]
+    fn parses_balances_and_owned_objects() {
+        let balances = json!({ "result": [{
+            "coinType": "0x2::iota::IOTA",
+            "coinObjectCount": 2,
+            "totalBalance": "340282366920938463463374607431768211455",
+        }] });
+        let parsed = parse_balances(&balances).unwrap();
+        assert_eq!(parsed[0].coin_type, crate::coins::iota_coin_type());
+        assert_eq!(parsed[0].coin_object_count, 2);
+        assert_eq!(parsed[0].total_balance, u128::MAX);
+        assert!(parse_balances(&json!({ "result": [{ "coinType": "0x2::iota::IOTA" }] })).is_err());
+
+        let page = json!({ "result": {
+            "data": [
+                { "data": {
+                    "objectId": format!("0x{}", "02".repeat(32)),
+                    "version": "3",
+                    "digest": "11111111111111111111111111111111",
+                    "type": "0xabc::nft::Nft",
+                } },
+                { "error": { "code": "deleted" } },
+            ],
+            "nextCursor": "0x2",
+            "hasNextPage": true,
+        } });
+        let (objects, cursor) = parse_owned_objects(&page).unwrap();
+        assert_eq!(cursor.as_deref(), Some("0x2"));
+        assert_eq!(objects.len(), 1);
+        assert_eq!(objects[0].object_ref.object_id, [2; 32]);
+        assert_eq!(objects[0].type_.as_deref(), Some("0xabc::nft::Nft"));
+
+        let req = get_owned_objects_request(&[0xAA; 32], None, Some(50));
+        assert_eq!(req["method"], "iotax_getOwnedObjects");
+        assert_eq!(req["params"][1]["options"]["showType"], true);
+        assert_eq!(
+            get_all_balances_request(&[0xAA; 32])["method"],
+            "iotax_getAllBalances"
+        );
+    }
+
+    fn gas_node(request: &Value) -> Result<Value, LedgerError> {
+        match request["method"].as_str().unwrap() {
+            "iotax_getReferenceGasPrice" => Ok(json!({ "result": "1000" })),
+            "iota_dryRunTransactionBlock" => {
+                let bytes = crate::tx::from_base64(request["params"][0].as_str().unwrap())?;
+                let tx = TransactionData::decode(&bytes)?;
+                assert_eq!(tx.gas_price, 1000);
+                if tx.gas_budget < Amount::from_nanos(2_000_000) {
+                    return Ok(json!({ "result": { "effects": { "status": {
+                        "status": "failure",
+                        "error": "InsufficientGas",
+                    } } } }));
+                }
+                Ok(json!({ "result": { "effects": {
+                    "status": { "status": "success" },
+                    "gasUsed": {
+                        "computationCost": "1000000",
+                        "storageCost": "1976000",
+                        "storageRebate": "978120",
+                        "nonRefundableStorageFee": "9880",
+                    },
+                } } }))
+            }
+            m => panic!("unexpected {m}"),
+        }
+    }
+
+    #[test]
+    fn estimates_gas_with_margin() {
+        let mut data = tx();
+        data.gas_budget = Amount::from_nanos(10_000_000);
+        let estimate = estimate_gas(&data, &gas_node).unwrap();
+        assert_eq!(estimate.gas_price, 1000);
+        assert_eq!(estimate.used.storage_rebate, 978_120);
+        assert_eq!(estimate.budget, Amount::from_nanos(3_571_200));
+        assert_eq!(
+            estimate_gas_with_margin(&data, &gas_node, 0)
+                .unwrap()
+                .budget,
+            Amount::from_nanos(2_976_000)
+        );
+
+        data.gas_budget = Amount::from_nanos(1);
+        let err = estimate_gas(&data, &gas_node).unwrap_err();
+        assert!(err.to_string().contains("InsufficientGas"), "{err}");
+    }
+
+    #[test]
+    fn builds_with_auto_gas() {
+        let gas = obj_ref(3, 9);
+        let build = |budget: Amount, price: u64| {
+            crate::tx::build_transfer_tx(
+                &crate::types::Address([0xAA; 32]),
+                &crate::types::Address([0xBB; 32]),
+                Amount::from_nanos(5),
+                &gas,
+                budget,
+                price,
+            )
+        };
+        let tx = build_with_auto_gas(&gas_node, Amount::from_nanos(10_000_000), build).unwrap();
+        let data = TransactionData::from_intent_bytes(&tx).unwrap();
+        assert_eq!(data.gas_price, 1000);
+        assert_eq!(data.gas_budget, Amount::from_nanos(3_571_200));
+
+        let err = build_with_auto_gas(&gas_node, Amount::from_nanos(3_000_000), build).unwrap_err();
+        assert!(matches!(err, LedgerError::InvalidAmount(_)), "{err}");
+    }
+
+    #[test]
+    fn expiration_from_current_epoch() {
+        let node = |req: &Value| {
+            assert_eq!(req["method"], "iotax_getLatestIotaSystemState");
+            Ok(json!({ "result": { "epoch": "412", "protocolVersion": "3" } }))
+        };
+        assert_eq!(current_epoch(&node).unwrap(), 412);
+        assert_eq!(
+            expiration_in(&node, 2).unwrap(),
+            TransactionExpiration::Epoch(414)
+        );
+        assert!(expiration_in(&node, u64::MAX).is_err());
+
+        let tx = tx().to_intent_bytes().unwrap();
+        let expiring =
+            TransactionData::from_intent_bytes(&expire_in(&tx, &node, 0).unwrap()).unwrap();
+        assert_eq!(expiring.expiration, TransactionExpiration::Epoch(412));
+        assert_eq!(
+            expiring,
+            TransactionData {
+                expiration: TransactionExpiration::Epoch(412),
+                ..TransactionData::from_intent_bytes(&tx).unwrap()
+            }
+        );
+
+        // A fork's intent survives the re-encoding.
+        let fork = crate::NetworkProfile::IOTA.with_intent_app_id(7);
+        let fork_tx = TransactionData::from_intent_bytes(&tx)
+            .unwrap()
+            .to_intent_bytes_for(&fork)
+            .unwrap();
+        let expiring = expire_in(&fork_tx, &node, 1).unwrap();
+        assert!(expiring.starts_with(&fork.transaction_intent()));
+        assert_eq!(
+            TransactionData::from_intent_bytes(&expiring)
+                .unwrap()
+                .expiration,
+            TransactionExpiration::Epoch(413)
+        );
+    }
+
+    #[test]
+    fn surfaces_rpc_errors() {
+        let client = |_: &Value| Ok(json!({ "error": { "code": -32000, "message": "boom" } }));
+        assert!(matches!(
+            resolve_objects(&client, &tx()),
+            Err(LedgerError::Rpc(_))
+        ));
+    }
+}
@@ -0,0 +1,183 @@
+//! The keys a Ledger (or Speculos) holds for a BIP39 mnemonic.
+//!
+//! BIP39 turns the mnemonic into a seed; SLIP-0010 derives Ed25519 keys
+//! from it along fully hardened paths, as the IOTA app does. Use it to
+//! know which addresses a Speculos instance started with `--seed` will
+//! report.
+//!
+//! ```
+//! use ledger_iota::seed::Seed;
+//! use ledger_iota::Bip32Path;
+//!
+//! let seed = Seed::speculos();
+//! let (pubkey, address) = seed.keys(&Bip32Path::iota(0, 0, 0));
+//! ```
+
+use blake2::digest::consts::U32;
+use blake2::{Blake2b, Digest};
+use ed25519_dalek::{Signer, SigningKey};
+use hmac::{Hmac, Mac};
+use sha2::Sha512;
+
+use crate::types::{Address, Bip32Path, PublicKey, Signature, HARDENED};
+use crate::wipe::Transient;
+
+/// The mnemonic Speculos runs with unless given `--seed`.
+pub const SPECULOS_MNEMONIC: &str = "glory promote mansion idle axis finger extra february uncover one trip resource lawn turtle enact monster seven myth punch hobby comfort wild raise skin";
+
+/// A 64-byte BIP39 seed.
+pub struct Seed(Transient<Vec<u8>>);
+
+impl std::fmt::Debug for Seed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Seed(..)")
+    }
+}
+
+impl Seed {
+    /// PBKDF2-HMAC-SHA512 of the mnemonic, as BIP39 specifies. The words
+    /// are not checked against the wordlist; normalise them (NFKD, single
+    /// spaces) first.
+    #[must_use]
+    pub fn from_mnemonic(mnemonic: &str, passphrase: &str) -> Self {
+        let mut seed = vec![0; 64];
+        pbkdf2::pbkdf2_hmac::<Sha512>(
+            mnemonic.as_bytes(),
+            format!("mnemonic{passphrase}").as_bytes(),
+            2048,
+            &mut seed,
+        );
+        Self(Transient(seed))
+    }
+
+    #[must_use]
+    pub fn from_bytes(seed: &[u8]) -> Self {
+        Self(Transient(seed.to_vec()))
+    }
+
+    /// The seed of [`SPECULOS_MNEMONIC`].
+    #[must_use]
+    pub fn speculos() -> Self {
+        Self::from_mnemonic(SPECULOS_MNEMONIC, "")
+    }
+
+    /// SLIP-0010 Ed25519 key at `path`. Unhardened components are
+    /// hardened, as Ledger devices do for Ed25519.
+    #[must_use]
+    pub fn signing_key(&self, path: &Bip32Path) -> SigningKey {
+        SigningKey::from_bytes(&self.derive(path.components()).0)
+    }
+
+    /// Public key and address the app reports for `path`.
+    #[must_use]
+    pub fn keys(&self, path: &Bip32Path) -> (PublicKey, Address) {
+        let pubkey = PublicKey(self.signing_key(path).verifying_key().to_bytes());
+        let address = pubkey.to_address();
+        (pubkey, address)
+    }
+
+    /// The app's signature over an intent message (a transaction from
+    /// `build_transfer_tx`, or `[3, 0, 0] || message`): Ed25519 over its
+    /// Blake2b-256 digest.
+    #[must_use]
+    pub fn sign(&self, path: &Bip32Path, intent_message: &[u8]) -> Signature {
+        let digest = Blake2b::<U32>::digest(intent_message);
+        Signature(self.signing_key(path).sign(&digest).to_bytes())
+    }
+
+    /// `(private key, chain code)` at `path`.
+    fn derive(&self, path: &[u32]) -> ([u8; 32], [u8; 32]) {
+        let mut node = split(hmac_sha512(b"ed25519 seed", &[&self.0]));
+        for &index in path {
+            let (key, chain_code) = &node;
+            let index = (index | HARDENED).to_be_bytes();
+            node = split(hmac_sha512(chain_code, &[&[0], key, &index]));
+        }
+        node
+    }
+}
+
+fn hmac_sha512(key: &[u8], data: &[&[u8]]) -> [u8; 64] {
+    let mut mac = Hmac::<Sha512>::new_from_slice(key).expect("HMAC takes any key length");
+    for part in data {
+        mac.update(part);
+    }
+    mac.finalize().into_bytes().into()
+}
+
+fn split(i: [u8; 64]) -> ([u8; 32], [u8; 32]) {
+    let mut left = [0; 32];
+    let mut right = [0; 32];
+    left.copy_from_slice(&i[..32]);
+    right.copy_from_slice(&i[32..]);
+    (left, right)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bip39_vector() {
+        let words = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+        let seed = Seed::from_mnemonic(words, "TREZOR");
+        assert_eq!(
+            hex::encode(&*seed.0),
+            "c55257c360c07c72029aebc1b53c05ed0362ada38ead3e3e9efa3708e53495531f09a6987599d18264c1e1c92f2cf141630c7a3c4ab7c81b2f001698e7463b04"
+        );
+    }
+
+    /// SLIP-0010 test vector 1 for ed25519.
+    #[test]
+    fn slip10_vector() {
+        let seed = Seed::from_bytes(&hex::decode("000102030405060708090a0b0c0d0e0f").unwrap());
+        let (key, chain_code) = seed.derive(&[]);
+        assert_eq!(
+            hex::encode(key),
+            "2b4be7f19ee27bbf30c667b642d5f4aa69fd169872f8fc3059c08ebae2eb19e7"
+        );
+        assert_eq!(
+            hex::encode(chain_code),
+            "90046a93de5380a72b5e45010748567d5ea02bbf6522f979e05c0d8d8ca9fffb"
+        );
+        let (key, _) = seed.derive(&[HARDENED]);
+        assert_eq!(
+            hex::encode(key),
+            "68e0fe46dfb67e368c75379acec591dad19df3cde26e63b93a8e704f1dade7a3"
+        );
+        // Unhardened indices are hardened.
+        assert_eq!(seed.derive(&[0, 1]), seed.derive(&[HARDENED, 1 | HARDENED]));
+        let (key, _) = seed.derive(&[HARDENED, 1 | HARDENED]);
+        assert_eq!(
+            hex::encode(key),
+            "b1d0bad404bf35da785a64ca1ac54b2617211d2777696fbffaf208f746ae84f2"
+        );
+    }
+
+    #[test]
+    fn keys_differ_per_path() {
+        let seed = Seed::speculos();
+        let (pk, address) = seed.keys(&Bip32Path::iota(0, 0, 0));
+        assert_eq!(address, pk.to_address());
+        assert_ne!(seed.keys(&Bip32Path::iota(0, 0, 1)).0, pk);
+        assert_ne!(
+            Seed::from_mnemonic(SPECULOS_MNEMONIC, "x")
+                .keys(&Bip32Path::iota(0, 0, 0))
+                .0,
+            pk
+        );
+    }
+
+    #[test]
+    fn signatures_verify() {
+        use ed25519_dalek::{Verifier, VerifyingKey};
+
+        let seed = Seed::speculos();
+        let path = Bip32Path::iota(0, 0, 1);
+        let signature = seed.sign(&path, b"\x03\x00\x00hello");
+        let key = VerifyingKey::from_bytes(&seed.keys(&path).0 .0).unwrap();
+        let digest = Blake2b::<U32>::digest(b"\x03\x00\x00hello");
+        key.verify(&digest, &ed25519_dalek::Signature::from_bytes(&signature.0))
+            .unwrap();
+    }
+}
@@ -0,0 +1,53 @@
+//! `0x`-prefixed hex (de)serialization for byte fields.
+
+use serde::{de, Deserialize, Deserializer, Serializer};
+
+use crate::prelude::*;
+
+pub(crate) fn serialize<S: Serializer>(bytes: impl AsRef<[u8]>, s: S) -> Result<S::Ok, S::Error> {
+    s.serialize_str(&format!("0x{}", hex::encode(bytes)))
+}
+
+fn decode<'de, D: Deserializer<'de>>(d: D) -> Result<Vec<u8>, D::Error> {
+    let s = String::deserialize(d)?;
+    hex::decode(s.strip_prefix("0x").unwrap_or(&s)).map_err(de::Error::custom)
+}
+
+/// For `Vec<u8>` fields.
+pub(crate) mod vec {
+    use serde::{Deserializer, Serializer};
+
+    use crate::prelude::*;
+
+    pub(crate) fn serialize<S: Serializer>(bytes: &[u8], s: S) -> Result<S::Ok, S::Error> {
+        super::serialize(bytes, s)
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Vec<u8>, D::Error> {
+        super::decode(d)
+    }
+}
+
+/// For `[u8; N]` fields.
+pub(crate) mod array {
+    use serde::{de, Deserializer, Serializer};
+
+    use crate::prelude::*;
+
+    pub(crate) fn serialize<S: Serializer, const N: usize>(
+        bytes: &[u8; N],
+        s: S,
+    ) -> Result<S::Ok, S::Error> {
+        super::serialize(bytes, s)
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>, const N: usize>(
+        d: D,
+    ) -> Result<[u8; N], D::Error> {
+        let bytes = super::decode(d)?;
+        bytes
+            .as_slice()
+            .try_into()
+            .map_err(|_| de::Error::invalid_length(bytes.len(), &format!("{N} bytes").as_str()))
+    }
+}
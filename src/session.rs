@@ -0,0 +1,108 @@
+//! Signing for one account without repeating its path.
+//!
+//! [`LedgerIota::open_account`](crate::LedgerIota::open_account) derives
+//! the key once and returns an [`AccountSession`]. Its
+//! [`sign_tx`](AccountSession::sign_tx) decodes the transaction and
+//! refuses it with [`LedgerError::AccountMismatch`] unless the sender is
+//! the session's address, so a transaction built for one account can't
+//! be signed with another's key.
+
+use core::slice;
+
+use crate::api::{api_type, Address, PublicKey, SignRequest, Signature};
+use crate::error::LedgerError;
+use crate::objects::ObjectData;
+use crate::tx::TransactionData;
+use crate::types::Bip32Path;
+use crate::LedgerIota;
+
+/// One derivation path of a [`LedgerIota`], with its key and address
+/// cached.
+pub struct AccountSession<'a> {
+    ledger: &'a LedgerIota,
+    path: Bip32Path,
+    public_key: PublicKey,
+    address: Address,
+    account: [u8; 32],
+}
+
+impl<'a> AccountSession<'a> {
+    pub(crate) fn open(ledger: &'a LedgerIota, path: Bip32Path) -> Result<Self, LedgerError> {
+        let (public_key, address) =
+            ledger.track(crate::commands::get_pubkey::exec(ledger.transport(), &path))?;
+        Ok(Self {
+            ledger,
+            path,
+            account: address.0,
+            public_key: api_type(public_key),
+            address: api_type(address),
+        })
+    }
+
+    pub fn path(&self) -> &Bip32Path {
+        &self.path
+    }
+
+    pub fn public_key(&self) -> &PublicKey {
+        &self.public_key
+    }
+
+    pub fn address(&self) -> &Address {
+        &self.address
+    }
+
+    /// [`LedgerIota::sign_tx`] at the session's path, after checking that
+    /// the transaction's sender is the session's address. Transactions the
+    /// crate can't decode are refused, as their sender can't be checked.
+    pub fn sign_tx(
+        &self,
+        tx: &[u8],
+        objects: Option<&[ObjectData]>,
+    ) -> Result<Signature, LedgerError> {
+        let request = SignRequest::transaction(tx);
+        self.ledger
+            .signing(request, slice::from_ref(&self.path), || {
+                self.check_sender(tx)?;
+                self.ledger.sign_tx_inner(tx, &self.path, objects)
+            })
+    }
+
+    /// [`LedgerIota::sign_message`] at the session's path.
+    pub fn sign_message(&self, message: &[u8]) -> Result<Signature, LedgerError> {
+        self.ledger.sign_message(message, &self.path)
+    }
+
+    /// Show the session's address on the device; fails with
+    /// [`LedgerError::AccountMismatch`] if the device now derives a
+    /// different one (another seed was loaded).
+    pub fn verify_address(&self) -> Result<(), LedgerError> {
+        let (_, address) = self.ledger.track(crate::commands::verify_address::exec(
+            self.ledger.transport(),
+            &self.path,
+        ))?;
+        self.check_account(address.0)
+    }
+
+    fn check_sender(&self, tx: &[u8]) -> Result<(), LedgerError> {
+        self.check_account(TransactionData::from_intent_bytes(tx)?.sender)
+    }
+
+    fn check_account(&self, found: [u8; 32]) -> Result<(), LedgerError> {
+        if found == self.account {
+            return Ok(());
+        }
+        Err(LedgerError::AccountMismatch {
+            expected: self.account,
+            found,
+        })
+    }
+}
+
+impl core::fmt::Debug for AccountSession<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("AccountSession")
+            .field("path", &self.path)
+            .field("address", &self.address)
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,66 @@
+//! [`IotaSigner`]: what code that needs signatures can depend on instead
+//! of a device, so a software signer can stand in for it.
+
+use crate::api::{Address, LedgerIota, PublicKey, Signature};
+use crate::error::LedgerError;
+use crate::objects::ObjectData;
+use crate::types::Bip32Path;
+
+/// Keys and signatures for BIP32 paths.
+///
+/// Implemented by [`LedgerIota`] and, with feature `dev-signer`, by
+/// [`DevSigner`](crate::dev_signer::DevSigner), which computes what
+/// Speculos would without one.
+pub trait IotaSigner {
+    fn get_pubkey(&self, path: &Bip32Path) -> Result<(PublicKey, Address), LedgerError>;
+
+    /// Sign intent-prefixed transaction bytes; `objects` as for
+    /// [`LedgerIota::sign_tx`].
+    fn sign_tx(
+        &self,
+        tx: &[u8],
+        path: &Bip32Path,
+        objects: Option<&[ObjectData]>,
+    ) -> Result<Signature, LedgerError>;
+
+    /// Sign a personal message (the `[3, 0, 0]` intent is added).
+    fn sign_message(&self, message: &[u8], path: &Bip32Path) -> Result<Signature, LedgerError>;
+}
+
+impl IotaSigner for LedgerIota {
+    fn get_pubkey(&self, path: &Bip32Path) -> Result<(PublicKey, Address), LedgerError> {
+        LedgerIota::get_pubkey(self, path)
+    }
+
+    fn sign_tx(
+        &self,
+        tx: &[u8],
+        path: &Bip32Path,
+        objects: Option<&[ObjectData]>,
+    ) -> Result<Signature, LedgerError> {
+        LedgerIota::sign_tx(self, tx, path, objects)
+    }
+
+    fn sign_message(&self, message: &[u8], path: &Bip32Path) -> Result<Signature, LedgerError> {
+        LedgerIota::sign_message(self, message, path)
+    }
+}
+
+impl<S: IotaSigner + ?Sized> IotaSigner for &S {
+    fn get_pubkey(&self, path: &Bip32Path) -> Result<(PublicKey, Address), LedgerError> {
+        (**self).get_pubkey(path)
+    }
+
+    fn sign_tx(
+        &self,
+        tx: &[u8],
+        path: &Bip32Path,
+        objects: Option<&[ObjectData]>,
+    ) -> Result<Signature, LedgerError> {
+        (**self).sign_tx(tx, path, objects)
+    }
+
+    fn sign_message(&self, message: &[u8], path: &Bip32Path) -> Result<Signature, LedgerError> {
+        (**self).sign_message(message, path)
+    }
+}
@@ -0,0 +1,422 @@
+//! Differential runs against the reference Python client (feature `tcp`).
+//!
+//! Each [`Operation`] is run twice on one Speculos instance: through this
+//! crate, recording the APDUs, and through the app's Python client, which
+//! `tests/differential/reference.py` drives the same way. A
+//! [`Comparison`] reports where the transcripts or results part ways.
+//!
+//! The reference side is a command that takes the operation as arguments
+//! and prints one JSON object:
+//!
+//! ```text
+//! <command> version
+//! <command> pubkey <path>
+//! <command> sign-message <path> <hex message>
+//! <command> sign-tx <path> <hex tx> [<hex object>...]
+//! {"result": "<hex>", "transcript": ["> <hex>", "< <hex>", ...]}
+//! {"error": "<text>", "transcript": [...]}
+//! ```
+//!
+//! with `LEDGER_TCP_HOST`/`LEDGER_TCP_PORT` set to the Speculos APDU port.
+//! Results are the version as `major.minor.patch`, the public key or the
+//! signature in hex; transcript lines use [`Recorder`](super::Recorder)'s
+//! format. `reference.py` reports what the client's methods return, not a
+//! decoding of the APDUs of its own, so the results compare the two
+//! clients' parsing as well as their wire traffic.
+//!
+//! ```no_run
+//! use ledger_iota::testing::differential::{Harness, Operation};
+//! use ledger_iota::testing::speculos::ApprovalPolicy;
+//! use ledger_iota::Bip32Path;
+//!
+//! let harness = Harness::from_env();
+//! let op = Operation::SignMessage {
+//!     path: Bip32Path::iota(0, 0, 0),
+//!     message: b"hello".to_vec(),
+//! };
+//! let comparison = harness.compare(&op, Some(&ApprovalPolicy::approve_all()))?;
+//! assert_eq!(comparison.divergence(), None);
+//! # Ok::<(), std::io::Error>(())
+//! ```
+
+use std::io;
+use std::path::PathBuf;
+use std::process::Command;
+use std::sync::Arc;
+
+use serde_json::Value;
+
+use super::speculos::{ApprovalPolicy, Speculos};
+use super::Recorder;
+use crate::api::LedgerIota;
+use crate::error::LedgerError;
+use crate::objects::ObjectData;
+use crate::transport::tcp::TcpTransport;
+use crate::types::Bip32Path;
+
+/// An operation run on both clients.
+#[derive(Debug, Clone)]
+pub enum Operation {
+    Version,
+    PublicKey(Bip32Path),
+    SignMessage {
+        path: Bip32Path,
+        message: Vec<u8>,
+    },
+    SignTx {
+        path: Bip32Path,
+        tx: Vec<u8>,
+        objects: Vec<ObjectData>,
+    },
+}
+
+impl Operation {
+    /// Arguments for the reference command. Fails only if an object
+    /// can't be encoded.
+    pub fn args(&self) -> Result<Vec<String>, LedgerError> {
+        Ok(match self {
+            Self::Version => vec!["version".into()],
+            Self::PublicKey(path) => vec!["pubkey".into(), path.to_string()],
+            Self::SignMessage { path, message } => {
+                vec![
+                    "sign-message".into(),
+                    path.to_string(),
+                    hex::encode(message),
+                ]
+            }
+            Self::SignTx { path, tx, objects } => {
+                let mut args = vec!["sign-tx".into(), path.to_string(), hex::encode(tx)];
+                for obj in objects {
+                    args.push(hex::encode(obj.to_bytes()?));
+                }
+                args
+            }
+        })
+    }
+
+    fn is_signing(&self) -> bool {
+        matches!(self, Self::SignMessage { .. } | Self::SignTx { .. })
+    }
+
+    fn run(&self, ledger: &LedgerIota) -> Result<String, crate::LedgerError> {
+        Ok(match self {
+            Self::Version => {
+                let v = ledger.get_version()?;
+                format!("{}.{}.{}", v.major, v.minor, v.patch)
+            }
+            Self::PublicKey(path) => hex::encode(ledger.get_pubkey(path)?.0 .0),
+            Self::SignMessage { path, message } => {
+                hex::encode(ledger.sign_message(message, path)?.0)
+            }
+            Self::SignTx { path, tx, objects } => {
+                let objects = (!objects.is_empty()).then_some(objects.as_slice());
+                hex::encode(ledger.sign_tx(tx, path, objects)?.0)
+            }
+        })
+    }
+}
+
+/// What one client did.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Outcome {
+    /// The result, or the error text.
+    pub result: Result<String, String>,
+    pub transcript: Vec<String>,
+}
+
+impl Outcome {
+    /// Parse the reference command's output.
+    pub fn parse(json: &str) -> io::Result<Self> {
+        let invalid = |what: &str| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("reference client output: {what}"),
+            )
+        };
+        let value: Value =
+            serde_json::from_str(json.trim()).map_err(|e| invalid(&e.to_string()))?;
+        let text = |key: &str| value.get(key).and_then(Value::as_str).map(str::to_owned);
+        let result = match (text("result"), text("error")) {
+            (Some(result), None) => Ok(result),
+            (None, Some(error)) => Err(error),
+            _ => return Err(invalid("expected exactly one of \"result\" and \"error\"")),
+        };
+        let transcript = value
+            .get("transcript")
+            .and_then(Value::as_array)
+            .ok_or_else(|| invalid("missing \"transcript\" array"))?
+            .iter()
+            .map(|line| line.as_str().map(str::to_owned))
+            .collect::<Option<_>>()
+            .ok_or_else(|| invalid("transcript lines must be strings"))?;
+        Ok(Self { result, transcript })
+    }
+}
+
+/// Both clients' outcomes for one operation.
+#[derive(Debug, Clone)]
+pub struct Comparison {
+    pub operation: Operation,
+    pub ours: Outcome,
+    pub reference: Outcome,
+}
+
+impl Comparison {
+    /// Where the two clients differ, or `None`. Error texts aren't
+    /// compared, only whether both failed; the status words are in the
+    /// transcripts.
+    #[must_use]
+    pub fn divergence(&self) -> Option<String> {
+        let (ours, theirs) = (&self.ours.transcript, &self.reference.transcript);
+        if let Some(i) = (0..ours.len().max(theirs.len())).find(|&i| ours.get(i) != theirs.get(i)) {
+            let line = |t: &[String]| t.get(i).cloned().unwrap_or_else(|| "<end>".into());
+            return Some(format!(
+                "APDU {}: ours {}, reference {}",
+                i / 2 + 1,
+                line(ours),
+                line(theirs)
+            ));
+        }
+        match (&self.ours.result, &self.reference.result) {
+            (Ok(a), Ok(b)) if a != b => Some(format!("results differ: ours {a}, reference {b}")),
+            (Ok(_), Err(e)) => Some(format!("only the reference client failed: {e}")),
+            (Err(e), Ok(_)) => Some(format!("only this crate failed: {e}")),
+            _ => None,
+        }
+    }
+}
+
+/// Runs the reference command.
+#[derive(Debug, Clone)]
+pub struct ReferenceClient {
+    /// Program and leading arguments.
+    pub command: Vec<String>,
+}
+
+impl ReferenceClient {
+    /// `DIFF_REFERENCE_CMD` (split on whitespace), or `python3` with this
+    /// crate's `tests/differential/reference.py`.
+    #[must_use]
+    pub fn from_env() -> Self {
+        let command = match std::env::var("DIFF_REFERENCE_CMD") {
+            Ok(cmd) => cmd.split_whitespace().map(str::to_owned).collect(),
+            Err(_) => {
+                let script = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+                    .join("tests/differential/reference.py");
+                vec!["python3".into(), script.display().to_string()]
+            }
+        };
+        Self { command }
+    }
+
+    /// Run `op` against Speculos at `host:port`.
+    pub fn run(&self, op: &Operation, host: &str, port: u16) -> io::Result<Outcome> {
+        let (program, leading) = self.command.split_first().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "empty reference command")
+        })?;
+        let output = Command::new(program)
+            .args(leading)
+            .args(
+                op.args()
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?,
+            )
+            .env("LEDGER_TCP_HOST", host)
+            .env("LEDGER_TCP_PORT", port.to_string())
+            .output()?;
+        if !output.status.success() && output.stdout.is_empty() {
+            return Err(io::Error::other(format!(
+                "reference client exited with {}: {}",
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        Outcome::parse(&String::from_utf8_lossy(&output.stdout))
+    }
+}
+
+/// This crate and the reference client against one Speculos instance.
+#[derive(Debug, Clone)]
+pub struct Harness {
+    pub host: String,
+    /// APDU port.
+    pub port: u16,
+    pub speculos: Speculos,
+    pub reference: ReferenceClient,
+}
+
+impl Harness {
+    /// Speculos from the environment (see
+    /// [`speculos_transport`](super::speculos_transport) and
+    /// [`Speculos::from_env`]) and [`ReferenceClient::from_env`].
+    #[must_use]
+    pub fn from_env() -> Self {
+        let (host, port) = super::speculos_address();
+        Self {
+            host,
+            port,
+            speculos: Speculos::from_env(),
+            reference: ReferenceClient::from_env(),
+        }
+    }
+
+    /// Run `op` through this crate, then the reference client. Signing
+    /// prompts are answered under `policy` on both runs.
+    pub fn compare(
+        &self,
+        op: &Operation,
+        policy: Option<&ApprovalPolicy>,
+    ) -> io::Result<Comparison> {
+        let ours = self.with_prompts(op, policy, || self.run_ours(op))?;
+        let reference =
+            self.with_prompts(op, policy, || self.reference.run(op, &self.host, self.port))?;
+        Ok(Comparison {
+            operation: op.clone(),
+            ours,
+            reference,
+        })
+    }
+
+    fn with_prompts(
+        &self,
+        op: &Operation,
+        policy: Option<&ApprovalPolicy>,
+        run: impl FnOnce() -> io::Result<Outcome>,
+    ) -> io::Result<Outcome> {
+        let approver = policy
+            .filter(|_| op.is_signing())
+            .map(|policy| self.speculos.spawn(policy.clone()));
+        let outcome = run()?;
+        if let Some(approver) = approver {
+            approver
+                .join()
+                .map_err(|_| io::Error::other("approver panicked"))??;
+        }
+        Ok(outcome)
+    }
+
+    /// The connection is closed before returning, so the reference client
+    /// can connect next.
+    fn run_ours(&self, op: &Operation) -> io::Result<Outcome> {
+        let transport = TcpTransport::new(&self.host, self.port)
+            .map_err(|e| io::Error::new(io::ErrorKind::ConnectionRefused, e.to_string()))?;
+        let recorder = Arc::new(Recorder::new(transport));
+        let result = {
+            let ledger = LedgerIota::with_transport(Box::new(recorder.clone()));
+            op.run(&ledger).map_err(|e| e.to_string())
+        };
+        let transcript = recorder.transcript().lines().map(str::to_owned).collect();
+        Ok(Outcome { result, transcript })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn outcome(result: Result<&str, &str>, transcript: &[&str]) -> Outcome {
+        Outcome {
+            result: result.map(str::to_owned).map_err(str::to_owned),
+            transcript: transcript.iter().map(|&l| l.to_owned()).collect(),
+        }
+    }
+
+    fn comparison(ours: Outcome, reference: Outcome) -> Comparison {
+        Comparison {
+            operation: Operation::Version,
+            ours,
+            reference,
+        }
+    }
+
+    #[test]
+    fn args_encode_the_operation() {
+        let path = Bip32Path::iota(0, 0, 1);
+        assert_eq!(Operation::Version.args().unwrap(), ["version"]);
+        assert_eq!(
+            Operation::PublicKey(path.clone()).args().unwrap(),
+            ["pubkey", "m/44'/4218'/0'/0'/1'"]
+        );
+        let op = Operation::SignMessage {
+            path,
+            message: b"hi".to_vec(),
+        };
+        assert_eq!(
+            op.args().unwrap(),
+            ["sign-message", "m/44'/4218'/0'/0'/1'", "6869"]
+        );
+    }
+
+    #[test]
+    fn parses_reference_output() {
+        let ok =
+            Outcome::parse(r#"{"result": "1.0.0", "transcript": ["> 00", "< 9000"]}"#).unwrap();
+        assert_eq!(ok, outcome(Ok("1.0.0"), &["> 00", "< 9000"]));
+        let err = Outcome::parse(r#"{"error": "0x6985", "transcript": []}"#).unwrap();
+        assert_eq!(err.result, Err("0x6985".into()));
+
+        assert!(Outcome::parse("not json").is_err());
+        assert!(Outcome::parse(r#"{"transcript": []}"#).is_err());
+        assert!(Outcome::parse(r#"{"result": "", "error": "", "transcript": []}"#).is_err());
+        assert!(Outcome::parse(r#"{"result": ""}"#).is_err());
+        assert!(Outcome::parse(r#"{"result": "", "transcript": [1]}"#).is_err());
+    }
+
+    #[test]
+    fn reports_divergences() {
+        let same = outcome(Ok("ab"), &["> 01", "< 9000"]);
+        assert_eq!(comparison(same.clone(), same.clone()).divergence(), None);
+
+        let other_answer = outcome(Ok("ab"), &["> 01", "< 6985"]);
+        assert_eq!(
+            comparison(same.clone(), other_answer).divergence().unwrap(),
+            "APDU 1: ours < 9000, reference < 6985"
+        );
+        let longer = outcome(Ok("ab"), &["> 01", "< 9000", "> 02", "< 9000"]);
+        assert_eq!(
+            comparison(same.clone(), longer).divergence().unwrap(),
+            "APDU 2: ours <end>, reference > 02"
+        );
+        let other_result = outcome(Ok("cd"), &["> 01", "< 9000"]);
+        assert!(comparison(same.clone(), other_result)
+            .divergence()
+            .unwrap()
+            .starts_with("results differ"));
+        let failed = outcome(Err("rejected"), &["> 01", "< 9000"]);
+        assert!(comparison(same, failed.clone()).divergence().is_some());
+
+        // Error texts differ between clients.
+        let also_failed = outcome(Err("0x6985"), &["> 01", "< 9000"]);
+        assert_eq!(comparison(failed, also_failed).divergence(), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn runs_the_reference_command() {
+        let client = ReferenceClient {
+            command: vec![
+                "sh".into(),
+                "-c".into(),
+                r#"echo "{\"result\": \"$0 $1 $LEDGER_TCP_PORT\", \"transcript\": []}""#.into(),
+            ],
+        };
+        let outcome = client
+            .run(
+                &Operation::PublicKey(Bip32Path::iota(0, 0, 0)),
+                "127.0.0.1",
+                4242,
+            )
+            .unwrap();
+        assert_eq!(
+            outcome.result,
+            Ok("pubkey m/44'/4218'/0'/0'/0' 4242".into())
+        );
+
+        let failing = ReferenceClient {
+            command: vec!["sh".into(), "-c".into(), "echo broken >&2; exit 3".into()],
+        };
+        let err = failing
+            .run(&Operation::Version, "127.0.0.1", 1)
+            .unwrap_err();
+        assert!(err.to_string().contains("broken"), "{err}");
+    }
+}
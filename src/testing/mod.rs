@@ -0,0 +1,755 @@
+//! Test doubles for downstream integration tests (feature `testing`).
+//!
+//! - [`MockTransport`] answers with scripted raw responses and records
+//!   what was sent.
+//! - [`SimulatedDevice`] runs the device side of the block protocol and
+//!   answers version, public key and signing requests with keys derived
+//!   from a seed.
+//! - [`Recorder`] wraps any transport and keeps an APDU transcript for
+//!   golden-file comparisons.
+//! - [`speculos_transport`] / [`connect_speculos`] (with `tcp`) reach a
+//!   Speculos instance configured through the environment, and
+//!   [`speculos`] answers its prompts by what is on screen;
+//!   [`differential`] compares this crate with the Python client there.
+//! - [`fixture`] reads this crate's test vectors, and [`vectors`]
+//!   replays the signing vectors among them.
+//!
+//! ```
+//! use ledger_iota::testing::SimulatedDevice;
+//! use ledger_iota::{Bip32Path, LedgerIota};
+//!
+//! let ledger = LedgerIota::with_transport(Box::new(SimulatedDevice::speculos()));
+//! assert_eq!(ledger.get_version()?.name, "IOTA");
+//! let signature = ledger.sign_message(b"hello", &Bip32Path::iota(0, 0, 0))?;
+//! # Ok::<(), ledger_iota::LedgerError>(())
+//! ```
+
+#[cfg(feature = "tcp")]
+pub mod differential;
+#[cfg(feature = "tcp")]
+pub mod speculos;
+pub mod vectors;
+
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use blake2::digest::consts::U32;
+use blake2::{Blake2b, Digest};
+use ed25519_dalek::{Signer, SigningKey};
+
+use crate::apdu::{ApduAnswer, ApduCommand, Instruction};
+use crate::error::TransportError;
+use crate::log_policy::{LogPolicy, Sensitive};
+use crate::protocol::chunks::Block;
+use crate::seed::Seed;
+use crate::transport::Transport;
+use crate::types::{AppVersion, Bip32Path};
+
+/// `payload || 0x9000`
+#[must_use]
+pub fn apdu_ok(payload: &[u8]) -> Vec<u8> {
+    let mut raw = payload.to_vec();
+    raw.extend_from_slice(&[0x90, 0x00]);
+    raw
+}
+
+/// A response carrying only status word `sw`.
+#[must_use]
+pub fn apdu_status(sw: u16) -> Vec<u8> {
+    sw.to_be_bytes().to_vec()
+}
+
+/// The serialized blocks the host offers for a block protocol parameter,
+/// first block first.
+#[must_use]
+pub fn block_chain(data: &[u8]) -> Vec<Vec<u8>> {
+    crate::protocol::chunks::build_block_chain(data)
+        .iter()
+        .map(Block::serialize)
+        .collect()
+}
+
+/// Answers each exchange with the next scripted response.
+///
+/// Runs out with [`TransportError::Comm`] rather than panicking, so a
+/// short script shows up as an error from the code under test.
+#[derive(Debug, Default)]
+pub struct MockTransport {
+    responses: Mutex<VecDeque<Vec<u8>>>,
+    sent: Mutex<Vec<Vec<u8>>>,
+}
+
+impl MockTransport {
+    /// `responses` are raw answers: payload followed by the status word.
+    #[must_use]
+    pub fn new(responses: Vec<Vec<u8>>) -> Self {
+        Self {
+            responses: Mutex::new(responses.into()),
+            sent: Mutex::default(),
+        }
+    }
+
+    pub fn push_response(&self, raw: Vec<u8>) {
+        lock(&self.responses).push_back(raw);
+    }
+
+    /// Serialized commands sent so far (`[CLA][INS][P1][P2][LC][DATA]`).
+    #[must_use]
+    pub fn sent(&self) -> Vec<Vec<u8>> {
+        lock(&self.sent).clone()
+    }
+
+    /// Scripted responses not yet consumed.
+    #[must_use]
+    pub fn remaining(&self) -> usize {
+        lock(&self.responses).len()
+    }
+}
+
+impl Transport for MockTransport {
+    fn exchange(&self, command: &ApduCommand) -> Result<ApduAnswer, TransportError> {
+        lock(&self.sent).push(command.serialize()?);
+        let raw = lock(&self.responses)
+            .pop_front()
+            .ok_or_else(|| TransportError::Comm("MockTransport: no more responses".into()))?;
+        let answer = ApduAnswer::from_raw(raw);
+        command.check_response(&answer)?;
+        Ok(answer)
+    }
+}
+
+/// Block protocol exchange in progress: parameters are pulled block by
+/// block, following each block's next-hash.
+#[derive(Debug, Default)]
+struct Exchange {
+    ins: u8,
+    pending: VecDeque<[u8; 32]>,
+    params: Vec<Vec<u8>>,
+    current: Vec<u8>,
+}
+
+/// An IOTA app in software.
+///
+/// Keys are derived from a [`Seed`] by SLIP-0010, as on the device, so a
+/// simulator, a Speculos instance and a
+/// [`DevSigner`](crate::dev_signer::DevSigner) on the same seed report the
+/// same addresses. Signatures cover `Blake2b-256` of the intent message.
+#[derive(Debug)]
+pub struct SimulatedDevice {
+    seed: Seed,
+    version: AppVersion,
+    state: Mutex<SimState>,
+}
+
+#[derive(Debug, Default)]
+struct SimState {
+    exchange: Option<Exchange>,
+    fail_with: Option<u16>,
+    reject: bool,
+    signed: Vec<Vec<u8>>,
+}
+
+impl SimulatedDevice {
+    /// IOTA app 1.0.0 holding keys derived from `seed`.
+    #[must_use]
+    pub fn new(seed: Seed) -> Self {
+        Self {
+            seed,
+            version: AppVersion::new(1, 0, 0, "IOTA"),
+            state: Mutex::default(),
+        }
+    }
+
+    #[must_use]
+    pub fn from_mnemonic(mnemonic: &str, passphrase: &str) -> Self {
+        Self::new(Seed::from_mnemonic(mnemonic, passphrase))
+    }
+
+    /// The keys of a Speculos instance started without `--seed`.
+    #[must_use]
+    pub fn speculos() -> Self {
+        Self::new(Seed::speculos())
+    }
+
+    #[must_use]
+    pub fn with_version(mut self, version: AppVersion) -> Self {
+        self.version = version;
+        self
+    }
+
+    /// Answer every command with status `sw` (e.g. `0x5515` for a locked
+    /// device) until cleared with `None`.
+    pub fn fail_with(&self, sw: Option<u16>) {
+        lock(&self.state).fail_with = sw;
+    }
+
+    /// Make the user reject signing and address verification prompts.
+    pub fn set_reject(&self, reject: bool) {
+        lock(&self.state).reject = reject;
+    }
+
+    /// Payloads signed so far (intent-prefixed, as sent by the host).
+    #[must_use]
+    pub fn signed(&self) -> Vec<Vec<u8>> {
+        lock(&self.state).signed.clone()
+    }
+
+    /// The key the device uses for a serialized BIP32 path.
+    fn key(&self, path: &[u8]) -> Option<SigningKey> {
+        let path = Bip32Path::deserialize(path).ok()?;
+        Some(self.seed.signing_key(&path))
+    }
+
+    fn respond(&self, state: &mut SimState, command: &ApduCommand) -> Vec<u8> {
+        if let Some(sw) = state.fail_with {
+            return apdu_status(sw);
+        }
+        let Some((&msg, body)) = command.data.split_first() else {
+            return apdu_status(0x6A80);
+        };
+        match msg {
+            // Start: one first-block hash per parameter.
+            0x00 => {
+                let pending = body
+                    .chunks_exact(32)
+                    .map(|h| h.try_into().expect("32-byte chunk"))
+                    .collect();
+                state.exchange = Some(Exchange {
+                    ins: command.ins,
+                    pending,
+                    ..Exchange::default()
+                });
+            }
+            // GetChunk response: `[next hash][data]`.
+            0x01 => {
+                let Some(exchange) = state.exchange.as_mut() else {
+                    return apdu_status(0x6A80);
+                };
+                let Ok(block) = Block::deserialize(body) else {
+                    return apdu_status(0x6A80);
+                };
+                exchange.current.extend_from_slice(&block.data);
+                if block.next_hash == [0; 32] {
+                    exchange.params.push(std::mem::take(&mut exchange.current));
+                } else {
+                    exchange.pending.push_front(block.next_hash);
+                }
+            }
+            _ => return apdu_status(0x6A80),
+        }
+
+        let Some(exchange) = state.exchange.as_mut() else {
+            return apdu_status(0x6A80);
+        };
+        if let Some(hash) = exchange.pending.pop_front() {
+            let mut get_chunk = vec![0x02];
+            get_chunk.extend_from_slice(&hash);
+            return apdu_ok(&get_chunk);
+        }
+        let exchange = state.exchange.take().expect("exchange in progress");
+        self.finish(state, &exchange)
+    }
+
+    /// Answer a command once all its parameters have arrived.
+    fn finish(&self, state: &mut SimState, exchange: &Exchange) -> Vec<u8> {
+        let params = &exchange.params;
+        let result = match exchange.ins {
+            x if x == Instruction::GetVersion as u8 => {
+                let v = &self.version;
+                let mut out = vec![v.major, v.minor, v.patch];
+                if let Some(flags) = v.flags {
+                    out.push(v.name.len() as u8);
+                    out.extend_from_slice(v.name.as_bytes());
+                    out.extend_from_slice(&[1, flags.bits()]);
+                } else {
+                    out.extend_from_slice(v.name.as_bytes());
+                }
+                out
+            }
+            x if x == Instruction::GetPubkey as u8 || x == Instruction::VerifyAddress as u8 => {
+                let Some(path) = params.first() else {
+                    return apdu_status(0x6A80);
+                };
+                if x == Instruction::VerifyAddress as u8 && state.reject {
+                    return apdu_status(0x6985);
+                }
+                let Some(key) = self.key(path) else {
+                    return apdu_status(0x6A80);
+                };
+                let pubkey = key.verifying_key().to_bytes();
+                let mut hasher = Blake2b::<U32>::new();
+                hasher.update([0x00]);
+                hasher.update(pubkey);
+                let mut out = vec![32];
+                out.extend_from_slice(&pubkey);
+                out.push(32);
+                out.extend_from_slice(&hasher.finalize());
+                out
+            }
+            x if x == Instruction::SignTx as u8 => {
+                let (Some(tx), Some(path)) = (params.first(), params.get(1)) else {
+                    return apdu_status(0x6A80);
+                };
+                let Some(tx) = tx.get(4..) else {
+                    return apdu_status(0x6A80);
+                };
+                let Some(key) = self.key(path) else {
+                    return apdu_status(0x6A80);
+                };
+                if state.reject {
+                    return apdu_status(0x6985);
+                }
+                let digest = Blake2b::<U32>::digest(tx);
+                state.signed.push(tx.to_vec());
+                key.sign(&digest).to_bytes().to_vec()
+            }
+            x if x == Instruction::Quit as u8 => Vec::new(),
+            _ => return apdu_status(0x6D02),
+        };
+        let mut out = vec![0x01];
+        out.extend_from_slice(&result);
+        apdu_ok(&out)
+    }
+}
+
+impl Transport for SimulatedDevice {
+    fn exchange(&self, command: &ApduCommand) -> Result<ApduAnswer, TransportError> {
+        command.serialize()?;
+        let mut state = lock(&self.state);
+        let raw = self.respond(&mut state, command);
+        Ok(ApduAnswer::from_raw(raw))
+    }
+}
+
+/// Passes exchanges through to `inner` and records them.
+///
+/// [`transcript`](Self::transcript) renders one line per APDU: `> ` and
+/// the command in hex, then `< ` and the raw answer (payload and status
+/// word), or `! ` and the error.
+///
+/// Lines count as `debug` records for the [`LogPolicy`]:
+/// [`LogPolicy::DEFAULT`] unless set with
+/// [`with_log_policy`](Self::with_log_policy) or taken from the
+/// [`LedgerIota`](crate::LedgerIota) it's given to.
+#[derive(Debug)]
+pub struct Recorder<T> {
+    inner: T,
+    lines: Mutex<Vec<String>>,
+    policy: Mutex<LogPolicy>,
+}
+
+impl<T: Transport> Recorder<T> {
+    #[must_use]
+    pub fn new(inner: T) -> Self {
+        Self {
+            inner,
+            lines: Mutex::default(),
+            policy: Mutex::new(LogPolicy::DEFAULT),
+        }
+    }
+
+    /// Record only the command header and the status word when `policy`
+    /// keeps payloads out of `debug` records.
+    #[must_use]
+    pub fn with_log_policy(self, policy: &LogPolicy) -> Self {
+        self.set_log_policy(policy);
+        self
+    }
+
+    #[must_use]
+    pub fn transcript(&self) -> String {
+        let mut out = String::new();
+        for line in lock(&self.lines).iter() {
+            out.push_str(line);
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Forget what has been recorded so far.
+    pub fn clear(&self) {
+        lock(&self.lines).clear();
+    }
+
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+}
+
+impl<T: Transport> Transport for Recorder<T> {
+    fn exchange(&self, command: &ApduCommand) -> Result<ApduAnswer, TransportError> {
+        let sent = command.serialize()?;
+        let result = self.inner.exchange(command);
+        let reveal = lock(&self.policy).reveals(Sensitive::Payload, log::Level::Debug);
+        let mut lines = lock(&self.lines);
+        if reveal {
+            lines.push(format!("> {}", hex::encode(sent)));
+            lines.push(match &result {
+                Ok(answer) => format!("< {}", hex::encode(answer.raw())),
+                Err(e) => format!("! {e}"),
+            });
+        } else {
+            lines.push(format!("> {} <redacted payload>", hex::encode(&sent[..4])));
+            lines.push(match &result {
+                Ok(answer) => format!("< <redacted payload> {:04x}", answer.retcode()),
+                Err(e) => format!("! {e}"),
+            });
+        }
+        result
+    }
+
+    fn reconnect(&self) -> Result<(), TransportError> {
+        self.inner.reconnect()
+    }
+
+    fn limits(&self) -> crate::limits::PayloadLimits {
+        self.inner.limits()
+    }
+
+    fn set_log_policy(&self, policy: &LogPolicy) {
+        *lock(&self.policy) = *policy;
+        self.inner.set_log_policy(policy);
+    }
+}
+
+fn lock<T>(m: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    m.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Speculos at `LEDGER_TCP_HOST` (default `127.0.0.1`) and
+/// `LEDGER_TCP_PORT` (default `9999`).
+#[cfg(feature = "tcp")]
+#[must_use]
+pub fn speculos_transport() -> crate::TransportType {
+    let (host, port) = speculos_address();
+    crate::TransportType::TCP(host, port)
+}
+
+#[cfg(feature = "tcp")]
+fn speculos_address() -> (String, u16) {
+    let host = std::env::var("LEDGER_TCP_HOST").unwrap_or_else(|_| "127.0.0.1".into());
+    let port = std::env::var("LEDGER_TCP_PORT")
+        .ok()
+        .and_then(|p| p.parse().ok())
+        .unwrap_or(9999);
+    (host, port)
+}
+
+/// Connect to Speculos (see [`speculos_transport`]) and check the IOTA
+/// app is running.
+#[cfg(feature = "tcp")]
+pub fn connect_speculos() -> Result<crate::LedgerIota, crate::LedgerError> {
+    crate::LedgerIota::new(&speculos_transport())
+}
+
+/// Path of a vector shipped in this crate's `tests/vectors/`.
+#[must_use]
+pub fn fixture_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/vectors")
+        .join(name)
+}
+
+/// Contents of a shipped vector, e.g. `"transfer_single.bin"`.
+pub fn fixture(name: &str) -> std::io::Result<Vec<u8>> {
+    std::fs::read(fixture_path(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Bip32Path;
+    use crate::{LedgerError, LedgerIota};
+    use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+
+    #[cfg(not(feature = "iota-sdk-types"))]
+    #[test]
+    fn simulated_device_signs_verifiably() {
+        let device = SimulatedDevice::speculos();
+        let ledger = LedgerIota::with_transport(Box::new(device));
+        let path = Bip32Path::iota(0, 0, 3);
+
+        assert_eq!(ledger.get_version().unwrap().to_string(), "IOTA v1.0.0");
+        let (pubkey, _) = ledger.get_pubkey(&path).unwrap();
+        let (other, _) = ledger.get_pubkey(&Bip32Path::iota(0, 0, 4)).unwrap();
+        assert_ne!(pubkey, other);
+
+        // Long enough to span several blocks.
+        let message = vec![0x42; 1000];
+        let sig = ledger.sign_message(&message, &path).unwrap();
+        let mut intent = vec![3, 0, 0];
+        intent.extend_from_slice(&message);
+        let key = VerifyingKey::from_bytes(&pubkey.0).unwrap();
+        key.verify(
+            &Blake2b::<U32>::digest(&intent),
+            &Signature::from_bytes(&sig.0),
+        )
+        .unwrap();
+    }
+
+    #[cfg(not(feature = "iota-sdk-types"))]
+    #[test]
+    fn account_session_checks_the_sender() {
+        let device = std::sync::Arc::new(SimulatedDevice::speculos());
+        let ledger = LedgerIota::with_transport(Box::new(device.clone()));
+        let path = Bip32Path::iota(0, 0, 1);
+        let session = ledger.open_account(path.clone()).unwrap();
+        assert_eq!(
+            (*session.public_key(), *session.address()),
+            ledger.get_pubkey(&path).unwrap()
+        );
+
+        let tx_from = |sender| {
+            crate::tx::TransactionData::new(
+                crate::tx::ProgrammableTransaction::default(),
+                sender,
+                Vec::new(),
+                crate::Amount::from_nanos(1_000),
+                1,
+            )
+            .to_intent_bytes()
+            .unwrap()
+        };
+        let tx = tx_from(session.address().0);
+        let signature = session.sign_tx(&tx, None).unwrap();
+        assert_eq!(signature, ledger.sign_tx(&tx, &path, None).unwrap());
+
+        let other = ledger.get_pubkey(&Bip32Path::iota(0, 0, 2)).unwrap().1;
+        let err = session.sign_tx(&tx_from(other.0), None).unwrap_err();
+        assert!(matches!(
+            err,
+            LedgerError::AccountMismatch { expected, found }
+                if expected == session.address().0 && found == other.0
+        ));
+        assert!(session.sign_tx(&[0, 0, 0, 1], None).is_err());
+        assert_eq!(device.signed().len(), 2);
+
+        session.verify_address().unwrap();
+        session.sign_message(b"hello").unwrap();
+    }
+
+    #[test]
+    fn sign_listener_sees_every_outcome() {
+        use crate::api::{SignRequest, SignStatus};
+        use crate::types::TransactionDigest;
+
+        let events = std::sync::Arc::new(Mutex::new(Vec::new()));
+        let device = std::sync::Arc::new(SimulatedDevice::speculos());
+        let seen = events.clone();
+        let ledger =
+            LedgerIota::with_transport(Box::new(device.clone())).with_sign_listener(move |event| {
+                lock(&seen).push((event.request, event.path.clone(), event.status));
+            });
+        let path = Bip32Path::iota(0, 0, 0);
+        let tx = fixture("transfer_single.bin").unwrap();
+        let digest = TransactionDigest::from_intent_bytes(&tx).ok();
+        assert!(digest.is_some());
+
+        ledger.sign_tx(&tx, &path, None).unwrap();
+        device.set_reject(true);
+        ledger.sign_message(b"hello", &path).unwrap_err();
+        device.set_reject(false);
+        ledger.sign_message(&[0; 5000], &path).unwrap_err();
+        // Refusals before reaching the device are reported too.
+        let twice = [path.clone(), path.clone()];
+        ledger.sign_tx_multi(&tx, &twice, None).unwrap_err();
+        ledger
+            .sign_tx_base64("not base64!", &path, None)
+            .unwrap_err();
+        let other = Bip32Path::iota(0, 0, 1);
+        let session = ledger.open_account(other.clone()).unwrap();
+        assert!(matches!(
+            session.sign_tx(&tx, None),
+            Err(LedgerError::AccountMismatch { .. })
+        ));
+
+        let request = SignRequest::Transaction { digest };
+        assert_eq!(
+            *lock(&events),
+            [
+                (request, path.clone(), SignStatus::Signed),
+                (SignRequest::Message, path.clone(), SignStatus::Rejected),
+                (SignRequest::Message, path.clone(), SignStatus::Failed),
+                (request, path.clone(), SignStatus::Failed),
+                (request, path.clone(), SignStatus::Failed),
+                (
+                    SignRequest::Transaction { digest: None },
+                    path,
+                    SignStatus::Failed
+                ),
+                (request, other, SignStatus::Failed),
+            ]
+        );
+    }
+
+    #[test]
+    fn sign_tx_multi_signs_with_each_key() {
+        let device = std::sync::Arc::new(SimulatedDevice::speculos());
+        let ledger = LedgerIota::with_transport(Box::new(device.clone())).with_sign_journal(
+            crate::SignJournal::in_memory(std::time::Duration::from_secs(60)),
+        );
+        let tx = fixture("transfer_single.bin").unwrap();
+        let paths = [Bip32Path::iota(0, 0, 0), Bip32Path::iota(1, 0, 0)];
+
+        let signatures = ledger.sign_tx_multi(&tx, &paths, None).unwrap();
+        assert_eq!(signatures.len(), 2);
+        assert_ne!(signatures[0], signatures[1]);
+        assert_eq!(device.signed().len(), 2);
+        // Recorded once: signing it again is a repeat.
+        assert!(matches!(
+            ledger.sign_tx(&tx, &paths[0], None),
+            Err(LedgerError::DuplicateSignRequest { .. })
+        ));
+
+        let plain = LedgerIota::with_transport(Box::new(SimulatedDevice::speculos()));
+        for (path, signature) in paths.iter().zip(&signatures) {
+            assert_eq!(&plain.sign_tx(&tx, path, None).unwrap(), signature);
+        }
+
+        let twice = [paths[0].clone(), paths[1].clone(), paths[0].clone()];
+        assert!(matches!(
+            plain.sign_tx_multi(&tx, &twice, None),
+            Err(LedgerError::InvalidPath(_))
+        ));
+        assert!(plain.sign_tx_multi(&tx, &[], None).is_err());
+
+        device.set_reject(true);
+        let fresh = LedgerIota::with_transport(Box::new(device.clone()));
+        assert!(matches!(
+            fresh.sign_tx_multi(&tx, &paths, None),
+            Err(LedgerError::UserRejected { .. })
+        ));
+        assert_eq!(device.signed().len(), 2);
+    }
+
+    #[test]
+    fn network_addresses_derive_both_coin_types() {
+        let ledger = LedgerIota::with_transport(Box::new(SimulatedDevice::speculos()));
+        let report = ledger.network_addresses(1, 0, 2).unwrap();
+        assert_eq!(report.mainnet.path, Bip32Path::iota(1, 0, 2));
+        assert_eq!(report.testnet.path, Bip32Path::testnet(1, 0, 2));
+        let (public_key, address) = ledger.get_pubkey(&Bip32Path::testnet(1, 0, 2)).unwrap();
+        assert_eq!(report.testnet.public_key, public_key);
+        assert_eq!(report.testnet.address, address);
+        assert_ne!(report.mainnet.address, report.testnet.address);
+
+        let text = report.to_string();
+        let lines: Vec<_> = text.lines().collect();
+        assert_eq!(
+            lines,
+            [
+                format!("mainnet m/44'/4218'/1'/0'/2' {}", report.mainnet.address),
+                format!("testnet m/44'/1'/1'/0'/2' {}", report.testnet.address),
+            ]
+        );
+        assert!(ledger
+            .network_addresses(crate::types::HARDENED, 0, 0)
+            .is_err());
+    }
+
+    #[test]
+    fn sign_tx_reports_the_signing_mode() {
+        use crate::capabilities::SigningMode;
+        use crate::objects::{ObjectData, Owner};
+        use crate::types::TransactionDigest;
+
+        let gas =
+            ObjectData::gas_coin(1, vec![0; 40], Owner::Immutable, TransactionDigest::ZERO, 0);
+        let tx = crate::tx::TransactionData::new(
+            crate::tx::ProgrammableTransaction::default(),
+            [0xAA; 32],
+            vec![crate::tx::ObjectRef {
+                object_id: [0x30; 32],
+                version: 1,
+                digest: gas.digest().unwrap(),
+            }],
+            crate::Amount::from_nanos(1_000),
+            1,
+        )
+        .to_intent_bytes()
+        .unwrap();
+        let path = Bip32Path::iota(0, 0, 0);
+
+        let ledger = LedgerIota::with_transport(Box::new(SimulatedDevice::speculos()));
+        let signed = ledger.sign_tx_with_mode(&tx, &path, None).unwrap();
+        assert_eq!(signed.mode, SigningMode::Blind);
+        let signed = ledger.sign_tx_with_mode(&tx, &path, Some(&[gas])).unwrap();
+        assert_eq!(signed.mode, SigningMode::Clear);
+    }
+
+    #[test]
+    fn simulated_keys_match_the_seed() {
+        let path = Bip32Path::iota(0, 0, 5);
+        let (pubkey, address) = crate::seed::Seed::speculos().keys(&path);
+        let ledger = LedgerIota::with_transport(Box::new(SimulatedDevice::speculos()));
+        assert_eq!(
+            ledger.get_pubkey(&path).unwrap(),
+            (crate::api::api_type(pubkey), crate::api::api_type(address))
+        );
+    }
+
+    #[test]
+    fn simulated_failures() {
+        let device = SimulatedDevice::speculos();
+        device.set_reject(true);
+        device.fail_with(Some(0x5515));
+        let ledger = LedgerIota::with_transport(Box::new(device));
+        let path = Bip32Path::iota(0, 0, 0);
+        assert!(matches!(
+            ledger.get_version(),
+            Err(LedgerError::DeviceLocked)
+        ));
+
+        let device = SimulatedDevice::speculos();
+        device.set_reject(true);
+        let ledger = LedgerIota::with_transport(Box::new(device));
+        assert!(ledger.get_pubkey(&path).is_ok());
+        assert!(matches!(
+            ledger.sign_message(b"no", &path),
+            Err(LedgerError::UserRejected { .. })
+        ));
+    }
+
+    #[test]
+    fn mock_transport_records_and_runs_out() {
+        let mock = MockTransport::new(vec![apdu_ok(&[0x01, 1, 2, 3, b'I', b'O', b'T', b'A'])]);
+        let version = crate::commands::get_version::exec(&mock).unwrap();
+        assert_eq!((version.major, version.minor, version.patch), (1, 2, 3));
+        assert_eq!(mock.sent(), [vec![0x00, 0x00, 0x00, 0x00, 0x01, 0x00]]);
+        assert_eq!(mock.remaining(), 0);
+        assert!(matches!(
+            crate::commands::get_version::exec(&mock),
+            Err(LedgerError::Transport(TransportError::Comm(_)))
+        ));
+    }
+
+    #[test]
+    fn recorder_redacts_payloads() {
+        let recorder = Recorder::new(SimulatedDevice::speculos());
+        crate::commands::get_version::exec(&recorder).unwrap();
+        let full = recorder.transcript();
+
+        let recorder = recorder.with_log_policy(&LogPolicy::REDACT_ALL);
+        recorder.clear();
+        crate::commands::get_version::exec(&recorder).unwrap();
+        let redacted = recorder.transcript();
+        let lines: Vec<_> = redacted.lines().collect();
+        assert_eq!(lines[0], format!("> {} <redacted payload>", &full[2..10]));
+        assert_eq!(lines[1], "< <redacted payload> 9000");
+        assert!(!redacted.contains(&hex::encode(b"IOTA")));
+        assert!(full.contains(&hex::encode(b"IOTA")));
+
+        // A ledger passes its policy on.
+        let recorder = std::sync::Arc::new(Recorder::new(SimulatedDevice::speculos()));
+        let ledger = crate::LedgerIota::with_transport(Box::new(recorder.clone()))
+            .with_log_policy(LogPolicy::REDACT_ALL);
+        ledger.get_version().unwrap();
+        assert_eq!(recorder.transcript(), redacted);
+    }
+
+    #[test]
+    fn fixtures_load() {
+        assert!(!fixture("transfer_single.bin").unwrap().is_empty());
+    }
+}
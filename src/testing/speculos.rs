@@ -0,0 +1,1256 @@
+//! Driving Speculos prompts by what is on screen (feature `tcp`).
+//!
+//! Instead of fixed button sequences, an [`ApprovalPolicy`] reads each
+//! screen through the Speculos REST API, scrolls through the review and
+//! answers on the approve or reject screen once it has decided. Screen
+//! layouts can change between app releases without breaking the tests
+//! that use it.
+//!
+//! Nano models are driven with buttons. On Stax and Flex the same
+//! policies swipe through the review, hold to sign and tap reject, at the
+//! positions in the model's [`TouchLayout`].
+//!
+//! [`Speculos::assert_screen_matches`] compares the current screen with a
+//! PNG snapshot, for checking what the user is shown during a review.
+//!
+//! [`Launch`] starts a Speculos process with a chosen mnemonic; its
+//! [`seed`](Launch::seed) gives the keys the emulated app will report.
+//!
+//! ```no_run
+//! use ledger_iota::testing::speculos::{ApprovalPolicy, Speculos};
+//!
+//! let speculos = Speculos::from_env();
+//! let approver = speculos.spawn(ApprovalPolicy::approve_all());
+//! // ... ledger.sign_tx(...) blocks until the prompt is answered ...
+//! let decision = approver.join().unwrap()?;
+//! # Ok::<(), std::io::Error>(())
+//! ```
+
+use std::io::{self, Read, Write};
+use std::net::TcpStream;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use crate::log_policy::{LogPolicy, Sensitive};
+
+/// A Nano button.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Button {
+    Left,
+    Right,
+    Both,
+}
+
+impl Button {
+    fn name(self) -> &'static str {
+        match self {
+            Button::Left => "left",
+            Button::Right => "right",
+            Button::Both => "both",
+        }
+    }
+}
+
+/// A point on a touchscreen, in pixels from the top left.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Point {
+    pub x: u16,
+    pub y: u16,
+}
+
+impl Point {
+    #[must_use]
+    pub const fn new(x: u16, y: u16) -> Self {
+        Self { x, y }
+    }
+}
+
+/// Where the review controls sit on a touchscreen.
+///
+/// The built-in layouts follow Ledger's standard (NBGL) review screens;
+/// override fields for apps that draw their own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TouchLayout {
+    /// Swiping from `swipe_from` to `swipe_to` shows the next page.
+    pub swipe_from: Point,
+    pub swipe_to: Point,
+    /// Confirm and continue buttons, including warning acknowledgements.
+    pub confirm: Point,
+    /// The hold-to-sign button on the last review page.
+    pub hold: Point,
+    pub hold_for: Duration,
+    /// The reject button in the review footer.
+    pub reject: Point,
+    /// "Yes, reject" in the dialog the reject button opens.
+    pub confirm_reject: Point,
+}
+
+impl TouchLayout {
+    /// 400x672.
+    pub const STAX: Self = Self {
+        swipe_from: Point::new(350, 336),
+        swipe_to: Point::new(50, 336),
+        confirm: Point::new(200, 515),
+        hold: Point::new(350, 515),
+        hold_for: Duration::from_secs(3),
+        reject: Point::new(36, 606),
+        confirm_reject: Point::new(200, 515),
+    };
+
+    /// 480x600.
+    pub const FLEX: Self = Self {
+        swipe_from: Point::new(430, 300),
+        swipe_to: Point::new(50, 300),
+        confirm: Point::new(240, 435),
+        hold: Point::new(405, 435),
+        hold_for: Duration::from_secs(3),
+        reject: Point::new(56, 540),
+        confirm_reject: Point::new(240, 435),
+    };
+}
+
+/// The device model Speculos emulates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Model {
+    NanoS,
+    NanoSPlus,
+    NanoX,
+    Stax,
+    Flex,
+}
+
+impl Model {
+    /// Speculos' model name: `nanos`, `nanosp`, `nanox`, `stax` or `flex`.
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "nanos" => Some(Model::NanoS),
+            "nanosp" => Some(Model::NanoSPlus),
+            "nanox" => Some(Model::NanoX),
+            "stax" => Some(Model::Stax),
+            "flex" => Some(Model::Flex),
+            _ => None,
+        }
+    }
+
+    /// Speculos' model name.
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Model::NanoS => "nanos",
+            Model::NanoSPlus => "nanosp",
+            Model::NanoX => "nanox",
+            Model::Stax => "stax",
+            Model::Flex => "flex",
+        }
+    }
+
+    /// `None` for button models.
+    #[must_use]
+    pub fn touch_layout(self) -> Option<TouchLayout> {
+        match self {
+            Model::Stax => Some(TouchLayout::STAX),
+            Model::Flex => Some(TouchLayout::FLEX),
+            Model::NanoS | Model::NanoSPlus | Model::NanoX => None,
+        }
+    }
+}
+
+/// How a prompt was answered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Decision {
+    Approve,
+    Reject,
+}
+
+#[derive(Debug, Clone)]
+enum Rule {
+    Always(Decision),
+    /// Approve if the review showed exactly this address (hex, any case,
+    /// with or without `0x`), otherwise reject.
+    Recipient(String),
+    /// Approve if the review contained this text, otherwise reject.
+    Shows(String),
+}
+
+/// What to answer, and how to recognise the screens to answer on.
+///
+/// Screens are matched by the first word of their first line, case
+/// insensitively.
+#[derive(Debug, Clone)]
+pub struct ApprovalPolicy {
+    rule: Rule,
+    approve_labels: Vec<String>,
+    reject_labels: Vec<String>,
+    /// Warnings acknowledged with both buttons before the review.
+    acknowledge: Vec<String>,
+    max_screens: usize,
+}
+
+impl ApprovalPolicy {
+    fn with_rule(rule: Rule) -> Self {
+        Self {
+            rule,
+            approve_labels: ["Sign", "Approve", "Accept", "Confirm"]
+                .map(String::from)
+                .to_vec(),
+            reject_labels: ["Reject", "Cancel"].map(String::from).to_vec(),
+            acknowledge: ["Blind"].map(String::from).to_vec(),
+            max_screens: 64,
+        }
+    }
+
+    #[must_use]
+    pub fn approve_all() -> Self {
+        Self::with_rule(Rule::Always(Decision::Approve))
+    }
+
+    #[must_use]
+    pub fn reject_all() -> Self {
+        Self::with_rule(Rule::Always(Decision::Reject))
+    }
+
+    /// Approve only if the review shows `address` (hex) in full; a longer
+    /// or shorter address, or an empty `address`, rejects.
+    #[must_use]
+    pub fn approve_if_recipient(address: &str) -> Self {
+        let hex = address.trim_start_matches("0x").to_ascii_lowercase();
+        Self::with_rule(Rule::Recipient(hex))
+    }
+
+    /// Approve only if the review shows `text`.
+    #[must_use]
+    pub fn approve_if_shown(text: &str) -> Self {
+        Self::with_rule(Rule::Shows(text.to_ascii_lowercase()))
+    }
+
+    /// Also treat screens starting with `label` as the approve screen.
+    #[must_use]
+    pub fn approve_label(mut self, label: &str) -> Self {
+        self.approve_labels.push(label.into());
+        self
+    }
+
+    /// Also treat screens starting with `label` as the reject screen.
+    #[must_use]
+    pub fn reject_label(mut self, label: &str) -> Self {
+        self.reject_labels.push(label.into());
+        self
+    }
+
+    /// Acknowledge warning screens starting with `label` with both buttons.
+    #[must_use]
+    pub fn acknowledge_label(mut self, label: &str) -> Self {
+        self.acknowledge.push(label.into());
+        self
+    }
+
+    /// Give up after this many screens (default 64).
+    #[must_use]
+    pub fn max_screens(mut self, n: usize) -> Self {
+        self.max_screens = n;
+        self
+    }
+
+    fn decide(&self, review: &str) -> Decision {
+        let shown = |needle: &str| {
+            let compact: String = review.chars().filter(|c| !c.is_whitespace()).collect();
+            compact.contains(&needle.replace(char::is_whitespace, ""))
+        };
+        let approve = match &self.rule {
+            Rule::Always(decision) => return *decision,
+            Rule::Recipient(hex) => {
+                !hex.is_empty() && shown_addresses(review, hex.len()).any(|a| a == *hex)
+            }
+            Rule::Shows(text) => review.contains(text.as_str()) || shown(text),
+        };
+        if approve {
+            Decision::Approve
+        } else {
+            Decision::Reject
+        }
+    }
+}
+
+/// Hex addresses in `review`: the digits after a `0x`, continued on the
+/// following all-hex lines while shorter than `len`, as addresses wrap.
+/// Page headers such as `To (2/3)` between them are skipped.
+fn shown_addresses(review: &str, len: usize) -> impl Iterator<Item = String> + '_ {
+    let mut lines = review.lines().map(str::trim).peekable();
+    core::iter::from_fn(move || loop {
+        let line = lines.next()?;
+        let Some(at) = line.find("0x") else {
+            continue;
+        };
+        let mut hex: String = line[at + 2..]
+            .chars()
+            .take_while(char::is_ascii_hexdigit)
+            .collect();
+        if at + 2 + hex.len() == line.len() {
+            while hex.len() < len {
+                match lines.peek() {
+                    Some(next) if is_page_header(next) => {
+                        lines.next();
+                    }
+                    Some(next)
+                        if !next.is_empty() && next.chars().all(|c| c.is_ascii_hexdigit()) =>
+                    {
+                        hex.push_str(next);
+                        lines.next();
+                    }
+                    _ => break,
+                }
+            }
+        }
+        if !hex.is_empty() {
+            return Some(hex);
+        }
+    })
+}
+
+/// A line ending in a page counter like `(2/3)`.
+fn is_page_header(line: &str) -> bool {
+    line.strip_suffix(')')
+        .and_then(|l| l.rsplit_once('('))
+        .and_then(|(_, page)| page.split_once('/'))
+        .is_some_and(|(n, m)| {
+            [n, m]
+                .iter()
+                .all(|d| !d.is_empty() && d.chars().all(|c| c.is_ascii_digit()))
+        })
+}
+
+fn starts_with_label(screen: &[String], labels: &[String]) -> bool {
+    let Some(first) = screen
+        .first()
+        .and_then(|line| line.split_whitespace().next())
+    else {
+        return false;
+    };
+    labels.iter().any(|l| l.eq_ignore_ascii_case(first))
+}
+
+/// The next thing to do, given the screen.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum Step {
+    /// Show the next screen.
+    Next,
+    /// Dismiss a warning shown before the review.
+    Acknowledge,
+    /// Answer the prompt; on a touchscreen `hold` means hold to sign
+    /// rather than tap confirm.
+    Approve {
+        hold: bool,
+    },
+    Reject,
+    Done(Decision),
+}
+
+/// Walks one review flow under an [`ApprovalPolicy`], one screen at a
+/// time.
+pub(crate) struct Navigator<'a> {
+    policy: &'a ApprovalPolicy,
+    /// Touchscreens have no reject screen: reject is a button on every
+    /// page.
+    touch: bool,
+    /// Lower-cased text of every screen seen so far.
+    review: String,
+    screens: usize,
+    /// Number of reject screens passed while looking for approve.
+    passed_reject: usize,
+    pressed: Option<Decision>,
+}
+
+impl<'a> Navigator<'a> {
+    pub(crate) fn new(policy: &'a ApprovalPolicy, touch: bool) -> Self {
+        Self {
+            policy,
+            touch,
+            review: String::new(),
+            screens: 0,
+            passed_reject: 0,
+            pressed: None,
+        }
+    }
+
+    pub(crate) fn step(&mut self, screen: &[String]) -> io::Result<Step> {
+        if let Some(decision) = self.pressed {
+            return Ok(Step::Done(decision));
+        }
+        self.screens += 1;
+        if self.screens > self.policy.max_screens {
+            return Err(io::Error::other(format!(
+                "no approve or reject screen within {} screens",
+                self.policy.max_screens
+            )));
+        }
+        let policy = self.policy;
+        if starts_with_label(screen, &policy.acknowledge) && self.review.is_empty() {
+            return Ok(Step::Acknowledge);
+        }
+
+        let on_approve = starts_with_label(screen, &policy.approve_labels);
+        if self.touch {
+            if matches!(policy.rule, Rule::Always(Decision::Reject)) {
+                self.pressed = Some(Decision::Reject);
+                return Ok(Step::Reject);
+            }
+            if !on_approve {
+                self.read(screen);
+                return Ok(Step::Next);
+            }
+            let decision = policy.decide(&self.review);
+            self.pressed = Some(decision);
+            return Ok(match decision {
+                Decision::Approve => Step::Approve {
+                    hold: screen
+                        .iter()
+                        .any(|l| l.to_ascii_lowercase().contains("hold")),
+                },
+                Decision::Reject => Step::Reject,
+            });
+        }
+
+        let on_reject = starts_with_label(screen, &policy.reject_labels);
+        if !on_approve && !on_reject {
+            self.read(screen);
+            return Ok(Step::Next);
+        }
+
+        let decision = policy.decide(&self.review);
+        if on_reject {
+            self.passed_reject += 1;
+        }
+        // Reject may come before or after approve and some apps wrap
+        // around, so allow passing it a couple of times.
+        let answer = match decision {
+            Decision::Approve => on_approve,
+            Decision::Reject => on_reject,
+        };
+        if answer {
+            self.pressed = Some(decision);
+            return Ok(match decision {
+                Decision::Approve => Step::Approve { hold: false },
+                Decision::Reject => Step::Reject,
+            });
+        }
+        if self.passed_reject > 2 {
+            return Err(io::Error::other(
+                "went past the reject screen without finding approve",
+            ));
+        }
+        Ok(Step::Next)
+    }
+
+    fn read(&mut self, screen: &[String]) {
+        for line in screen {
+            self.review.push_str(&line.to_ascii_lowercase());
+            self.review.push('\n');
+        }
+    }
+}
+
+/// Screens to step through looking for a menu entry.
+const MAX_MENU_SCREENS: usize = 16;
+
+fn screen_shows(screen: &[String], text: &str) -> bool {
+    screen.iter().any(|l| l.to_ascii_lowercase().contains(text))
+}
+
+/// The Speculos REST API.
+#[derive(Debug, Clone)]
+pub struct Speculos {
+    host: String,
+    port: u16,
+    pub model: Model,
+    /// Overrides the model's [`TouchLayout`].
+    pub layout: Option<TouchLayout>,
+    /// Where snapshots live; a subdirectory per model. Defaults to
+    /// `tests/snapshots` of the crate under test.
+    pub snapshot_dir: PathBuf,
+    /// How long to wait for a prompt to appear.
+    pub prompt_timeout: Duration,
+    /// Pause after each button press for the screen to update.
+    pub settle: Duration,
+    /// Whether screen text goes into logs and errors (which count as
+    /// `error` records).
+    pub log_policy: LogPolicy,
+}
+
+impl Speculos {
+    #[must_use]
+    pub fn new(host: &str, port: u16) -> Self {
+        Self {
+            host: host.into(),
+            port,
+            model: Model::NanoSPlus,
+            layout: None,
+            snapshot_dir: std::env::var_os("CARGO_MANIFEST_DIR")
+                .map_or_else(PathBuf::new, PathBuf::from)
+                .join("tests/snapshots"),
+            prompt_timeout: Duration::from_secs(30),
+            settle: Duration::from_millis(300),
+            log_policy: LogPolicy::DEFAULT,
+        }
+    }
+
+    /// `LEDGER_TCP_HOST` (default `127.0.0.1`), `SPECULOS_API_PORT`
+    /// (default `5000`) and `SPECULOS_MODEL` (default `nanosp`).
+    #[must_use]
+    pub fn from_env() -> Self {
+        let host = std::env::var("LEDGER_TCP_HOST").unwrap_or_else(|_| "127.0.0.1".into());
+        let port = std::env::var("SPECULOS_API_PORT")
+            .ok()
+            .and_then(|p| p.parse().ok())
+            .unwrap_or(5000);
+        let model = std::env::var("SPECULOS_MODEL")
+            .ok()
+            .and_then(|m| Model::from_name(&m))
+            .unwrap_or(Model::NanoSPlus);
+        Self::new(&host, port).with_model(model)
+    }
+
+    #[must_use]
+    pub fn with_model(mut self, model: Model) -> Self {
+        self.model = model;
+        self
+    }
+
+    #[must_use]
+    pub fn with_log_policy(mut self, policy: LogPolicy) -> Self {
+        self.log_policy = policy;
+        self
+    }
+
+    #[must_use]
+    pub fn with_snapshot_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.snapshot_dir = dir.into();
+        self
+    }
+
+    #[must_use]
+    pub fn with_layout(mut self, layout: TouchLayout) -> Self {
+        self.layout = Some(layout);
+        self
+    }
+
+    /// The touch layout in use; `None` on button models.
+    #[must_use]
+    pub fn touch_layout(&self) -> Option<TouchLayout> {
+        self.layout.or_else(|| self.model.touch_layout())
+    }
+
+    fn http(&self, method: &str, path: &str, body: Option<&str>) -> io::Result<String> {
+        let body = self.http_bytes(method, path, body)?;
+        Ok(String::from_utf8_lossy(&body).into_owned())
+    }
+
+    fn http_bytes(&self, method: &str, path: &str, body: Option<&str>) -> io::Result<Vec<u8>> {
+        let addr = format!("{}:{}", self.host, self.port);
+        let mut stream = TcpStream::connect(&addr)?;
+        let request = match body {
+            Some(b) => format!(
+                "{method} {path} HTTP/1.1\r\nHost: {addr}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{b}",
+                b.len()
+            ),
+            None => format!("{method} {path} HTTP/1.1\r\nHost: {addr}\r\nConnection: close\r\n\r\n"),
+        };
+        stream.write_all(request.as_bytes())?;
+        let mut response = Vec::new();
+        stream.read_to_end(&mut response)?;
+        let body = response
+            .windows(4)
+            .position(|w| w == b"\r\n\r\n")
+            .map_or(response.len(), |at| at + 4);
+        Ok(response.split_off(body))
+    }
+
+    /// Text lines of the current screen, top to bottom.
+    pub fn screen(&self) -> io::Result<Vec<String>> {
+        let body = self.http("GET", "/events?currentscreenonly=true", None)?;
+        Ok(screen_text(&body))
+    }
+
+    /// The current screen as PNG.
+    pub fn screenshot(&self) -> io::Result<Vec<u8>> {
+        self.http_bytes("GET", "/screenshot", None)
+    }
+
+    /// Wait until a line of the screen contains `text`, pressing nothing.
+    pub fn wait_for_text(&self, text: &str) -> io::Result<Vec<String>> {
+        let start = Instant::now();
+        loop {
+            let screen = self.screen()?;
+            if screen.iter().any(|line| line.contains(text)) {
+                return Ok(screen);
+            }
+            if start.elapsed() > self.prompt_timeout {
+                return Err(io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    format!(
+                        "{text:?} never appeared; last screen: {}",
+                        self.log_policy.show(
+                            Sensitive::Screen,
+                            log::Level::Error,
+                            format_args!("{screen:?}")
+                        )
+                    ),
+                ));
+            }
+            thread::sleep(self.settle);
+        }
+    }
+
+    /// Compare the current screen with snapshot `name` (e.g.
+    /// `"review_transfer.png"`) in [`snapshot_dir`](Self::snapshot_dir)
+    /// for this model.
+    ///
+    /// The screen is retried for a moment in case it is mid-transition.
+    /// With `UPDATE_SNAPSHOTS` set the snapshot is (re)recorded instead;
+    /// on a mismatch the actual screen is saved next to it as
+    /// `<name>.actual.png`.
+    ///
+    /// # Panics
+    ///
+    /// If the screen doesn't match, or on I/O errors.
+    pub fn assert_screen_matches(&self, name: &str) {
+        let path = self.snapshot_dir.join(self.model.name()).join(name);
+        let update = std::env::var_os("UPDATE_SNAPSHOTS").is_some();
+        let mut last = Err(String::new());
+        for _ in 0..5 {
+            let actual = self
+                .screenshot()
+                .unwrap_or_else(|e| panic!("screenshot for {name}: {e}"));
+            last = compare_snapshot(&path, &actual, update);
+            if last.is_ok() {
+                return;
+            }
+            thread::sleep(self.settle);
+        }
+        if let Err(e) = last {
+            panic!("{e}");
+        }
+    }
+
+    pub fn press(&self, button: Button) -> io::Result<()> {
+        self.http(
+            "POST",
+            &format!("/button/{}", button.name()),
+            Some(r#"{"action":"press-and-release"}"#),
+        )?;
+        thread::sleep(self.settle);
+        Ok(())
+    }
+
+    fn finger(&self, action: &str, at: Point, delay: Option<Duration>) -> io::Result<()> {
+        let delay = delay.map_or(String::new(), |d| {
+            format!(r#","delay":{}"#, d.as_secs_f32())
+        });
+        let body = format!(
+            r#"{{"action":"{action}","x":{},"y":{}{delay}}}"#,
+            at.x, at.y
+        );
+        self.http("POST", "/finger", Some(&body))?;
+        Ok(())
+    }
+
+    pub fn tap(&self, at: Point) -> io::Result<()> {
+        self.finger("press-and-release", at, None)?;
+        thread::sleep(self.settle);
+        Ok(())
+    }
+
+    pub fn long_press(&self, at: Point, hold: Duration) -> io::Result<()> {
+        self.finger("press-and-release", at, Some(hold))?;
+        thread::sleep(hold + self.settle);
+        Ok(())
+    }
+
+    pub fn swipe(&self, from: Point, to: Point) -> io::Result<()> {
+        self.finger("press", from, None)?;
+        self.finger("release", to, None)?;
+        thread::sleep(self.settle);
+        Ok(())
+    }
+
+    /// Show the next review screen: the right button, or a swipe on
+    /// touchscreens.
+    pub fn next_screen(&self) -> io::Result<()> {
+        self.perform(&Step::Next)
+    }
+
+    fn perform(&self, step: &Step) -> io::Result<()> {
+        let Some(layout) = self.touch_layout() else {
+            return match step {
+                Step::Next => self.press(Button::Right),
+                _ => self.press(Button::Both),
+            };
+        };
+        match *step {
+            Step::Next => self.swipe(layout.swipe_from, layout.swipe_to),
+            Step::Acknowledge | Step::Approve { hold: false } => self.tap(layout.confirm),
+            Step::Approve { hold: true } => self.long_press(layout.hold, layout.hold_for),
+            Step::Reject => {
+                self.tap(layout.reject)?;
+                self.tap(layout.confirm_reject)
+            }
+            Step::Done(_) => Ok(()),
+        }
+    }
+
+    /// Wait for the current screen to change from `idle`.
+    fn wait_for_change(&self, idle: &[String]) -> io::Result<Vec<String>> {
+        let start = Instant::now();
+        loop {
+            let screen = self.screen()?;
+            if screen != idle && !screen.is_empty() {
+                return Ok(screen);
+            }
+            if start.elapsed() > self.prompt_timeout {
+                return Err(io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    "no prompt appeared on the device",
+                ));
+            }
+            thread::sleep(self.settle);
+        }
+    }
+
+    /// Answer the next prompt under `policy`.
+    ///
+    /// Waits for the screen to leave the one showing when called, so call
+    /// it before the command that triggers the prompt (or use
+    /// [`spawn`](Self::spawn)).
+    pub fn answer(&self, policy: &ApprovalPolicy) -> io::Result<Decision> {
+        let idle = self.screen()?;
+        self.wait_for_change(&idle)?;
+        self.answer_shown(policy)
+    }
+
+    /// Answer the prompt already on screen under `policy`, e.g. after
+    /// stepping through part of it with [`next_screen`](Self::next_screen).
+    pub fn answer_shown(&self, policy: &ApprovalPolicy) -> io::Result<Decision> {
+        let mut screen = self.screen()?;
+        let mut navigator = Navigator::new(policy, self.touch_layout().is_some());
+        loop {
+            log::debug!(
+                "screen: {}",
+                self.log_policy.show(
+                    Sensitive::Screen,
+                    log::Level::Debug,
+                    format_args!("{screen:?}")
+                )
+            );
+            match navigator.step(&screen)? {
+                Step::Done(decision) => return Ok(decision),
+                step => self.perform(&step)?,
+            }
+            screen = self.screen()?;
+        }
+    }
+
+    /// Turn the app's blind signing setting on or off, reading the screens
+    /// to find the setting, and return to the screen showing when called.
+    ///
+    /// Only button models; touchscreen settings fail with
+    /// [`io::ErrorKind::Unsupported`].
+    pub fn set_blind_signing(&self, enabled: bool) -> io::Result<()> {
+        if self.touch_layout().is_some() {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "settings navigation is only implemented for button models",
+            ));
+        }
+        let home = self.screen()?;
+        self.walk_to(Button::Right, |s| screen_shows(s, "settings"))?;
+        self.press(Button::Both)?;
+        let setting = self.walk_to(Button::Right, |s| screen_shows(s, "blind signing"))?;
+        if screen_shows(&setting, "enabled") != enabled {
+            self.press(Button::Both)?;
+        }
+        self.walk_to(Button::Right, |s| screen_shows(s, "back"))?;
+        self.press(Button::Both)?;
+        self.walk_to(Button::Left, |s| s == home)?;
+        Ok(())
+    }
+
+    /// Press `button` until `found` matches the screen.
+    fn walk_to(
+        &self,
+        button: Button,
+        found: impl Fn(&[String]) -> bool,
+    ) -> io::Result<Vec<String>> {
+        for _ in 0..MAX_MENU_SCREENS {
+            let screen = self.screen()?;
+            if found(&screen) {
+                return Ok(screen);
+            }
+            self.press(button)?;
+        }
+        Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("screen not found within {MAX_MENU_SCREENS} presses"),
+        ))
+    }
+
+    /// [`answer`](Self::answer) on a background thread.
+    pub fn spawn(&self, policy: ApprovalPolicy) -> JoinHandle<io::Result<Decision>> {
+        let speculos = self.clone();
+        thread::spawn(move || speculos.answer(&policy))
+    }
+}
+
+/// How to start a local `speculos` process.
+///
+/// ```no_run
+/// use ledger_iota::testing::speculos::{Launch, Model};
+/// use ledger_iota::{Bip32Path, LedgerIota};
+///
+/// let launch = Launch::new("tests/elf/app-iota-nanosp.elf")
+///     .with_mnemonic("abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about");
+/// let instance = launch.spawn()?;
+/// let ledger = LedgerIota::new(&instance.transport())?;
+/// let path = Bip32Path::iota(0, 0, 0);
+/// assert_eq!(ledger.get_pubkey(&path)?, launch.seed().keys(&path));
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct Launch {
+    /// The `speculos` executable (default: `speculos` on `PATH`).
+    pub program: PathBuf,
+    pub elf: PathBuf,
+    pub model: Model,
+    pub mnemonic: String,
+    pub apdu_port: u16,
+    pub api_port: u16,
+    /// How long to wait for the APDU port to accept connections.
+    pub startup_timeout: Duration,
+}
+
+impl Launch {
+    #[must_use]
+    pub fn new(elf: impl Into<PathBuf>) -> Self {
+        Self {
+            program: "speculos".into(),
+            elf: elf.into(),
+            model: Model::NanoSPlus,
+            mnemonic: crate::seed::SPECULOS_MNEMONIC.into(),
+            apdu_port: 9999,
+            api_port: 5000,
+            startup_timeout: Duration::from_secs(30),
+        }
+    }
+
+    #[must_use]
+    pub fn with_mnemonic(mut self, mnemonic: &str) -> Self {
+        self.mnemonic = mnemonic.into();
+        self
+    }
+
+    #[must_use]
+    pub fn with_model(mut self, model: Model) -> Self {
+        self.model = model;
+        self
+    }
+
+    #[must_use]
+    pub fn with_ports(mut self, apdu_port: u16, api_port: u16) -> Self {
+        self.apdu_port = apdu_port;
+        self.api_port = api_port;
+        self
+    }
+
+    /// The keys the emulated app derives.
+    #[must_use]
+    pub fn seed(&self) -> crate::seed::Seed {
+        crate::seed::Seed::from_mnemonic(&self.mnemonic, "")
+    }
+
+    /// The command line, for running it some other way (e.g. in a
+    /// container).
+    #[must_use]
+    pub fn command(&self) -> Command {
+        let mut command = Command::new(&self.program);
+        command
+            .arg("--model")
+            .arg(self.model.name())
+            .args(["--display", "headless"])
+            .arg("--seed")
+            .arg(&self.mnemonic)
+            .arg("--apdu-port")
+            .arg(self.apdu_port.to_string())
+            .arg("--api-port")
+            .arg(self.api_port.to_string())
+            .arg(&self.elf);
+        command
+    }
+
+    /// Start Speculos and wait until it accepts APDUs.
+    pub fn spawn(&self) -> io::Result<Instance> {
+        let child = self
+            .command()
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()?;
+        let mut instance = Instance {
+            launch: self.clone(),
+            child,
+        };
+        instance.wait_ready()?;
+        Ok(instance)
+    }
+}
+
+/// A running Speculos process, stopped on drop.
+#[derive(Debug)]
+pub struct Instance {
+    launch: Launch,
+    child: Child,
+}
+
+impl Instance {
+    #[must_use]
+    pub fn launch(&self) -> &Launch {
+        &self.launch
+    }
+
+    #[must_use]
+    pub fn transport(&self) -> crate::TransportType {
+        crate::TransportType::TCP("127.0.0.1".into(), self.launch.apdu_port)
+    }
+
+    /// REST API client for this instance.
+    #[must_use]
+    pub fn api(&self) -> Speculos {
+        Speculos::new("127.0.0.1", self.launch.api_port).with_model(self.launch.model)
+    }
+
+    /// Restart with another mnemonic. Speculos reads the seed only at
+    /// startup, so open connections are lost.
+    pub fn reconfigure(&mut self, mnemonic: &str) -> io::Result<()> {
+        self.stop();
+        let launch = self.launch.clone().with_mnemonic(mnemonic);
+        *self = launch.spawn()?;
+        Ok(())
+    }
+
+    fn stop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+
+    fn wait_ready(&mut self) -> io::Result<()> {
+        let start = Instant::now();
+        loop {
+            if let Some(status) = self.child.try_wait()? {
+                return Err(io::Error::other(format!("speculos exited with {status}")));
+            }
+            if TcpStream::connect(("127.0.0.1", self.launch.apdu_port)).is_ok() {
+                return Ok(());
+            }
+            if start.elapsed() > self.launch.startup_timeout {
+                self.stop();
+                return Err(io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    "speculos did not open its APDU port",
+                ));
+            }
+            thread::sleep(Duration::from_millis(200));
+        }
+    }
+}
+
+impl Drop for Instance {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// Check `actual` against the PNG at `path`, or record it with `update`.
+fn compare_snapshot(path: &Path, actual: &[u8], update: bool) -> Result<(), String> {
+    let io_err = |e: io::Error| format!("{}: {e}", path.display());
+    let actual_path = path.with_extension("actual.png");
+    if update {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(io_err)?;
+        }
+        std::fs::write(path, actual).map_err(io_err)?;
+        let _ = std::fs::remove_file(&actual_path);
+        return Ok(());
+    }
+    let expected = std::fs::read(path)
+        .map_err(|e| format!("{}: {e} (run with UPDATE_SNAPSHOTS=1)", path.display()))?;
+    if expected == actual {
+        let _ = std::fs::remove_file(&actual_path);
+        return Ok(());
+    }
+    std::fs::write(&actual_path, actual).map_err(io_err)?;
+    Err(format!(
+        "screen differs from {}; actual saved to {}",
+        path.display(),
+        actual_path.display()
+    ))
+}
+
+/// The `text` fields of a Speculos `/events` response.
+fn screen_text(body: &str) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut rest = body;
+    while let Some(at) = rest.find("\"text\"") {
+        rest = &rest[at + "\"text\"".len()..];
+        let Some(open) = rest.find('"') else { break };
+        rest = &rest[open + 1..];
+        let mut text = String::new();
+        let mut chars = rest.char_indices();
+        let mut end = rest.len();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '"' => {
+                    end = i + 1;
+                    break;
+                }
+                '\\' => match chars.next() {
+                    Some((_, 'n')) => text.push('\n'),
+                    Some((_, 't')) => text.push('\t'),
+                    Some((_, other)) => text.push(other),
+                    None => {}
+                },
+                c => text.push(c),
+            }
+        }
+        rest = &rest[end..];
+        lines.push(text);
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn screen(lines: &[&str]) -> Vec<String> {
+        lines.iter().map(|l| (*l).to_string()).collect()
+    }
+
+    /// Run `policy` over `screens`, moving to the next screen after
+    /// each step.
+    fn drive(
+        policy: &ApprovalPolicy,
+        touch: bool,
+        screens: &[&[&str]],
+    ) -> io::Result<(Decision, Vec<Step>)> {
+        let mut navigator = Navigator::new(policy, touch);
+        let mut steps = Vec::new();
+        for i in 0.. {
+            match navigator.step(&screen(screens[i % screens.len()]))? {
+                Step::Done(decision) => return Ok((decision, steps)),
+                step => steps.push(step),
+            }
+        }
+        unreachable!()
+    }
+
+    const BLIND: &[&[&str]] = &[
+        &["Blind signing", "ahead"],
+        &["Review", "transaction"],
+        &["Hash", "4f3a"],
+        &["Sign", "transaction"],
+        &["Reject"],
+    ];
+
+    const TRANSFER: &[&[&str]] = &[
+        &["Review", "transaction"],
+        &["To", "0xABCD0123"],
+        &["Amount", "1 IOTA"],
+        &["Sign", "transaction"],
+        &["Reject"],
+    ];
+
+    const TOUCH_TRANSFER: &[&[&str]] = &[
+        &["Review transaction", "to send IOTA"],
+        &["To", "0xABCD0123", "Amount", "1 IOTA"],
+        &["Sign transaction?", "Hold to sign"],
+    ];
+
+    #[test]
+    fn approve_all_walks_to_sign() {
+        let (decision, steps) = drive(&ApprovalPolicy::approve_all(), false, BLIND).unwrap();
+        assert_eq!(decision, Decision::Approve);
+        assert_eq!(
+            steps,
+            [
+                Step::Acknowledge,
+                Step::Next,
+                Step::Next,
+                Step::Approve { hold: false }
+            ]
+        );
+    }
+
+    #[test]
+    fn reject_all_walks_past_sign() {
+        let (decision, steps) = drive(&ApprovalPolicy::reject_all(), false, TRANSFER).unwrap();
+        assert_eq!(decision, Decision::Reject);
+        assert_eq!(steps.last(), Some(&Step::Reject));
+        assert_eq!(steps.len(), 5);
+    }
+
+    #[test]
+    fn recipient_rule() {
+        let approve = ApprovalPolicy::approve_if_recipient("0xabcd0123");
+        assert_eq!(
+            drive(&approve, false, TRANSFER).unwrap().0,
+            Decision::Approve
+        );
+        let other = ApprovalPolicy::approve_if_recipient("ffff");
+        assert_eq!(drive(&other, false, TRANSFER).unwrap().0, Decision::Reject);
+        let shown = ApprovalPolicy::approve_if_shown("1 iota");
+        assert_eq!(drive(&shown, false, TRANSFER).unwrap().0, Decision::Approve);
+
+        // Only the whole address counts.
+        for address in ["", "0x", "abcd", "0xabcd0123ff", "bcd0123"] {
+            let policy = ApprovalPolicy::approve_if_recipient(address);
+            assert_eq!(
+                drive(&policy, false, TRANSFER).unwrap().0,
+                Decision::Reject,
+                "{address:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn recipient_wraps_across_lines() {
+        let wrapped: &[&[&str]] = &[
+            &["To (1/2)", "0xABCD"],
+            &["To (2/2)", "0123"],
+            &["Sign", "transaction"],
+            &["Reject"],
+        ];
+        let approve = ApprovalPolicy::approve_if_recipient("abcd0123");
+        assert_eq!(
+            drive(&approve, false, wrapped).unwrap().0,
+            Decision::Approve
+        );
+        let split: &[&[&str]] = &[
+            &["To", "0xABCD", "0123"],
+            &["Fee", "1"],
+            &["Sign", "transaction"],
+            &["Reject"],
+        ];
+        assert_eq!(drive(&approve, false, split).unwrap().0, Decision::Approve);
+        // Joining stops at the expected length.
+        let review = "to\n0xabcd\n0123\nfee\n";
+        assert_eq!(shown_addresses(review, 8).collect::<Vec<_>>(), ["abcd0123"]);
+    }
+
+    #[test]
+    fn unknown_layout_gives_up() {
+        let screens: &[&[&str]] = &[&["Review"], &["Amount", "1"]];
+        let policy = ApprovalPolicy::approve_all().max_screens(10);
+        assert!(drive(&policy, false, screens).is_err());
+        let policy = ApprovalPolicy::approve_all().approve_label("Amount");
+        assert_eq!(drive(&policy, false, screens).unwrap().0, Decision::Approve);
+    }
+
+    #[test]
+    fn touch_swipes_and_holds_to_sign() {
+        let (decision, steps) =
+            drive(&ApprovalPolicy::approve_all(), true, TOUCH_TRANSFER).unwrap();
+        assert_eq!(decision, Decision::Approve);
+        assert_eq!(
+            steps,
+            [Step::Next, Step::Next, Step::Approve { hold: true }]
+        );
+
+        let other = ApprovalPolicy::approve_if_recipient("ffff");
+        let (decision, steps) = drive(&other, true, TOUCH_TRANSFER).unwrap();
+        assert_eq!(decision, Decision::Reject);
+        assert_eq!(steps.last(), Some(&Step::Reject));
+
+        // Nothing to read before rejecting outright.
+        let (_, steps) = drive(&ApprovalPolicy::reject_all(), true, TOUCH_TRANSFER).unwrap();
+        assert_eq!(steps, [Step::Reject]);
+    }
+
+    #[test]
+    fn models() {
+        assert_eq!(Model::from_name("STAX"), Some(Model::Stax));
+        assert_eq!(
+            Model::from_name("flex").unwrap().touch_layout(),
+            Some(TouchLayout::FLEX)
+        );
+        assert!(Model::from_name("nanox").unwrap().touch_layout().is_none());
+        assert!(Model::from_name("blue").is_none());
+        let speculos = Speculos::new("localhost", 5000).with_model(Model::NanoX);
+        assert!(speculos.touch_layout().is_none());
+        let custom = TouchLayout {
+            hold_for: Duration::from_secs(5),
+            ..TouchLayout::STAX
+        };
+        assert_eq!(speculos.with_layout(custom).touch_layout(), Some(custom));
+    }
+
+    #[test]
+    fn launch_command() {
+        let launch = Launch::new("app.elf")
+            .with_model(Model::Flex)
+            .with_mnemonic("abandon about")
+            .with_ports(40000, 40001);
+        let command = launch.command();
+        let args: Vec<_> = command.get_args().map(|a| a.to_str().unwrap()).collect();
+        assert_eq!(
+            args,
+            [
+                "--model",
+                "flex",
+                "--display",
+                "headless",
+                "--seed",
+                "abandon about",
+                "--apdu-port",
+                "40000",
+                "--api-port",
+                "40001",
+                "app.elf"
+            ]
+        );
+        let path = crate::Bip32Path::iota(0, 0, 0);
+        assert_eq!(
+            launch.seed().keys(&path),
+            crate::seed::Seed::from_mnemonic("abandon about", "").keys(&path)
+        );
+        let missing = Launch {
+            program: "/nonexistent/speculos".into(),
+            ..launch
+        };
+        assert!(missing.spawn().is_err());
+    }
+
+    #[test]
+    fn snapshot_comparison() {
+        let dir = crate::fs_util::TempDir::new("snapshots");
+        let path = dir.join("nanox/review.png");
+        let actual_path = dir.join("nanox/review.actual.png");
+
+        assert!(compare_snapshot(&path, b"png-1", false)
+            .unwrap_err()
+            .contains("UPDATE_SNAPSHOTS"));
+        compare_snapshot(&path, b"png-1", true).unwrap();
+        compare_snapshot(&path, b"png-1", false).unwrap();
+
+        assert!(compare_snapshot(&path, b"png-2", false).is_err());
+        assert_eq!(std::fs::read(&actual_path).unwrap(), b"png-2");
+        compare_snapshot(&path, b"png-1", false).unwrap();
+        assert!(!actual_path.exists());
+    }
+
+    #[test]
+    fn parses_event_text() {
+        let body =
+            r#"{"events": [{"text": "Sign", "x": 1, "y": 2}, {"text": "say \"hi\"\n", "x": 1}]}"#;
+        assert_eq!(screen_text(body), ["Sign", "say \"hi\"\n"]);
+        assert!(screen_text(r#"{"events": []}"#).is_empty());
+    }
+}
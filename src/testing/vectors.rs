@@ -0,0 +1,289 @@
+//! Signing vectors from `tests/vectors/`, replayed through a device.
+//!
+//! The vector files are shared with the encoding checks in
+//! `tests/vectors.rs`: `{"source": ..., "vectors": [...]}`, where
+//! `source` names where the expected values come from. This module reads
+//! the signing vectors among them and skips the other kinds:
+//!
+//! ```json
+//! {
+//!   "name": "test_sign_tx_blind",
+//!   "path": "m/44'/4218'/0'/0'/1'",
+//!   "kind": "transaction",
+//!   "data": "<base64 intent message>",
+//!   "objects": ["<hex ObjectData>"],
+//!   "signature": "<hex>"
+//! }
+//! ```
+//!
+//! `kind` is `transaction` (`data` is the intent message) or `message`
+//! (`data` is the message); `objects` and `signature` are optional.
+//! `name` is the app test the vector corresponds to: the app's ragger
+//! tests record their screens in
+//! `snapshots/<model>/<test name>/00000.png, 00001.png, ...`, which
+//! [`Vector::snapshots`] lists.
+
+use std::path::{Path, PathBuf};
+
+use serde_json::Value;
+
+use crate::api::LedgerIota;
+use crate::error::LedgerError;
+use crate::objects::ObjectData;
+use crate::types::{Bip32Path, Signature};
+
+/// What a vector signs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VectorKind {
+    /// An intent-prefixed transaction.
+    Transaction,
+    /// A personal message, without the intent prefix.
+    Message,
+}
+
+/// One signing case.
+#[derive(Debug, Clone)]
+pub struct Vector {
+    pub name: String,
+    /// The `source` of the file the vector came from.
+    pub source: String,
+    pub path: Bip32Path,
+    pub kind: VectorKind,
+    pub data: Vec<u8>,
+    pub objects: Vec<ObjectData>,
+    /// The expected signature with the default Speculos seed, if known.
+    pub signature: Option<Signature>,
+}
+
+/// The result of [`Vector::replay`].
+#[derive(Debug)]
+pub struct Replay {
+    pub signature: Signature,
+    /// `None` when the vector has no recorded signature.
+    pub matches: Option<bool>,
+}
+
+impl Vector {
+    /// The intent message the device signs.
+    #[must_use]
+    pub fn intent_message(&self) -> Vec<u8> {
+        match self.kind {
+            VectorKind::Transaction => self.data.clone(),
+            VectorKind::Message => [&[3, 0, 0][..], &self.data].concat(),
+        }
+    }
+
+    /// Sign through `ledger` and compare with the recorded signature.
+    /// Prompts must be answered separately, e.g. with
+    /// [`Speculos::spawn`](super::speculos::Speculos::spawn).
+    pub fn replay(&self, ledger: &LedgerIota) -> Result<Replay, LedgerError> {
+        let signature = match self.kind {
+            VectorKind::Transaction => {
+                let objects = (!self.objects.is_empty()).then_some(self.objects.as_slice());
+                ledger.sign_tx(&self.data, &self.path, objects)?
+            }
+            VectorKind::Message => ledger.sign_message(&self.data, &self.path)?,
+        };
+        let matches = self.signature.as_ref().map(|s| *s == signature);
+        Ok(Replay { signature, matches })
+    }
+
+    /// The screens the app's test of the same name recorded for `model`
+    /// (e.g. `"nanosp"`), in order. `root` is the directory holding
+    /// `snapshots/`, such as the app's `tests/`.
+    pub fn snapshots(&self, root: &Path, model: &str) -> std::io::Result<Vec<PathBuf>> {
+        let dir = root.join("snapshots").join(model).join(&self.name);
+        let mut screens: Vec<_> = std::fs::read_dir(dir)?
+            .map(|entry| entry.map(|e| e.path()))
+            .filter(|path| {
+                path.as_ref()
+                    .map_or(true, |p| p.extension().is_some_and(|e| e == "png"))
+            })
+            .collect::<Result<_, _>>()?;
+        screens.sort();
+        Ok(screens)
+    }
+}
+
+/// The signing vectors of a vector file.
+pub fn parse(json: &str) -> Result<Vec<Vector>, LedgerError> {
+    let root: Value = serde_json::from_str(json)
+        .map_err(|e| LedgerError::InvalidEncoding(format!("vectors: {e}")))?;
+    let source = root
+        .get("source")
+        .and_then(Value::as_str)
+        .filter(|s| !s.is_empty())
+        .ok_or_else(|| invalid("", "missing \"source\""))?;
+    root.get("vectors")
+        .and_then(Value::as_array)
+        .ok_or_else(|| invalid("", "missing \"vectors\" array"))?
+        .iter()
+        .filter(|v| matches!(v["kind"].as_str(), Some("transaction" | "message")))
+        .map(|v| parse_vector(source, v))
+        .collect()
+}
+
+/// The signing vectors shipped in this crate's `tests/vectors/`.
+pub fn shipped() -> std::io::Result<Vec<Vector>> {
+    load_dir(super::fixture_path(""))
+}
+
+/// Every `*.json` vector file in `dir`, in file name order. Malformed
+/// files are [`InvalidData`](std::io::ErrorKind::InvalidData) errors.
+pub fn load_dir(dir: impl AsRef<Path>) -> std::io::Result<Vec<Vector>> {
+    let mut files: Vec<_> = std::fs::read_dir(dir)?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<Result<_, _>>()?;
+    files.retain(|p| p.extension().is_some_and(|e| e == "json"));
+    files.sort();
+    let mut vectors = Vec::new();
+    for file in files {
+        let parsed = parse(&std::fs::read_to_string(&file)?).map_err(|e| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("{}: {e}", file.display()),
+            )
+        })?;
+        vectors.extend(parsed);
+    }
+    Ok(vectors)
+}
+
+fn invalid(name: &str, what: &str) -> LedgerError {
+    LedgerError::InvalidEncoding(format!("vector {name:?}: {what}"))
+}
+
+fn parse_vector(source: &str, v: &Value) -> Result<Vector, LedgerError> {
+    let field = |key: &str| v.get(key).and_then(Value::as_str);
+    let name = field("name").ok_or_else(|| invalid("", "missing name"))?;
+    let hex_field = |value: &str, key: &str| {
+        hex::decode(value.trim_start_matches("0x"))
+            .map_err(|e| invalid(name, &format!("{key}: {e}")))
+    };
+
+    let path = field("path")
+        .ok_or_else(|| invalid(name, "missing path"))?
+        .parse()
+        .map_err(|e| invalid(name, &format!("path: {e}")))?;
+    let kind = match field("kind") {
+        Some("transaction") => VectorKind::Transaction,
+        _ => VectorKind::Message,
+    };
+    let data = crate::tx::from_base64(field("data").ok_or_else(|| invalid(name, "missing data"))?)
+        .map_err(|e| invalid(name, &format!("data: {e}")))?;
+    let objects = match v.get("objects") {
+        None => Vec::new(),
+        Some(list) => list
+            .as_array()
+            .ok_or_else(|| invalid(name, "objects is not an array"))?
+            .iter()
+            .map(|o| {
+                let hex = o
+                    .as_str()
+                    .ok_or_else(|| invalid(name, "object is not a string"))?;
+                ObjectData::from_bytes(&hex_field(hex, "objects")?)
+            })
+            .collect::<Result<_, _>>()?,
+    };
+    let signature = field("signature")
+        .map(|s| {
+            let bytes = hex_field(s, "signature")?;
+            let bytes: [u8; 64] = bytes
+                .try_into()
+                .map_err(|_| invalid(name, "signature is not 64 bytes"))?;
+            Ok::<_, LedgerError>(Signature(bytes))
+        })
+        .transpose()?;
+
+    Ok(Vector {
+        name: name.into(),
+        source: source.into(),
+        path,
+        kind,
+        data,
+        objects,
+        signature,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::seed::Seed;
+
+    fn shipped() -> Vec<Vector> {
+        super::shipped().unwrap()
+    }
+
+    #[test]
+    fn loads_shipped_vectors() {
+        let vectors = shipped();
+        assert!(vectors.iter().any(|v| v.kind == VectorKind::Transaction));
+        assert!(vectors.iter().any(|v| v.kind == VectorKind::Message));
+        // Encoding vectors in the same directory are skipped.
+        assert!(vectors.iter().all(|v| !v.source.is_empty()));
+    }
+
+    /// The expected signatures are what the Speculos seed produces.
+    #[test]
+    fn signatures_match_speculos_seed() {
+        let seed = Seed::speculos();
+        for v in shipped() {
+            let expected = v
+                .signature
+                .clone()
+                .unwrap_or_else(|| panic!("{}: no signature", v.name));
+            assert_eq!(
+                seed.sign(&v.path, &v.intent_message()),
+                expected,
+                "{}",
+                v.name
+            );
+        }
+    }
+
+    #[test]
+    fn replays_against_a_device() {
+        let ledger =
+            LedgerIota::with_transport(Box::new(crate::testing::SimulatedDevice::speculos()));
+        for v in shipped() {
+            // Same keys as Speculos, so signatures match.
+            let replay = v.replay(&ledger).unwrap();
+            assert_eq!(replay.matches, Some(true), "{}", v.name);
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_vectors() {
+        assert!(parse("{}").is_err());
+        assert!(
+            parse(r#"{"source": "s", "vectors": [{"name": "x", "kind": "message"}]}"#).is_err()
+        );
+        let no_source = r#"{"vectors": [{"name": "x", "path": "m/44'/4218'/0'/0'/0'", "kind": "message", "data": ""}]}"#;
+        assert!(parse(no_source).is_err());
+        let other_kind = r#"{"source": "s", "vectors": [{"name": "x", "kind": "objects"}]}"#;
+        assert!(parse(other_kind).unwrap().is_empty());
+        let short_sig = r#"{"source": "s", "vectors": [{"name": "x", "path": "m/44'/4218'/0'/0'/0'", "kind": "message", "data": "aGk=", "signature": "abcd"}]}"#;
+        assert!(parse(short_sig).is_err());
+        let ok = r#"{"source": "s", "vectors": [{"name": "x", "path": "m/44'/4218'/0'/0'/0'", "kind": "message", "data": "aGk="}]}"#;
+        let v = &parse(ok).unwrap()[0];
+        assert_eq!(v.intent_message(), b"\x03\x00\x00hi");
+        assert!(v.signature.is_none());
+    }
+
+    #[test]
+    fn lists_snapshots_in_order() {
+        let root = crate::fs_util::TempDir::new("vectors");
+        let dir = root.join("snapshots/nanosp/case");
+        std::fs::create_dir_all(&dir).unwrap();
+        for name in ["00001.png", "00000.png", "notes.txt"] {
+            std::fs::write(dir.join(name), b"").unwrap();
+        }
+        let v = Vector {
+            name: "case".into(),
+            ..parse(r#"{"source": "s", "vectors": [{"name": "x", "path": "m/44'/4218'/0'/0'/0'", "kind": "message", "data": ""}]}"#).unwrap().remove(0)
+        };
+        let screens = v.snapshots(&root, "nanosp").unwrap();
+        assert_eq!(screens, [dir.join("00000.png"), dir.join("00001.png")]);
+    }
+}
@@ -0,0 +1,153 @@
+//! Reassembly of APDU responses from Ledger HID packets.
+//!
+//! Each 64-byte packet is `[channel: u16][tag][seq: u16][data]`; the first
+//! packet's data starts with the big-endian response length.
+
+use crate::error::TransportError;
+use crate::wipe::Transient;
+
+pub(crate) const CHANNEL: u16 = 0x0101;
+pub(crate) const TAG: u8 = 0x05;
+
+/// Outcome of feeding one packet to a [`Reassembler`].
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum Packet {
+    /// The response is complete.
+    Done,
+    /// More packets follow.
+    More,
+    /// Not part of this response (other channel or tag, or a repeat of a
+    /// packet already taken); read the next one.
+    Skipped,
+    /// A packet went missing; the response can't be completed.
+    Gap { expected: u16, got: u16 },
+}
+
+pub(crate) struct Reassembler {
+    result: Transient<Vec<u8>>,
+    expected_len: usize,
+    next_seq: u16,
+}
+
+impl Reassembler {
+    pub(crate) fn new() -> Self {
+        Self {
+            result: Transient(Vec::new()),
+            expected_len: 0,
+            next_seq: 0,
+        }
+    }
+
+    pub(crate) fn push(&mut self, packet: &[u8]) -> Result<Packet, TransportError> {
+        if packet.len() < 5 {
+            return Err(TransportError::Comm(format!(
+                "HID short read: got {} bytes, need at least 5",
+                packet.len()
+            )));
+        }
+        let channel = u16::from_be_bytes([packet[0], packet[1]]);
+        if channel != CHANNEL || packet[2] != TAG {
+            return Ok(Packet::Skipped);
+        }
+        let seq = u16::from_be_bytes([packet[3], packet[4]]);
+        if seq < self.next_seq {
+            return Ok(Packet::Skipped);
+        }
+        if seq > self.next_seq {
+            return Ok(Packet::Gap {
+                expected: self.next_seq,
+                got: seq,
+            });
+        }
+
+        let data = if seq == 0 {
+            if packet.len() < 7 {
+                return Err(TransportError::Comm(format!(
+                    "HID short read: got {} bytes, need at least 7",
+                    packet.len()
+                )));
+            }
+            self.expected_len = usize::from(u16::from_be_bytes([packet[5], packet[6]]));
+            &packet[7..]
+        } else {
+            &packet[5..]
+        };
+
+        let take = (self.expected_len - self.result.len()).min(data.len());
+        self.result.extend_from_slice(&data[..take]);
+        if self.result.len() >= self.expected_len {
+            return Ok(Packet::Done);
+        }
+        self.next_seq += 1;
+        Ok(Packet::More)
+    }
+
+    pub(crate) fn finish(self) -> Vec<u8> {
+        self.result.into_inner()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn packet(seq: u16, data: &[u8]) -> Vec<u8> {
+        let mut p = CHANNEL.to_be_bytes().to_vec();
+        p.push(TAG);
+        p.extend_from_slice(&seq.to_be_bytes());
+        p.extend_from_slice(data);
+        p.resize(64, 0);
+        p
+    }
+
+    fn first(len: u16, data: &[u8]) -> Vec<u8> {
+        let mut d = len.to_be_bytes().to_vec();
+        d.extend_from_slice(data);
+        packet(0, &d)
+    }
+
+    #[test]
+    fn single_packet() {
+        let mut r = Reassembler::new();
+        assert_eq!(r.push(&first(3, &[1, 2, 3])).unwrap(), Packet::Done);
+        assert_eq!(r.finish(), [1, 2, 3]);
+    }
+
+    #[test]
+    fn skips_duplicates_and_foreign_packets() {
+        let mut r = Reassembler::new();
+        let head = first(60, &[0xAA; 57]);
+        assert_eq!(r.push(&head).unwrap(), Packet::More);
+        assert_eq!(r.push(&head).unwrap(), Packet::Skipped);
+
+        let mut other_channel = packet(1, &[0xEE; 3]);
+        other_channel[1] = 0x02;
+        assert_eq!(r.push(&other_channel).unwrap(), Packet::Skipped);
+
+        assert_eq!(r.push(&packet(1, &[0xBB; 3])).unwrap(), Packet::Done);
+        let out = r.finish();
+        assert_eq!(out.len(), 60);
+        assert_eq!(&out[57..], [0xBB; 3]);
+    }
+
+    #[test]
+    fn reports_gaps() {
+        let mut r = Reassembler::new();
+        assert_eq!(r.push(&first(200, &[0; 57])).unwrap(), Packet::More);
+        assert_eq!(
+            r.push(&packet(2, &[0; 59])).unwrap(),
+            Packet::Gap {
+                expected: 1,
+                got: 2
+            }
+        );
+    }
+
+    #[test]
+    fn short_packets_are_errors() {
+        assert!(Reassembler::new().push(&[0x01, 0x01, TAG]).is_err());
+        assert!(Reassembler::new()
+            .push(&[0x01, 0x01, TAG, 0, 0, 0])
+            .is_err());
+    }
+}
@@ -1,20 +1,25 @@
 use std::sync::Mutex;
 
-use crate::apdu::{ApduAnswer, ApduCommand};
+use crate::apdu::{ApduAnswer, ApduCommand, Instruction};
 use crate::error::TransportError;
+use crate::limits::PayloadLimits;
+use crate::transport::framing::{self, Packet, Reassembler};
 use crate::transport::Transport;
+use crate::wipe::Transient;
 
 const LEDGER_VID: u16 = 0x2c97;
 const LEDGER_USAGE_PAGE: u16 = 0xFFA0;
-const LEDGER_CHANNEL: u16 = 0x0101;
-const LEDGER_TAG: u8 = 0x05;
+const LEDGER_CHANNEL: u16 = framing::CHANNEL;
+const LEDGER_TAG: u8 = framing::TAG;
 const LEDGER_PACKET_WRITE_SIZE: usize = 65;
 const LEDGER_PACKET_READ_SIZE: usize = 64;
 const LEDGER_TIMEOUT_MS: i32 = 30_000;
 const CHUNK_SIZE: usize = LEDGER_PACKET_WRITE_SIZE - 6;
+/// Upper bound on stale packets discarded before a write.
+const MAX_DRAIN_PACKETS: usize = 64;
 
 /// Detected from the upper byte of the USB product ID.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum DeviceType {
     NanoS,
     NanoSPlus,
@@ -56,6 +61,7 @@ impl std::fmt::Display for DeviceType {
 pub struct HidTransport {
     device: Mutex<hidapi::HidDevice>,
     device_type: DeviceType,
+    resend: bool,
 }
 
 impl HidTransport {
@@ -72,6 +78,7 @@ impl HidTransport {
                 return Ok(Self {
                     device: Mutex::new(device),
                     device_type,
+                    resend: false,
                 });
             }
         }
@@ -83,6 +90,15 @@ impl HidTransport {
         self.device_type
     }
 
+    /// Resend a command once if packets of its response were lost. Only
+    /// `GetVersion` and `GetPubkey` APDUs are resent: any other command
+    /// may already have been acted on.
+    #[must_use]
+    pub fn with_resend(mut self, resend: bool) -> Self {
+        self.resend = resend;
+        self
+    }
+
     /// Drop the stale handle, re-enumerate USB, and open a fresh device.
     pub fn reconnect(&self) -> Result<(), TransportError> {
         let api = hidapi::HidApi::new().map_err(|e| TransportError::Comm(e.to_string()))?;
@@ -118,12 +134,12 @@ impl HidTransport {
 
     fn write_apdu(device: &hidapi::HidDevice, apdu: &[u8]) -> Result<(), TransportError> {
         // HID framing: 2-byte length prefix, then APDU, split into 59-byte chunks
-        let mut payload = Vec::with_capacity(2 + apdu.len());
+        let mut payload = Transient(Vec::with_capacity(2 + apdu.len()));
         payload.push(((apdu.len() >> 8) & 0xFF) as u8);
         payload.push((apdu.len() & 0xFF) as u8);
         payload.extend_from_slice(apdu);
 
-        let mut buffer = vec![0u8; LEDGER_PACKET_WRITE_SIZE];
+        let mut buffer = Transient(vec![0u8; LEDGER_PACKET_WRITE_SIZE]);
 
         for (seq_idx, chunk) in payload.chunks(CHUNK_SIZE).enumerate() {
             buffer[0] = 0x00;
@@ -144,11 +160,11 @@ impl HidTransport {
         Ok(())
     }
 
-    fn read_apdu(device: &hidapi::HidDevice) -> Result<Vec<u8>, TransportError> {
-        let mut buffer = vec![0u8; LEDGER_PACKET_READ_SIZE];
-        let mut result = Vec::new();
-        let mut expected_len: Option<usize> = None;
-        let mut seq_idx: u16 = 0;
+    /// Read one response. `Ok(None)` if a packet went missing; the rest
+    /// of the response is drained.
+    fn read_apdu(device: &hidapi::HidDevice) -> Result<Option<Vec<u8>>, TransportError> {
+        let mut buffer = Transient(vec![0u8; LEDGER_PACKET_READ_SIZE]);
+        let mut response = Reassembler::new();
 
         loop {
             let n = device
@@ -159,50 +175,29 @@ impl HidTransport {
                 return Err(TransportError::Timeout(LEDGER_TIMEOUT_MS as u32));
             }
 
-            let channel = ((buffer[0] as u16) << 8) | (buffer[1] as u16);
-            if channel != LEDGER_CHANNEL {
-                return Err(TransportError::Comm("HID channel mismatch".into()));
-            }
-            if buffer[2] != LEDGER_TAG {
-                return Err(TransportError::Comm("HID tag mismatch".into()));
-            }
-
-            let pkt_seq = ((buffer[3] as u16) << 8) | (buffer[4] as u16);
-            if pkt_seq != seq_idx {
-                return Err(TransportError::Comm(format!(
-                    "sequence mismatch: expected {seq_idx}, got {pkt_seq}"
-                )));
-            }
-
-            let data_start;
-            if seq_idx == 0 {
-                // First packet has a 2-byte length prefix before the data
-                let apdu_len = ((buffer[5] as usize) << 8) | (buffer[6] as usize);
-                expected_len = Some(apdu_len);
-                data_start = 7;
-            } else {
-                data_start = 5;
-            }
-
-            if n < data_start {
-                return Err(TransportError::Comm(format!(
-                    "HID short read: got {n} bytes, need at least {data_start}"
-                )));
+            match response.push(&buffer[..n])? {
+                Packet::Done => return Ok(Some(response.finish())),
+                Packet::More => {}
+                Packet::Skipped => log::debug!("skipping stale or foreign HID packet"),
+                Packet::Gap { expected, got } => {
+                    log::warn!("HID packet lost: expected sequence {expected}, got {got}");
+                    Self::drain(device);
+                    return Ok(None);
+                }
             }
+        }
+    }
 
-            let remaining = expected_len.unwrap() - result.len();
-            let available = n - data_start;
-            let take = remaining.min(available);
-            result.extend_from_slice(&buffer[data_start..data_start + take]);
-
-            if result.len() >= expected_len.unwrap() {
-                break;
+    /// Discard packets already waiting to be read, e.g. the tail of a
+    /// response abandoned after an error.
+    fn drain(device: &hidapi::HidDevice) {
+        let mut buffer = Transient(vec![0u8; LEDGER_PACKET_READ_SIZE]);
+        for _ in 0..MAX_DRAIN_PACKETS {
+            match device.read_timeout(&mut buffer, 0) {
+                Ok(n) if n > 0 => log::debug!("drained stale HID packet"),
+                _ => return,
             }
-
-            seq_idx += 1;
         }
-
-        Ok(result)
     }
 }
 
@@ -211,14 +206,31 @@ impl Transport for HidTransport {
         self.reconnect()
     }
 
+    fn limits(&self) -> PayloadLimits {
+        self.device_type.into()
+    }
+
     fn exchange(&self, command: &ApduCommand) -> Result<ApduAnswer, TransportError> {
         let device = self
             .device
             .lock()
             .map_err(|e| TransportError::Comm(format!("mutex poisoned: {e}")))?;
-        let serialized = command.serialize();
+        let serialized = Transient(command.serialize()?);
+        Self::drain(&device);
         Self::write_apdu(&device, &serialized)?;
-        let response = Self::read_apdu(&device)?;
-        Ok(ApduAnswer::from_raw(response))
+        let mut response = Self::read_apdu(&device)?;
+        if response.is_none()
+            && self.resend
+            && Instruction::of(command).is_some_and(Instruction::is_idempotent)
+        {
+            log::warn!("resending APDU after HID packet loss");
+            Self::write_apdu(&device, &serialized)?;
+            response = Self::read_apdu(&device)?;
+        }
+        let response =
+            response.ok_or_else(|| TransportError::Comm("HID packets lost in response".into()))?;
+        let answer = ApduAnswer::from_raw(response);
+        command.check_response(&answer)?;
+        Ok(answer)
     }
 }
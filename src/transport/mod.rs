@@ -3,6 +3,8 @@
 //! - [`hid::HidTransport`] -- USB HID for real hardware (feature `hid`, default)
 //! - [`tcp::TcpTransport`] -- TCP for the Speculos simulator (feature `tcp`)
 
+#[cfg(any(feature = "hid", feature = "fuzzing", test))]
+pub(crate) mod framing;
 #[cfg(feature = "hid")]
 pub mod hid;
 #[cfg(feature = "tcp")]
@@ -10,6 +12,8 @@ pub mod tcp;
 
 use crate::apdu::{ApduAnswer, ApduCommand};
 use crate::error::TransportError;
+use crate::limits::PayloadLimits;
+use crate::log_policy::LogPolicy;
 
 pub trait Transport: Send + Sync {
     fn exchange(&self, command: &ApduCommand) -> Result<ApduAnswer, TransportError>;
@@ -21,6 +25,37 @@ pub trait Transport: Send + Sync {
     fn reconnect(&self) -> Result<(), TransportError> {
         Err(TransportError::Comm("reconnect not supported".into()))
     }
+
+    /// Payload limits of the connected device model; transports that
+    /// can't tell the model return [`PayloadLimits::DEFAULT`].
+    fn limits(&self) -> PayloadLimits {
+        PayloadLimits::DEFAULT
+    }
+
+    /// The policy of the [`LedgerIota`](crate::LedgerIota) this transport
+    /// was given to, for transports that log or record exchanges; the
+    /// default ignores it.
+    fn set_log_policy(&self, _policy: &LogPolicy) {}
+}
+
+/// Lets a caller keep a handle on a transport it gives to
+/// [`LedgerIota::with_transport`](crate::LedgerIota::with_transport).
+impl<T: Transport> Transport for std::sync::Arc<T> {
+    fn exchange(&self, command: &ApduCommand) -> Result<ApduAnswer, TransportError> {
+        (**self).exchange(command)
+    }
+
+    fn reconnect(&self) -> Result<(), TransportError> {
+        (**self).reconnect()
+    }
+
+    fn limits(&self) -> PayloadLimits {
+        (**self).limits()
+    }
+
+    fn set_log_policy(&self, policy: &LogPolicy) {
+        (**self).set_log_policy(policy);
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -5,6 +5,7 @@ use std::sync::Mutex;
 use crate::apdu::{ApduAnswer, ApduCommand};
 use crate::error::TransportError;
 use crate::transport::Transport;
+use crate::wipe::Transient;
 
 /// TCP transport for the Speculos simulator (default `127.0.0.1:9999`).
 ///
@@ -33,7 +34,7 @@ impl TcpTransport {
 
 impl Transport for TcpTransport {
     fn exchange(&self, command: &ApduCommand) -> Result<ApduAnswer, TransportError> {
-        let apdu = command.serialize();
+        let apdu = Transient(command.serialize()?);
         let mut stream = self
             .stream
             .lock()
@@ -47,19 +48,31 @@ impl Transport for TcpTransport {
         let mut len_buf = [0u8; 4];
         stream.read_exact(&mut len_buf)?;
         let resp_len = u32::from_be_bytes(len_buf) as usize;
-        if resp_len > 65536 {
+        let max = command
+            .max_response_len()
+            .map_or(65536, |max| max.min(65536));
+        if resp_len > max {
+            // Read past the body and SW so the next exchange starts on a
+            // frame boundary; a length over the hard cap can't be trusted
+            // to frame anything.
+            if resp_len <= 65536 {
+                std::io::copy(
+                    &mut (&mut *stream).take(resp_len as u64 + 2),
+                    &mut std::io::sink(),
+                )?;
+            }
             return Err(TransportError::Comm(format!(
-                "response too large: {resp_len} bytes (max 65536)"
+                "response too large: {resp_len} bytes (max {max})"
             )));
         }
 
-        let mut resp = vec![0u8; resp_len + 2];
+        let mut resp = Transient(vec![0u8; resp_len + 2]);
         stream.read_exact(&mut resp[..resp_len])?;
 
         // SW is sent bare after the framed data -- Speculos quirk
         stream.read_exact(&mut resp[resp_len..resp_len + 2])?;
 
-        Ok(ApduAnswer::from_raw(resp))
+        Ok(ApduAnswer::from_raw(resp.into_inner()))
     }
 }
 
@@ -154,6 +167,38 @@ mod tests {
         assert!(matches!(err, TransportError::Comm(_)));
     }
 
+    #[test]
+    fn response_longer_than_le_rejected() {
+        let (transport, mut server) = mock_pair();
+
+        let handle = std::thread::spawn(move || {
+            let first = transport.exchange(&ApduCommand::new(0x01).with_le(1));
+            let second = transport.exchange(&ApduCommand::new(0x00));
+            (first, second)
+        });
+
+        let mut req = [0u8; 4 + 5];
+        server.read_exact(&mut req).unwrap();
+        assert_eq!(req[4..], [0x00, 0x01, 0x00, 0x00, 0x01]);
+
+        server.write_all(&2u32.to_be_bytes()).unwrap();
+        server.write_all(&[0xAA, 0xBB, 0x90, 0x00]).unwrap();
+        server.flush().unwrap();
+
+        // The oversized body and SW were consumed, so the next exchange
+        // reads its own frame.
+        server.read_exact(&mut req).unwrap();
+        server.write_all(&1u32.to_be_bytes()).unwrap();
+        server.write_all(&[0xCC, 0x90, 0x00]).unwrap();
+        server.flush().unwrap();
+
+        let (first, second) = handle.join().unwrap();
+        assert!(matches!(first.unwrap_err(), TransportError::Comm(_)));
+        let second = second.unwrap();
+        assert_eq!(second.retcode(), 0x9000);
+        assert_eq!(second.data(), &[0xCC]);
+    }
+
     #[test]
     fn connection_refused() {
         // Port 1 should be refused on most systems
@@ -0,0 +1,763 @@
+//! Transaction building helpers for IOTA Rebased.
+//!
+//! Provides BCS-encoded transaction construction so callers don't have to
+//! hand-roll the binary format. [`build_transfer_tx`] covers the common
+//! case; anything else (Move calls, shared objects) goes through the
+//! [`ptb`] builder.
+
+use crate::amount::Amount;
+use crate::error::LedgerError;
+use crate::prelude::*;
+use crate::types::Address;
+
+/// Split intent-prefixed transaction bytes into the intent and the
+/// `TransactionData`. Any app id is accepted; callers that know their
+/// network compare it against
+/// [`NetworkProfile::transaction_intent`](crate::NetworkProfile::transaction_intent).
+pub(crate) fn split_intent(tx: &[u8]) -> Result<([u8; 3], &[u8]), LedgerError> {
+    match tx {
+        [0, 0, app_id, body @ ..] => Ok(([0, 0, *app_id], body)),
+        _ => Err(LedgerError::InvalidTransaction(
+            "missing TransactionData intent prefix".into(),
+        )),
+    }
+}
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+pub mod policy;
+pub mod ptb;
+pub mod pure;
+pub mod template;
+
+pub use policy::{RecipientPolicy, RecipientRules};
+pub use ptb::{
+    Argument, CallArg, Command, MoveCall, ObjectArg, ObjectRef, ProgrammableTransaction,
+    ProgrammableTransactionBuilder, TransactionData, TransactionExpiration,
+};
+pub use pure::PureArg;
+pub use template::{Template, TemplateRecipient};
+
+/// Reference to a gas coin object (from RPC).
+pub type GasCoinRef = ObjectRef;
+
+/// BCS-encode an `IntentMessage<TransactionData::V1>` that splits `amount`
+/// from the gas coin and transfers it to `recipient`.
+///
+/// The returned bytes include the intent prefix `[0, 0, 0]` and are ready
+/// to be passed directly to [`LedgerIota::sign_tx`](crate::LedgerIota::sign_tx).
+///
+/// With the `iota-sdk-types` feature, debug builds parse the output back
+/// through the SDK (see [`validate_with_sdk`]) and return an error on any
+/// mismatch.
+///
+/// The recipient is checked against [`RecipientRules::default()`]; use
+/// [`build_transfer_tx_with_policy`] for stricter rules.
+///
+/// # ProgrammableTransaction layout
+///
+/// - inputs:   `[Pure(recipient), Pure(amount)]`
+/// - commands: `[SplitCoins(GasCoin, [Input(1)]), TransferObjects([Result(0)], Input(0))]`
+#[must_use = "the transaction is only built, not signed or sent"]
+pub fn build_transfer_tx(
+    sender: &Address,
+    recipient: &Address,
+    amount: Amount,
+    gas: &GasCoinRef,
+    gas_budget: Amount,
+    gas_price: u64,
+) -> Result<Vec<u8>, LedgerError> {
+    build_transfer_tx_with_policy(
+        sender,
+        recipient,
+        amount,
+        gas,
+        gas_budget,
+        gas_price,
+        &RecipientRules::default(),
+    )
+}
+
+/// [`build_transfer_tx`] with a caller-supplied [`RecipientPolicy`].
+pub fn build_transfer_tx_with_policy(
+    sender: &Address,
+    recipient: &Address,
+    amount: Amount,
+    gas: &GasCoinRef,
+    gas_budget: Amount,
+    gas_price: u64,
+    policy: &(impl RecipientPolicy + ?Sized),
+) -> Result<Vec<u8>, LedgerError> {
+    policy.check(sender, recipient)?;
+
+    let mut ptb = ProgrammableTransactionBuilder::new();
+    let recipient = ptb.pure_address(&recipient.0);
+    let amount = ptb.pure_u64(amount.nanos());
+    let coin = ptb.split_coins(Argument::GasCoin, vec![amount]);
+    ptb.transfer_objects(vec![coin], recipient);
+
+    TransactionData::new(
+        ptb.finish()?,
+        sender.0,
+        vec![gas.clone()],
+        gas_budget,
+        gas_price,
+    )
+    .to_intent_bytes()
+}
+
+/// One recipient of a [`build_batch_transfer_tx`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Payment {
+    pub recipient: Address,
+    pub amount: Amount,
+}
+
+/// BCS-encode a transaction that sweeps several owned IOTA coins into the
+/// gas coin and pays each of `payments` from it.
+///
+/// `coins[0]` is the gas payment; the remaining coins are merged into it
+/// first, so fragmented balances can fund payments larger than any single
+/// coin. Whatever is left stays in the gas coin.
+///
+/// Recipients are checked against [`RecipientRules::default()`]; use
+/// [`build_batch_transfer_tx_with_policy`] for stricter rules.
+///
+/// # ProgrammableTransaction layout
+///
+/// - inputs:   `[Object(coins[1..])..., Pure(amount_i)..., Pure(recipient_i)...]`
+/// - commands: `[MergeCoins(GasCoin, coins[1..])]` (if more than one coin),
+///   `SplitCoins(GasCoin, [amount_i...])`, then
+///   `TransferObjects([NestedResult(split, i)], recipient_i)` per payment
+pub fn build_batch_transfer_tx(
+    sender: &Address,
+    payments: &[Payment],
+    coins: &[GasCoinRef],
+    gas_budget: Amount,
+    gas_price: u64,
+) -> Result<Vec<u8>, LedgerError> {
+    build_batch_transfer_tx_with_policy(
+        sender,
+        payments,
+        coins,
+        gas_budget,
+        gas_price,
+        &RecipientRules::default(),
+    )
+}
+
+/// [`build_batch_transfer_tx`] with a caller-supplied [`RecipientPolicy`].
+pub fn build_batch_transfer_tx_with_policy(
+    sender: &Address,
+    payments: &[Payment],
+    coins: &[GasCoinRef],
+    gas_budget: Amount,
+    gas_price: u64,
+    policy: &(impl RecipientPolicy + ?Sized),
+) -> Result<Vec<u8>, LedgerError> {
+    let (gas, extra_coins) = coins
+        .split_first()
+        .ok_or_else(|| LedgerError::InvalidTransaction("no coins to pay from".into()))?;
+    if payments.is_empty() {
+        return Err(LedgerError::InvalidTransaction("no payments".into()));
+    }
+    for (i, coin) in coins.iter().enumerate() {
+        if coins[..i].iter().any(|c| c.object_id == coin.object_id) {
+            return Err(LedgerError::InvalidTransaction(format!(
+                "coin 0x{} listed twice",
+                hex::encode(coin.object_id)
+            )));
+        }
+    }
+    for payment in payments {
+        policy.check(sender, &payment.recipient)?;
+    }
+    payments
+        .iter()
+        .try_fold(Amount::ZERO, |total, p| total.checked_add(p.amount))
+        .ok_or_else(|| LedgerError::InvalidAmount("total of payments overflows u64".into()))?;
+
+    let mut ptb = ProgrammableTransactionBuilder::new();
+
+    if !extra_coins.is_empty() {
+        let sources = extra_coins
+            .iter()
+            .map(|c| ptb.object(ObjectArg::ImmOrOwnedObject(c.clone())))
+            .collect();
+        ptb.merge_coins(Argument::GasCoin, sources);
+    }
+
+    let amounts = payments
+        .iter()
+        .map(|p| ptb.pure_u64(p.amount.nanos()))
+        .collect();
+    let Argument::Result(split) = ptb.split_coins(Argument::GasCoin, amounts) else {
+        unreachable!("commands always yield Argument::Result");
+    };
+
+    for (i, payment) in payments.iter().enumerate() {
+        let i = u16::try_from(i).map_err(|_| {
+            LedgerError::InvalidTransaction(format!(
+                "more than {} payments",
+                usize::from(u16::MAX) + 1
+            ))
+        })?;
+        let recipient = ptb.pure_address(&payment.recipient.0);
+        ptb.transfer_objects(vec![Argument::NestedResult(split, i)], recipient);
+    }
+
+    TransactionData::new(
+        ptb.finish()?,
+        sender.0,
+        vec![gas.clone()],
+        gas_budget,
+        gas_price,
+    )
+    .to_intent_bytes()
+}
+
+/// Last step of every builder: in debug builds with the `iota-sdk-types`
+/// feature, cross-check the hand-rolled encoding against the SDK.
+fn finish(tx: Vec<u8>) -> Result<Vec<u8>, LedgerError> {
+    #[cfg(all(feature = "iota-sdk-types", debug_assertions))]
+    validate_with_sdk(&tx)?;
+    Ok(tx)
+}
+
+/// BCS-serialize an SDK transaction (`TransactionData::V1` on the wire) and
+/// prepend the TransactionData intent, ready for
+/// [`LedgerIota::sign_tx`](crate::LedgerIota::sign_tx).
+#[cfg(feature = "iota-sdk-types")]
+pub fn serialize_transaction_data(
+    tx: &iota_sdk_types::Transaction,
+) -> Result<Vec<u8>, LedgerError> {
+    let body = bcs::to_bytes(tx).map_err(|e| {
+        LedgerError::InvalidTransaction(format!("failed to serialize transaction: {e}"))
+    })?;
+    let mut out = Vec::with_capacity(3 + body.len());
+    out.extend_from_slice(&crate::NetworkProfile::IOTA.transaction_intent());
+    out.extend_from_slice(&body);
+    Ok(out)
+}
+
+/// Parse intent-prefixed transaction bytes with the SDK's BCS types and
+/// check that re-serializing yields exactly the same bytes.
+///
+/// Catches drift between this crate's hand-rolled BCS and the protocol
+/// definitions before anything is sent to the device.
+#[cfg(feature = "iota-sdk-types")]
+pub fn validate_with_sdk(tx: &[u8]) -> Result<(), LedgerError> {
+    let (_, body) = split_intent(tx)?;
+
+    let parsed: iota_sdk_types::Transaction = bcs::from_bytes(body).map_err(|e| {
+        LedgerError::InvalidTransaction(format!("SDK failed to parse transaction: {e}"))
+    })?;
+    let reencoded = bcs::to_bytes(&parsed).map_err(|e| {
+        LedgerError::InvalidTransaction(format!("SDK failed to serialize transaction: {e}"))
+    })?;
+
+    if reencoded != body {
+        let offset = reencoded
+            .iter()
+            .zip(body)
+            .position(|(a, b)| a != b)
+            .unwrap_or_else(|| reencoded.len().min(body.len()));
+        return Err(LedgerError::InvalidTransaction(format!(
+            "SDK round trip differs at byte {}",
+            3 + offset
+        )));
+    }
+
+    Ok(())
+}
+
+/// Decode standard (padded) base64, as used for tx bytes by the JSON-RPC
+/// API and wallet-dapp interfaces. Surrounding whitespace is ignored.
+///
+/// Only the canonical encoding is accepted: padded to a multiple of four
+/// characters, with the unused bits of the last character zero.
+pub fn from_base64(s: &str) -> Result<Vec<u8>, LedgerError> {
+    let s = s.trim();
+    if s.len() % 4 != 0 {
+        return Err(LedgerError::InvalidEncoding(format!(
+            "invalid base64 length: {}",
+            s.len()
+        )));
+    }
+    let unpadded = s.trim_end_matches('=');
+    if s.len() - unpadded.len() > 2 {
+        return Err(LedgerError::InvalidEncoding(
+            "too much base64 padding".into(),
+        ));
+    }
+
+    let mut out = Vec::with_capacity(unpadded.len() * 3 / 4);
+    let mut acc: u32 = 0;
+    let mut bits = 0;
+    for (i, c) in unpadded.bytes().enumerate() {
+        let val = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            _ => {
+                return Err(LedgerError::InvalidEncoding(format!(
+                    "invalid base64 character {:?} at offset {i}",
+                    c as char
+                )));
+            }
+        };
+        acc = (acc << 6) | val as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((acc >> bits) as u8);
+        }
+    }
+    if acc & ((1 << bits) - 1) != 0 {
+        return Err(LedgerError::InvalidEncoding(
+            "non-canonical base64: unused bits are set".into(),
+        ));
+    }
+
+    Ok(out)
+}
+
+/// Encode bytes as standard (padded) base64.
+#[must_use]
+pub fn to_base64(data: &[u8]) -> String {
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let b = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        let n = ((b[0] as u32) << 16) | ((b[1] as u32) << 8) | b[2] as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(BASE64_ALPHABET[(n >> (18 - 6 * i)) as usize & 0x3F] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::RecipientError;
+    use crate::types::ObjectDigest;
+
+    #[test]
+    fn transfer_tx_has_intent_prefix() {
+        let sender = Address([0x01; 32]);
+        let recipient = Address([0x02; 32]);
+        let gas = GasCoinRef {
+            object_id: [0u8; 32],
+            version: 1,
+            digest: ObjectDigest([0u8; 32]),
+        };
+
+        let tx = build_transfer_tx(
+            &sender,
+            &recipient,
+            Amount::from_nanos(1_000_000),
+            &gas,
+            Amount::from_nanos(10_000_000),
+            1000,
+        )
+        .unwrap();
+
+        // intent prefix
+        assert_eq!(&tx[0..3], &[0, 0, 0]);
+        // TransactionData::V1
+        assert_eq!(tx[3], 0x00);
+        // TransactionKind::ProgrammableTransaction
+        assert_eq!(tx[4], 0x00);
+    }
+
+    #[test]
+    fn transfer_tx_deterministic() {
+        let sender = Address([0xAA; 32]);
+        let recipient = Address([0xBB; 32]);
+        let gas = GasCoinRef {
+            object_id: [0xCC; 32],
+            version: 42,
+            digest: ObjectDigest([0xDD; 32]),
+        };
+
+        let a = build_transfer_tx(
+            &sender,
+            &recipient,
+            Amount::from_nanos(500),
+            &gas,
+            Amount::from_nanos(5_000_000),
+            750,
+        )
+        .unwrap();
+        let b = build_transfer_tx(
+            &sender,
+            &recipient,
+            Amount::from_nanos(500),
+            &gas,
+            Amount::from_nanos(5_000_000),
+            750,
+        )
+        .unwrap();
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn transfer_tx_known_bytes() {
+        let gas = GasCoinRef {
+            object_id: [0xCC; 32],
+            version: 42,
+            digest: ObjectDigest([0xDD; 32]),
+        };
+        let tx = build_transfer_tx(
+            &Address([0xAA; 32]),
+            &Address([0xBB; 32]),
+            Amount::from_nanos(500),
+            &gas,
+            Amount::from_nanos(5_000_000),
+            750,
+        )
+        .unwrap();
+        let expected = concat!(
+            "000000",
+            "0000",
+            "02",
+            "0020bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb",
+            "0008f401000000000000",
+            "02",
+            "020001010100",
+            "0101020000010000",
+            "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa",
+            "01",
+            "cccccccccccccccccccccccccccccccccccccccccccccccccccccccccccccccc",
+            "2a00000000000000",
+            "20dddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddd",
+            "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa",
+            "ee02000000000000",
+            "404b4c0000000000",
+            "00",
+        );
+        assert_eq!(hex::encode(tx), expected);
+    }
+
+    fn coin(byte: u8) -> GasCoinRef {
+        GasCoinRef {
+            object_id: [byte; 32],
+            version: 1,
+            digest: ObjectDigest([byte; 32]),
+        }
+    }
+
+    fn payment(byte: u8, nanos: u64) -> Payment {
+        Payment {
+            recipient: Address([byte; 32]),
+            amount: Amount::from_nanos(nanos),
+        }
+    }
+
+    #[test]
+    fn batch_transfer_single_coin_matches_transfer_layout() {
+        let gas = coin(0xCC);
+        let batch = build_batch_transfer_tx(
+            &Address([0xAA; 32]),
+            &[payment(0xBB, 500)],
+            core::slice::from_ref(&gas),
+            Amount::from_nanos(5_000_000),
+            750,
+        )
+        .unwrap();
+        let single = build_transfer_tx(
+            &Address([0xAA; 32]),
+            &Address([0xBB; 32]),
+            Amount::from_nanos(500),
+            &gas,
+            Amount::from_nanos(5_000_000),
+            750,
+        )
+        .unwrap();
+        // Same transaction apart from input order and NestedResult vs Result.
+        let mut ptb = ProgrammableTransactionBuilder::new();
+        let amount = ptb.pure_u64(500);
+        ptb.split_coins(Argument::GasCoin, vec![amount]);
+        let recipient = ptb.pure_address(&[0xBB; 32]);
+        ptb.transfer_objects(vec![Argument::NestedResult(0, 0)], recipient);
+        let mut expected = TransactionData::from_intent_bytes(&single).unwrap();
+        expected.kind = ptb.finish().unwrap();
+        assert_eq!(batch, expected.to_intent_bytes().unwrap());
+    }
+
+    #[test]
+    fn batch_transfer_merges_extra_coins() {
+        let tx = build_batch_transfer_tx(
+            &Address([0xAA; 32]),
+            &[payment(0x01, 10), payment(0x02, 20)],
+            &[coin(0xC0), coin(0xC1), coin(0xC2)],
+            Amount::from_nanos(5_000_000),
+            1000,
+        )
+        .unwrap();
+
+        let mut r = crate::bcs_util::Reader::new(&tx[5..]);
+        assert_eq!(r.read_len().unwrap(), 6); // 2 coins + 2 amounts + 2 recipients
+        for expected in [0xC1, 0xC2] {
+            assert_eq!(r.read_raw(2).unwrap(), [1, 0]); // Object(ImmOrOwnedObject)
+            assert_eq!(r.read_array::<32>().unwrap(), [expected; 32]);
+            assert_eq!(r.read_u64().unwrap(), 1);
+            assert_eq!(r.read_bytes().unwrap(), [expected; 32]);
+        }
+        for nanos in [10, 20] {
+            assert_eq!(r.read_u8().unwrap(), 0); // Pure
+            assert_eq!(r.read_bytes().unwrap(), u64::to_le_bytes(nanos));
+        }
+        for recipient in [0x01, 0x02] {
+            assert_eq!(r.read_u8().unwrap(), 0);
+            assert_eq!(r.read_bytes().unwrap(), [recipient; 32]);
+        }
+
+        assert_eq!(r.read_len().unwrap(), 4); // merge, split, 2 transfers
+        assert_eq!(r.read_u8().unwrap(), 3); // MergeCoins
+        assert_eq!(r.read_u8().unwrap(), 0); // GasCoin
+        assert_eq!(r.read_raw(7).unwrap(), [2, 1, 0, 0, 1, 1, 0]);
+        // SplitCoins(GasCoin, [Input(2), Input(3)])
+        assert_eq!(r.read_raw(9).unwrap(), [2, 0, 2, 1, 2, 0, 1, 3, 0]);
+        // TransferObjects([NestedResult(1, 0)], Input(4))
+        assert_eq!(r.read_raw(10).unwrap(), [1, 1, 3, 1, 0, 0, 0, 1, 4, 0]);
+        // TransferObjects([NestedResult(1, 1)], Input(5))
+        assert_eq!(r.read_raw(10).unwrap(), [1, 1, 3, 1, 0, 1, 0, 1, 5, 0]);
+    }
+
+    #[test]
+    fn batch_transfer_rejects_bad_input() {
+        let budget = Amount::from_nanos(1);
+        assert!(matches!(
+            build_batch_transfer_tx(&Address([0; 32]), &[payment(1, 1)], &[], budget, 1),
+            Err(LedgerError::InvalidTransaction(_))
+        ));
+        assert!(matches!(
+            build_batch_transfer_tx(&Address([0; 32]), &[], &[coin(1)], budget, 1),
+            Err(LedgerError::InvalidTransaction(_))
+        ));
+        assert!(matches!(
+            build_batch_transfer_tx(
+                &Address([0; 32]),
+                &[payment(1, 1)],
+                &[coin(1), coin(1)],
+                budget,
+                1
+            ),
+            Err(LedgerError::InvalidTransaction(_))
+        ));
+        assert!(matches!(
+            build_batch_transfer_tx(
+                &Address([0; 32]),
+                &[payment(1, u64::MAX), payment(2, 1)],
+                &[coin(1)],
+                budget,
+                1
+            ),
+            Err(LedgerError::InvalidAmount(_))
+        ));
+    }
+
+    #[test]
+    fn builders_reject_zero_recipient_by_default() {
+        let budget = Amount::from_nanos(1);
+        assert!(matches!(
+            build_transfer_tx(
+                &Address([0xAA; 32]),
+                &Address([0; 32]),
+                budget,
+                &coin(1),
+                budget,
+                1
+            ),
+            Err(LedgerError::RecipientRejected(RecipientError::ZeroAddress))
+        ));
+        assert!(matches!(
+            build_batch_transfer_tx(
+                &Address([0xAA; 32]),
+                &[payment(0, 1)],
+                &[coin(1)],
+                budget,
+                1
+            ),
+            Err(LedgerError::RecipientRejected(RecipientError::ZeroAddress))
+        ));
+    }
+
+    #[test]
+    fn builders_apply_custom_policy() {
+        let budget = Amount::from_nanos(1);
+        let rules = RecipientRules::new()
+            .deny_self_transfer()
+            .block([Address([0x02; 32])]);
+        assert!(matches!(
+            build_transfer_tx_with_policy(
+                &Address([0xAA; 32]),
+                &Address([0xAA; 32]),
+                budget,
+                &coin(1),
+                budget,
+                1,
+                &rules
+            ),
+            Err(LedgerError::RecipientRejected(RecipientError::SelfTransfer))
+        ));
+        assert!(matches!(
+            build_batch_transfer_tx_with_policy(
+                &Address([0xAA; 32]),
+                &[payment(0x01, 1), payment(0x02, 1)],
+                &[coin(1)],
+                budget,
+                1,
+                &rules
+            ),
+            Err(LedgerError::RecipientRejected(RecipientError::Blocked(_)))
+        ));
+    }
+
+    #[cfg(feature = "iota-sdk-types")]
+    #[test]
+    fn transfer_tx_round_trips_through_sdk() {
+        let gas = GasCoinRef {
+            object_id: [0xCC; 32],
+            version: 42,
+            digest: ObjectDigest([0xDD; 32]),
+        };
+        let tx = build_transfer_tx(
+            &Address([0xAA; 32]),
+            &Address([0xBB; 32]),
+            Amount::from_nanos(500),
+            &gas,
+            Amount::from_nanos(5_000_000),
+            750,
+        )
+        .unwrap();
+        validate_with_sdk(&tx).unwrap();
+    }
+
+    #[cfg(feature = "iota-sdk-types")]
+    #[test]
+    fn serialize_transaction_data_matches_builder() {
+        let gas = GasCoinRef {
+            object_id: [0xCC; 32],
+            version: 42,
+            digest: ObjectDigest([0xDD; 32]),
+        };
+        let built = build_transfer_tx(
+            &Address([0xAA; 32]),
+            &Address([0xBB; 32]),
+            Amount::from_nanos(500),
+            &gas,
+            Amount::from_nanos(5_000_000),
+            750,
+        )
+        .unwrap();
+        let parsed: iota_sdk_types::Transaction = bcs::from_bytes(&built[3..]).unwrap();
+        assert_eq!(serialize_transaction_data(&parsed).unwrap(), built);
+    }
+
+    #[cfg(feature = "iota-sdk-types")]
+    #[test]
+    fn sdk_validation_rejects_corrupted_tx() {
+        let gas = GasCoinRef {
+            object_id: [0xCC; 32],
+            version: 42,
+            digest: ObjectDigest([0xDD; 32]),
+        };
+        let mut tx = build_transfer_tx(
+            &Address([0xAA; 32]),
+            &Address([0xBB; 32]),
+            Amount::from_nanos(500),
+            &gas,
+            Amount::from_nanos(5_000_000),
+            750,
+        )
+        .unwrap();
+        tx.push(0x00); // trailing garbage
+        assert!(matches!(
+            validate_with_sdk(&tx),
+            Err(LedgerError::InvalidTransaction(_))
+        ));
+    }
+
+    #[test]
+    fn base64_round_trip() {
+        for len in 0..8 {
+            let data: Vec<u8> = (0..len as u8).map(|b| b.wrapping_mul(37)).collect();
+            assert_eq!(from_base64(&to_base64(&data)).unwrap(), data);
+        }
+    }
+
+    #[test]
+    fn base64_known_vectors() {
+        assert_eq!(to_base64(b""), "");
+        assert_eq!(to_base64(b"f"), "Zg==");
+        assert_eq!(to_base64(b"fo"), "Zm8=");
+        assert_eq!(to_base64(b"foo"), "Zm9v");
+        assert_eq!(from_base64("Zm9vYg==").unwrap(), b"foob");
+        assert_eq!(from_base64(" Zm8=\n").unwrap(), b"fo");
+    }
+
+    #[test]
+    fn base64_rejects_invalid_input() {
+        assert!(matches!(
+            from_base64("Zm9v!"),
+            Err(LedgerError::InvalidEncoding(_))
+        ));
+        assert!(matches!(
+            from_base64("Zm9vY"),
+            Err(LedgerError::InvalidEncoding(_))
+        ));
+        // Missing, misplaced or excess padding, and set unused bits.
+        for s in [
+            "Zg===", "Zm9vYg", "Zg=", "Zm8==", "Zm9v====", "Zg==Zg==", "Zh==", "Zm9=",
+        ] {
+            assert!(
+                matches!(from_base64(s), Err(LedgerError::InvalidEncoding(_))),
+                "{s:?}"
+            );
+        }
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn transfer_tx_round_trips(
+            sender in proptest::array::uniform32(proptest::num::u8::ANY),
+            recipient in proptest::array::uniform32(1u8..),
+            object_id in proptest::array::uniform32(proptest::num::u8::ANY),
+            version: u64,
+            amount: u64,
+            budget: u64,
+            price: u64,
+        ) {
+            proptest::prop_assume!(sender != recipient);
+            let gas = GasCoinRef {
+                object_id,
+                version,
+                digest: ObjectDigest([0x5A; 32]),
+            };
+            let tx = build_transfer_tx(
+                &Address(sender),
+                &Address(recipient),
+                Amount::from_nanos(amount),
+                &gas,
+                Amount::from_nanos(budget),
+                price,
+            )
+            .unwrap();
+            let decoded = TransactionData::from_intent_bytes(&tx).unwrap();
+            proptest::prop_assert_eq!(decoded.to_intent_bytes().unwrap(), tx);
+        }
+    }
+}
@@ -0,0 +1,153 @@
+//! Recipient checks run by the transaction builders.
+//!
+//! Every builder passes each recipient through a [`RecipientPolicy`] before
+//! encoding anything. The plain builders use [`RecipientRules::default()`];
+//! the `_with_policy` variants take a caller-supplied policy.
+
+use alloc::collections::BTreeSet;
+
+use crate::error::RecipientError;
+use crate::types::Address;
+
+/// Decides whether `sender` may send to `recipient`.
+///
+/// Implemented for [`RecipientRules`] and for any
+/// `Fn(&Address, &Address) -> Result<(), RecipientError>`.
+pub trait RecipientPolicy {
+    fn check(&self, sender: &Address, recipient: &Address) -> Result<(), RecipientError>;
+}
+
+impl<F> RecipientPolicy for F
+where
+    F: Fn(&Address, &Address) -> Result<(), RecipientError>,
+{
+    fn check(&self, sender: &Address, recipient: &Address) -> Result<(), RecipientError> {
+        self(sender, recipient)
+    }
+}
+
+/// Configurable default policy.
+///
+/// Always rejects the all-zero address (funds sent there are unrecoverable).
+/// Self-transfers are allowed unless [`deny_self_transfer`](Self::deny_self_transfer)
+/// is set. The blocklist wins over the allowlist; an empty allowlist means
+/// "anyone not blocked".
+#[derive(Debug, Clone)]
+pub struct RecipientRules {
+    allow_self_transfer: bool,
+    blocklist: BTreeSet<Address>,
+    allowlist: BTreeSet<Address>,
+}
+
+impl Default for RecipientRules {
+    fn default() -> Self {
+        Self {
+            allow_self_transfer: true,
+            blocklist: BTreeSet::new(),
+            allowlist: BTreeSet::new(),
+        }
+    }
+}
+
+impl RecipientRules {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn deny_self_transfer(mut self) -> Self {
+        self.allow_self_transfer = false;
+        self
+    }
+
+    #[must_use]
+    pub fn block(mut self, addrs: impl IntoIterator<Item = Address>) -> Self {
+        self.blocklist.extend(addrs);
+        self
+    }
+
+    /// Restrict recipients to `addrs` (cumulative across calls).
+    #[must_use]
+    pub fn allow_only(mut self, addrs: impl IntoIterator<Item = Address>) -> Self {
+        self.allowlist.extend(addrs);
+        self
+    }
+}
+
+impl RecipientPolicy for RecipientRules {
+    fn check(&self, sender: &Address, recipient: &Address) -> Result<(), RecipientError> {
+        if recipient.0 == [0u8; 32] {
+            return Err(RecipientError::ZeroAddress);
+        }
+        if !self.allow_self_transfer && recipient == sender {
+            return Err(RecipientError::SelfTransfer);
+        }
+        if self.blocklist.contains(recipient) {
+            return Err(RecipientError::Blocked(recipient.0));
+        }
+        if !self.allowlist.is_empty() && !self.allowlist.contains(recipient) {
+            return Err(RecipientError::NotAllowed(recipient.0));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SENDER: Address = Address([0xAA; 32]);
+
+    #[test]
+    fn default_rejects_only_zero_address() {
+        let rules = RecipientRules::default();
+        assert_eq!(
+            rules.check(&SENDER, &Address([0; 32])),
+            Err(RecipientError::ZeroAddress)
+        );
+        assert_eq!(rules.check(&SENDER, &SENDER), Ok(()));
+        assert_eq!(rules.check(&SENDER, &Address([0x01; 32])), Ok(()));
+    }
+
+    #[test]
+    fn self_transfer_can_be_denied() {
+        let rules = RecipientRules::new().deny_self_transfer();
+        assert_eq!(
+            rules.check(&SENDER, &SENDER),
+            Err(RecipientError::SelfTransfer)
+        );
+    }
+
+    #[test]
+    fn blocklist_beats_allowlist() {
+        let rules = RecipientRules::new()
+            .allow_only([Address([0x01; 32]), Address([0x02; 32])])
+            .block([Address([0x02; 32])]);
+        assert_eq!(rules.check(&SENDER, &Address([0x01; 32])), Ok(()));
+        assert_eq!(
+            rules.check(&SENDER, &Address([0x02; 32])),
+            Err(RecipientError::Blocked([0x02; 32]))
+        );
+        assert_eq!(
+            rules.check(&SENDER, &Address([0x03; 32])),
+            Err(RecipientError::NotAllowed([0x03; 32]))
+        );
+    }
+
+    #[test]
+    fn closures_are_policies() {
+        let policy = |_: &Address, r: &Address| {
+            if r.0[0] == 0xFF {
+                Err(RecipientError::Custom("burn prefix".into()))
+            } else {
+                Ok(())
+            }
+        };
+        assert!(policy.check(&SENDER, &Address([0x01; 32])).is_ok());
+        assert_eq!(
+            policy.check(&SENDER, &Address([0xFF; 32])),
+            Err(RecipientError::Custom("burn prefix".into()))
+        );
+    }
+}
@@ -0,0 +1,988 @@
+//! Programmable transaction building blocks.
+//!
+//! Mirrors the on-chain `ProgrammableTransaction` layout: a list of inputs
+//! ([`CallArg`]) and a list of [`Command`]s referring to them through
+//! [`Argument`]s. [`ProgrammableTransactionBuilder`] hands out the
+//! arguments; [`TransactionData`] wraps the result with sender and gas.
+
+use crate::amount::Amount;
+use crate::bcs_util::{self, Reader};
+use crate::error::LedgerError;
+use crate::objects::{self, TypeTag};
+use crate::prelude::*;
+use crate::types::ObjectDigest;
+
+use super::pure::PureArg;
+
+/// `(ObjectID, SequenceNumber, ObjectDigest)` of an owned or immutable object.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ObjectRef {
+    pub object_id: [u8; 32],
+    pub version: u64,
+    pub digest: ObjectDigest,
+}
+
+impl ObjectRef {
+    fn encode(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&self.object_id);
+        bcs_util::write_u64(buf, self.version);
+        bcs_util::write_bytes(buf, &self.digest.0);
+    }
+
+    fn decode(r: &mut Reader<'_>) -> Result<Self, LedgerError> {
+        let object_id = r.read_array()?;
+        let version = r.read_u64()?;
+        let digest = ObjectDigest(read_digest(r)?);
+        Ok(Self {
+            object_id,
+            version,
+            digest,
+        })
+    }
+}
+
+#[cfg(feature = "iota-sdk-types")]
+impl From<iota_sdk_types::ObjectReference> for ObjectRef {
+    fn from(r: iota_sdk_types::ObjectReference) -> Self {
+        let (object_id, version, digest) = r.into_parts();
+        Self {
+            object_id: object_id.into_inner(),
+            version,
+            digest: ObjectDigest(*digest.inner()),
+        }
+    }
+}
+
+#[cfg(feature = "iota-sdk-types")]
+impl From<ObjectRef> for iota_sdk_types::ObjectReference {
+    fn from(r: ObjectRef) -> Self {
+        Self::new(
+            iota_sdk_types::ObjectId::new(r.object_id),
+            r.version,
+            iota_sdk_types::ObjectDigest::new(r.digest.0),
+        )
+    }
+}
+
+fn read_digest(r: &mut Reader<'_>) -> Result<[u8; 32], LedgerError> {
+    let offset = r.position();
+    r.read_bytes()?.try_into().map_err(|_| {
+        LedgerError::InvalidEncoding(format!("digest at offset {offset} is not 32 bytes"))
+    })
+}
+
+fn invalid_variant(what: &str, variant: u8, offset: usize) -> LedgerError {
+    LedgerError::InvalidEncoding(format!(
+        "invalid {what} variant {variant} at offset {offset}"
+    ))
+}
+
+/// An object input to a programmable transaction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ObjectArg {
+    /// Owned or immutable object, pinned to an exact version.
+    ImmOrOwnedObject(ObjectRef),
+    /// Shared object (pools, registries, system objects). Validators pick
+    /// the version; `mutable: false` lets the transaction run without
+    /// contending for write access.
+    SharedObject {
+        id: [u8; 32],
+        initial_shared_version: u64,
+        mutable: bool,
+    },
+    /// Object sent to another object (transfer-to-object) and claimed in
+    /// this transaction via `transfer::receive`.
+    Receiving(ObjectRef),
+}
+
+impl ObjectArg {
+    /// The shared `0x6` clock. Always immutable -- Move only takes `&Clock`.
+    #[must_use]
+    pub fn clock() -> Self {
+        Self::SharedObject {
+            id: system_object_id(0x6),
+            initial_shared_version: 1,
+            mutable: false,
+        }
+    }
+
+    /// The shared `0x5` IOTA system state, used by staking calls.
+    #[must_use]
+    pub fn system_state(mutable: bool) -> Self {
+        Self::SharedObject {
+            id: system_object_id(0x5),
+            initial_shared_version: 1,
+            mutable,
+        }
+    }
+
+    pub fn id(&self) -> &[u8; 32] {
+        match self {
+            Self::ImmOrOwnedObject(r) | Self::Receiving(r) => &r.object_id,
+            Self::SharedObject { id, .. } => id,
+        }
+    }
+
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::ImmOrOwnedObject(_) => "owned",
+            Self::SharedObject { .. } => "shared",
+            Self::Receiving(_) => "receiving",
+        }
+    }
+
+    fn encode(&self, buf: &mut Vec<u8>) {
+        match self {
+            Self::ImmOrOwnedObject(r) => {
+                buf.push(0);
+                r.encode(buf);
+            }
+            Self::SharedObject {
+                id,
+                initial_shared_version,
+                mutable,
+            } => {
+                buf.push(1);
+                buf.extend_from_slice(id);
+                bcs_util::write_u64(buf, *initial_shared_version);
+                bcs_util::write_bool(buf, *mutable);
+            }
+            Self::Receiving(r) => {
+                buf.push(2);
+                r.encode(buf);
+            }
+        }
+    }
+}
+
+impl ObjectArg {
+    fn decode(r: &mut Reader<'_>) -> Result<Self, LedgerError> {
+        let offset = r.position();
+        Ok(match r.read_u8()? {
+            0 => Self::ImmOrOwnedObject(ObjectRef::decode(r)?),
+            1 => Self::SharedObject {
+                id: r.read_array()?,
+                initial_shared_version: r.read_u64()?,
+                mutable: r.read_bool()?,
+            },
+            2 => Self::Receiving(ObjectRef::decode(r)?),
+            v => return Err(invalid_variant("ObjectArg", v, offset)),
+        })
+    }
+}
+
+fn system_object_id(last_byte: u8) -> [u8; 32] {
+    let mut id = [0u8; 32];
+    id[31] = last_byte;
+    id
+}
+
+/// A programmable transaction input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CallArg {
+    /// BCS-encoded pure value (u64, address, vector, ...).
+    Pure(Vec<u8>),
+    Object(ObjectArg),
+}
+
+impl CallArg {
+    fn encode(&self, buf: &mut Vec<u8>) {
+        match self {
+            Self::Pure(bytes) => {
+                buf.push(0);
+                bcs_util::write_bytes(buf, bytes);
+            }
+            Self::Object(obj) => {
+                buf.push(1);
+                obj.encode(buf);
+            }
+        }
+    }
+
+    fn decode(r: &mut Reader<'_>) -> Result<Self, LedgerError> {
+        let offset = r.position();
+        Ok(match r.read_u8()? {
+            0 => Self::Pure(r.read_bytes()?.to_vec()),
+            1 => Self::Object(ObjectArg::decode(r)?),
+            v => return Err(invalid_variant("CallArg", v, offset)),
+        })
+    }
+}
+
+/// Reference to a value inside a programmable transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Argument {
+    /// The coin used to pay for gas.
+    GasCoin,
+    /// An entry in the transaction's inputs.
+    Input(u16),
+    /// The result of an earlier command.
+    Result(u16),
+    /// One value of an earlier command that returned several.
+    NestedResult(u16, u16),
+}
+
+impl Argument {
+    fn encode(&self, buf: &mut Vec<u8>) {
+        match *self {
+            Self::GasCoin => buf.push(0),
+            Self::Input(i) => {
+                buf.push(1);
+                bcs_util::write_u16(buf, i);
+            }
+            Self::Result(i) => {
+                buf.push(2);
+                bcs_util::write_u16(buf, i);
+            }
+            Self::NestedResult(i, j) => {
+                buf.push(3);
+                bcs_util::write_u16(buf, i);
+                bcs_util::write_u16(buf, j);
+            }
+        }
+    }
+
+    fn decode(r: &mut Reader<'_>) -> Result<Self, LedgerError> {
+        let offset = r.position();
+        Ok(match r.read_u8()? {
+            0 => Self::GasCoin,
+            1 => Self::Input(r.read_u16()?),
+            2 => Self::Result(r.read_u16()?),
+            3 => Self::NestedResult(r.read_u16()?, r.read_u16()?),
+            v => return Err(invalid_variant("Argument", v, offset)),
+        })
+    }
+}
+
+/// Call to a Move function: `package::module::function<type_arguments>(arguments)`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MoveCall {
+    pub package: [u8; 32],
+    pub module: String,
+    pub function: String,
+    pub type_arguments: Vec<TypeTag>,
+    pub arguments: Vec<Argument>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    MoveCall(Box<MoveCall>),
+    /// `TransferObjects(objects, recipient)`
+    TransferObjects(Vec<Argument>, Argument),
+    /// `SplitCoins(coin, amounts)`
+    SplitCoins(Argument, Vec<Argument>),
+    /// `MergeCoins(target, sources)`
+    MergeCoins(Argument, Vec<Argument>),
+    /// `Publish(modules, dependencies)`
+    Publish(Vec<Vec<u8>>, Vec<[u8; 32]>),
+    /// `MakeMoveVec(element_type, elements)`
+    MakeMoveVec(Option<TypeTag>, Vec<Argument>),
+    /// `Upgrade(modules, dependencies, package, ticket)`
+    Upgrade(Vec<Vec<u8>>, Vec<[u8; 32]>, [u8; 32], Argument),
+}
+
+impl Command {
+    fn encode(&self, buf: &mut Vec<u8>) {
+        match self {
+            Self::MoveCall(call) => {
+                buf.push(0);
+                buf.extend_from_slice(&call.package);
+                bcs_util::write_string(buf, &call.module);
+                bcs_util::write_string(buf, &call.function);
+                bcs_util::write_vec(buf, &call.type_arguments, objects::encode_type_tag);
+                bcs_util::write_vec(buf, &call.arguments, |b, a| a.encode(b));
+            }
+            Self::TransferObjects(objects, recipient) => {
+                buf.push(1);
+                bcs_util::write_vec(buf, objects, |b, a| a.encode(b));
+                recipient.encode(buf);
+            }
+            Self::SplitCoins(coin, amounts) => {
+                buf.push(2);
+                coin.encode(buf);
+                bcs_util::write_vec(buf, amounts, |b, a| a.encode(b));
+            }
+            Self::MergeCoins(target, sources) => {
+                buf.push(3);
+                target.encode(buf);
+                bcs_util::write_vec(buf, sources, |b, a| a.encode(b));
+            }
+            Self::Publish(modules, deps) => {
+                buf.push(4);
+                bcs_util::write_vec(buf, modules, |b, m| bcs_util::write_bytes(b, m));
+                bcs_util::write_vec(buf, deps, |b, d| b.extend_from_slice(d));
+            }
+            Self::MakeMoveVec(type_, elements) => {
+                buf.push(5);
+                bcs_util::write_option(buf, type_.as_ref(), objects::encode_type_tag);
+                bcs_util::write_vec(buf, elements, |b, a| a.encode(b));
+            }
+            Self::Upgrade(modules, deps, package, ticket) => {
+                buf.push(6);
+                bcs_util::write_vec(buf, modules, |b, m| bcs_util::write_bytes(b, m));
+                bcs_util::write_vec(buf, deps, |b, d| b.extend_from_slice(d));
+                buf.extend_from_slice(package);
+                ticket.encode(buf);
+            }
+        }
+    }
+
+    fn decode(r: &mut Reader<'_>) -> Result<Self, LedgerError> {
+        let offset = r.position();
+        let args = |r: &mut Reader<'_>| r.read_vec(Argument::decode);
+        let modules = |r: &mut Reader<'_>| r.read_vec(|r| Ok(r.read_bytes()?.to_vec()));
+        let deps = |r: &mut Reader<'_>| r.read_vec(|r| r.read_array());
+        Ok(match r.read_u8()? {
+            0 => Self::MoveCall(Box::new(MoveCall {
+                package: r.read_array()?,
+                module: r.read_string()?.to_owned(),
+                function: r.read_string()?.to_owned(),
+                type_arguments: r.read_vec(objects::decode_type_tag)?,
+                arguments: args(r)?,
+            })),
+            1 => Self::TransferObjects(args(r)?, Argument::decode(r)?),
+            2 => Self::SplitCoins(Argument::decode(r)?, args(r)?),
+            3 => Self::MergeCoins(Argument::decode(r)?, args(r)?),
+            4 => Self::Publish(modules(r)?, deps(r)?),
+            5 => Self::MakeMoveVec(r.read_option(objects::decode_type_tag)?, args(r)?),
+            6 => Self::Upgrade(modules(r)?, deps(r)?, r.read_array()?, Argument::decode(r)?),
+            v => return Err(invalid_variant("Command", v, offset)),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProgrammableTransaction {
+    pub inputs: Vec<CallArg>,
+    pub commands: Vec<Command>,
+}
+
+impl ProgrammableTransaction {
+    fn encode(&self, buf: &mut Vec<u8>) {
+        bcs_util::write_vec(buf, &self.inputs, |b, i| i.encode(b));
+        bcs_util::write_vec(buf, &self.commands, |b, c| c.encode(b));
+    }
+
+    fn decode(r: &mut Reader<'_>) -> Result<Self, LedgerError> {
+        Ok(Self {
+            inputs: r.read_vec(CallArg::decode)?,
+            commands: r.read_vec(Command::decode)?,
+        })
+    }
+
+    /// Owned, immutable and receiving object inputs -- the objects whose
+    /// data can be looked up for clear signing. Shared objects are skipped.
+    pub fn object_refs(&self) -> impl Iterator<Item = &ObjectRef> {
+        self.inputs.iter().filter_map(|input| match input {
+            CallArg::Object(ObjectArg::ImmOrOwnedObject(r) | ObjectArg::Receiving(r)) => Some(r),
+            _ => None,
+        })
+    }
+
+    /// `(command index, recipient)` of each `TransferObjects` whose
+    /// recipient is a pure address input. Recipients computed by earlier
+    /// commands are skipped.
+    #[must_use]
+    pub fn recipients(&self) -> Vec<(usize, [u8; 32])> {
+        self.commands
+            .iter()
+            .enumerate()
+            .filter_map(|(i, command)| match command {
+                Command::TransferObjects(_, Argument::Input(input)) => {
+                    match self.inputs.get(usize::from(*input))? {
+                        CallArg::Pure(bytes) => Some((i, <[u8; 32]>::try_from(&bytes[..]).ok()?)),
+                        CallArg::Object(_) => None,
+                    }
+                }
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+/// Collects inputs and commands, returning the [`Argument`] for each.
+///
+/// Object inputs are deduplicated by id (the protocol rejects duplicates);
+/// adding the same shared object twice yields one input that is mutable if
+/// either use asked for it.
+///
+/// Misuse that the returned [`Argument`]s can't express (an object added
+/// both as owned and as shared, more than `u16::MAX + 1` inputs or
+/// commands) is reported by [`finish`](Self::finish).
+#[derive(Debug, Clone, Default)]
+pub struct ProgrammableTransactionBuilder {
+    inputs: Vec<CallArg>,
+    commands: Vec<Command>,
+    /// First misuse, returned by `finish`.
+    error: Option<String>,
+}
+
+impl ProgrammableTransactionBuilder {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an already BCS-encoded pure value.
+    pub fn pure_bytes(&mut self, bytes: Vec<u8>) -> Argument {
+        self.push_input(CallArg::Pure(bytes))
+    }
+
+    /// Add a typed pure value.
+    pub fn pure(&mut self, val: impl Into<PureArg>) -> Argument {
+        self.pure_bytes(val.into().to_bytes())
+    }
+
+    pub fn pure_u64(&mut self, val: u64) -> Argument {
+        self.pure(val)
+    }
+
+    pub fn pure_address(&mut self, addr: &[u8; 32]) -> Argument {
+        self.pure(PureArg::Address(*addr))
+    }
+
+    pub fn object(&mut self, arg: ObjectArg) -> Argument {
+        let existing = self.inputs.iter_mut().position(|input| match input {
+            CallArg::Object(o) => o.id() == arg.id(),
+            CallArg::Pure(_) => false,
+        });
+
+        let Some(idx) = existing else {
+            return self.push_input(CallArg::Object(arg));
+        };
+
+        match (&mut self.inputs[idx], &arg) {
+            (
+                CallArg::Object(ObjectArg::SharedObject {
+                    initial_shared_version,
+                    mutable,
+                    ..
+                }),
+                ObjectArg::SharedObject {
+                    initial_shared_version: new_version,
+                    mutable: new_mutable,
+                    ..
+                },
+            ) if initial_shared_version == new_version => *mutable |= *new_mutable,
+            (CallArg::Object(existing), _) if *existing == arg => {}
+            (CallArg::Object(existing), _) => {
+                let (was, now) = (existing.kind(), arg.kind());
+                let message = if was == now {
+                    format!(
+                        "object 0x{} is added twice as {now}, with different versions",
+                        hex::encode(arg.id())
+                    )
+                } else {
+                    format!(
+                        "object 0x{} is added as {was} and as {now}",
+                        hex::encode(arg.id())
+                    )
+                };
+                self.fail(message);
+            }
+            (CallArg::Pure(_), _) => unreachable!("only object inputs match"),
+        }
+        Argument::Input(self.index(idx, "inputs"))
+    }
+
+    pub fn command(&mut self, command: Command) -> Argument {
+        self.commands.push(command);
+        Argument::Result(self.index(self.commands.len() - 1, "commands"))
+    }
+
+    pub fn split_coins(&mut self, coin: Argument, amounts: Vec<Argument>) -> Argument {
+        self.command(Command::SplitCoins(coin, amounts))
+    }
+
+    pub fn merge_coins(&mut self, target: Argument, sources: Vec<Argument>) -> Argument {
+        self.command(Command::MergeCoins(target, sources))
+    }
+
+    pub fn transfer_objects(&mut self, objects: Vec<Argument>, recipient: Argument) -> Argument {
+        self.command(Command::TransferObjects(objects, recipient))
+    }
+
+    pub fn move_call(
+        &mut self,
+        package: [u8; 32],
+        module: &str,
+        function: &str,
+        type_arguments: Vec<TypeTag>,
+        arguments: Vec<Argument>,
+    ) -> Argument {
+        self.command(Command::MoveCall(Box::new(MoveCall {
+            package,
+            module: module.into(),
+            function: function.into(),
+            type_arguments,
+            arguments,
+        })))
+    }
+
+    /// The transaction, or [`LedgerError::InvalidTransaction`] for the
+    /// first misuse of the builder.
+    pub fn finish(self) -> Result<ProgrammableTransaction, LedgerError> {
+        if let Some(error) = self.error {
+            return Err(LedgerError::InvalidTransaction(error));
+        }
+        Ok(ProgrammableTransaction {
+            inputs: self.inputs,
+            commands: self.commands,
+        })
+    }
+
+    fn push_input(&mut self, arg: CallArg) -> Argument {
+        self.inputs.push(arg);
+        Argument::Input(self.index(self.inputs.len() - 1, "inputs"))
+    }
+
+    /// `idx` as an argument index; past `u16::MAX` the builder fails.
+    fn index(&mut self, idx: usize, what: &str) -> u16 {
+        u16::try_from(idx).unwrap_or_else(|_| {
+            self.fail(format!("more than {} {what}", usize::from(u16::MAX) + 1));
+            u16::MAX
+        })
+    }
+
+    fn fail(&mut self, message: String) {
+        self.error.get_or_insert(message);
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TransactionExpiration {
+    #[default]
+    None,
+    /// Invalid after the given epoch.
+    Epoch(u64),
+}
+
+/// `TransactionData::V1` with a programmable transaction kind.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransactionData {
+    pub kind: ProgrammableTransaction,
+    pub sender: [u8; 32],
+    pub gas_payment: Vec<ObjectRef>,
+    pub gas_owner: [u8; 32],
+    pub gas_price: u64,
+    pub gas_budget: Amount,
+    pub expiration: TransactionExpiration,
+}
+
+impl TransactionData {
+    /// Sender pays for gas, no expiration.
+    #[must_use]
+    pub fn new(
+        kind: ProgrammableTransaction,
+        sender: [u8; 32],
+        gas_payment: Vec<ObjectRef>,
+        gas_budget: Amount,
+        gas_price: u64,
+    ) -> Self {
+        Self {
+            kind,
+            sender,
+            gas_payment,
+            gas_owner: sender,
+            gas_price,
+            gas_budget,
+            expiration: TransactionExpiration::None,
+        }
+    }
+
+    /// Object inputs (see [`ProgrammableTransaction::object_refs`]) and gas
+    /// coins, deduplicated by id: everything the device can clear-sign
+    /// given [`ObjectData`](crate::ObjectData).
+    #[must_use]
+    pub fn object_refs(&self) -> Vec<ObjectRef> {
+        let mut refs: Vec<ObjectRef> = Vec::new();
+        for r in self.kind.object_refs().chain(&self.gas_payment) {
+            if !refs.iter().any(|seen| seen.object_id == r.object_id) {
+                refs.push(r.clone());
+            }
+        }
+        refs
+    }
+
+    /// BCS encoding of `TransactionData` (no intent prefix).
+    pub fn encode_into(&self, buf: &mut Vec<u8>) {
+        buf.push(0x00); // TransactionData::V1
+        buf.push(0x00); // TransactionKind::ProgrammableTransaction
+        self.kind.encode(buf);
+        buf.extend_from_slice(&self.sender);
+
+        // GasData
+        bcs_util::write_vec(buf, &self.gas_payment, |b, r| r.encode(b));
+        buf.extend_from_slice(&self.gas_owner);
+        bcs_util::write_u64(buf, self.gas_price);
+        bcs_util::write_u64(buf, self.gas_budget.nanos());
+
+        match self.expiration {
+            TransactionExpiration::None => buf.push(0),
+            TransactionExpiration::Epoch(epoch) => {
+                buf.push(1);
+                bcs_util::write_u64(buf, epoch);
+            }
+        }
+    }
+
+    /// Parse BCS `TransactionData` (no intent prefix). Only programmable
+    /// transactions are supported.
+    pub fn decode(bytes: &[u8]) -> Result<Self, LedgerError> {
+        let mut r = Reader::new(bytes);
+        let version = r.read_u8()?;
+        if version != 0 {
+            return Err(invalid_variant("TransactionData", version, 0));
+        }
+        let kind = r.read_u8()?;
+        if kind != 0 {
+            return Err(LedgerError::InvalidTransaction(format!(
+                "unsupported transaction kind {kind} (only programmable transactions)"
+            )));
+        }
+        let kind = ProgrammableTransaction::decode(&mut r)?;
+        let sender = r.read_array()?;
+        let gas_payment = r.read_vec(ObjectRef::decode)?;
+        let gas_owner = r.read_array()?;
+        let gas_price = r.read_u64()?;
+        let gas_budget = Amount::from_nanos(r.read_u64()?);
+        let offset = r.position();
+        let expiration = match r.read_u8()? {
+            0 => TransactionExpiration::None,
+            1 => TransactionExpiration::Epoch(r.read_u64()?),
+            v => return Err(invalid_variant("TransactionExpiration", v, offset)),
+        };
+        r.finish()?;
+        Ok(Self {
+            kind,
+            sender,
+            gas_payment,
+            gas_owner,
+            gas_price,
+            gas_budget,
+            expiration,
+        })
+    }
+
+    /// [`decode`](Self::decode) for intent-prefixed bytes, as passed to
+    /// [`LedgerIota::sign_tx`](crate::LedgerIota::sign_tx). The intent's
+    /// app id isn't checked.
+    pub fn from_intent_bytes(tx: &[u8]) -> Result<Self, LedgerError> {
+        Self::decode(super::split_intent(tx)?.1)
+    }
+
+    /// Intent-prefixed bytes for IOTA, ready for
+    /// [`LedgerIota::sign_tx`](crate::LedgerIota::sign_tx).
+    pub fn to_intent_bytes(&self) -> Result<Vec<u8>, LedgerError> {
+        self.to_intent_bytes_for(&crate::NetworkProfile::IOTA)
+    }
+
+    /// Intent-prefixed bytes for the network of `profile`.
+    pub fn to_intent_bytes_for(
+        &self,
+        profile: &crate::NetworkProfile,
+    ) -> Result<Vec<u8>, LedgerError> {
+        let mut tx = profile.transaction_intent().to_vec();
+        self.encode_into(&mut tx);
+        super::finish(tx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn object_ref(byte: u8) -> ObjectRef {
+        ObjectRef {
+            object_id: [byte; 32],
+            version: 7,
+            digest: ObjectDigest([byte.wrapping_add(1); 32]),
+        }
+    }
+
+    #[test]
+    fn shared_object_encoding() {
+        let mut buf = Vec::new();
+        CallArg::Object(ObjectArg::SharedObject {
+            id: [0x11; 32],
+            initial_shared_version: 0x0102,
+            mutable: true,
+        })
+        .encode(&mut buf);
+
+        assert_eq!(buf[0], 1); // CallArg::Object
+        assert_eq!(buf[1], 1); // ObjectArg::SharedObject
+        assert_eq!(&buf[2..34], &[0x11; 32]);
+        assert_eq!(&buf[34..42], &0x0102u64.to_le_bytes());
+        assert_eq!(buf[42], 1); // mutable
+        assert_eq!(buf.len(), 43);
+    }
+
+    #[test]
+    fn owned_object_encoding() {
+        let mut buf = Vec::new();
+        CallArg::Object(ObjectArg::ImmOrOwnedObject(object_ref(0x22))).encode(&mut buf);
+
+        assert_eq!(&buf[..2], &[1, 0]);
+        assert_eq!(&buf[2..34], &[0x22; 32]);
+        assert_eq!(&buf[34..42], &7u64.to_le_bytes());
+        assert_eq!(buf[42], 32); // digest length prefix
+        assert_eq!(&buf[43..75], &[0x23; 32]);
+    }
+
+    #[test]
+    fn receiving_object_encoding() {
+        let mut buf = Vec::new();
+        CallArg::Object(ObjectArg::Receiving(object_ref(0x22))).encode(&mut buf);
+
+        let mut owned = Vec::new();
+        CallArg::Object(ObjectArg::ImmOrOwnedObject(object_ref(0x22))).encode(&mut owned);
+
+        assert_eq!(&buf[..2], &[1, 2]);
+        assert_eq!(&buf[2..], &owned[2..]);
+    }
+
+    #[test]
+    fn receiving_as_move_call_argument() {
+        let mut ptb = ProgrammableTransactionBuilder::new();
+        let parent = ptb.object(ObjectArg::ImmOrOwnedObject(object_ref(0x10)));
+        let child = ptb.object(ObjectArg::Receiving(object_ref(0x20)));
+        let obj = ptb.move_call([0x44; 32], "vault", "claim", vec![], vec![parent, child]);
+        let me = ptb.pure_address(&[0xAB; 32]);
+        ptb.transfer_objects(vec![obj], me);
+
+        assert_eq!(child, Argument::Input(1));
+        let pt = ptb.finish().unwrap();
+        assert!(matches!(
+            pt.inputs[1],
+            CallArg::Object(ObjectArg::Receiving(_))
+        ));
+    }
+
+    #[test]
+    fn clock_is_immutable_shared_0x6() {
+        let ObjectArg::SharedObject {
+            id,
+            initial_shared_version,
+            mutable,
+        } = ObjectArg::clock()
+        else {
+            panic!("clock must be shared");
+        };
+        assert_eq!(id[31], 0x06);
+        assert!(id[..31].iter().all(|&b| b == 0));
+        assert_eq!(initial_shared_version, 1);
+        assert!(!mutable);
+    }
+
+    #[test]
+    fn builder_assigns_input_and_result_indices() {
+        let mut ptb = ProgrammableTransactionBuilder::new();
+        let amt = ptb.pure_u64(5);
+        let rcpt = ptb.pure_address(&[0xAB; 32]);
+        let coin = ptb.split_coins(Argument::GasCoin, vec![amt]);
+        let transfer = ptb.transfer_objects(vec![coin], rcpt);
+
+        assert_eq!(amt, Argument::Input(0));
+        assert_eq!(rcpt, Argument::Input(1));
+        assert_eq!(coin, Argument::Result(0));
+        assert_eq!(transfer, Argument::Result(1));
+
+        let pt = ptb.finish().unwrap();
+        assert_eq!(pt.inputs.len(), 2);
+        assert_eq!(pt.commands.len(), 2);
+    }
+
+    #[test]
+    fn builder_pure_encodes_typed_values() {
+        let mut ptb = ProgrammableTransactionBuilder::new();
+        ptb.pure("hi");
+        ptb.pure(PureArg::option(Some(PureArg::U16(1))));
+        let pt = ptb.finish().unwrap();
+        assert_eq!(pt.inputs[0], CallArg::Pure(vec![2, b'h', b'i']));
+        assert_eq!(pt.inputs[1], CallArg::Pure(vec![1, 1, 0]));
+    }
+
+    #[test]
+    fn builder_dedups_shared_objects_and_widens_mutability() {
+        let mut ptb = ProgrammableTransactionBuilder::new();
+        let a = ptb.object(ObjectArg::system_state(false));
+        let b = ptb.object(ObjectArg::system_state(true));
+        assert_eq!(a, b);
+
+        let pt = ptb.finish().unwrap();
+        assert_eq!(pt.inputs.len(), 1);
+        assert!(matches!(
+            pt.inputs[0],
+            CallArg::Object(ObjectArg::SharedObject { mutable: true, .. })
+        ));
+    }
+
+    #[test]
+    fn builder_rejects_conflicting_object_inputs() {
+        let shared = ObjectArg::SharedObject {
+            id: [0x10; 32],
+            initial_shared_version: 1,
+            mutable: false,
+        };
+        let mut ptb = ProgrammableTransactionBuilder::new();
+        let owned = ptb.object(ObjectArg::ImmOrOwnedObject(object_ref(0x10)));
+        assert_eq!(
+            ptb.object(ObjectArg::ImmOrOwnedObject(object_ref(0x10))),
+            owned
+        );
+        ptb.object(shared);
+        assert!(matches!(
+            ptb.finish(),
+            Err(LedgerError::InvalidTransaction(msg)) if msg.contains("as owned and as shared")
+        ));
+
+        let mut ptb = ProgrammableTransactionBuilder::new();
+        let mut newer = object_ref(0x10);
+        newer.version += 1;
+        ptb.object(ObjectArg::ImmOrOwnedObject(object_ref(0x10)));
+        ptb.object(ObjectArg::ImmOrOwnedObject(newer));
+        assert!(ptb.finish().is_err());
+    }
+
+    #[test]
+    fn builder_rejects_too_many_inputs() {
+        let mut ptb = ProgrammableTransactionBuilder::new();
+        for _ in 0..=u16::MAX {
+            ptb.pure_u64(1);
+        }
+        assert_eq!(ptb.clone().finish().unwrap().inputs.len(), 65_536);
+        assert_eq!(ptb.pure_u64(1), Argument::Input(u16::MAX));
+        assert!(matches!(
+            ptb.finish(),
+            Err(LedgerError::InvalidTransaction(msg)) if msg == "more than 65536 inputs"
+        ));
+    }
+
+    #[test]
+    fn move_call_with_shared_clock() {
+        let mut ptb = ProgrammableTransactionBuilder::new();
+        let pool = ptb.object(ObjectArg::SharedObject {
+            id: [0x33; 32],
+            initial_shared_version: 99,
+            mutable: true,
+        });
+        let clock = ptb.object(ObjectArg::clock());
+        ptb.move_call([0x44; 32], "pool", "swap", vec![], vec![pool, clock]);
+        let pt = ptb.finish().unwrap();
+
+        let mut buf = Vec::new();
+        pt.commands[0].encode(&mut buf);
+        let mut expected = vec![0x00]; // Command::MoveCall
+        expected.extend_from_slice(&[0x44; 32]);
+        expected.extend_from_slice(&[4, b'p', b'o', b'o', b'l']);
+        expected.extend_from_slice(&[4, b's', b'w', b'a', b'p']);
+        expected.push(0); // no type arguments
+        expected.extend_from_slice(&[2, 1, 0, 0, 1, 1, 0]); // [Input(0), Input(1)]
+        assert_eq!(buf, expected);
+    }
+
+    #[test]
+    fn recipients_resolve_pure_inputs() {
+        let mut ptb = ProgrammableTransactionBuilder::new();
+        let amt = ptb.pure_u64(5);
+        let split = ptb.split_coins(Argument::GasCoin, vec![amt]);
+        let alice = ptb.pure_address(&[0xA1; 32]);
+        ptb.transfer_objects(vec![split], alice);
+        ptb.transfer_objects(vec![Argument::GasCoin], amt);
+        ptb.transfer_objects(vec![split], Argument::Result(0));
+        let bob = ptb.pure_address(&[0xB0; 32]);
+        ptb.transfer_objects(vec![Argument::GasCoin], bob);
+
+        assert_eq!(
+            ptb.finish().unwrap().recipients(),
+            vec![(1, [0xA1; 32]), (4, [0xB0; 32])]
+        );
+    }
+
+    #[test]
+    fn decode_round_trips_every_command() {
+        let mut ptb = ProgrammableTransactionBuilder::new();
+        let coin = ptb.object(ObjectArg::ImmOrOwnedObject(object_ref(0x10)));
+        let recv = ptb.object(ObjectArg::Receiving(object_ref(0x20)));
+        let clock = ptb.object(ObjectArg::clock());
+        let amt = ptb.pure_u64(5);
+        ptb.merge_coins(Argument::GasCoin, vec![coin]);
+        let split = ptb.split_coins(Argument::GasCoin, vec![amt]);
+        ptb.move_call(
+            [0x44; 32],
+            "m",
+            "f",
+            vec![TypeTag::vector(TypeTag::U8)],
+            vec![recv, clock, Argument::NestedResult(1, 0)],
+        );
+        ptb.command(Command::MakeMoveVec(Some(TypeTag::U64), vec![amt]));
+        ptb.command(Command::Publish(vec![vec![1, 2]], vec![[0x01; 32]]));
+        ptb.command(Command::Upgrade(vec![vec![3]], vec![], [0x55; 32], split));
+        let me = ptb.pure_address(&[0xAB; 32]);
+        ptb.transfer_objects(vec![split], me);
+
+        let mut data = TransactionData::new(
+            ptb.finish().unwrap(),
+            [0xAB; 32],
+            vec![object_ref(0x30)],
+            Amount::from_nanos(1_000),
+            7,
+        );
+        data.expiration = TransactionExpiration::Epoch(3);
+
+        let bytes = data.to_intent_bytes().unwrap();
+        let decoded = TransactionData::from_intent_bytes(&bytes).unwrap();
+        assert_eq!(decoded, data);
+
+        let ids: Vec<_> = decoded.kind.object_refs().map(|r| r.object_id[0]).collect();
+        assert_eq!(ids, [0x10, 0x20]);
+        let ids: Vec<_> = decoded
+            .object_refs()
+            .iter()
+            .map(|r| r.object_id[0])
+            .collect();
+        assert_eq!(ids, [0x10, 0x20, 0x30]);
+    }
+
+    #[test]
+    fn decode_rejects_malformed() {
+        let data = TransactionData::new(
+            ProgrammableTransaction::default(),
+            [0x01; 32],
+            vec![],
+            Amount::from_nanos(1),
+            1,
+        );
+        let bytes = data.to_intent_bytes().unwrap();
+        assert!(TransactionData::from_intent_bytes(&bytes[1..]).is_err());
+
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert!(TransactionData::from_intent_bytes(&trailing).is_err());
+
+        let mut system_kind = bytes.clone();
+        system_kind[4] = 1;
+        assert!(matches!(
+            TransactionData::from_intent_bytes(&system_kind),
+            Err(LedgerError::InvalidTransaction(_))
+        ));
+
+        assert!(TransactionData::from_intent_bytes(&bytes[..bytes.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn expiration_epoch_encoding() {
+        let mut data = TransactionData::new(
+            ProgrammableTransaction::default(),
+            [0x01; 32],
+            vec![],
+            Amount::from_nanos(1),
+            1,
+        );
+        data.expiration = TransactionExpiration::Epoch(12);
+        let mut buf = Vec::new();
+        data.encode_into(&mut buf);
+        assert_eq!(&buf[buf.len() - 9..], &[1, 12, 0, 0, 0, 0, 0, 0, 0]);
+    }
+}
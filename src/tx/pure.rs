@@ -0,0 +1,135 @@
+//! Typed pure arguments for programmable transactions.
+//!
+//! Pure inputs are opaque BCS bytes on the wire; the Move VM decodes them
+//! against the parameter type of the function being called. [`PureArg`]
+//! produces those bytes from typed values so callers don't hand-serialize.
+
+use crate::bcs_util;
+use crate::prelude::*;
+
+/// A Move value that can be passed as a `CallArg::Pure` input.
+///
+/// `U256` is little-endian, matching BCS. `Vector` elements should all be
+/// the same variant -- BCS has no per-element tags, so a mixed vector
+/// encodes fine but won't decode as any Move type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PureArg {
+    Bool(bool),
+    U8(u8),
+    U16(u16),
+    U32(u32),
+    U64(u64),
+    U128(u128),
+    U256([u8; 32]),
+    Address([u8; 32]),
+    /// `std::string::String` (or `ascii::String`); UTF-8 bytes with a length prefix.
+    String(String),
+    Vector(Vec<PureArg>),
+    Option(Option<Box<PureArg>>),
+}
+
+impl PureArg {
+    /// `vector<u8>` from raw bytes.
+    #[must_use]
+    pub fn bytes(bytes: &[u8]) -> Self {
+        Self::Vector(bytes.iter().copied().map(Self::U8).collect())
+    }
+
+    /// `Option<T>` from a Rust option.
+    #[must_use]
+    pub fn option(value: Option<PureArg>) -> Self {
+        Self::Option(value.map(Box::new))
+    }
+
+    pub fn encode(&self, buf: &mut Vec<u8>) {
+        match self {
+            Self::Bool(v) => bcs_util::write_bool(buf, *v),
+            Self::U8(v) => bcs_util::write_u8(buf, *v),
+            Self::U16(v) => bcs_util::write_u16(buf, *v),
+            Self::U32(v) => bcs_util::write_u32(buf, *v),
+            Self::U64(v) => bcs_util::write_u64(buf, *v),
+            Self::U128(v) => bcs_util::write_u128(buf, *v),
+            Self::U256(v) | Self::Address(v) => buf.extend_from_slice(v),
+            Self::String(s) => bcs_util::write_string(buf, s),
+            Self::Vector(items) => bcs_util::write_vec(buf, items, |b, v| v.encode(b)),
+            Self::Option(v) => bcs_util::write_option(buf, v.as_deref(), |b, v| v.encode(b)),
+        }
+    }
+
+    #[must_use]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        self.encode(&mut buf);
+        buf
+    }
+}
+
+macro_rules! impl_from {
+    ($($ty:ty => $variant:ident),* $(,)?) => {
+        $(impl From<$ty> for PureArg {
+            fn from(v: $ty) -> Self {
+                Self::$variant(v)
+            }
+        })*
+    };
+}
+
+impl_from! {
+    bool => Bool,
+    u8 => U8,
+    u16 => U16,
+    u32 => U32,
+    u64 => U64,
+    u128 => U128,
+    String => String,
+}
+
+impl From<&str> for PureArg {
+    fn from(v: &str) -> Self {
+        Self::String(v.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn integers_little_endian() {
+        assert_eq!(PureArg::Bool(true).to_bytes(), [1]);
+        assert_eq!(PureArg::U8(0xAB).to_bytes(), [0xAB]);
+        assert_eq!(PureArg::U16(0x0102).to_bytes(), [0x02, 0x01]);
+        assert_eq!(PureArg::U32(0x01020304).to_bytes(), [4, 3, 2, 1]);
+        assert_eq!(PureArg::U64(1).to_bytes(), [1, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(PureArg::U128(1 << 64).to_bytes()[8], 1);
+
+        let mut u256 = [0u8; 32];
+        u256[0] = 7;
+        assert_eq!(PureArg::U256(u256).to_bytes(), u256);
+    }
+
+    #[test]
+    fn address_has_no_length_prefix() {
+        assert_eq!(PureArg::Address([0x11; 32]).to_bytes(), [0x11; 32]);
+    }
+
+    #[test]
+    fn string_and_bytes_are_length_prefixed() {
+        assert_eq!(PureArg::from("abc").to_bytes(), [3, b'a', b'b', b'c']);
+        assert_eq!(PureArg::bytes(&[9, 8]).to_bytes(), [2, 9, 8]);
+    }
+
+    #[test]
+    fn vector_of_u16() {
+        let v = PureArg::Vector(vec![1u16.into(), 0x0203u16.into()]);
+        assert_eq!(v.to_bytes(), [2, 1, 0, 3, 2]);
+    }
+
+    #[test]
+    fn option_encoding() {
+        assert_eq!(PureArg::option(None).to_bytes(), [0]);
+        assert_eq!(PureArg::option(Some(5u8.into())).to_bytes(), [1, 5]);
+        let nested = PureArg::option(Some(PureArg::Vector(vec![])));
+        assert_eq!(nested.to_bytes(), [1, 0]);
+    }
+}
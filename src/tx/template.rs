@@ -0,0 +1,236 @@
+//! Reusable, reviewable transaction shapes.
+//!
+//! A [`Template`] fixes everything about a recurring payout except the
+//! amounts, the coins to pay from and the gas price. It serializes to a
+//! stable byte format, and [`Template::hash`] identifies it, so a template
+//! can be reviewed and approved once and then instantiated repeatedly.
+
+use sha2::{Digest, Sha256};
+
+use crate::amount::Amount;
+use crate::bcs_util::{self, Reader};
+use crate::error::LedgerError;
+use crate::prelude::*;
+use crate::types::Address;
+
+use super::{build_batch_transfer_tx, GasCoinRef, Payment};
+
+/// Serialization format version, first byte of [`Template::to_bytes`].
+const TEMPLATE_FORMAT_VERSION: u8 = 1;
+
+/// A fixed recipient of a [`Template`], optionally capped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TemplateRecipient {
+    pub address: Address,
+    /// Largest amount [`Template::instantiate`] will accept for this recipient.
+    pub max_amount: Option<Amount>,
+}
+
+/// A payout to fixed recipients with amounts supplied at instantiation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template {
+    /// Free-form label, covered by the hash.
+    pub name: String,
+    pub sender: Address,
+    pub recipients: Vec<TemplateRecipient>,
+    pub gas_budget: Amount,
+}
+
+impl Template {
+    /// Stable binary encoding: format version byte followed by BCS fields.
+    #[must_use]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = vec![TEMPLATE_FORMAT_VERSION];
+        bcs_util::write_string(&mut buf, &self.name);
+        buf.extend_from_slice(&self.sender.0);
+        bcs_util::write_vec(&mut buf, &self.recipients, |b, r| {
+            b.extend_from_slice(&r.address.0);
+            bcs_util::write_option(b, r.max_amount.as_ref(), |b, a| {
+                bcs_util::write_u64(b, a.nanos())
+            });
+        });
+        bcs_util::write_u64(&mut buf, self.gas_budget.nanos());
+        buf
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, LedgerError> {
+        let mut r = Reader::new(bytes);
+        let version = r.read_u8()?;
+        if version != TEMPLATE_FORMAT_VERSION {
+            return Err(LedgerError::InvalidEncoding(format!(
+                "unsupported template format version {version}"
+            )));
+        }
+        let name = r.read_string()?.to_owned();
+        let sender = Address(r.read_array()?);
+        let recipients = r.read_vec(|r| {
+            Ok(TemplateRecipient {
+                address: Address(r.read_array()?),
+                max_amount: r.read_option(|r| r.read_u64().map(Amount::from_nanos))?,
+            })
+        })?;
+        let gas_budget = Amount::from_nanos(r.read_u64()?);
+        r.finish()?;
+        Ok(Self {
+            name,
+            sender,
+            recipients,
+            gas_budget,
+        })
+    }
+
+    /// SHA-256 of [`to_bytes`](Self::to_bytes). Any change to the template,
+    /// including its name, changes the hash.
+    #[must_use]
+    pub fn hash(&self) -> [u8; 32] {
+        Sha256::digest(self.to_bytes()).into()
+    }
+
+    /// Build intent-prefixed transaction bytes paying `amounts[i]` to
+    /// `recipients[i]` (see [`build_batch_transfer_tx`]).
+    ///
+    /// Fails if the number of amounts doesn't match or any amount exceeds
+    /// its recipient's cap.
+    pub fn instantiate(
+        &self,
+        amounts: &[Amount],
+        coins: &[GasCoinRef],
+        gas_price: u64,
+    ) -> Result<Vec<u8>, LedgerError> {
+        if amounts.len() != self.recipients.len() {
+            return Err(LedgerError::InvalidTransaction(format!(
+                "template has {} recipients, got {} amounts",
+                self.recipients.len(),
+                amounts.len()
+            )));
+        }
+
+        let payments = self
+            .recipients
+            .iter()
+            .zip(amounts)
+            .map(|(r, &amount)| match r.max_amount {
+                Some(max) if amount > max => Err(LedgerError::InvalidAmount(format!(
+                    "{amount} to {} exceeds template cap of {max}",
+                    r.address
+                ))),
+                _ => Ok(Payment {
+                    recipient: r.address,
+                    amount,
+                }),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        build_batch_transfer_tx(&self.sender, &payments, coins, self.gas_budget, gas_price)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::ObjectDigest;
+
+    fn template() -> Template {
+        Template {
+            name: "monthly payroll".into(),
+            sender: Address([0xAA; 32]),
+            recipients: vec![
+                TemplateRecipient {
+                    address: Address([0x01; 32]),
+                    max_amount: Some(Amount::from_nanos(1_000)),
+                },
+                TemplateRecipient {
+                    address: Address([0x02; 32]),
+                    max_amount: None,
+                },
+            ],
+            gas_budget: Amount::from_nanos(5_000_000),
+        }
+    }
+
+    fn coin() -> GasCoinRef {
+        GasCoinRef {
+            object_id: [0xCC; 32],
+            version: 1,
+            digest: ObjectDigest([0xDD; 32]),
+        }
+    }
+
+    #[test]
+    fn bytes_round_trip() {
+        let t = template();
+        let bytes = t.to_bytes();
+        assert_eq!(bytes[0], TEMPLATE_FORMAT_VERSION);
+        assert_eq!(Template::from_bytes(&bytes).unwrap(), t);
+    }
+
+    #[test]
+    fn from_bytes_rejects_bad_input() {
+        let mut bytes = template().to_bytes();
+        bytes.push(0);
+        assert!(Template::from_bytes(&bytes).is_err());
+
+        let mut bytes = template().to_bytes();
+        bytes[0] = 99;
+        assert!(Template::from_bytes(&bytes).is_err());
+
+        assert!(Template::from_bytes(&[]).is_err());
+    }
+
+    #[test]
+    fn hash_changes_with_content() {
+        let t = template();
+        assert_eq!(t.hash(), template().hash());
+
+        let mut renamed = template();
+        renamed.name.push('!');
+        assert_ne!(renamed.hash(), t.hash());
+
+        let mut uncapped = template();
+        uncapped.recipients[0].max_amount = None;
+        assert_ne!(uncapped.hash(), t.hash());
+    }
+
+    #[test]
+    fn instantiate_matches_batch_builder() {
+        let t = template();
+        let amounts = [Amount::from_nanos(500), Amount::from_nanos(7)];
+        let tx = t.instantiate(&amounts, &[coin()], 1000).unwrap();
+
+        let expected = build_batch_transfer_tx(
+            &t.sender,
+            &[
+                Payment {
+                    recipient: Address([0x01; 32]),
+                    amount: amounts[0],
+                },
+                Payment {
+                    recipient: Address([0x02; 32]),
+                    amount: amounts[1],
+                },
+            ],
+            &[coin()],
+            t.gas_budget,
+            1000,
+        )
+        .unwrap();
+        assert_eq!(tx, expected);
+    }
+
+    #[test]
+    fn instantiate_enforces_shape_and_caps() {
+        let t = template();
+        assert!(matches!(
+            t.instantiate(&[Amount::from_nanos(1)], &[coin()], 1),
+            Err(LedgerError::InvalidTransaction(_))
+        ));
+        assert!(matches!(
+            t.instantiate(
+                &[Amount::from_nanos(1_001), Amount::from_nanos(1)],
+                &[coin()],
+                1
+            ),
+            Err(LedgerError::InvalidAmount(_))
+        ));
+    }
+}
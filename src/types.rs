@@ -1,14 +1,71 @@
-//! Core types: BIP32 derivation paths, app version, public key, address, signature.
+//! Core types: BIP32 derivation paths, app version, public key, address, signature, digest.
 
+use crate::base58;
 use crate::error::LedgerError;
-use byteorder::{LittleEndian, WriteBytesExt};
+use crate::prelude::*;
 
-const HARDENED: u32 = 0x8000_0000;
+/// Bit set on hardened path components.
+pub const HARDENED: u32 = 0x8000_0000;
+
+/// `index'`, e.g. `hardened(44)` for the purpose component.
+#[must_use]
+pub const fn hardened(index: u32) -> u32 {
+    index | HARDENED
+}
+
+/// Whether `component` has the hardened bit set.
+#[must_use]
+pub const fn is_hardened(component: u32) -> bool {
+    component & HARDENED != 0
+}
+
+/// Deepest path Ledger devices derive.
+pub const MAX_PATH_DEPTH: usize = 10;
+
+/// SLIP-44 coin type, the second component of a derivation path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CoinType {
+    /// `4218'`
+    IotaMainnet,
+    /// `1'`, shared by all testnets.
+    Testnet,
+    /// Any other coin type, e.g. Shimmer (`4219'`).
+    Custom(u32),
+}
+
+impl CoinType {
+    /// The unhardened SLIP-44 number.
+    #[must_use]
+    pub const fn value(self) -> u32 {
+        match self {
+            Self::IotaMainnet => 4218,
+            Self::Testnet => 1,
+            Self::Custom(v) => v,
+        }
+    }
+
+    /// Maps 4218 and 1 to their named variants.
+    #[must_use]
+    pub const fn from_value(value: u32) -> Self {
+        match value {
+            4218 => Self::IotaMainnet,
+            1 => Self::Testnet,
+            v => Self::Custom(v),
+        }
+    }
+}
+
+impl core::fmt::Display for CoinType {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}'", self.value())
+    }
+}
 
 /// BIP32 derivation path for IOTA addresses.
 ///
 /// All components must be hardened. Supports `44'/4218'/...` (mainnet)
-/// or `44'/1'/...` (testnet).
+/// or `44'/1'/...` (testnet); [`Bip32Path::custom_coin`] and
+/// [`Bip32Path::parse_for_coin`] opt into other coin types.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Bip32Path(Vec<u32>);
 
@@ -23,25 +80,79 @@ impl Bip32Path {
     /// Mainnet: `44'/4218'/account'/change'/index'`
     #[must_use]
     pub fn iota(account: u32, change: u32, index: u32) -> Self {
-        Self(vec![
-            44 | HARDENED,
-            4218 | HARDENED,
-            account | HARDENED,
-            change | HARDENED,
-            index | HARDENED,
-        ])
+        Self::hardened(CoinType::IotaMainnet, account, change, index)
     }
 
     /// Testnet: `44'/1'/account'/change'/index'`
     #[must_use]
     pub fn testnet(account: u32, change: u32, index: u32) -> Self {
-        Self(vec![
-            44 | HARDENED,
-            1 | HARDENED,
-            account | HARDENED,
-            change | HARDENED,
-            index | HARDENED,
-        ])
+        Self::hardened(CoinType::Testnet, account, change, index)
+    }
+
+    /// Sets the hardened bit on each component without range checks.
+    fn hardened(coin: CoinType, account: u32, change: u32, index: u32) -> Self {
+        Self(
+            [44, coin.value(), account, change, index]
+                .iter()
+                .map(|&c| hardened(c))
+                .collect(),
+        )
+    }
+
+    /// `44'/coin'/account'/change'/index'`, with every component hardened.
+    /// Fails if a component is 2^31 or larger. Whether the device derives
+    /// keys for a [`CoinType::Custom`] depends on the app.
+    pub fn for_coin(
+        coin: CoinType,
+        account: u32,
+        change: u32,
+        index: u32,
+    ) -> Result<Self, LedgerError> {
+        let components = [44, coin.value(), account, change, index];
+        if let Some(i) = components.iter().position(|&c| is_hardened(c)) {
+            return Err(LedgerError::InvalidPath(format!(
+                "component {i} ({}) is too large to harden",
+                components[i]
+            )));
+        }
+        Ok(Self::hardened(coin, account, change, index))
+    }
+
+    /// `44'/coin_type'/account'/change'/index'` for a coin type other than
+    /// IOTA's, e.g. Shimmer (4219'); see [`for_coin`](Self::for_coin).
+    pub fn custom_coin(
+        coin_type: u32,
+        account: u32,
+        change: u32,
+        index: u32,
+    ) -> Result<Self, LedgerError> {
+        Self::for_coin(CoinType::from_value(coin_type), account, change, index)
+    }
+
+    /// Parses like `FromStr`, but also accepts `coin` as the coin type, so
+    /// that paths from [`for_coin`](Self::for_coin) read back from their
+    /// `Display` form.
+    pub fn parse_for_coin(s: &str, coin: CoinType) -> Result<Self, LedgerError> {
+        let path = Self(parse_components(s)?);
+        path.validate_coin(Some(coin))?;
+        Ok(path)
+    }
+
+    /// Coin type of the second component, if the path has one.
+    #[must_use]
+    pub fn coin_type(&self) -> Option<CoinType> {
+        self.0.get(1).map(|&c| CoinType::from_value(c & !HARDENED))
+    }
+
+    /// Mainnet paths `44'/4218'/account'/change'/i'` for each `i` in
+    /// `indices`; indices from 2^31 on (which can't be hardened) are
+    /// skipped.
+    pub fn iter_indices(
+        account: u32,
+        change: u32,
+        indices: core::ops::Range<u32>,
+    ) -> impl Iterator<Item = Self> {
+        (indices.start..indices.end.min(HARDENED)).map(move |i| Self::iota(account, change, i))
     }
 
     /// Wire format: `[n: u8][path[0]: u32 LE]...[path[n-1]: u32 LE]`
@@ -49,37 +160,107 @@ impl Bip32Path {
         let mut buf = Vec::with_capacity(1 + self.0.len() * 4);
         buf.push(self.0.len() as u8);
         for &component in &self.0 {
-            buf.write_u32::<LittleEndian>(component).unwrap();
+            buf.extend_from_slice(&component.to_le_bytes());
         }
         buf
     }
 
+    /// Inverse of [`serialize`](Self::serialize). Checks the structure
+    /// only; pass the components to [`new`](Self::new) to validate them.
+    pub fn deserialize(bytes: &[u8]) -> Result<Self, LedgerError> {
+        let Some((&n, rest)) = bytes.split_first() else {
+            return Err(LedgerError::InvalidPath("empty path encoding".into()));
+        };
+        if rest.len() != usize::from(n) * 4 {
+            return Err(LedgerError::InvalidPath(format!(
+                "{n} components need {} bytes, got {}",
+                usize::from(n) * 4,
+                rest.len()
+            )));
+        }
+        Ok(Self(
+            rest.chunks_exact(4)
+                .map(|c| u32::from_le_bytes(c.try_into().expect("4 bytes")))
+                .collect(),
+        ))
+    }
+
     pub fn components(&self) -> &[u32] {
         &self.0
     }
 
+    /// Canonical form of a path string: `m/` prefix, `'` for hardened
+    /// components, no surrounding whitespace.
+    ///
+    /// ```
+    /// # use ledger_iota::Bip32Path;
+    /// assert_eq!(Bip32Path::normalize(" 44h/4218H/0'/0h/1h ")?, "m/44'/4218'/0'/0'/1'");
+    /// # Ok::<(), ledger_iota::LedgerError>(())
+    /// ```
+    pub fn normalize(s: &str) -> Result<String, LedgerError> {
+        Ok(s.parse::<Self>()?.to_string())
+    }
+
+    /// Whether `prefix` is this path or one of its ancestors, e.g. the
+    /// account path `44'/4218'/0'`.
+    #[must_use]
+    pub fn starts_with(&self, prefix: &Self) -> bool {
+        self.0.starts_with(&prefix.0)
+    }
+
+    /// `purpose'/coin'/account'`, or `None` for shorter paths.
+    #[must_use]
+    pub fn account_path(&self) -> Option<Self> {
+        self.0.get(..3).map(|c| Self(c.to_vec()))
+    }
+
+    /// Components below the account (`change'/index'` for a full path).
+    #[must_use]
+    pub fn address_components(&self) -> &[u32] {
+        self.0.get(3..).unwrap_or_default()
+    }
+
     fn validate(&self) -> Result<(), LedgerError> {
+        self.validate_coin(None)
+    }
+
+    /// [`validate`](Self::validate), also accepting `extra_coin` as the
+    /// coin type.
+    fn validate_coin(&self, extra_coin: Option<CoinType>) -> Result<(), LedgerError> {
         if self.0.len() < 2 {
             return Err(LedgerError::InvalidPath(
                 "path must have at least 2 components".into(),
             ));
         }
 
-        if self.0[0] != (44 | HARDENED) {
+        if self.0.len() > MAX_PATH_DEPTH {
+            return Err(LedgerError::InvalidPath(format!(
+                "path has {} components, devices accept at most {MAX_PATH_DEPTH}",
+                self.0.len()
+            )));
+        }
+
+        if self.0[0] != hardened(44) {
             return Err(LedgerError::InvalidPath(
                 "first component must be 44'".into(),
             ));
         }
 
         let coin = self.0[1];
-        if coin != (4218 | HARDENED) && coin != (1 | HARDENED) {
-            return Err(LedgerError::InvalidPath(
-                "coin type must be 4218' (mainnet) or 1' (testnet)".into(),
-            ));
+        if coin != hardened(4218)
+            && coin != hardened(1)
+            && extra_coin.map_or(true, |extra| coin != hardened(extra.value()))
+        {
+            return Err(LedgerError::InvalidPath(match extra_coin {
+                Some(extra) => {
+                    format!("coin type must be 4218' (mainnet), 1' (testnet) or {extra}")
+                }
+                None => "coin type must be 4218' (mainnet) or 1' (testnet)".into(),
+            }));
         }
 
         for (i, &c) in self.0.iter().enumerate() {
-            if c & HARDENED == 0 {
+            if !is_hardened(c) {
                 return Err(LedgerError::InvalidPath(format!(
                     "component {i} must be hardened"
                 )));
@@ -90,28 +271,172 @@ impl Bip32Path {
     }
 }
 
-impl std::fmt::Display for Bip32Path {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Display for Bip32Path {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "m")?;
         for &c in &self.0 {
             let val = c & !HARDENED;
-            let h = if c & HARDENED != 0 { "'" } else { "" };
+            let h = if is_hardened(c) { "'" } else { "" };
             write!(f, "/{val}{h}")?;
         }
         Ok(())
     }
 }
 
+/// Parses `m/44'/4218'/0'/0'/0'`; `h` or `H` may replace `'` and the
+/// leading `m/` is optional. The result is validated like
+/// [`Bip32Path::new`].
+impl core::str::FromStr for Bip32Path {
+    type Err = LedgerError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::new(parse_components(s)?)
+    }
+}
+
+/// Components of a path string in the syntax [`Bip32Path`]'s `FromStr`
+/// accepts, unvalidated.
+fn parse_components(s: &str) -> Result<Vec<u32>, LedgerError> {
+    let s = s.trim();
+    let body = s.strip_prefix("m/").unwrap_or(s);
+    if body.is_empty() {
+        return Err(LedgerError::InvalidPath(format!("{s:?} has no components")));
+    }
+    body.split('/')
+        .map(|part| {
+            let (digits, hardened) = match part.strip_suffix(['\'', 'h', 'H']) {
+                Some(digits) => (digits, true),
+                None => (part, false),
+            };
+            let value: u32 = digits
+                .parse()
+                .ok()
+                .filter(|v| v & HARDENED == 0)
+                .ok_or_else(|| {
+                    LedgerError::InvalidPath(format!("invalid component {part:?} in {s:?}"))
+                })?;
+            Ok(if hardened { value | HARDENED } else { value })
+        })
+        .collect()
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
 pub struct AppVersion {
     pub major: u8,
     pub minor: u8,
     pub patch: u8,
     pub name: String,
+    /// Settings byte appended by newer app builds; `None` for older ones.
+    pub flags: Option<AppFlags>,
 }
 
-impl std::fmt::Display for AppVersion {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+/// App build and settings bits reported after the app name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct AppFlags(pub u8);
+
+impl AppFlags {
+    /// Built with debug logging; not a release build.
+    pub const DEBUG_BUILD: u8 = 0x01;
+    /// Blind signing is enabled in the app settings.
+    pub const BLIND_SIGNING: u8 = 0x02;
+
+    #[must_use]
+    pub const fn bits(self) -> u8 {
+        self.0
+    }
+
+    #[must_use]
+    pub const fn contains(self, bits: u8) -> bool {
+        self.0 & bits == bits
+    }
+
+    #[must_use]
+    pub const fn is_debug_build(self) -> bool {
+        self.contains(Self::DEBUG_BUILD)
+    }
+
+    #[must_use]
+    pub const fn blind_signing_enabled(self) -> bool {
+        self.contains(Self::BLIND_SIGNING)
+    }
+}
+
+impl AppVersion {
+    /// Version without a settings byte.
+    #[must_use]
+    pub fn new(major: u8, minor: u8, patch: u8, name: impl Into<String>) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+            name: name.into(),
+            flags: None,
+        }
+    }
+
+    /// `major.minor.patch` or later.
+    #[must_use]
+    pub fn at_least(&self, major: u8, minor: u8, patch: u8) -> bool {
+        (self.major, self.minor, self.patch) >= (major, minor, patch)
+    }
+
+    /// Check against a requirement such as `">=1.0, <2"`: comma-separated
+    /// comparisons (`>=`, `>`, `<=`, `<`, `=`, or none for `=`) that must
+    /// all hold. Missing minor/patch numbers count as 0. The app name is
+    /// ignored.
+    pub fn satisfies(&self, requirement: &str) -> Result<bool, LedgerError> {
+        let invalid = || LedgerError::InvalidVersionReq(requirement.into());
+        let own = (self.major, self.minor, self.patch);
+        let mut all = true;
+        for comparison in requirement.split(',') {
+            let comparison = comparison.trim();
+            let (op, version) = [">=", "<=", ">", "<", "="]
+                .iter()
+                .find_map(|op| Some((*op, comparison.strip_prefix(op)?)))
+                .unwrap_or(("=", comparison));
+            let mut parts = version.trim().split('.').map(|p| p.parse::<u8>().ok());
+            let major = parts.next().flatten().ok_or_else(invalid)?;
+            let minor = parts.next().map_or(Some(0), |p| p).ok_or_else(invalid)?;
+            let patch = parts.next().map_or(Some(0), |p| p).ok_or_else(invalid)?;
+            if parts.next().is_some() {
+                return Err(invalid());
+            }
+            let other = (major, minor, patch);
+            all &= match op {
+                ">=" => own >= other,
+                "<=" => own <= other,
+                ">" => own > other,
+                "<" => own < other,
+                _ => own == other,
+            };
+        }
+        Ok(all)
+    }
+}
+
+/// Orders by version number; the app name, then the flags, only break
+/// ties so the order agrees with `Eq`.
+impl Ord for AppVersion {
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        (self.major, self.minor, self.patch, &self.name, self.flags).cmp(&(
+            other.major,
+            other.minor,
+            other.patch,
+            &other.name,
+            other.flags,
+        ))
+    }
+}
+
+impl PartialOrd for AppVersion {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl core::fmt::Display for AppVersion {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(
             f,
             "{} v{}.{}.{}",
@@ -121,17 +446,97 @@ impl std::fmt::Display for AppVersion {
 }
 
 /// 32-byte Ed25519 public key.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct PublicKey(pub [u8; 32]);
 
 /// 32-byte Blake2b-256 address.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct Address(pub [u8; 32]);
 
 /// 64-byte Ed25519 signature.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Signature(pub [u8; 64]);
 
+/// Signature scheme flag for Ed25519, prefixed to the key in address
+/// derivation and user signatures.
+pub const ED25519_FLAG: u8 = 0x00;
+
+impl PublicKey {
+    /// `Blake2b-256(ED25519_FLAG || pubkey)`, the same address the device
+    /// reports for this key.
+    #[must_use]
+    pub fn to_address(&self) -> Address {
+        use blake2::digest::consts::U32;
+        use blake2::{Blake2b, Digest as _};
+
+        let mut hasher = Blake2b::<U32>::new();
+        hasher.update([ED25519_FLAG]);
+        hasher.update(self.0);
+        Address(hasher.finalize().into())
+    }
+
+    /// Whether the key is a canonically encoded Ed25519 curve point that
+    /// isn't of small order.
+    #[cfg(feature = "verify")]
+    #[must_use]
+    pub fn is_valid_point(&self) -> bool {
+        use curve25519_dalek::edwards::CompressedEdwardsY;
+
+        CompressedEdwardsY(self.0)
+            .decompress()
+            .is_some_and(|p| !p.is_small_order() && p.compress().0 == self.0)
+    }
+}
+
+/// Length of a serialized Ed25519 user signature: flag, signature, key.
+pub const USER_SIGNATURE_LEN: usize = 1 + 64 + 32;
+
+impl Signature {
+    /// The `UserSignature` wire format nodes expect:
+    /// `ED25519_FLAG || signature || pubkey`.
+    #[must_use]
+    pub fn to_user_signature(&self, pubkey: &PublicKey) -> [u8; USER_SIGNATURE_LEN] {
+        let mut out = [0u8; USER_SIGNATURE_LEN];
+        out[0] = ED25519_FLAG;
+        out[1..65].copy_from_slice(&self.0);
+        out[65..].copy_from_slice(&pubkey.0);
+        out
+    }
+
+    /// [`to_user_signature`](Self::to_user_signature) as base64, the form
+    /// `iota_executeTransactionBlock` takes.
+    #[must_use]
+    pub fn to_user_signature_base64(&self, pubkey: &PublicKey) -> String {
+        crate::tx::to_base64(&self.to_user_signature(pubkey))
+    }
+
+    /// Split an Ed25519 user signature into its signature and public key.
+    pub fn from_user_signature(bytes: &[u8]) -> Result<(Self, PublicKey), LedgerError> {
+        if bytes.len() != USER_SIGNATURE_LEN {
+            return Err(LedgerError::InvalidEncoding(format!(
+                "user signature must be {USER_SIGNATURE_LEN} bytes, got {}",
+                bytes.len()
+            )));
+        }
+        if bytes[0] != ED25519_FLAG {
+            return Err(LedgerError::InvalidEncoding(format!(
+                "unsupported signature scheme flag 0x{:02x}",
+                bytes[0]
+            )));
+        }
+        let mut sig = [0u8; 64];
+        let mut pk = [0u8; 32];
+        sig.copy_from_slice(&bytes[1..65]);
+        pk.copy_from_slice(&bytes[65..]);
+        Ok((Self(sig), PublicKey(pk)))
+    }
+
+    /// [`from_user_signature`](Self::from_user_signature) for base64 input.
+    pub fn from_user_signature_base64(s: &str) -> Result<(Self, PublicKey), LedgerError> {
+        Self::from_user_signature(&crate::tx::from_base64(s)?)
+    }
+}
+
 impl AsRef<[u8]> for PublicKey {
     fn as_ref(&self) -> &[u8] {
         &self.0
@@ -150,24 +555,270 @@ impl AsRef<[u8]> for Signature {
     }
 }
 
-impl std::fmt::Display for PublicKey {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Display for PublicKey {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "{}", hex::encode(self.0))
     }
 }
 
-impl std::fmt::Display for Address {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Display for Address {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "0x{}", hex::encode(self.0))
     }
 }
 
-impl std::fmt::Display for Signature {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+/// How addresses are rendered in summaries: `head` and `tail` hex digits
+/// around `ellipsis`, like the device screen, or in full.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AddressFormat {
+    pub head: usize,
+    pub tail: usize,
+    pub ellipsis: &'static str,
+}
+
+impl AddressFormat {
+    /// All 64 digits.
+    pub const FULL: Self = Self {
+        head: 64,
+        tail: 0,
+        ellipsis: "",
+    };
+
+    /// `0x1b36…363e`.
+    pub const SHORT: Self = Self {
+        head: 4,
+        tail: 4,
+        ellipsis: "\u{2026}",
+    };
+
+    /// `0x`-prefixed; shown in full if `head + tail` covers every digit.
+    #[must_use]
+    pub fn apply(&self, bytes: &[u8; 32]) -> String {
+        let digits = hex::encode(bytes);
+        if self.head + self.tail >= digits.len() {
+            return format!("0x{digits}");
+        }
+        format!(
+            "0x{}{}{}",
+            &digits[..self.head],
+            self.ellipsis,
+            &digits[digits.len() - self.tail..]
+        )
+    }
+}
+
+impl Default for AddressFormat {
+    fn default() -> Self {
+        Self::SHORT
+    }
+}
+
+impl Address {
+    /// Truncated form for display, e.g. `0x1b36…363e`.
+    #[must_use]
+    pub fn short(&self) -> String {
+        self.format(AddressFormat::SHORT)
+    }
+
+    #[must_use]
+    pub fn format(&self, format: AddressFormat) -> String {
+        format.apply(&self.0)
+    }
+}
+
+impl From<[u8; 32]> for Address {
+    fn from(bytes: [u8; 32]) -> Self {
+        Self(bytes)
+    }
+}
+
+/// Parses 64 hex digits, with or without a `0x` prefix.
+impl core::str::FromStr for Address {
+    type Err = LedgerError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let digits = s.strip_prefix("0x").unwrap_or(s);
+        if digits.len() != 64 {
+            return Err(LedgerError::InvalidEncoding(format!(
+                "address {s:?} must be 64 hex digits, got {}",
+                digits.len()
+            )));
+        }
+        let mut out = [0u8; 32];
+        hex::decode_to_slice(digits, &mut out)
+            .map_err(|e| LedgerError::InvalidEncoding(format!("address {s:?}: {e}")))?;
+        Ok(Self(out))
+    }
+}
+
+impl TryFrom<&str> for Address {
+    type Error = LedgerError;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl zeroize::Zeroize for Signature {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl core::fmt::Display for Signature {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "{}", hex::encode(self.0))
     }
 }
 
+macro_rules! digest_type {
+    ($(#[$meta:meta])* $name:ident) => {
+        $(#[$meta])*
+        #[derive(Clone, Copy, PartialEq, Eq, Hash, Default)]
+        pub struct $name(pub [u8; 32]);
+
+        impl $name {
+            pub const ZERO: Self = Self([0; 32]);
+
+            #[must_use]
+            pub const fn from_bytes(bytes: [u8; 32]) -> Self {
+                Self(bytes)
+            }
+
+            #[must_use]
+            pub const fn as_bytes(&self) -> &[u8; 32] {
+                &self.0
+            }
+
+            /// BCS encoding: the length prefix followed by the digest.
+            #[must_use]
+            pub fn to_bcs_bytes(&self) -> [u8; 33] {
+                let mut out = [0u8; 33];
+                out[0] = 32;
+                out[1..].copy_from_slice(&self.0);
+                out
+            }
+        }
+
+        impl From<[u8; 32]> for $name {
+            fn from(bytes: [u8; 32]) -> Self {
+                Self(bytes)
+            }
+        }
+
+        impl AsRef<[u8]> for $name {
+            fn as_ref(&self) -> &[u8] {
+                &self.0
+            }
+        }
+
+        impl core::fmt::Display for $name {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                f.write_str(&base58::encode(&self.0))
+            }
+        }
+
+        impl core::fmt::Debug for $name {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                write!(f, concat!(stringify!($name), "({})"), self)
+            }
+        }
+
+        /// Parses the base58 form used by the RPC and explorers.
+        impl core::str::FromStr for $name {
+            type Err = LedgerError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                base58::decode(s)
+                    .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+                    .map(Self)
+                    .ok_or_else(|| {
+                        LedgerError::InvalidEncoding(format!(
+                            "{s:?} is not a base58 32-byte digest"
+                        ))
+                    })
+            }
+        }
+    };
+}
+
+digest_type!(
+    /// Digest of a transaction, as shown by explorers and returned by the
+    /// RPC (`previousTransaction`, `digest` of a transaction block).
+    ///
+    /// BCS encodes digests as a length-prefixed byte vector; the `0x20`
+    /// prefix is added when encoding.
+    TransactionDigest
+);
+
+digest_type!(
+    /// Digest of an object's current version, part of an object reference.
+    ObjectDigest
+);
+
+/// Hash the app shows when it blind-signs a transaction: `Blake2b-256`
+/// of the intent-prefixed bytes, the digest the signature covers.
+///
+/// Displays as `0x` and 64 lowercase hex digits, as on the device, so a
+/// wallet can show it next to the signing prompt for the user to compare.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BlindSigningHash(pub [u8; 32]);
+
+impl BlindSigningHash {
+    /// Hash of `tx` exactly as passed to
+    /// [`LedgerIota::sign_tx`](crate::LedgerIota::sign_tx), intent prefix
+    /// included.
+    #[must_use]
+    pub fn of(tx: &[u8]) -> Self {
+        Self(salted_digest(&[], tx))
+    }
+}
+
+impl core::fmt::Display for BlindSigningHash {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "0x{}", hex::encode(self.0))
+    }
+}
+
+impl core::fmt::Debug for BlindSigningHash {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "BlindSigningHash({self})")
+    }
+}
+
+/// Prefix hashed before the BCS bytes of `TransactionData`.
+const TRANSACTION_DATA_SALT: &[u8] = b"TransactionData::";
+
+/// Prefix hashed before the BCS bytes of an object.
+pub(crate) const OBJECT_SALT: &[u8] = b"Object::";
+
+/// `Blake2b-256(salt || bcs)`, the hashing behind on-chain digests.
+pub(crate) fn salted_digest(salt: &[u8], bcs: &[u8]) -> [u8; 32] {
+    use blake2::digest::consts::U32;
+    use blake2::{Blake2b, Digest as _};
+
+    let mut hasher = Blake2b::<U32>::new();
+    hasher.update(salt);
+    hasher.update(bcs);
+    hasher.finalize().into()
+}
+
+impl TransactionDigest {
+    /// Digest of BCS-encoded `TransactionData` (no intent prefix).
+    #[must_use]
+    pub fn from_tx_data(tx_data: &[u8]) -> Self {
+        Self(salted_digest(TRANSACTION_DATA_SALT, tx_data))
+    }
+
+    /// Digest of intent-prefixed transaction bytes, as passed to
+    /// [`LedgerIota::sign_tx`](crate::LedgerIota::sign_tx). The digest
+    /// doesn't cover the intent, so any app id is accepted.
+    pub fn from_intent_bytes(tx: &[u8]) -> Result<Self, LedgerError> {
+        Ok(Self::from_tx_data(crate::tx::split_intent(tx)?.1))
+    }
+}
+
 #[cfg(feature = "iota-sdk-types")]
 impl From<PublicKey> for iota_sdk_types::Ed25519PublicKey {
     fn from(pk: PublicKey) -> Self {
@@ -189,10 +840,463 @@ impl From<Signature> for iota_sdk_types::Ed25519Signature {
     }
 }
 
+#[cfg(feature = "serde")]
+mod serde_impls {
+    use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::{Address, PublicKey, Signature};
+    use crate::prelude::*;
+    use crate::serde_hex;
+
+    impl Serialize for PublicKey {
+        fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+            serde_hex::array::serialize(&self.0, s)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for PublicKey {
+        fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+            serde_hex::array::deserialize(d).map(Self)
+        }
+    }
+
+    impl Serialize for Address {
+        fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+            serde_hex::array::serialize(&self.0, s)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for Address {
+        fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+            serde_hex::array::deserialize(d).map(Self)
+        }
+    }
+
+    /// Base64; see [`signature_hex`](super::signature_hex) for hex.
+    impl Serialize for Signature {
+        fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+            s.serialize_str(&crate::tx::to_base64(&self.0))
+        }
+    }
+
+    impl<'de> Deserialize<'de> for Signature {
+        fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+            let bytes =
+                crate::tx::from_base64(&String::deserialize(d)?).map_err(de::Error::custom)?;
+            bytes
+                .as_slice()
+                .try_into()
+                .map(Self)
+                .map_err(|_| de::Error::invalid_length(bytes.len(), &"64 bytes"))
+        }
+    }
+}
+
+/// Hex (`0x`-prefixed) instead of base64 for a [`Signature`] field:
+/// `#[serde(with = "ledger_iota::types::signature_hex")]`.
+#[cfg(feature = "serde")]
+pub mod signature_hex {
+    use serde::{Deserializer, Serializer};
+
+    use super::Signature;
+
+    pub fn serialize<S: Serializer>(sig: &Signature, s: S) -> Result<S::Ok, S::Error> {
+        crate::serde_hex::array::serialize(&sig.0, s)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Signature, D::Error> {
+        crate::serde_hex::array::deserialize(d).map(Signature)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_formats() {
+        use serde_json::json;
+
+        let addr = Address([0xAB; 32]);
+        let v = serde_json::to_value(addr).unwrap();
+        assert_eq!(v, json!(format!("0x{}", "ab".repeat(32))));
+        assert_eq!(serde_json::from_value::<Address>(v).unwrap(), addr);
+        let bare = json!("cd".repeat(32));
+        assert_eq!(
+            serde_json::from_value::<PublicKey>(bare).unwrap(),
+            PublicKey([0xCD; 32])
+        );
+        assert!(serde_json::from_value::<Address>(json!("0xabcd")).is_err());
+
+        let sig = Signature([7; 64]);
+        let v = serde_json::to_value(&sig).unwrap();
+        assert_eq!(v, json!(crate::tx::to_base64(&[7; 64])));
+        assert_eq!(serde_json::from_value::<Signature>(v).unwrap(), sig);
+        assert!(serde_json::from_value::<Signature>(json!("AAAA")).is_err());
+
+        #[derive(serde::Serialize, serde::Deserialize)]
+        struct Hexed(#[serde(with = "signature_hex")] Signature);
+        let v = serde_json::to_value(Hexed(sig.clone())).unwrap();
+        assert_eq!(v, json!(format!("0x{}", "07".repeat(64))));
+        assert_eq!(serde_json::from_value::<Hexed>(v).unwrap().0, sig);
+    }
+
+    #[test]
+    fn pubkey_to_address() {
+        // Derived independently with hashlib.blake2b(b"\x00" + pk, digest_size=32).
+        let pk = PublicKey([0xAA; 32]);
+        assert_eq!(
+            hex::encode(pk.to_address().0),
+            "9f89215dc3a091bc288a2ddfb1860f0cb9efc4d39a2bb728944f741a650a7fb1"
+        );
+        assert_ne!(PublicKey([0xAB; 32]).to_address(), pk.to_address());
+    }
+
+    #[cfg(feature = "verify")]
+    #[test]
+    fn pubkey_point_validation() {
+        let mut base = [0x66; 32];
+        base[0] = 0x58;
+        assert!(PublicKey(base).is_valid_point());
+        assert!(PublicKey([0xAA; 32]).is_valid_point());
+        // Not the y-coordinate of any point.
+        assert!(!PublicKey([0x02; 32]).is_valid_point());
+        // y = 1, the identity.
+        let mut identity = [0; 32];
+        identity[0] = 1;
+        assert!(!PublicKey(identity).is_valid_point());
+        // y = p + 3, a valid point under a non-canonical encoding.
+        let mut non_canonical = [0xFF; 32];
+        non_canonical[0] = 0xF0;
+        non_canonical[31] = 0x7F;
+        assert!(!PublicKey(non_canonical).is_valid_point());
+    }
+
+    #[test]
+    fn user_signature_round_trip() {
+        let sig = Signature([0x11; 64]);
+        let pk = PublicKey([0x22; 32]);
+        let bytes = sig.to_user_signature(&pk);
+        assert_eq!(bytes[0], ED25519_FLAG);
+        assert_eq!(&bytes[1..65], &[0x11; 64]);
+        assert_eq!(&bytes[65..], &[0x22; 32]);
+        assert_eq!(
+            Signature::from_user_signature(&bytes).unwrap(),
+            (sig.clone(), pk)
+        );
+
+        let b64 = sig.to_user_signature_base64(&pk);
+        assert_eq!(b64.len(), 132);
+        assert_eq!(
+            Signature::from_user_signature_base64(&b64).unwrap(),
+            (sig, pk)
+        );
+    }
+
+    #[test]
+    fn user_signature_rejects_bad_input() {
+        let mut bytes = Signature([0; 64]).to_user_signature(&PublicKey([0; 32]));
+        assert!(Signature::from_user_signature(&bytes[..96]).is_err());
+        bytes[0] = 0x01; // secp256k1
+        assert!(matches!(
+            Signature::from_user_signature(&bytes),
+            Err(LedgerError::InvalidEncoding(_))
+        ));
+        assert!(Signature::from_user_signature_base64("not base64!").is_err());
+    }
+
+    #[test]
+    fn address_short() {
+        let addr: Address = "0x1b3600000000000000000000000000000000000000000000000000000000363e"
+            .parse()
+            .unwrap();
+        assert_eq!(addr.short(), "0x1b36\u{2026}363e");
+        assert_eq!(addr.format(AddressFormat::FULL), addr.to_string());
+        let custom = AddressFormat {
+            head: 6,
+            tail: 2,
+            ellipsis: "..",
+        };
+        assert_eq!(addr.format(custom), "0x1b3600..3e");
+        let wide = AddressFormat {
+            head: 40,
+            tail: 30,
+            ellipsis: "..",
+        };
+        assert_eq!(addr.format(wide), addr.to_string());
+    }
+
+    #[test]
+    fn address_from_str() {
+        let hex = "ab".repeat(32);
+        let addr: Address = hex.parse().unwrap();
+        assert_eq!(addr, Address([0xAB; 32]));
+        assert_eq!(
+            Address::try_from(format!("0x{hex}").as_str()).unwrap(),
+            addr
+        );
+        assert_eq!(addr.to_string().parse::<Address>().unwrap(), addr);
+        assert!("0xabcd".parse::<Address>().is_err());
+        assert!(format!("{hex}00").parse::<Address>().is_err());
+        assert!(format!("0x{}", "zz".repeat(32)).parse::<Address>().is_err());
+    }
+
+    #[test]
+    fn transaction_digest() {
+        // hashlib.blake2b(b"TransactionData::" + bytes([1, 2, 3]), digest_size=32)
+        let d = TransactionDigest::from_tx_data(&[1, 2, 3]);
+        assert_eq!(
+            d.to_string(),
+            "45BcYgib9Z6jfxixeAWNGjH8JfAx8NzrDnhRAnn7FnpG"
+        );
+        assert_eq!(
+            TransactionDigest::from_intent_bytes(&[0, 0, 0, 1, 2, 3]).unwrap(),
+            d
+        );
+        // Forks' app ids aren't part of the digest.
+        assert_eq!(
+            TransactionDigest::from_intent_bytes(&[0, 0, 7, 1, 2, 3]).unwrap(),
+            d
+        );
+        assert!(TransactionDigest::from_intent_bytes(&[3, 0, 0, 1, 2, 3]).is_err());
+        assert!(TransactionDigest::from_intent_bytes(&[0, 0]).is_err());
+    }
+
+    #[test]
+    fn blind_signing_hash() {
+        // hashlib.blake2b(bytes([0, 0, 0, 1, 2, 3]), digest_size=32)
+        let hash = BlindSigningHash::of(&[0, 0, 0, 1, 2, 3]);
+        assert_eq!(
+            hash.to_string(),
+            "0xee461b814937755e5cd5ab7d72d521d48bc4150c1c2c52cca6d5c6666afaec9d"
+        );
+        assert_eq!(format!("{hash:?}"), format!("BlindSigningHash({hash})"));
+    }
+
+    #[test]
+    fn digest_debug_names_the_type() {
+        let obj = ObjectDigest([4; 32]);
+        assert_eq!(format!("{obj:?}"), format!("ObjectDigest({obj})"));
+    }
+
+    #[test]
+    fn digest_base58() {
+        let d: TransactionDigest = "JEKNVnkbo3jma5nREBBJCDoXFVeKkD56V3xKrvRmWxFG"
+            .parse()
+            .unwrap();
+        assert_eq!(d, TransactionDigest::from_bytes([0xFF; 32]));
+        assert_eq!(
+            d.to_string(),
+            "JEKNVnkbo3jma5nREBBJCDoXFVeKkD56V3xKrvRmWxFG"
+        );
+        assert_eq!(TransactionDigest::ZERO.to_string(), "1".repeat(32));
+        assert_eq!(d.to_bcs_bytes()[0], 32);
+        assert_eq!(&d.to_bcs_bytes()[1..], d.as_bytes());
+
+        assert!("2g".parse::<ObjectDigest>().is_err()); // one byte
+        assert!("0OIl".parse::<ObjectDigest>().is_err());
+    }
+
+    #[test]
+    fn parse_path_strings() {
+        let expected = Bip32Path::iota(0, 0, 5);
+        for s in [
+            "m/44'/4218'/0'/0'/5'",
+            "44'/4218'/0'/0'/5'",
+            "m/44h/4218h/0h/0h/5h",
+            " m/44H/4218H/0'/0h/5' ",
+        ] {
+            assert_eq!(s.parse::<Bip32Path>().unwrap(), expected, "{s}");
+        }
+        let testnet: Bip32Path = "m/44'/1'/2'/0'/3'".parse().unwrap();
+        assert_eq!(testnet, Bip32Path::testnet(2, 0, 3));
+        assert_eq!(expected.to_string().parse::<Bip32Path>().unwrap(), expected);
+    }
+
+    #[test]
+    fn parse_path_rejects_invalid() {
+        for s in [
+            "",
+            "m/",
+            "m/44'/4218'/0'/0'/x'",
+            "m/44'/4218'//0'",
+            "m/44'/4218'/0'/0'/5",     // not hardened
+            "m/44'/60'/0'/0'/0'",      // wrong coin type
+            "m/44'/4218'/2147483648'", // already has the hardened bit
+            "m/44''/4218'/0'",
+        ] {
+            assert!(
+                matches!(s.parse::<Bip32Path>(), Err(LedgerError::InvalidPath(_))),
+                "{s:?}"
+            );
+        }
+    }
+
+    fn app(major: u8, minor: u8, patch: u8) -> AppVersion {
+        AppVersion::new(major, minor, patch, "IOTA")
+    }
+
+    #[test]
+    fn app_version_order() {
+        assert!(app(1, 0, 0) > app(0, 9, 9));
+        assert!(app(0, 10, 0) > app(0, 9, 255));
+        assert_eq!(app(1, 2, 3).cmp(&app(1, 2, 3)), core::cmp::Ordering::Equal);
+        assert_eq!(
+            [app(1, 0, 0), app(0, 9, 1)].iter().max(),
+            Some(&app(1, 0, 0))
+        );
+        assert!(app(1, 0, 0).at_least(0, 9, 0));
+        assert!(!app(0, 8, 9).at_least(0, 9, 0));
+    }
+
+    #[test]
+    fn app_version_requirements() {
+        let v = app(1, 2, 3);
+        assert!(v.satisfies(">=1.0, <2").unwrap());
+        assert!(v.satisfies("1.2.3").unwrap());
+        assert!(v.satisfies("=1.2.3").unwrap());
+        assert!(v.satisfies("> 1.2, <= 1.2.3").unwrap());
+        assert!(!v.satisfies(">=1.0, <1.2").unwrap());
+        assert!(!v.satisfies("1.2").unwrap());
+        for bad in ["", ">=", "1.2.3.4", ">=x", "1.256", "~1.2"] {
+            assert!(
+                matches!(v.satisfies(bad), Err(LedgerError::InvalidVersionReq(_))),
+                "{bad:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn depth_limit() {
+        let mut components = Bip32Path::iota(0, 0, 0).components().to_vec();
+        components.resize(MAX_PATH_DEPTH, HARDENED);
+        assert!(Bip32Path::new(components.clone()).is_ok());
+
+        components.push(HARDENED);
+        let err = Bip32Path::new(components).unwrap_err();
+        assert!(err.to_string().contains("at most 10"), "{err}");
+
+        let deep = format!("m/44'/4218'{}", "/0'".repeat(300));
+        assert!(deep.parse::<Bip32Path>().is_err());
+    }
+
+    #[test]
+    fn const_components() {
+        const ACCOUNT: [u32; 3] = [hardened(44), hardened(4218), hardened(0)];
+        assert_eq!(
+            Bip32Path::new(ACCOUNT.to_vec()).unwrap().to_string(),
+            "m/44'/4218'/0'"
+        );
+        assert!(is_hardened(ACCOUNT[2]));
+        assert!(!is_hardened(44));
+    }
+
+    #[test]
+    fn core_types_as_map_keys() {
+        use std::collections::{BTreeSet, HashMap, HashSet};
+
+        let mut labels = HashMap::new();
+        labels.insert(Address([1; 32]), "treasury");
+        assert_eq!(labels[&Address([1; 32])], "treasury");
+
+        let keys: BTreeSet<_> = [PublicKey([2; 32]), PublicKey([1; 32])].into();
+        assert_eq!(keys.first(), Some(&PublicKey([1; 32])));
+
+        let signatures: HashSet<_> = [Signature([3; 64]), Signature([3; 64])].into();
+        assert_eq!(signatures.len(), 1);
+    }
+
+    #[test]
+    fn normalize() {
+        assert_eq!(
+            Bip32Path::normalize("m/44H/4218h/0'/0'/3'").unwrap(),
+            "m/44'/4218'/0'/0'/3'"
+        );
+        assert_eq!(Bip32Path::normalize("44'/1'/2'").unwrap(), "m/44'/1'/2'");
+        assert!(Bip32Path::normalize("m/44'/60'/0'").is_err());
+    }
+
+    #[test]
+    fn account_split_and_prefix() {
+        let path = Bip32Path::iota(2, 0, 7);
+        let account: Bip32Path = "m/44'/4218'/2'".parse().unwrap();
+        assert_eq!(path.account_path(), Some(account.clone()));
+        assert_eq!(path.address_components(), &[HARDENED, 7 | HARDENED]);
+        assert!(path.starts_with(&account));
+        assert!(path.starts_with(&path));
+        assert!(!account.starts_with(&path));
+        assert!(!Bip32Path::iota(3, 0, 7).starts_with(&account));
+        assert!(!Bip32Path::testnet(2, 0, 7).starts_with(&account));
+
+        assert_eq!(account.address_components(), &[] as &[u32]);
+        let short: Bip32Path = "m/44'/4218'".parse().unwrap();
+        assert_eq!(short.account_path(), None);
+        assert!(short.address_components().is_empty());
+    }
+
+    #[test]
+    fn for_coin() {
+        assert_eq!(
+            Bip32Path::for_coin(CoinType::IotaMainnet, 1, 0, 2).unwrap(),
+            Bip32Path::iota(1, 0, 2)
+        );
+        assert_eq!(
+            Bip32Path::for_coin(CoinType::Testnet, 1, 0, 2).unwrap(),
+            Bip32Path::testnet(1, 0, 2)
+        );
+        let shimmer = Bip32Path::for_coin(CoinType::Custom(4219), 0, 0, 0).unwrap();
+        assert_eq!(shimmer.to_string(), "m/44'/4219'/0'/0'/0'");
+        assert_eq!(shimmer.coin_type(), Some(CoinType::Custom(4219)));
+        assert_eq!(
+            Bip32Path::iota(0, 0, 0).coin_type(),
+            Some(CoinType::IotaMainnet)
+        );
+        assert!(Bip32Path::for_coin(CoinType::Custom(HARDENED), 0, 0, 0).is_err());
+        assert!(Bip32Path::for_coin(CoinType::Testnet, HARDENED, 0, 0).is_err());
+        assert_eq!(CoinType::from_value(4218), CoinType::IotaMainnet);
+        assert_eq!(CoinType::Testnet.to_string(), "1'");
+    }
+
+    #[test]
+    fn custom_coin_type() {
+        let shimmer = Bip32Path::custom_coin(4219, 0, 0, 1).unwrap();
+        assert_eq!(shimmer.to_string(), "m/44'/4219'/0'/0'/1'");
+        assert_eq!(
+            Bip32Path::custom_coin(4218, 2, 0, 7).unwrap(),
+            Bip32Path::iota(2, 0, 7)
+        );
+        assert!(Bip32Path::custom_coin(HARDENED, 0, 0, 0).is_err());
+        assert!(Bip32Path::custom_coin(4219, 0, 0, u32::MAX).is_err());
+
+        // Strict validation stays the default; fork paths parse back with
+        // an explicit coin type.
+        assert!(Bip32Path::new(shimmer.components().to_vec()).is_err());
+        assert!(shimmer.to_string().parse::<Bip32Path>().is_err());
+        assert_eq!(
+            Bip32Path::parse_for_coin(&shimmer.to_string(), CoinType::Custom(4219)).unwrap(),
+            shimmer
+        );
+        assert_eq!(
+            Bip32Path::parse_for_coin("m/44'/4218'/0'/0'/1'", CoinType::Custom(4219)).unwrap(),
+            Bip32Path::iota(0, 0, 1)
+        );
+        assert!(Bip32Path::parse_for_coin(&shimmer.to_string(), CoinType::Custom(9999)).is_err());
+        assert!(Bip32Path::parse_for_coin("m/44'/4219'/0/0'/1'", CoinType::Custom(4219)).is_err());
+    }
+
+    #[test]
+    fn index_range() {
+        let paths: Vec<_> = Bip32Path::iter_indices(1, 0, 0..20).collect();
+        assert_eq!(paths.len(), 20);
+        assert_eq!(paths[0], Bip32Path::iota(1, 0, 0));
+        assert_eq!(paths[19].to_string(), "m/44'/4218'/1'/0'/19'");
+        assert_eq!(
+            Bip32Path::iter_indices(0, 0, HARDENED - 1..u32::MAX).count(),
+            1
+        );
+    }
+
     #[test]
     fn iota_mainnet_path() {
         let path = Bip32Path::iota(0, 0, 0);
@@ -239,4 +1343,22 @@ mod tests {
         let result = Bip32Path::new(vec![44 | 0x80000000]);
         assert!(result.is_err());
     }
+
+    #[test]
+    fn path_deserialize_rejects_bad_lengths() {
+        assert!(Bip32Path::deserialize(&[]).is_err());
+        assert!(Bip32Path::deserialize(&[2, 0, 0, 0, 0x80]).is_err());
+        assert!(Bip32Path::deserialize(&[0, 1]).is_err());
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn path_serialize_round_trips(components in proptest::collection::vec(proptest::num::u32::ANY, 0..=MAX_PATH_DEPTH)) {
+            let path = Bip32Path(components);
+            let bytes = path.serialize();
+            let decoded = Bip32Path::deserialize(&bytes).unwrap();
+            proptest::prop_assert_eq!(&decoded, &path);
+            proptest::prop_assert_eq!(decoded.serialize(), bytes);
+        }
+    }
 }
@@ -0,0 +1,79 @@
+//! Wiping of transient buffers that held transaction bytes, messages or
+//! signatures.
+//!
+//! With the `zeroize` feature, [`Transient`] and the `Drop` impls of the
+//! block types overwrite their bytes when dropped; without it,
+//! [`Wipe::wipe`] is a no-op, so call sites don't need `cfg`s.
+
+use std::ops::{Deref, DerefMut};
+
+pub(crate) trait Wipe {
+    fn wipe(&mut self);
+}
+
+impl Wipe for Vec<u8> {
+    #[inline]
+    fn wipe(&mut self) {
+        // Clears the whole allocation, not just `len` bytes.
+        #[cfg(feature = "zeroize")]
+        zeroize::Zeroize::zeroize(self);
+    }
+}
+
+impl Wipe for Vec<Vec<u8>> {
+    #[inline]
+    fn wipe(&mut self) {
+        self.iter_mut().for_each(Wipe::wipe);
+    }
+}
+
+/// Wipes the wrapped buffer on drop, including on early return.
+pub(crate) struct Transient<T: Wipe>(pub T);
+
+impl<T: Wipe> Transient<T> {
+    /// Moves the buffer out without wiping it, for returning to the caller.
+    pub(crate) fn into_inner(mut self) -> T
+    where
+        T: Default,
+    {
+        std::mem::take(&mut self.0)
+    }
+}
+
+impl<T: Wipe> Deref for Transient<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: Wipe> DerefMut for Transient<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T: Wipe> Drop for Transient<T> {
+    fn drop(&mut self) {
+        self.0.wipe();
+    }
+}
+
+#[cfg(all(test, feature = "zeroize"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wipe_clears_nested_buffers() {
+        let mut params = vec![vec![0xAA; 16], vec![0xBB; 4]];
+        params.wipe();
+        assert!(params.iter().all(Vec::is_empty));
+    }
+
+    #[test]
+    fn into_inner_skips_wipe() {
+        let t = Transient(vec![1, 2, 3]);
+        assert_eq!(t.into_inner(), [1, 2, 3]);
+    }
+}
@@ -0,0 +1,418 @@
+//! Protocol conformance suite: a fixed matrix of operations run against an
+//! IOTA app, ending in a pass/fail report.
+//!
+//! ```sh
+//! # software device (feature `testing`)
+//! cargo test --features testing --test conformance
+//! # Speculos, see `speculos_transport` for the environment
+//! CONFORMANCE_TARGET=speculos cargo test --features tcp --test conformance
+//! # a connected Ledger; approve the signing prompts as they come up
+//! CONFORMANCE_TARGET=hid CONFORMANCE_INTERACTIVE=1 cargo test --test conformance
+//! ```
+//!
+//! On a real app, rejection flows need someone at the device and only run
+//! with `CONFORMANCE_INTERACTIVE=1`. `CONFORMANCE_REPORT=<file>` also
+//! writes the report to a file. The process exits non-zero if any case
+//! fails.
+
+use std::fmt::Write as _;
+use std::process::ExitCode;
+#[cfg(feature = "testing")]
+use std::sync::Arc;
+
+use ledger_iota::tx::{build_batch_transfer_tx, build_transfer_tx};
+use ledger_iota::{
+    Amount, Bip32Path, GasCoinRef, LedgerError, LedgerIota, ObjectData, Owner, Payment,
+    TransactionDigest,
+};
+
+enum Outcome {
+    Pass,
+    Fail(String),
+    Skip(String),
+}
+
+impl From<Result<(), String>> for Outcome {
+    fn from(result: Result<(), String>) -> Self {
+        match result {
+            Ok(()) => Outcome::Pass,
+            Err(e) => Outcome::Fail(e),
+        }
+    }
+}
+
+/// The app under test and how to make it decline prompts.
+struct Target {
+    name: String,
+    ledger: LedgerIota,
+    #[cfg(feature = "testing")]
+    simulated: Option<Arc<ledger_iota::testing::SimulatedDevice>>,
+    interactive: bool,
+}
+
+impl Target {
+    fn connect() -> Result<Option<Self>, String> {
+        let interactive = std::env::var_os("CONFORMANCE_INTERACTIVE").is_some();
+        let target = std::env::var("CONFORMANCE_TARGET").unwrap_or_default();
+        let ledger = match target.as_str() {
+            #[cfg(feature = "testing")]
+            "" | "sim" => {
+                let device = Arc::new(ledger_iota::testing::SimulatedDevice::speculos());
+                return Ok(Some(Self {
+                    name: "simulated device".into(),
+                    ledger: LedgerIota::with_transport(Box::new(device.clone())),
+                    simulated: Some(device),
+                    interactive: true,
+                }));
+            }
+            #[cfg(not(feature = "testing"))]
+            "" => return Ok(None),
+            #[cfg(feature = "tcp")]
+            "speculos" => {
+                let host = std::env::var("LEDGER_TCP_HOST").unwrap_or_else(|_| "127.0.0.1".into());
+                let port = std::env::var("LEDGER_TCP_PORT")
+                    .ok()
+                    .and_then(|p| p.parse().ok())
+                    .unwrap_or(9999);
+                LedgerIota::new(&ledger_iota::TransportType::TCP(host, port))
+            }
+            #[cfg(feature = "hid")]
+            "hid" => LedgerIota::new(&ledger_iota::TransportType::NativeHID),
+            other => return Err(format!("unsupported CONFORMANCE_TARGET {other:?}")),
+        };
+        let ledger = ledger.map_err(|e| format!("connecting to {target}: {e}"))?;
+        Ok(Some(Self {
+            name: target,
+            ledger,
+            #[cfg(feature = "testing")]
+            simulated: None,
+            interactive,
+        }))
+    }
+
+    /// Ask for the next prompt to be approved (`reject == false`) or
+    /// declined. `false` if nobody can answer it.
+    fn prompt(&self, reject: bool, what: &str) -> bool {
+        #[cfg(feature = "testing")]
+        if let Some(device) = &self.simulated {
+            device.set_reject(reject);
+            return true;
+        }
+        if !self.interactive {
+            return false;
+        }
+        let action = if reject { "REJECT" } else { "approve" };
+        eprintln!(">>> {action} on the device: {what}");
+        true
+    }
+}
+
+fn path(account: u32, change: u32, index: u32) -> Bip32Path {
+    Bip32Path::iota(account, change, index)
+}
+
+fn bytes32(bytes: &[u8]) -> [u8; 32] {
+    bytes.try_into().expect("32-byte address")
+}
+
+fn sender(target: &Target) -> Result<[u8; 32], String> {
+    let (_, address) = target
+        .ledger
+        .get_pubkey(&path(0, 0, 0))
+        .map_err(|e| e.to_string())?;
+    Ok(bytes32(address.as_ref()))
+}
+
+/// A gas coin owned by `owner` and a reference to it.
+fn gas_coin(owner: [u8; 32]) -> (ObjectData, GasCoinRef) {
+    let mut contents = vec![0xC0; 32];
+    contents.extend_from_slice(&10_000_000_000u64.to_le_bytes());
+    let coin = ObjectData::gas_coin(
+        7,
+        contents.clone(),
+        Owner::AddressOwner(owner),
+        TransactionDigest::ZERO,
+        0,
+    );
+    let gas = GasCoinRef {
+        object_id: bytes32(&contents[..32]),
+        version: 7,
+        digest: coin.digest().unwrap(),
+    };
+    (coin, gas)
+}
+
+fn transfer(target: &Target) -> Result<(Vec<u8>, ObjectData), String> {
+    let sender = sender(target)?;
+    let (coin, gas) = gas_coin(sender);
+    let tx = build_transfer_tx(
+        &ledger_iota::Address(sender),
+        &ledger_iota::Address([0xB0; 32]),
+        Amount::from_nanos(1_000),
+        &gas,
+        Amount::from_nanos(10_000_000),
+        1_000,
+    )
+    .map_err(|e| e.to_string())?;
+    Ok((tx, coin))
+}
+
+fn version(target: &Target) -> Outcome {
+    match target.ledger.get_version() {
+        Ok(v) if v.name.eq_ignore_ascii_case("iota") => Outcome::Pass,
+        Ok(v) => Outcome::Fail(format!("app name is {:?}", v.name)),
+        Err(e) => Outcome::Fail(e.to_string()),
+    }
+}
+
+fn pubkeys_many_paths(target: &Target) -> Outcome {
+    let mut paths = Vec::new();
+    for account in 0..3 {
+        for change in 0..2 {
+            for index in 0..3 {
+                paths.push(Bip32Path::iota(account, change, index));
+                paths.push(Bip32Path::testnet(account, change, index));
+            }
+        }
+    }
+    let keys = match target.ledger.get_pubkeys(paths.iter().cloned()) {
+        Ok(keys) => keys,
+        Err(e) => return Outcome::Fail(e.to_string()),
+    };
+    let mut addresses: Vec<[u8; 32]> = keys.iter().map(|(_, a)| bytes32(a.as_ref())).collect();
+    addresses.sort_unstable();
+    addresses.dedup();
+    if addresses.len() != paths.len() {
+        return Outcome::Fail(format!(
+            "{} paths gave {} distinct addresses",
+            paths.len(),
+            addresses.len()
+        ));
+    }
+    Outcome::Pass
+}
+
+fn pubkeys_deterministic(target: &Target) -> Outcome {
+    let path = path(1, 0, 5);
+    let first = target.ledger.get_pubkey(&path);
+    let second = target.ledger.get_pubkey(&path);
+    match (first, second) {
+        (Ok(a), Ok(b)) if a == b => Outcome::Pass,
+        (Ok(_), Ok(_)) => Outcome::Fail("same path, different keys".into()),
+        (Err(e), _) | (_, Err(e)) => Outcome::Fail(e.to_string()),
+    }
+}
+
+fn sign_message(target: &Target, len: usize) -> Outcome {
+    if !target.prompt(false, &format!("sign a {len}-byte message")) {
+        return Outcome::Skip("needs CONFORMANCE_INTERACTIVE=1".into());
+    }
+    target
+        .ledger
+        .sign_message(&vec![b'm'; len], &path(0, 0, 0))
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+        .into()
+}
+
+fn message_over_limit(target: &Target) -> Outcome {
+    let len = target.ledger.limits().message + 1;
+    match target.ledger.sign_message(&vec![0; len], &path(0, 0, 0)) {
+        Err(LedgerError::MessageTooLarge { .. }) => Outcome::Pass,
+        Err(e) => Outcome::Fail(format!("expected MessageTooLarge, got {e}")),
+        Ok(_) => Outcome::Fail("oversized message was signed".into()),
+    }
+}
+
+fn blind_sign(target: &Target) -> Outcome {
+    let (tx, _) = match transfer(target) {
+        Ok(t) => t,
+        Err(e) => return Outcome::Fail(e),
+    };
+    if !target.prompt(false, "blind-sign a transfer") {
+        return Outcome::Skip("needs CONFORMANCE_INTERACTIVE=1".into());
+    }
+    match target.ledger.sign_tx(&tx, &path(0, 0, 0), None) {
+        Ok(_) => Outcome::Pass,
+        Err(LedgerError::BlindSigningDisabled | LedgerError::ClearSigningDataMissing { .. }) => {
+            Outcome::Skip("blind signing is disabled in the app settings".into())
+        }
+        Err(e) => Outcome::Fail(e.to_string()),
+    }
+}
+
+fn clear_sign(target: &Target) -> Outcome {
+    let (tx, coin) = match transfer(target) {
+        Ok(t) => t,
+        Err(e) => return Outcome::Fail(e),
+    };
+    if !target.prompt(false, "clear-sign a transfer of 0.000001 IOTA") {
+        return Outcome::Skip("needs CONFORMANCE_INTERACTIVE=1".into());
+    }
+    target
+        .ledger
+        .sign_tx(&tx, &path(0, 0, 0), Some(&[coin]))
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+        .into()
+}
+
+/// A batch transfer filling about three quarters of the transaction limit.
+fn large_tx(target: &Target) -> Outcome {
+    let sender = match sender(target) {
+        Ok(s) => s,
+        Err(e) => return Outcome::Fail(e),
+    };
+    let (coin, gas) = gas_coin(sender);
+    let build = |n: usize| {
+        let payments: Vec<_> = (0..n)
+            .map(|i| Payment {
+                recipient: ledger_iota::Address([(i % 255) as u8 + 1; 32]),
+                amount: Amount::from_nanos(1_000 + i as u64),
+            })
+            .collect();
+        build_batch_transfer_tx(
+            &ledger_iota::Address(sender),
+            &payments,
+            std::slice::from_ref(&gas),
+            Amount::from_nanos(50_000_000),
+            1_000,
+        )
+    };
+    let (one, two) = match (build(1), build(2)) {
+        (Ok(one), Ok(two)) => (one.len(), two.len()),
+        (Err(e), _) | (_, Err(e)) => return Outcome::Fail(e.to_string()),
+    };
+    let Some(limit) = target.ledger.limits().transaction else {
+        return Outcome::Skip("no transaction limit set".into());
+    };
+    let count = (limit * 3 / 4 - one) / (two - one);
+    let tx = match build(count) {
+        Ok(tx) => tx,
+        Err(e) => return Outcome::Fail(e.to_string()),
+    };
+    if !target.prompt(false, &format!("sign a transfer to {count} recipients")) {
+        return Outcome::Skip("needs CONFORMANCE_INTERACTIVE=1".into());
+    }
+    match target.ledger.sign_tx(&tx, &path(0, 0, 0), Some(&[coin])) {
+        Ok(_) => Outcome::Pass,
+        Err(LedgerError::BlindSigningDisabled | LedgerError::ClearSigningDataMissing { .. }) => {
+            Outcome::Skip("app needs blind signing for this transaction".into())
+        }
+        Err(e) => Outcome::Fail(format!("{} bytes: {e}", tx.len())),
+    }
+}
+
+fn tx_over_limit(target: &Target) -> Outcome {
+    let Some(limit) = target.ledger.limits().transaction else {
+        return Outcome::Skip("no transaction limit set".into());
+    };
+    let tx = vec![0; limit + 1];
+    match target.ledger.sign_tx(&tx, &path(0, 0, 0), None) {
+        Err(LedgerError::PayloadTooLarge { .. }) => Outcome::Pass,
+        Err(e) => Outcome::Fail(format!("expected PayloadTooLarge, got {e}")),
+        Ok(_) => Outcome::Fail("oversized transaction was signed".into()),
+    }
+}
+
+fn reject_message(target: &Target) -> Outcome {
+    if !target.prompt(true, "sign a message") {
+        return Outcome::Skip("needs CONFORMANCE_INTERACTIVE=1".into());
+    }
+    match target.ledger.sign_message(b"reject me", &path(0, 0, 0)) {
+        Err(LedgerError::UserRejected { .. }) => Outcome::Pass,
+        Err(e) => Outcome::Fail(format!("expected UserRejected, got {e}")),
+        Ok(_) => Outcome::Fail("rejected message was signed".into()),
+    }
+}
+
+fn reject_tx(target: &Target) -> Outcome {
+    let (tx, coin) = match transfer(target) {
+        Ok(t) => t,
+        Err(e) => return Outcome::Fail(e),
+    };
+    if !target.prompt(true, "sign a transfer") {
+        return Outcome::Skip("needs CONFORMANCE_INTERACTIVE=1".into());
+    }
+    match target.ledger.sign_tx(&tx, &path(0, 0, 0), Some(&[coin])) {
+        Err(LedgerError::UserRejected { .. }) => Outcome::Pass,
+        Err(e) => Outcome::Fail(format!("expected UserRejected, got {e}")),
+        Ok(_) => Outcome::Fail("rejected transaction was signed".into()),
+    }
+}
+
+type Case = (&'static str, fn(&Target) -> Outcome);
+
+const CASES: &[Case] = &[
+    ("version", version),
+    ("pubkeys_many_paths", pubkeys_many_paths),
+    ("pubkeys_deterministic", pubkeys_deterministic),
+    ("sign_message_small", |t| sign_message(t, 32)),
+    ("sign_message_at_limit", |t| {
+        sign_message(t, t.ledger.limits().message)
+    }),
+    ("sign_message_over_limit", message_over_limit),
+    ("blind_sign", blind_sign),
+    ("clear_sign", clear_sign),
+    ("large_tx", large_tx),
+    ("tx_over_limit", tx_over_limit),
+    ("reject_message", reject_message),
+    ("reject_tx", reject_tx),
+];
+
+fn main() -> ExitCode {
+    let target = match Target::connect() {
+        Ok(Some(target)) => target,
+        Ok(None) => {
+            println!("conformance: skipped (set CONFORMANCE_TARGET or enable `testing`)");
+            return ExitCode::SUCCESS;
+        }
+        Err(e) => {
+            eprintln!("conformance: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut report = String::new();
+    let app = match target.ledger.get_version() {
+        Ok(v) => v.to_string(),
+        Err(e) => format!("unknown ({e})"),
+    };
+    let header = format!("conformance: {app} on {}", target.name);
+    println!("{header}");
+    let _ = writeln!(report, "{header}");
+    let (mut passed, mut failed, mut skipped) = (0, 0, 0);
+    for (name, case) in CASES {
+        let line = match case(&target) {
+            Outcome::Pass => {
+                passed += 1;
+                format!("PASS {name}")
+            }
+            Outcome::Fail(why) => {
+                failed += 1;
+                format!("FAIL {name}: {why}")
+            }
+            Outcome::Skip(why) => {
+                skipped += 1;
+                format!("SKIP {name}: {why}")
+            }
+        };
+        println!("{line}");
+        let _ = writeln!(report, "{line}");
+    }
+    let summary = format!("{passed} passed, {failed} failed, {skipped} skipped");
+    println!("{summary}");
+    let _ = writeln!(report, "{summary}");
+
+    if let Some(file) = std::env::var_os("CONFORMANCE_REPORT") {
+        if let Err(e) = std::fs::write(&file, report) {
+            eprintln!("conformance: writing {}: {e}", file.to_string_lossy());
+            return ExitCode::FAILURE;
+        }
+    }
+    if failed > 0 {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
@@ -0,0 +1,73 @@
+//! Differential tests against the app's reference Python client — requires
+//! Speculos and the client (see `testing::differential`).
+//!
+//! ```sh
+//! podman compose up -d
+//! IOTA_CLIENT_PATH=/path/to/app-iota/tests \
+//!     cargo test --features tcp,testing --test differential -- --ignored
+//! ```
+
+#![cfg(all(feature = "tcp", feature = "testing"))]
+
+use ledger_iota::testing::differential::{Harness, Operation};
+use ledger_iota::testing::speculos::ApprovalPolicy;
+use ledger_iota::testing::vectors;
+use ledger_iota::Bip32Path;
+
+fn assert_same(harness: &Harness, op: Operation, policy: Option<&ApprovalPolicy>) {
+    let comparison = harness.compare(&op, policy).unwrap();
+    if let Some(divergence) = comparison.divergence() {
+        panic!(
+            "{:?}: {divergence}\n--- ours ---\n{}\n--- reference ---\n{}",
+            op.args(),
+            comparison.ours.transcript.join("\n"),
+            comparison.reference.transcript.join("\n")
+        );
+    }
+}
+
+#[test]
+#[ignore = "requires Speculos and the Python client"]
+fn version_and_pubkeys() {
+    let harness = Harness::from_env();
+    assert_same(&harness, Operation::Version, None);
+    for path in [
+        Bip32Path::iota(0, 0, 0),
+        Bip32Path::iota(3, 1, 42),
+        Bip32Path::testnet(0, 0, 0),
+    ] {
+        assert_same(&harness, Operation::PublicKey(path), None);
+    }
+}
+
+#[test]
+#[ignore = "requires Speculos and the Python client"]
+fn signing() {
+    let harness = Harness::from_env();
+    let approve = ApprovalPolicy::approve_all();
+    for vector in vectors::shipped().unwrap() {
+        let op = match vector.kind {
+            vectors::VectorKind::Message => Operation::SignMessage {
+                path: vector.path,
+                message: vector.data,
+            },
+            vectors::VectorKind::Transaction => Operation::SignTx {
+                path: vector.path,
+                tx: vector.data,
+                objects: vector.objects,
+            },
+        };
+        assert_same(&harness, op, Some(&approve));
+    }
+}
+
+#[test]
+#[ignore = "requires Speculos and the Python client"]
+fn rejection() {
+    let harness = Harness::from_env();
+    let op = Operation::SignMessage {
+        path: Bip32Path::iota(0, 0, 0),
+        message: b"reject me".to_vec(),
+    };
+    assert_same(&harness, op, Some(&ApprovalPolicy::reject_all()));
+}
@@ -0,0 +1,156 @@
+#!/usr/bin/env python3
+"""Drive the IOTA app's Python client for `testing::differential`.
+
+Usage: reference.py version | pubkey <path> | sign-message <path> <hex>
+       | sign-tx <path> <hex tx> [<hex object>...]
+
+Prints {"result": ..., "transcript": [...]} or {"error": ..., ...} as one
+JSON line. The client is imported from the app repository's tests:
+
+    IOTA_CLIENT_PATH=/path/to/app-iota/tests \
+    IOTA_CLIENT_MODULE=application_client.iota_command_sender \
+    IOTA_CLIENT_CLASS=IotaCommandSender
+
+and given a ragger-style backend that talks to Speculos at
+LEDGER_TCP_HOST:LEDGER_TCP_PORT and records every APDU.
+
+The result is what the client's method returned, normalized (see
+`normalize`), never re-decoded from the APDUs: a client that parses an
+answer differently from the Rust side shows up as a divergence.
+
+METHODS names the client method behind each operation. The names have
+not been checked against a particular release of the client; override
+them with IOTA_CLIENT_METHODS='{"pubkey": "...", ...}'. A name the
+client doesn't have is reported with the methods it does have, instead
+of being called.
+"""
+
+import contextlib
+import importlib
+import json
+import os
+import socket
+import struct
+import sys
+from collections import namedtuple
+
+RAPDU = namedtuple("RAPDU", ["status", "data"])
+
+# operation -> client method
+METHODS = {
+    "version": "get_version",
+    "pubkey": "get_public_key",
+    "sign-message": "sign_message",
+    "sign-tx": "sign_tx",
+}
+
+
+def call_args(op, args):
+    if op == "version":
+        return []
+    if op == "pubkey":
+        return [args[0]]
+    if op == "sign-message":
+        return [args[0], bytes.fromhex(args[1])]
+    objects = [bytes.fromhex(o) for o in args[2:]] or None
+    return [args[0], bytes.fromhex(args[1]), objects]
+
+
+def method(client, op):
+    names = dict(METHODS, **json.loads(os.environ.get("IOTA_CLIENT_METHODS", "{}")))
+    found = getattr(client, names[op], None)
+    if not callable(found):
+        available = sorted(n for n in dir(client) if not n.startswith("_"))
+        raise AttributeError(
+            "client has no method %r for %s; it has %s" % (names[op], op, available)
+        )
+    return found
+
+
+class SpeculosBackend:
+    """The subset of ragger's BackendInterface the client uses."""
+
+    def __init__(self, host, port):
+        self.sock = socket.create_connection((host, port))
+        self.transcript = []
+        self.last_async_response = None
+
+    def _read(self, n):
+        buf = b""
+        while len(buf) < n:
+            chunk = self.sock.recv(n - len(buf))
+            if not chunk:
+                raise ConnectionError("Speculos closed the connection")
+            buf += chunk
+        return buf
+
+    def exchange_raw(self, data=b"", tick_timeout=0):
+        self.sock.sendall(struct.pack(">I", len(data)) + data)
+        (length,) = struct.unpack(">I", self._read(4))
+        answer = self._read(length + 2)
+        self.transcript.append("> " + data.hex())
+        self.transcript.append("< " + answer.hex())
+        return RAPDU(int.from_bytes(answer[-2:], "big"), answer[:-2])
+
+    def exchange(self, cla, ins, p1=0, p2=0, data=b"", tick_timeout=0):
+        header = bytes([cla, ins, p1, p2, len(data)])
+        return self.exchange_raw(header + data)
+
+    @contextlib.contextmanager
+    def exchange_async_raw(self, data=b""):
+        self.last_async_response = self.exchange_raw(data)
+        yield
+
+    @contextlib.contextmanager
+    def exchange_async(self, cla, ins, p1=0, p2=0, data=b""):
+        self.last_async_response = self.exchange(cla, ins, p1, p2, data)
+        yield
+
+
+def normalize(op, value):
+    """The client's return value in the Rust side's result format."""
+    if hasattr(value, "data") and hasattr(value, "status"):  # a RAPDU
+        value = value.data
+    if op == "version":
+        if isinstance(value, str):
+            return value
+        if all(hasattr(value, f) for f in ("major", "minor", "patch")):
+            return "%d.%d.%d" % (value.major, value.minor, value.patch)
+        if isinstance(value, (tuple, list)) and len(value) >= 3:
+            return "%d.%d.%d" % tuple(value[:3])
+    if isinstance(value, (tuple, list)) and value:
+        value = value[0]  # (public key, address) or (signature, ...)
+    if isinstance(value, (bytes, bytearray)):
+        return bytes(value).hex()
+    if isinstance(value, str):
+        return value.lower().removeprefix("0x")
+    raise TypeError("can't compare %s result %r" % (op, value))
+
+
+def main(argv):
+    op, args = argv[1], argv[2:]
+    sys.path.insert(0, os.environ.get("IOTA_CLIENT_PATH", "."))
+    module = importlib.import_module(
+        os.environ.get("IOTA_CLIENT_MODULE", "application_client.iota_command_sender")
+    )
+    client_class = getattr(module, os.environ.get("IOTA_CLIENT_CLASS", "IotaCommandSender"))
+    backend = SpeculosBackend(
+        os.environ.get("LEDGER_TCP_HOST", "127.0.0.1"),
+        int(os.environ.get("LEDGER_TCP_PORT", "9999")),
+    )
+    out = {"transcript": backend.transcript}
+    try:
+        client = client_class(backend)
+        value = method(client, op)(*call_args(op, args))
+        if hasattr(value, "__enter__"):  # ragger-style async signing
+            with value:
+                pass
+            value = backend.last_async_response
+        out["result"] = normalize(op, value)
+    except Exception as e:  # reported, not raised: the transcript still matters
+        out["error"] = "%s: %s" % (type(e).__name__, e)
+    print(json.dumps(out))
+
+
+if __name__ == "__main__":
+    main(sys.argv)
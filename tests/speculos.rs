@@ -2,11 +2,12 @@
 //!
 //! ```sh
 //! podman compose up -d
-//! cargo test --features tcp -- --ignored
+//! cargo test --features tcp,testing -- --ignored
 //! ```
 
-#![cfg(feature = "tcp")]
+#![cfg(all(feature = "tcp", feature = "testing"))]
 
+use ledger_iota::testing::speculos::{ApprovalPolicy, Decision, Speculos};
 use ledger_iota::{Bip32Path, LedgerError, LedgerIota, TransportType};
 
 fn connect() -> LedgerIota {
@@ -15,41 +16,9 @@ fn connect() -> LedgerIota {
     LedgerIota::new(&transport).expect("failed to connect to Speculos — is it running?")
 }
 
-/// Base64-decode helper (avoids extra dev-dependency).
+/// Base64-decode a test vector.
 fn b64(s: &str) -> Vec<u8> {
-    let mut dec = Vec::new();
-    // simple base64 decode
-    let lut: Vec<u8> = (0..256u16)
-        .map(|i| {
-            let c = i as u8;
-            match c {
-                b'A'..=b'Z' => c - b'A',
-                b'a'..=b'z' => c - b'a' + 26,
-                b'0'..=b'9' => c - b'0' + 52,
-                b'+' => 62,
-                b'/' => 63,
-                _ => 0xFF,
-            }
-        })
-        .collect();
-
-    let bytes: Vec<u8> = s
-        .bytes()
-        .filter(|&b| lut[b as usize] != 0xFF && b != b'=')
-        .collect();
-    for chunk in bytes.chunks(4) {
-        let vals: Vec<u8> = chunk.iter().map(|&b| lut[b as usize]).collect();
-        if vals.len() >= 2 {
-            dec.push((vals[0] << 2) | (vals[1] >> 4));
-        }
-        if vals.len() >= 3 {
-            dec.push((vals[1] << 4) | (vals[2] >> 2));
-        }
-        if vals.len() >= 4 {
-            dec.push((vals[2] << 6) | vals[3]);
-        }
-    }
-    dec
+    ledger_iota::tx::from_base64(s).expect("invalid base64 test vector")
 }
 
 #[test]
@@ -112,83 +81,14 @@ fn get_pubkey_deterministic() {
     assert_eq!(addr1, addr2);
 }
 
-fn speculos_api_port() -> u16 {
-    std::env::var("SPECULOS_API_PORT")
-        .ok()
-        .and_then(|s| s.parse().ok())
-        .unwrap_or(5000)
-}
-
-/// HTTP helper — sends a request to the Speculos REST API and returns the response body.
-fn speculos_http(api_port: u16, method: &str, path: &str, body: Option<&str>) -> String {
-    use std::io::{Read, Write};
-    use std::net::TcpStream;
-
-    let addr = format!("127.0.0.1:{api_port}");
-    let mut s = TcpStream::connect(&addr).expect("cannot reach Speculos REST API");
-    let req = if let Some(b) = body {
-        format!(
-            "{method} {path} HTTP/1.1\r\nHost: {addr}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{b}",
-            b.len()
-        )
-    } else {
-        format!("{method} {path} HTTP/1.1\r\nHost: {addr}\r\nConnection: close\r\n\r\n")
-    };
-    s.write_all(req.as_bytes()).unwrap();
-    let mut buf = Vec::new();
-    s.read_to_end(&mut buf).unwrap();
-    let resp = String::from_utf8_lossy(&buf);
-    // Return just the body (after the blank line)
-    resp.split("\r\n\r\n").nth(1).unwrap_or("").to_string()
-}
-
-/// Read the current screen text from the Speculos events API.
-fn screen_text(api_port: u16) -> String {
-    speculos_http(api_port, "GET", "/events?currentscreenonly=true", None)
-}
-
-/// Press a single button via the Speculos REST API.
-fn press_button(api_port: u16, button: &str) {
-    let path = format!("/button/{button}");
-    speculos_http(
-        api_port,
-        "POST",
-        &path,
-        Some(r#"{"action":"press-and-release"}"#),
-    );
-    std::thread::sleep(std::time::Duration::from_millis(500));
-}
-
-/// Press a sequence of buttons via the Speculos REST API.
-/// "R" = right, "B" = both, "L" = left.
-fn press_buttons(api_port: u16, sequence: &str) {
-    for ch in sequence.chars() {
-        let button = match ch {
-            'R' => "right",
-            'B' => "both",
-            'L' => "left",
-            _ => continue,
-        };
-        press_button(api_port, button);
-    }
-}
-
-/// Ensure blind signing is enabled. Checks the actual device state via the events
-/// API and only toggles if needed, so it's safe to call multiple times.
-fn ensure_blind_signing(api_port: u16) {
+/// Ensure blind signing is enabled, once per test run.
+fn ensure_blind_signing(speculos: &Speculos) {
     use std::sync::Once;
     static INIT: Once = Once::new();
     INIT.call_once(|| {
-        // Navigate: Home → Right (Settings) → Both (enter settings)
-        press_buttons(api_port, "RB");
-        let text = screen_text(api_port);
-        if text.contains("Enabled") {
-            // Already enabled — go back: Right (Back) → Both (exit) → Left (home)
-            press_buttons(api_port, "RBL");
-        } else {
-            // Toggle on: Both (toggle) → Right (Back) → Both (exit) → Left (home)
-            press_buttons(api_port, "BRBL");
-        }
+        speculos
+            .set_blind_signing(true)
+            .expect("failed to enable blind signing");
     });
 }
 
@@ -197,24 +97,19 @@ fn ensure_blind_signing(api_port: u16) {
 #[test]
 #[ignore = "requires Speculos"]
 fn sign_blind() {
-    let api_port = speculos_api_port();
-    ensure_blind_signing(api_port);
+    let speculos = Speculos::from_env();
+    ensure_blind_signing(&speculos);
 
     let ledger = connect();
     let path = Bip32Path::iota(0, 0, 1);
 
-    // Whole-gas-coin transfer from the ragger test vectors
+    // Whole-gas-coin transfer from tests/vectors/signing.json
     let tx = b64("AAAAAAABACAbNmnjIYk+5Jw4egj8JR2//zfNKpgebEc6Wyr94Z02PgEBAQABAAAPWOsTUUVNYjpqQ2YZjWzVqkoSoSo8rvtQFHbgbYvVtgEe7Gwy7zZGczH7ewiLSssc5G9zY1QwjgP/bOkTpCc04oYAAAAAAAAAIMfrxsRDCzz35Y11q1PduRgCdN72Oxq1YZ+9twls29cSD1jrE1FFTWI6akNmGY1s1apKEqEqPK77UBR24G2L1bboAwAAAAAAAEBCDwAAAAAAAA==");
 
-    // Approve in background while sign_tx blocks for user interaction.
-    // Flow: Both (accept blind signing risk) → Right×3 (review/hash screens) → Both (sign)
-    let handle = std::thread::spawn(move || {
-        std::thread::sleep(std::time::Duration::from_secs(2));
-        press_buttons(api_port, "BRRRB");
-    });
+    let handle = speculos.spawn(ApprovalPolicy::approve_all());
 
     let sig = ledger.sign_tx(&tx, &path, None).unwrap();
-    handle.join().unwrap();
+    handle.join().unwrap().unwrap();
 
     let sig_bytes: &[u8] = sig.as_ref();
     assert_eq!(sig_bytes.len(), 64);
@@ -225,30 +120,45 @@ fn sign_blind() {
 #[test]
 #[ignore = "requires Speculos"]
 fn sign_deterministic() {
-    let api_port = speculos_api_port();
-    ensure_blind_signing(api_port);
+    let speculos = Speculos::from_env();
+    ensure_blind_signing(&speculos);
 
     let ledger = connect();
     let path = Bip32Path::iota(0, 0, 1);
     let tx = b64("AAAAAAABACAbNmnjIYk+5Jw4egj8JR2//zfNKpgebEc6Wyr94Z02PgEBAQABAAAPWOsTUUVNYjpqQ2YZjWzVqkoSoSo8rvtQFHbgbYvVtgEe7Gwy7zZGczH7ewiLSssc5G9zY1QwjgP/bOkTpCc04oYAAAAAAAAAIMfrxsRDCzz35Y11q1PduRgCdN72Oxq1YZ+9twls29cSD1jrE1FFTWI6akNmGY1s1apKEqEqPK77UBR24G2L1bboAwAAAAAAAEBCDwAAAAAAAA==");
 
-    let handle = std::thread::spawn(move || {
-        std::thread::sleep(std::time::Duration::from_secs(2));
-        press_buttons(api_port, "BRRRB");
-    });
+    let handle = speculos.spawn(ApprovalPolicy::approve_all());
     let sig1 = ledger.sign_tx(&tx, &path, None).unwrap();
-    handle.join().unwrap();
+    handle.join().unwrap().unwrap();
 
-    let handle = std::thread::spawn(move || {
-        std::thread::sleep(std::time::Duration::from_secs(2));
-        press_buttons(api_port, "BRRRB");
-    });
+    let handle = speculos.spawn(ApprovalPolicy::approve_all());
     let sig2 = ledger.sign_tx(&tx, &path, None).unwrap();
-    handle.join().unwrap();
+    handle.join().unwrap().unwrap();
 
     assert_eq!(sig1, sig2);
 }
 
+/// With a sign journal attached, signing the same transaction again is
+/// refused before anything is sent to the device.
+#[test]
+#[ignore = "requires Speculos"]
+fn sign_journal_rejects_replay() {
+    let speculos = Speculos::from_env();
+    ensure_blind_signing(&speculos);
+
+    let journal = ledger_iota::SignJournal::in_memory(std::time::Duration::from_secs(600));
+    let ledger = connect().with_sign_journal(journal);
+    let path = Bip32Path::iota(0, 0, 1);
+    let tx = b64("AAAAAAABACAbNmnjIYk+5Jw4egj8JR2//zfNKpgebEc6Wyr94Z02PgEBAQABAAAPWOsTUUVNYjpqQ2YZjWzVqkoSoSo8rvtQFHbgbYvVtgEe7Gwy7zZGczH7ewiLSssc5G9zY1QwjgP/bOkTpCc04oYAAAAAAAAAIMfrxsRDCzz35Y11q1PduRgCdN72Oxq1YZ+9twls29cSD1jrE1FFTWI6akNmGY1s1apKEqEqPK77UBR24G2L1bboAwAAAAAAAEBCDwAAAAAAAA==");
+
+    let handle = speculos.spawn(ApprovalPolicy::approve_all());
+    ledger.sign_tx(&tx, &path, None).unwrap();
+    handle.join().unwrap().unwrap();
+
+    let err = ledger.sign_tx(&tx, &path, None).unwrap_err();
+    assert!(matches!(err, LedgerError::DuplicateSignRequest { .. }));
+}
+
 /// Verify the signature is cryptographically valid against the derived pubkey.
 #[test]
 #[ignore = "requires Speculos"]
@@ -256,8 +166,8 @@ fn sign_verify() {
     use blake2::{digest::consts::U32, Blake2b, Digest};
     use ed25519_dalek::{Signature as DalekSig, Verifier, VerifyingKey};
 
-    let api_port = speculos_api_port();
-    ensure_blind_signing(api_port);
+    let speculos = Speculos::from_env();
+    ensure_blind_signing(&speculos);
 
     let ledger = connect();
     let path = Bip32Path::iota(0, 0, 1);
@@ -265,12 +175,9 @@ fn sign_verify() {
 
     let tx = b64("AAAAAAABACAbNmnjIYk+5Jw4egj8JR2//zfNKpgebEc6Wyr94Z02PgEBAQABAAAPWOsTUUVNYjpqQ2YZjWzVqkoSoSo8rvtQFHbgbYvVtgEe7Gwy7zZGczH7ewiLSssc5G9zY1QwjgP/bOkTpCc04oYAAAAAAAAAIMfrxsRDCzz35Y11q1PduRgCdN72Oxq1YZ+9twls29cSD1jrE1FFTWI6akNmGY1s1apKEqEqPK77UBR24G2L1bboAwAAAAAAAEBCDwAAAAAAAA==");
 
-    let handle = std::thread::spawn(move || {
-        std::thread::sleep(std::time::Duration::from_secs(2));
-        press_buttons(api_port, "BRRRB");
-    });
+    let handle = speculos.spawn(ApprovalPolicy::approve_all());
     let sig = ledger.sign_tx(&tx, &path, None).unwrap();
-    handle.join().unwrap();
+    handle.join().unwrap().unwrap();
 
     let pk_bytes: &[u8] = pubkey.as_ref();
     let sig_bytes: &[u8] = sig.as_ref();
@@ -288,27 +195,20 @@ fn sign_verify() {
 #[test]
 #[ignore = "requires Speculos"]
 fn sign_user_rejected() {
-    let api_port = speculos_api_port();
-    ensure_blind_signing(api_port);
+    let speculos = Speculos::from_env();
+    ensure_blind_signing(&speculos);
 
     let ledger = connect();
     let path = Bip32Path::iota(0, 0, 1);
     let tx = b64("AAAAAAABACAbNmnjIYk+5Jw4egj8JR2//zfNKpgebEc6Wyr94Z02PgEBAQABAAAPWOsTUUVNYjpqQ2YZjWzVqkoSoSo8rvtQFHbgbYvVtgEe7Gwy7zZGczH7ewiLSssc5G9zY1QwjgP/bOkTpCc04oYAAAAAAAAAIMfrxsRDCzz35Y11q1PduRgCdN72Oxq1YZ+9twls29cSD1jrE1FFTWI6akNmGY1s1apKEqEqPK77UBR24G2L1bboAwAAAAAAAEBCDwAAAAAAAA==");
 
-    // Reject: Both (accept blind signing warning) → Right (to Reject) → Both (confirm reject)
-    // The exact button sequence depends on the app UI; we navigate past the
-    // blind-sign warning, then scroll to the reject option and confirm it.
-    let handle = std::thread::spawn(move || {
-        std::thread::sleep(std::time::Duration::from_secs(2));
-        // Navigate through review screens until we find reject
-        press_buttons(api_port, "BRRRRB");
-    });
+    let handle = speculos.spawn(ApprovalPolicy::reject_all());
 
     let err = ledger.sign_tx(&tx, &path, None).unwrap_err();
-    handle.join().unwrap();
+    assert_eq!(handle.join().unwrap().unwrap(), Decision::Reject);
 
     assert!(
-        matches!(err, LedgerError::UserRejected),
+        matches!(err, LedgerError::UserRejected { .. }),
         "expected UserRejected, got: {err:?}"
     );
 }
@@ -317,22 +217,17 @@ fn sign_user_rejected() {
 #[test]
 #[ignore = "requires Speculos"]
 fn verify_address_matches_pubkey() {
-    let api_port = speculos_api_port();
+    let speculos = Speculos::from_env();
 
     let ledger = connect();
     let path = Bip32Path::iota(0, 0, 0);
 
     let (pk_get, addr_get) = ledger.get_pubkey(&path).unwrap();
 
-    // verify_address shows the address on-screen and blocks until user confirms.
-    // R×3: Verify → Address 1/2 → Address 2/2 → Confirm, then B to approve
-    let handle = std::thread::spawn(move || {
-        std::thread::sleep(std::time::Duration::from_secs(2));
-        press_buttons(api_port, "RRRB");
-    });
+    let handle = speculos.spawn(ApprovalPolicy::approve_all());
 
     let (pk_verify, addr_verify) = ledger.verify_address(&path).unwrap();
-    handle.join().unwrap();
+    handle.join().unwrap().unwrap();
 
     assert_eq!(pk_get, pk_verify);
     assert_eq!(addr_get, addr_verify);
@@ -345,7 +240,7 @@ fn sign_message() {
     use blake2::{digest::consts::U32, Blake2b, Digest};
     use ed25519_dalek::{Signature as DalekSig, Verifier, VerifyingKey};
 
-    let api_port = speculos_api_port();
+    let speculos = Speculos::from_env();
 
     let ledger = connect();
     let path = Bip32Path::iota(0, 0, 1);
@@ -353,14 +248,10 @@ fn sign_message() {
 
     let message = b"Hello";
 
-    // Personal message flow: Review → Message → Sign
-    let handle = std::thread::spawn(move || {
-        std::thread::sleep(std::time::Duration::from_secs(2));
-        press_buttons(api_port, "RRB");
-    });
+    let handle = speculos.spawn(ApprovalPolicy::approve_all());
 
     let sig = ledger.sign_message(message, &path).unwrap();
-    handle.join().unwrap();
+    handle.join().unwrap().unwrap();
 
     let sig_bytes: &[u8] = sig.as_ref();
     assert_eq!(sig_bytes.len(), 64);
@@ -381,41 +272,137 @@ fn sign_message() {
 #[test]
 #[ignore = "requires Speculos"]
 fn sign_large_tx() {
-    use ledger_iota::{build_transfer_tx, GasCoinRef};
+    use ledger_iota::types::Address;
+    use ledger_iota::{build_transfer_tx, Amount, GasCoinRef, ObjectDigest};
 
-    let api_port = speculos_api_port();
-    ensure_blind_signing(api_port);
+    let speculos = Speculos::from_env();
+    ensure_blind_signing(&speculos);
 
     let ledger = connect();
     let path = Bip32Path::iota(0, 0, 1);
 
     // build_transfer_tx produces ~200 bytes which crosses the 180-byte block
     // boundary, forcing the protocol to chunk into multiple blocks.
-    let sender = [0x01; 32];
-    let recipient = [0x02; 32];
+    let sender = Address([0x01; 32]);
+    let recipient = Address([0x02; 32]);
     let gas = GasCoinRef {
         object_id: [0xAA; 32],
         version: 1,
-        digest: [0xBB; 32],
+        digest: ObjectDigest([0xBB; 32]),
     };
-    let tx = build_transfer_tx(&sender, &recipient, 1_000_000, &gas, 10_000_000, 1000);
+    let tx = build_transfer_tx(
+        &sender,
+        &recipient,
+        Amount::from_nanos(1_000_000),
+        &gas,
+        Amount::from_nanos(10_000_000),
+        1000,
+    )
+    .unwrap();
     assert!(
         tx.len() > 180,
         "TX must exceed single block size for this test"
     );
 
-    // Clear-sign flow (device parses the transfer TX):
-    // R×7: Review → From 1/2 → From 2/2 → To 1/2 → To 2/2 → Amount → Max Gas → Sign
-    // B: approve
-    let handle = std::thread::spawn(move || {
-        std::thread::sleep(std::time::Duration::from_secs(2));
-        press_buttons(api_port, "RRRRRRRB");
-    });
+    // Clear-sign flow: the device shows the recipient, so only approve
+    // if it is the one we built the transaction for.
+    let handle = speculos.spawn(ApprovalPolicy::approve_if_recipient(&"02".repeat(32)));
 
     let sig = ledger.sign_tx(&tx, &path, None).unwrap();
-    handle.join().unwrap();
+    assert_eq!(handle.join().unwrap().unwrap(), Decision::Approve);
 
     let sig_bytes: &[u8] = sig.as_ref();
     assert_eq!(sig_bytes.len(), 64);
     assert!(sig_bytes.iter().any(|&b| b != 0));
 }
+
+/// What the device shows while clear-signing matches the snapshots in
+/// `tests/snapshots/<model>/`. Record them with `UPDATE_SNAPSHOTS=1` and
+/// review the PNGs before committing.
+#[test]
+#[ignore = "requires Speculos"]
+fn clear_sign_screens_match_snapshots() {
+    use ledger_iota::types::Address;
+    use ledger_iota::{build_transfer_tx, Amount, GasCoinRef, ObjectDigest};
+
+    let speculos = Speculos::from_env();
+    let ledger = connect();
+    let gas = GasCoinRef {
+        object_id: [0xAA; 32],
+        version: 1,
+        digest: ObjectDigest([0xBB; 32]),
+    };
+    let tx = build_transfer_tx(
+        &Address([0x01; 32]),
+        &Address([0x02; 32]),
+        Amount::from_nanos(1_000_000),
+        &gas,
+        Amount::from_nanos(10_000_000),
+        1000,
+    )
+    .unwrap();
+
+    let signer = std::thread::spawn(move || ledger.sign_tx(&tx, &Bip32Path::iota(0, 0, 1), None));
+
+    speculos.wait_for_text("Review").unwrap();
+    speculos.assert_screen_matches("review_transfer.png");
+    for _ in 0..10 {
+        if speculos
+            .screen()
+            .unwrap()
+            .iter()
+            .any(|l| l.contains("Amount"))
+        {
+            break;
+        }
+        speculos.next_screen().unwrap();
+    }
+    speculos.assert_screen_matches("review_transfer_amount.png");
+
+    let decision = speculos
+        .answer_shown(&ApprovalPolicy::approve_all())
+        .unwrap();
+    assert_eq!(decision, Decision::Approve);
+    signer.join().unwrap().unwrap();
+}
+
+/// The emulator's keys are the ones `testing::seed` derives from its
+/// mnemonic (`SPECULOS_SEED`, default the Speculos one).
+#[test]
+#[ignore = "requires Speculos"]
+fn keys_match_seed() {
+    use ledger_iota::seed::{Seed, SPECULOS_MNEMONIC};
+
+    let mnemonic = std::env::var("SPECULOS_SEED").unwrap_or_else(|_| SPECULOS_MNEMONIC.into());
+    let seed = Seed::from_mnemonic(&mnemonic, "");
+    let ledger = connect();
+    for path in [
+        Bip32Path::iota(0, 0, 0),
+        Bip32Path::iota(1, 0, 7),
+        Bip32Path::testnet(0, 1, 2),
+    ] {
+        assert_eq!(
+            ledger.get_pubkey(&path).unwrap(),
+            seed.keys(&path),
+            "{path}"
+        );
+    }
+}
+
+/// Replay the signing vectors; signatures must match the expected ones
+/// when Speculos runs with its default seed.
+#[test]
+#[ignore = "requires Speculos"]
+fn signing_vectors_replay() {
+    use ledger_iota::testing::vectors;
+
+    let speculos = Speculos::from_env();
+    ensure_blind_signing(&speculos);
+    let ledger = connect();
+    for vector in vectors::shipped().unwrap() {
+        let handle = speculos.spawn(ApprovalPolicy::approve_all());
+        let replay = vector.replay(&ledger).unwrap();
+        handle.join().unwrap().unwrap();
+        assert_ne!(replay.matches, Some(false), "{}", vector.name);
+    }
+}
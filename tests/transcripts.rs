@@ -0,0 +1,119 @@
+//! Golden APDU transcripts: every command is run against a simulated
+//! device and the exact bytes exchanged are compared with
+//! `tests/golden/<name>.apdu`.
+//!
+//! After an intended wire-format change, regenerate with
+//!
+//! ```sh
+//! UPDATE_GOLDEN=1 cargo test --features testing --test transcripts
+//! ```
+//!
+//! and review the diff.
+
+#![cfg(feature = "testing")]
+
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use ledger_iota::testing::{fixture, Recorder, SimulatedDevice};
+use ledger_iota::{Bip32Path, LedgerIota, ObjectData, Owner, TransactionDigest};
+
+fn golden_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/golden")
+        .join(format!("{name}.apdu"))
+}
+
+fn assert_golden(name: &str, actual: &str) {
+    let path = golden_path(name);
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, actual).unwrap();
+        return;
+    }
+    let expected = fs::read_to_string(&path)
+        .unwrap_or_else(|e| panic!("{}: {e} (run with UPDATE_GOLDEN=1)", path.display()));
+    if actual != expected {
+        let line = actual
+            .lines()
+            .zip(expected.lines())
+            .position(|(a, e)| a != e)
+            .unwrap_or_else(|| actual.lines().count().min(expected.lines().count()));
+        panic!(
+            "{name}: transcript differs from {} at line {}\n--- actual ---\n{actual}",
+            path.display(),
+            line + 1
+        );
+    }
+}
+
+/// Run `f` against a fresh simulated device and check its transcript.
+fn check(name: &str, f: impl FnOnce(&LedgerIota)) {
+    let recorder = Arc::new(Recorder::new(SimulatedDevice::speculos()));
+    let ledger = LedgerIota::with_transport(Box::new(recorder.clone()));
+    f(&ledger);
+    assert_golden(name, &recorder.transcript());
+}
+
+fn path() -> Bip32Path {
+    Bip32Path::iota(0, 0, 0)
+}
+
+#[test]
+fn get_version() {
+    check("get_version", |ledger| {
+        ledger.get_version().unwrap();
+    });
+}
+
+#[test]
+fn get_pubkey() {
+    check("get_pubkey", |ledger| {
+        ledger.get_pubkey(&path()).unwrap();
+    });
+}
+
+#[test]
+fn verify_address() {
+    check("verify_address", |ledger| {
+        ledger.verify_address(&path()).unwrap();
+    });
+}
+
+#[test]
+fn sign_message() {
+    check("sign_message", |ledger| {
+        ledger.sign_message(&[0x42; 300], &path()).unwrap();
+    });
+}
+
+#[test]
+fn sign_tx() {
+    let tx = fixture("transfer_single.bin").unwrap();
+    check("sign_tx", |ledger| {
+        ledger.sign_tx(&tx, &path(), None).unwrap();
+    });
+}
+
+#[test]
+fn sign_tx_with_objects() {
+    let tx = fixture("transfer_single.bin").unwrap();
+    let mut contents = vec![0xCC; 32];
+    contents.extend_from_slice(&1_000_000_000u64.to_le_bytes());
+    let gas = ObjectData::gas_coin(
+        42,
+        contents,
+        Owner::AddressOwner([0xAA; 32]),
+        TransactionDigest::ZERO,
+        0,
+    );
+    check("sign_tx_with_objects", |ledger| {
+        ledger.sign_tx(&tx, &path(), Some(&[gas])).unwrap();
+    });
+}
+
+#[test]
+fn quit() {
+    check("quit", |ledger| ledger.quit().unwrap());
+}
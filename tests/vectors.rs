@@ -0,0 +1,163 @@
+//! Byte-for-byte checks against the vectors in `tests/vectors/`.
+//!
+//! Every `*.json` file there is `{"source": ..., "vectors": [...]}`.
+//! `source` says where the expected bytes come from: for vectors taken
+//! from the app or another implementation, the repository and commit.
+//! The files shipped so far are regression snapshots of this crate's own
+//! output, and say so. Vectors are:
+//!
+//! - `kind: "objects"` -- `objects` are passed to `encode_objects`
+//! - `kind: "transfer"` -- the fields are passed to `build_transfer_tx`
+//! - `kind: "transaction"` / `"message"` -- signing vectors, replayed by
+//!   `testing::vectors` and skipped here
+//!
+//! The expected output is either inline (`expected`, hex) or in a raw
+//! binary file next to the JSON (`expected_file`). Dropping new files
+//! into the directory is enough to have them checked.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use ledger_iota::types::Address;
+use ledger_iota::{
+    build_transfer_tx, encode_objects, Amount, GasCoinRef, ObjectData, ObjectDigest, Owner,
+    StructTag, TransactionDigest,
+};
+use serde_json::Value;
+
+fn vectors_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/vectors")
+}
+
+fn str_field<'a>(v: &'a Value, name: &str) -> &'a str {
+    v[name]
+        .as_str()
+        .unwrap_or_else(|| panic!("`{name}` missing or not a string in {v}"))
+}
+
+fn u64_field(v: &Value, name: &str) -> u64 {
+    v[name]
+        .as_u64()
+        .unwrap_or_else(|| panic!("`{name}` missing or not a u64 in {v}"))
+}
+
+fn bytes<const N: usize>(v: &Value, name: &str) -> [u8; N] {
+    let mut out = [0u8; N];
+    hex::decode_to_slice(str_field(v, name), &mut out).unwrap();
+    out
+}
+
+fn owner(v: &Value) -> Owner {
+    if v == "immutable" {
+        return Owner::Immutable;
+    }
+    if v.get("address").is_some() {
+        return Owner::AddressOwner(bytes(v, "address"));
+    }
+    if v.get("object").is_some() {
+        return Owner::ObjectOwner(bytes(v, "object"));
+    }
+    Owner::Shared {
+        initial_shared_version: u64_field(v, "shared"),
+    }
+}
+
+fn object(v: &Value) -> ObjectData {
+    let builder = ObjectData::builder()
+        .version(u64_field(v, "version"))
+        .contents(hex::decode(str_field(v, "contents")).unwrap())
+        .owner(owner(&v["owner"]))
+        .previous_transaction(
+            str_field(v, "previous_transaction")
+                .parse::<TransactionDigest>()
+                .unwrap(),
+        )
+        .storage_rebate(u64_field(v, "storage_rebate"));
+    let type_tag = |name| str_field(v, name).parse::<StructTag>().unwrap();
+    let builder = match str_field(v, "type") {
+        "gas_coin" => builder.gas_coin(),
+        "coin" => builder.coin(type_tag("coin_type")),
+        "staked_iota" => builder.staked_iota(),
+        "timelocked_staked_iota" => builder.timelocked_staked_iota(),
+        "timelocked_iota" => builder.timelocked_iota(),
+        "other" => builder
+            .other(type_tag("object_type"))
+            .has_public_transfer(v["has_public_transfer"].as_bool().unwrap()),
+        other => panic!("unknown object type {other:?}"),
+    };
+    builder.build().unwrap()
+}
+
+fn transfer(v: &Value) -> Vec<u8> {
+    let gas = &v["gas"];
+    build_transfer_tx(
+        &Address(bytes(v, "sender")),
+        &Address(bytes(v, "recipient")),
+        Amount::from_nanos(u64_field(v, "amount")),
+        &GasCoinRef {
+            object_id: bytes(gas, "object_id"),
+            version: u64_field(gas, "version"),
+            digest: ObjectDigest(bytes(gas, "digest")),
+        },
+        Amount::from_nanos(u64_field(v, "gas_budget")),
+        u64_field(v, "gas_price"),
+    )
+    .unwrap()
+}
+
+fn expected(v: &Value) -> Vec<u8> {
+    match v.get("expected_file") {
+        Some(file) => fs::read(vectors_dir().join(file.as_str().unwrap())).unwrap(),
+        None => hex::decode(str_field(v, "expected")).unwrap(),
+    }
+}
+
+#[test]
+fn reference_vectors() {
+    let mut files: Vec<PathBuf> = fs::read_dir(vectors_dir())
+        .unwrap()
+        .map(|e| e.unwrap().path())
+        .filter(|p| p.extension().is_some_and(|e| e == "json"))
+        .collect();
+    files.sort();
+    assert!(!files.is_empty(), "no vectors found");
+
+    let mut checked = 0;
+    for file in files {
+        let root: Value = serde_json::from_slice(&fs::read(&file).unwrap()).unwrap();
+        let source = str_field(&root, "source");
+        assert!(!source.is_empty(), "{}: empty source", file.display());
+        for v in root["vectors"].as_array().expect("`vectors` array") {
+            let name = format!("{}: {} ({source})", file.display(), str_field(v, "name"));
+            let actual = match str_field(v, "kind") {
+                "objects" => {
+                    let objects: Vec<ObjectData> = v["objects"]
+                        .as_array()
+                        .unwrap()
+                        .iter()
+                        .map(object)
+                        .collect();
+                    encode_objects(&objects).unwrap()
+                }
+                "transfer" => transfer(v),
+                "transaction" | "message" => continue,
+                kind => panic!("{name}: unknown kind {kind:?}"),
+            };
+            let expected = expected(v);
+            if actual != expected {
+                let offset = actual
+                    .iter()
+                    .zip(&expected)
+                    .position(|(a, b)| a != b)
+                    .unwrap_or(actual.len().min(expected.len()));
+                panic!(
+                    "{name}: output differs at byte {offset}\n  expected {}\n  actual   {}",
+                    hex::encode(&expected),
+                    hex::encode(&actual)
+                );
+            }
+            checked += 1;
+        }
+    }
+    assert!(checked > 0);
+}